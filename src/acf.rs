@@ -0,0 +1,135 @@
+use crate::LagError;
+use num_traits::Float;
+
+/// Computes the autocorrelation function of a time series with missing values.
+///
+/// Only valid observations, i.e. those whose `mask` entry is `true`, contribute to the mean
+/// and variance, and the autocovariance at lag `k` is averaged over the pairs
+/// `(x(t), x(t - k))` for which both values are valid. This allows lag selection on gappy
+/// series without imputing the missing values first. Lags without any valid pair are `NaN`.
+///
+/// ## Arguments
+/// * `data` - The time series data.
+/// * `mask` - The validity of every observation; `true` marks a valid value.
+/// * `max_lag` - The largest lag to compute the autocorrelation for.
+///
+/// ## Returns
+/// The autocorrelations for the lags `0..=max_lag`, or an error if `data` is empty, the
+/// mask length does not match, or `max_lag` is not less than the series length.
+///
+/// ## Example
+/// ```
+/// use timelag::autocorrelation_masked;
+///
+/// let data = [1.0, -1.0, 1.0, f64::NAN, 1.0, -1.0, 1.0, -1.0];
+/// let mask: Vec<bool> = data.iter().map(|x| !x.is_nan()).collect();
+///
+/// let acf = autocorrelation_masked(&data, &mask, 2).unwrap();
+/// assert!((acf[0] - 1.0).abs() < 1e-12);
+/// assert!(acf[1] < -0.9);
+/// assert!(acf[2] > 0.8);
+/// ```
+pub fn autocorrelation_masked<T>(
+    data: &[T],
+    mask: &[bool],
+    max_lag: usize,
+) -> Result<Vec<T>, LagError>
+where
+    T: Float,
+{
+    if data.is_empty() {
+        return Err(LagError::EmptyData);
+    }
+
+    if mask.len() != data.len() {
+        return Err(LagError::InvalidLength);
+    }
+
+    if max_lag >= data.len() {
+        return Err(LagError::LagExceedsValueCount);
+    }
+
+    let valid = || {
+        data.iter()
+            .zip(mask)
+            .filter(|(_, &valid)| valid)
+            .map(|(&x, _)| x)
+    };
+    let count = mask.iter().filter(|&&valid| valid).count();
+    let Some(count) = T::from(count).filter(|count| !count.is_zero()) else {
+        return Ok(vec![T::nan(); max_lag + 1]);
+    };
+
+    let mean = valid().fold(T::zero(), |sum, x| sum + x) / count;
+    let variance = valid().fold(T::zero(), |sum, x| sum + (x - mean) * (x - mean)) / count;
+
+    let acf = (0..=max_lag)
+        .map(|lag| {
+            let (sum, pairs) = (lag..data.len())
+                .filter(|&t| mask[t] && mask[t - lag])
+                .fold((T::zero(), T::zero()), |(sum, pairs), t| {
+                    (
+                        sum + (data[t] - mean) * (data[t - lag] - mean),
+                        pairs + T::one(),
+                    )
+                });
+
+            if pairs.is_zero() {
+                T::nan()
+            } else {
+                sum / pairs / variance
+            }
+        })
+        .collect();
+
+    Ok(acf)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_all_valid_matches_pairwise_acf() {
+        let data: Vec<f64> = (0..50).map(|t| ((t * 7) % 11) as f64).collect();
+        let mask = vec![true; data.len()];
+        let acf = autocorrelation_masked(&data, &mask, 5).unwrap();
+
+        let mean = data.iter().sum::<f64>() / 50.0;
+        let variance = data.iter().map(|x| (x - mean).powi(2)).sum::<f64>() / 50.0;
+        for (lag, &value) in acf.iter().enumerate() {
+            let covariance = (lag..50)
+                .map(|t| (data[t] - mean) * (data[t - lag] - mean))
+                .sum::<f64>()
+                / (50 - lag) as f64;
+            assert!((value - covariance / variance).abs() < 1e-12);
+        }
+    }
+
+    #[test]
+    fn test_masked_values_are_ignored() {
+        // The masked values would otherwise dominate the result.
+        let data = [1.0, 2.0, 1e9, 4.0, 5.0, 6.0];
+        let mask = [true, true, false, true, true, true];
+        let acf = autocorrelation_masked(&data, &mask, 1).unwrap();
+        assert!((acf[0] - 1.0).abs() < 1e-12);
+        assert!(acf[1] > 0.0 && acf[1] < 1.0);
+    }
+
+    #[test]
+    fn test_no_valid_pairs() {
+        let data = [1.0, 2.0, 3.0, 4.0];
+        let mask = [true, false, true, false];
+        let acf = autocorrelation_masked(&data, &mask, 1).unwrap();
+        assert!(acf[1].is_nan());
+
+        assert_eq!(
+            autocorrelation_masked(&data, &mask[..3], 1).unwrap_err(),
+            LagError::InvalidLength
+        );
+        assert_eq!(
+            autocorrelation_masked(&data, &mask, 4).unwrap_err(),
+            LagError::LagExceedsValueCount
+        );
+    }
+}
@@ -0,0 +1,276 @@
+use crate::rng::SplitMix64;
+use crate::{shift_of, LagMatrix};
+use num_traits::Float;
+
+impl<T, S> LagMatrix<T, S>
+where
+    T: Float,
+    S: AsRef<[T]>,
+{
+    /// Creates a copy of the matrix with independent Gaussian noise added to every lagged
+    /// value, e.g. to augment the training data of small datasets.
+    ///
+    /// Fill values, static columns and row padding are left untouched. Since every value
+    /// receives its own noise, an observation differs between the lags it appears in;
+    /// use [`augment_jitter`](Self::augment_jitter) to perturb observations consistently.
+    ///
+    /// ## Arguments
+    /// * `noise_std` - The standard deviation of the noise.
+    /// * `seed` - The seed of the pseudo-random number generator.
+    ///
+    /// ## Example
+    /// ```
+    /// use timelag::lag_matrix;
+    ///
+    /// let lagged = lag_matrix(&[1.0, 2.0, 3.0], 0..=1, f64::INFINITY, 0).unwrap();
+    /// let augmented = lagged.augment_gaussian(0.1, 42);
+    ///
+    /// // The fill value of the first lag remains.
+    /// assert_eq!(augmented[3], f64::INFINITY);
+    /// assert_ne!(augmented[0], lagged[0]);
+    /// ```
+    pub fn augment_gaussian(&self, noise_std: T, seed: u64) -> LagMatrix<T> {
+        let mut rng = SplitMix64::new(seed);
+        self.augment(|_, _, _| noise(&mut rng, noise_std))
+    }
+
+    /// Creates a copy of the matrix with Gaussian noise added to every observation of the
+    /// underlying series, e.g. to augment the training data of small datasets.
+    ///
+    /// Unlike [`augment_gaussian`](Self::augment_gaussian), an observation receives the same
+    /// noise in every lag it appears in, such that the result equals lagging the jittered
+    /// series. Fill values, static columns and row padding are left untouched.
+    ///
+    /// ## Arguments
+    /// * `noise_std` - The standard deviation of the noise.
+    /// * `seed` - The seed of the pseudo-random number generator.
+    ///
+    /// ## Example
+    /// ```
+    /// use timelag::lag_matrix;
+    ///
+    /// let lagged = lag_matrix(&[1.0, 2.0, 3.0], 0..=1, f64::INFINITY, 0).unwrap();
+    /// let jittered = lagged.augment_jitter(0.1, 42);
+    ///
+    /// // The first lag still holds the preceding observations.
+    /// assert_eq!(jittered[4..6], jittered[0..2]);
+    /// ```
+    pub fn augment_jitter(&self, noise_std: T, seed: u64) -> LagMatrix<T> {
+        let mut rng = SplitMix64::new(seed);
+        let source_length = self.source_length;
+        let offsets: Vec<T> = (0..self.series_count * source_length)
+            .map(|_| noise(&mut rng, noise_std))
+            .collect();
+        self.augment(|series, step, _| offsets[series * source_length + step])
+    }
+
+    /// Creates a copy of the matrix whose underlying series are resampled along a randomly
+    /// stretched and compressed time axis, e.g. to augment the training data of small datasets
+    /// with observations of varying pace.
+    ///
+    /// The pace of time is drawn at a few evenly spaced knots as `exp(sigma * z)` with standard
+    /// normal `z` and interpolated linearly in between; the warped time axis starts and ends
+    /// with the series, and the values are interpolated linearly between the neighbouring
+    /// observations. Like [`augment_jitter`](Self::augment_jitter), an observation has the same
+    /// value in every lag it appears in. Observations that are not part of any lag are not
+    /// available for interpolation and keep their value. Fill values, static columns and row
+    /// padding are left untouched.
+    ///
+    /// ## Arguments
+    /// * `sigma` - The standard deviation of the logarithmic pace; `0` retains the matrix.
+    /// * `seed` - The seed of the pseudo-random number generator.
+    ///
+    /// ## Example
+    /// ```
+    /// use timelag::lag_matrix;
+    ///
+    /// let data: Vec<f64> = (0..10).map(f64::from).collect();
+    /// let lagged = lag_matrix(&data, 0..=1, f64::INFINITY, 0).unwrap();
+    /// let warped = lagged.augment_time_warp(0.5, 42);
+    ///
+    /// // The warped series still starts and ends with the original observations.
+    /// assert_eq!(warped[0], 0.0);
+    /// assert_eq!(warped[9], 9.0);
+    /// assert_eq!(warped[11..20], warped[0..9]);
+    /// assert_eq!(lagged.augment_time_warp(0.0, 42), lagged);
+    /// ```
+    pub fn augment_time_warp(&self, sigma: T, seed: u64) -> LagMatrix<T> {
+        let source_length = self.source_length;
+
+        // Gather the observation of every source step from the lags it appears in.
+        let mut observations = vec![None; self.series_count * source_length];
+        for lag_index in 0..self.num_lags {
+            for series in 0..self.series_count {
+                let shift = shift_of(self.lags[lag_index], self.directions[series]);
+                for t in 0..self.series_length {
+                    if !self.is_padding(lag_index, series, t) {
+                        let step = (self.time_offset + t).wrapping_sub_signed(shift);
+                        let value = self.data.as_ref()[self.offset_of(lag_index, series, t)];
+                        observations[series * source_length + step] = Some(value);
+                    }
+                }
+            }
+        }
+
+        let positions = warp_positions(&mut SplitMix64::new(seed), sigma, source_length);
+        let warped: Vec<Option<T>> = observations
+            .chunks(source_length.max(1))
+            .flat_map(|series| {
+                positions.iter().map(|&position| {
+                    let index = position.floor();
+                    let weight = position - index;
+                    let index = index.to_usize().expect("the position is finite");
+                    let value = series[index]?;
+                    if weight == T::zero() {
+                        return Some(value);
+                    }
+                    let next = series.get(index + 1).copied().flatten()?;
+                    Some(value + (next - value) * weight)
+                })
+            })
+            .collect();
+
+        self.augment(|series, step, value| {
+            warped[series * source_length + step].map_or(T::zero(), |warped| warped - value)
+        })
+    }
+
+    /// Copies the matrix, adding `noise(series, source_step, value)` to every value that is
+    /// neither a fill value nor padding.
+    fn augment<F>(&self, mut noise: F) -> LagMatrix<T>
+    where
+        F: FnMut(usize, usize, T) -> T,
+    {
+        let mut augmented = self.view().into_owned();
+        augmented.row_checksums = None;
+
+        for lag_index in 0..self.num_lags {
+            for series in 0..self.series_count {
+                let shift = shift_of(self.lags[lag_index], self.directions[series]);
+                for t in 0..self.series_length {
+                    if self.is_padding(lag_index, series, t) {
+                        continue;
+                    }
+
+                    let step = (self.time_offset + t).wrapping_sub_signed(shift);
+                    let offset = self.offset_of(lag_index, series, t);
+                    let value = augmented.data[offset];
+                    augmented.data[offset] = value + noise(series, step, value);
+                }
+            }
+        }
+
+        augmented
+    }
+}
+
+/// The number of knots at which the pace of a time warp is drawn.
+const WARP_KNOTS: usize = 4;
+
+/// Draws the warped position of every time step of a series of the specified length,
+/// increasing from the first to the last time step; see [`LagMatrix::augment_time_warp`].
+fn warp_positions<T: Float>(rng: &mut SplitMix64, sigma: T, length: usize) -> Vec<T> {
+    if length < 2 {
+        return vec![T::zero(); length];
+    }
+
+    let knots: Vec<T> = (0..WARP_KNOTS)
+        .map(|_| (sigma * T::from(rng.next_gaussian()).expect("the value is finite")).exp())
+        .collect();
+    let last = T::from(length - 1).expect("the length is representable");
+    let segments = T::from(WARP_KNOTS - 1).expect("the count is representable");
+    let pace = |step: usize| {
+        let position = T::from(step).expect("the step is representable") / last * segments;
+        let knot = position.floor().to_usize().unwrap_or(0).min(WARP_KNOTS - 2);
+        let weight = position - T::from(knot).expect("the knot is representable");
+        knots[knot] + (knots[knot + 1] - knots[knot]) * weight
+    };
+
+    let mut positions = Vec::with_capacity(length);
+    let mut position = T::zero();
+    positions.push(position);
+    for step in 1..length {
+        position = position + pace(step - 1);
+        positions.push(position);
+    }
+
+    // Rescale the warped axis to the series, pinning the last position against rounding.
+    let scale = last / position;
+    for position in &mut positions {
+        *position = (*position * scale).min(last);
+    }
+    positions
+}
+
+/// Draws Gaussian noise of the specified standard deviation.
+fn noise<T: Float>(rng: &mut SplitMix64, std: T) -> T {
+    std * T::from(rng.next_gaussian()).expect("the value is finite")
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{lag_matrix, lag_matrix_2d_directed, LagDirection, MatrixLayout};
+
+    #[test]
+    fn test_padding_and_static_columns_untouched() {
+        let data: Vec<f64> = (0..10).map(f64::from).collect();
+        let directions = [LagDirection::Lag, LagDirection::Lead];
+        let lagged = lag_matrix_2d_directed(
+            &data,
+            MatrixLayout::ColumnMajor(5),
+            [0, 2],
+            &directions,
+            f64::INFINITY,
+            6,
+        )
+        .unwrap()
+        .with_static_columns(&[-1.0]);
+
+        for augmented in [
+            lagged.augment_gaussian(1.0, 1),
+            lagged.augment_jitter(1.0, 1),
+        ] {
+            for (row, (original, augmented)) in lagged.rows().zip(augmented.rows()).enumerate() {
+                for (col, (&a, &b)) in original.iter().zip(augmented).enumerate() {
+                    let untouched = a == f64::INFINITY || col == 4;
+                    assert_eq!(a == b, untouched, "row {row}, column {col}");
+                }
+            }
+            assert_eq!(augmented[5], lagged[5]);
+        }
+    }
+
+    #[test]
+    fn test_time_warp_keeps_the_series_monotonic() {
+        let data: Vec<f64> = (0..20).map(f64::from).collect();
+        let lagged = lag_matrix(&data, [0, 2, -1], f64::INFINITY, 0).unwrap();
+
+        for seed in 0..8 {
+            let warped = lagged.augment_time_warp(0.8, seed);
+            let current = warped.lag(0).unwrap();
+            assert!(current.windows(2).all(|pair| pair[0] <= pair[1]));
+            assert_eq!((current[0], current[19]), (0.0, 19.0));
+
+            // The warped observations are consistent across lags, and fills remain.
+            assert_eq!(warped.lag(2).unwrap()[2..], current[..18]);
+            assert_eq!(warped.lag(-1).unwrap()[..19], current[1..]);
+            assert_eq!(warped.lag(-1).unwrap()[19], f64::INFINITY);
+        }
+        assert_ne!(
+            lagged.augment_time_warp(0.8, 1),
+            lagged.augment_time_warp(0.8, 2)
+        );
+    }
+
+    #[test]
+    fn test_jitter_is_consistent_across_lags() {
+        let data: Vec<f32> = (0..8).map(|t| t as f32).collect();
+        let lagged = lag_matrix(&data, [0, 3], f32::INFINITY, 0).unwrap();
+        let jittered = lagged.augment_jitter(0.5, 9);
+
+        // Lag three at time step t holds the observation at lag zero of time step t - 3.
+        assert_eq!(jittered.lag(3).unwrap()[3..], jittered.lag(0).unwrap()[..5]);
+        assert_eq!(jittered, lagged.view().augment_jitter(0.5, 9));
+        assert_ne!(jittered, lagged.augment_jitter(0.5, 10));
+    }
+}
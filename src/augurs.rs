@@ -0,0 +1,316 @@
+use crate::{lag_matrix, levinson_durbin, LagError};
+use augurs_core::{Fit, Forecast, ForecastIntervals, ModelError, Predict};
+
+impl ModelError for LagError {}
+
+/// An autoregressive forecasting model for the [augurs](https://crates.io/crates/augurs)
+/// ecosystem, fitting an AR(p) process on the lagged series.
+///
+/// The coefficients are estimated with the Yule–Walker equations, see [`levinson_durbin`],
+/// and the in-sample predictions are computed from the lag matrix of the series. Prediction
+/// intervals assume Gaussian innovations.
+///
+/// ## Example
+/// ```
+/// use augurs_core::prelude::*;
+/// use timelag::AutoregressiveModel;
+///
+/// let data: Vec<f64> = (0..50).map(|t| (t as f64 * 0.3).sin()).collect();
+///
+/// let fitted = AutoregressiveModel::new(2).fit(&data).unwrap();
+/// let forecast = fitted.predict(5, 0.95).unwrap();
+///
+/// assert_eq!(forecast.point.len(), 5);
+/// assert_eq!(forecast.intervals.unwrap().lower.len(), 5);
+/// ```
+#[derive(Debug, Copy, Clone, Eq, PartialEq, Hash)]
+pub struct AutoregressiveModel {
+    order: usize,
+}
+
+impl AutoregressiveModel {
+    /// Creates a model of the order `p`, i.e. regressing on the lags `1..=p`.
+    #[inline(always)]
+    pub const fn new(order: usize) -> Self {
+        Self { order }
+    }
+
+    /// The order `p` of the model.
+    #[inline(always)]
+    pub const fn order(&self) -> usize {
+        self.order
+    }
+}
+
+impl Fit for AutoregressiveModel {
+    type Fitted = FittedAutoregressiveModel;
+    type Error = LagError;
+
+    /// Fits the model to the series.
+    ///
+    /// ## Returns
+    /// The fitted model, or
+    /// * [`LagError::EmptyData`] if the series is empty,
+    /// * [`LagError::InvalidLags`] if the order is zero,
+    /// * [`LagError::LagExceedsValueCount`] if the order is not less than the series length,
+    /// * [`LagError::SingularMatrix`] if the series is constant.
+    fn fit(&self, y: &[f64]) -> Result<Self::Fitted, Self::Error> {
+        if y.is_empty() {
+            return Err(LagError::EmptyData);
+        }
+        if self.order == 0 {
+            return Err(LagError::InvalidLags);
+        }
+        if self.order >= y.len() {
+            return Err(LagError::LagExceedsValueCount);
+        }
+
+        let count = y.len() as f64;
+        let mean = y.iter().sum::<f64>() / count;
+        let centered: Vec<f64> = y.iter().map(|value| value - mean).collect();
+
+        // The biased estimator keeps the autocovariance matrix positive definite.
+        let autocovariance: Vec<f64> = (0..=self.order)
+            .map(|lag| {
+                (lag..centered.len())
+                    .map(|t| centered[t] * centered[t - lag])
+                    .sum::<f64>()
+                    / count
+            })
+            .collect();
+        let fit = levinson_durbin(&autocovariance, self.order)?;
+
+        Ok(FittedAutoregressiveModel {
+            mean,
+            coefficients: fit.coefficients().to_vec(),
+            error_variance: fit.error_variance(),
+            centered,
+        })
+    }
+}
+
+/// An [`AutoregressiveModel`] fitted to a series.
+#[derive(Debug, Clone, PartialEq)]
+pub struct FittedAutoregressiveModel {
+    mean: f64,
+    coefficients: Vec<f64>,
+    error_variance: f64,
+    centered: Vec<f64>,
+}
+
+impl FittedAutoregressiveModel {
+    /// The AR coefficients for the lags `1..=p`.
+    #[inline(always)]
+    pub fn coefficients(&self) -> &[f64] {
+        &self.coefficients
+    }
+
+    /// The mean of the training series.
+    #[inline(always)]
+    pub const fn mean(&self) -> f64 {
+        self.mean
+    }
+
+    /// The variance of the one-step prediction error.
+    #[inline(always)]
+    pub const fn error_variance(&self) -> f64 {
+        self.error_variance
+    }
+
+    /// The prediction of the centered series from its preceding values, most recent first.
+    fn predict_next<'a>(&self, previous: impl Iterator<Item = &'a f64>) -> f64 {
+        self.coefficients
+            .iter()
+            .zip(previous)
+            .map(|(coefficient, value)| coefficient * value)
+            .sum()
+    }
+}
+
+impl Predict for FittedAutoregressiveModel {
+    type Error = LagError;
+
+    /// Computes the one-step predictions of the training series, treating the values before
+    /// its start as the mean.
+    fn predict_in_sample_inplace(
+        &self,
+        level: Option<f64>,
+        forecast: &mut Forecast,
+    ) -> Result<(), Self::Error> {
+        let order = self.coefficients.len();
+        let lagged = lag_matrix(&self.centered, 1..=order, 0.0, 0)?.transpose();
+        let point = lagged
+            .rows()
+            .map(|previous| self.mean + self.predict_next(previous.iter()));
+        let spread = vec![self.error_variance.sqrt(); self.centered.len()];
+        write_forecast(forecast, point, &spread, level);
+        Ok(())
+    }
+
+    /// Forecasts the next `horizon` values recursively.
+    fn predict_inplace(
+        &self,
+        horizon: usize,
+        level: Option<f64>,
+        forecast: &mut Forecast,
+    ) -> Result<(), Self::Error> {
+        let order = self.coefficients.len();
+        let start = self.centered.len().saturating_sub(order);
+        let mut history = self.centered[start..].to_vec();
+        let mut point = Vec::with_capacity(horizon);
+        for _ in 0..horizon {
+            let next = self.predict_next(history.iter().rev());
+            history.push(next);
+            point.push(self.mean + next);
+        }
+
+        // The forecast error variance grows with the ψ weights of the MA(∞) representation.
+        let mut psi = Vec::with_capacity(horizon);
+        let mut sum_of_squares = 0.0;
+        let mut spread = Vec::with_capacity(horizon);
+        for h in 0..horizon {
+            let weight: f64 = if h == 0 {
+                1.0
+            } else {
+                (1..=h.min(order))
+                    .map(|i| self.coefficients[i - 1] * psi[h - i])
+                    .sum()
+            };
+            psi.push(weight);
+            sum_of_squares += weight * weight;
+            spread.push((self.error_variance * sum_of_squares).sqrt());
+        }
+
+        write_forecast(forecast, point.into_iter(), &spread, level);
+        Ok(())
+    }
+
+    #[inline(always)]
+    fn training_data_size(&self) -> usize {
+        self.centered.len()
+    }
+}
+
+/// Appends the point forecasts and, if a level is given, the Gaussian prediction intervals
+/// of the specified standard deviations.
+fn write_forecast(
+    forecast: &mut Forecast,
+    point: impl Iterator<Item = f64>,
+    spread: &[f64],
+    level: Option<f64>,
+) {
+    let start = forecast.point.len();
+    forecast.point.extend(point);
+
+    let Some(level) = level else {
+        return;
+    };
+
+    let z = normal_quantile(0.5 + level / 2.0);
+    let intervals = forecast
+        .intervals
+        .get_or_insert_with(|| ForecastIntervals::with_capacity(level, spread.len()));
+    for (point, spread) in forecast.point[start..].iter().zip(spread) {
+        intervals.lower.push(point - z * spread);
+        intervals.upper.push(point + z * spread);
+    }
+}
+
+/// The quantile function of the standard normal distribution, using Acklam's rational
+/// approximation with a relative error below `1.15e-9`; `NaN` outside of `(0, 1)`.
+fn normal_quantile(p: f64) -> f64 {
+    const A: [f64; 6] = [
+        -3.969683028665376e1,
+        2.209460984245205e2,
+        -2.759285104469687e2,
+        1.38357751867269e2,
+        -3.066479806614716e1,
+        2.506628277459239,
+    ];
+    const B: [f64; 5] = [
+        -5.447609879822406e1,
+        1.615858368580409e2,
+        -1.556989798598866e2,
+        6.680131188771972e1,
+        -1.328068155288572e1,
+    ];
+    const C: [f64; 6] = [
+        -7.784894002430293e-3,
+        -3.223964580411365e-1,
+        -2.400758277161838,
+        -2.549732539343734,
+        4.374664141464968,
+        2.938163982698783,
+    ];
+    const D: [f64; 4] = [
+        7.784695709041462e-3,
+        3.224671290700398e-1,
+        2.445134137142996,
+        3.754408661907416,
+    ];
+    const LOW: f64 = 0.02425;
+
+    if !(p > 0.0 && p < 1.0) {
+        return f64::NAN;
+    }
+
+    let tail = |q: f64| {
+        (((((C[0] * q + C[1]) * q + C[2]) * q + C[3]) * q + C[4]) * q + C[5])
+            / ((((D[0] * q + D[1]) * q + D[2]) * q + D[3]) * q + 1.0)
+    };
+
+    if p < LOW {
+        tail((-2.0 * p.ln()).sqrt())
+    } else if p > 1.0 - LOW {
+        -tail((-2.0 * (1.0 - p).ln()).sqrt())
+    } else {
+        let q = p - 0.5;
+        let r = q * q;
+        (((((A[0] * r + A[1]) * r + A[2]) * r + A[3]) * r + A[4]) * r + A[5]) * q
+            / (((((B[0] * r + B[1]) * r + B[2]) * r + B[3]) * r + B[4]) * r + 1.0)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::synthetic::ar_process;
+
+    #[test]
+    fn test_recovers_ar_process() {
+        let data: Vec<f64> = ar_process(&[0.6, -0.3], 1.0, 5000, 7);
+        let fitted = AutoregressiveModel::new(2).fit(&data).unwrap();
+        assert!((fitted.coefficients()[0] - 0.6).abs() < 0.05);
+        assert!((fitted.coefficients()[1] + 0.3).abs() < 0.05);
+        assert!((fitted.error_variance() - 1.0).abs() < 0.1);
+
+        let in_sample = fitted.predict_in_sample(0.9).unwrap();
+        assert_eq!(in_sample.point.len(), data.len());
+        assert_eq!(in_sample.point[0], fitted.mean());
+
+        // Forecasts revert to the mean while the intervals widen.
+        let forecast = fitted.predict(50, 0.9).unwrap();
+        assert!((forecast.point[49] - fitted.mean()).abs() < 1e-6);
+        let intervals = forecast.intervals.unwrap();
+        let width = |h: usize| intervals.upper[h] - intervals.lower[h];
+        assert!(width(0) < width(49));
+        assert!((width(0) - 2.0 * 1.644854 * fitted.error_variance().sqrt()).abs() < 1e-5);
+    }
+
+    #[test]
+    fn test_invalid_inputs() {
+        let model = AutoregressiveModel::new(3);
+        assert_eq!(model.fit(&[]).unwrap_err(), LagError::EmptyData);
+        assert_eq!(
+            model.fit(&[1.0, 2.0, 3.0]).unwrap_err(),
+            LagError::LagExceedsValueCount
+        );
+        assert_eq!(
+            AutoregressiveModel::new(0).fit(&[1.0]).unwrap_err(),
+            LagError::InvalidLags
+        );
+        assert_eq!(model.fit(&[1.0; 10]).unwrap_err(), LagError::SingularMatrix);
+        assert!(normal_quantile(1.0).is_nan());
+        assert!((normal_quantile(0.975) - 1.959964).abs() < 1e-6);
+    }
+}
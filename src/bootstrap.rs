@@ -0,0 +1,261 @@
+use crate::rng::SplitMix64;
+use crate::{LagError, LagMatrix};
+
+/// Creates a moving block bootstrap over the time steps of a lag matrix.
+///
+/// Every replicate is assembled from blocks of `block_len` consecutive time steps with
+/// uniformly drawn starting points, truncated to the original series length. Keeping
+/// consecutive observations together retains the temporal dependence within each block,
+/// which makes the replicates suitable for quantifying the uncertainty of estimators
+/// fitted on lagged data. Row weights, if any, are resampled alongside the data.
+///
+/// The replicates no longer follow the time axis of the source, such that their lagged gaps
+/// are those of the resampled time steps, tracked as gaps within the series: see
+/// [`has_interior_gaps`](LagMatrix::has_interior_gaps).
+/// [`valid_length`](LagMatrix::valid_length) and [`iter_complete`](LagMatrix::iter_complete)
+/// skip the resampled incomplete time steps, and
+/// [`trim_incomplete`](LagMatrix::trim_incomplete) rejects replicates holding any.
+///
+/// The returned [`BlockBootstrap`] is an iterator producing `n_samples` replicates;
+/// use [`BlockBootstrap::resample_into`] to reuse a matrix buffer across replicates.
+///
+/// ## Arguments
+/// * `matrix` - The lag matrix to resample.
+/// * `block_len` - The number of consecutive time steps per block.
+/// * `n_samples` - The number of replicates to produce.
+/// * `seed` - The seed of the pseudo-random number generator.
+///
+/// ## Returns
+/// The bootstrap, or [`LagError::InvalidLength`] if `block_len` is zero or exceeds the
+/// series length.
+///
+/// ## Example
+/// ```
+/// use timelag::{block_bootstrap, lag_matrix_2d, MatrixLayout};
+///
+/// let data: Vec<f64> = (0..20).map(f64::from).collect();
+/// let lagged = lag_matrix_2d(&data, MatrixLayout::ColumnMajor(20), 0..=1, f64::NAN, 0).unwrap();
+///
+/// let replicates: Vec<_> = block_bootstrap(&lagged, 5, 10, 42).unwrap().collect();
+/// assert_eq!(replicates.len(), 10);
+///
+/// // Within each block, the lag-zero value of one row is the lag-one value of the next.
+/// let first = &replicates[0];
+/// assert_eq!(first[2 + 1], first[0]);
+/// ```
+pub fn block_bootstrap<T>(
+    matrix: &LagMatrix<T>,
+    block_len: usize,
+    n_samples: usize,
+    seed: u64,
+) -> Result<BlockBootstrap<'_, T>, LagError>
+where
+    T: Copy,
+{
+    if block_len == 0 || block_len > matrix.series_length {
+        return Err(LagError::InvalidLength);
+    }
+
+    Ok(BlockBootstrap {
+        matrix,
+        block_len,
+        remaining: n_samples,
+        rng: SplitMix64::new(seed),
+        starts: Vec::with_capacity(matrix.series_length.div_ceil(block_len)),
+    })
+}
+
+/// A moving block bootstrap over the time steps of a lag matrix; see [`block_bootstrap`].
+#[derive(Debug, Clone)]
+pub struct BlockBootstrap<'a, T> {
+    matrix: &'a LagMatrix<T>,
+    block_len: usize,
+    remaining: usize,
+    rng: SplitMix64,
+    starts: Vec<usize>,
+}
+
+impl<T> BlockBootstrap<'_, T>
+where
+    T: Copy,
+{
+    /// The number of consecutive time steps per block.
+    #[inline(always)]
+    pub const fn block_len(&self) -> usize {
+        self.block_len
+    }
+
+    /// The number of replicates left to produce.
+    #[inline(always)]
+    pub const fn remaining(&self) -> usize {
+        self.remaining
+    }
+
+    /// Writes the next replicate into `target`, reusing its allocation.
+    ///
+    /// If `target` does not have the shape of the source matrix, it is overwritten
+    /// with a copy of the source first.
+    ///
+    /// ## Returns
+    /// `true` if a replicate was produced, or `false` if all replicates were produced.
+    pub fn resample_into(&mut self, target: &mut LagMatrix<T>) -> bool {
+        if self.remaining == 0 {
+            return false;
+        }
+        self.remaining -= 1;
+
+        let source = self.matrix;
+        if !same_shape(source, target) {
+            target.clone_from(source);
+        }
+
+        target.row_checksums = None;
+
+        let len = source.series_length;
+        self.starts.clear();
+        for _ in 0..len.div_ceil(self.block_len) {
+            self.starts.push(self.rng.below(len - self.block_len + 1));
+        }
+
+        // Every block is a contiguous run of time steps: whole rows of a column-major
+        // matrix and a segment of every row of a row-major matrix.
+        let stride = source.row_stride;
+        let mut gaps = vec![false; source.features_per_step() * len];
+        for (block, &start) in self.starts.iter().enumerate() {
+            let t = block * self.block_len;
+            let count = self.block_len.min(len - t);
+
+            if source.row_major {
+                for row in 0..source.num_rows {
+                    let offset = row * stride;
+                    target.data[offset + t..offset + t + count]
+                        .copy_from_slice(&source.data[offset + start..offset + start + count]);
+                }
+            } else {
+                target.data[t * stride..(t + count) * stride]
+                    .copy_from_slice(&source.data[start * stride..(start + count) * stride]);
+            }
+
+            if let (Some(source), Some(target)) = (&source.row_weights, &mut target.row_weights) {
+                target[t..t + count].copy_from_slice(&source[start..start + count]);
+            }
+
+            for lag_index in 0..source.num_lags {
+                for series in 0..source.series_count {
+                    for step in 0..count {
+                        gaps[source.gap_index(lag_index, series, t + step)] =
+                            source.is_padding(lag_index, series, start + step);
+                    }
+                }
+            }
+        }
+
+        target.replace_gaps(gaps);
+        true
+    }
+}
+
+impl<T> Iterator for BlockBootstrap<'_, T>
+where
+    T: Copy,
+{
+    type Item = LagMatrix<T>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let mut target = self.matrix.clone();
+        self.resample_into(&mut target).then_some(target)
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        (self.remaining, Some(self.remaining))
+    }
+}
+
+impl<T> ExactSizeIterator for BlockBootstrap<'_, T> where T: Copy {}
+
+/// Determines whether `target` can hold a replicate of `source` without reallocation.
+fn same_shape<T>(source: &LagMatrix<T>, target: &LagMatrix<T>) -> bool {
+    source.data.len() == target.data.len()
+        && source.row_major == target.row_major
+        && source.row_stride == target.row_stride
+        && source.num_rows == target.num_rows
+        && source.num_cols == target.num_cols
+        && source.row_weights.as_ref().map(Vec::len) == target.row_weights.as_ref().map(Vec::len)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{lag_matrix, lag_matrix_2d, MatrixLayout};
+
+    #[test]
+    fn test_blocks_are_contiguous_rowwise() {
+        let data: Vec<i32> = (0..10).collect();
+        let lagged = lag_matrix(&data, [0], -1, 12).unwrap();
+
+        let mut target = LagMatrix::default();
+        let mut bootstrap = block_bootstrap(&lagged, 3, 4, 1).unwrap();
+        while bootstrap.resample_into(&mut target) {
+            assert_eq!(target.len(), 12);
+            assert_eq!(&target[10..], &[-1, -1]);
+            for block in target[..10].chunks(3) {
+                assert!(block.windows(2).all(|pair| pair[1] == pair[0] + 1));
+            }
+        }
+        assert_eq!(bootstrap.remaining(), 0);
+    }
+
+    #[test]
+    fn test_reproducible_with_weights() {
+        let data: Vec<f64> = (0..16).map(f64::from).collect();
+        let lagged = lag_matrix_2d(&data, MatrixLayout::ColumnMajor(8), [0, 1], 0.0, 0)
+            .unwrap()
+            .with_row_weights((0..8).map(f64::from).collect())
+            .unwrap();
+
+        let a: Vec<_> = block_bootstrap(&lagged, 2, 3, 9).unwrap().collect();
+        let b: Vec<_> = block_bootstrap(&lagged, 2, 3, 9).unwrap().collect();
+        assert_eq!(a, b);
+
+        for replicate in &a {
+            // The weights follow their rows.
+            let weights = replicate.row_weights().unwrap();
+            for (t, row) in replicate.chunks(4).enumerate() {
+                assert_eq!(row[0], data[weights[t] as usize * 2]);
+            }
+        }
+    }
+
+    #[test]
+    fn test_replicates_track_resampled_gaps() {
+        let data: Vec<i32> = (1..=12).collect();
+        let lagged = lag_matrix(&data, 0..=2, 0, 0).unwrap();
+
+        for replicate in block_bootstrap(&lagged, 3, 8, 5).unwrap() {
+            let gaps: Vec<bool> = (0..12).map(|t| replicate[24 + t] == 0).collect();
+            let complete: Vec<usize> = replicate.iter_complete().map(|(t, _)| t).collect();
+            let expected: Vec<usize> = (0..12).filter(|&t| !gaps[t]).collect();
+            assert_eq!(complete, expected);
+            assert_eq!(replicate.valid_length(), expected.len());
+
+            if gaps.contains(&true) {
+                assert_eq!(replicate.trim_incomplete(), Err(LagError::InteriorGaps));
+            } else {
+                assert_eq!(replicate.clone().trim_incomplete(), Ok(replicate));
+            }
+        }
+    }
+
+    #[test]
+    fn test_invalid_block_len() {
+        let lagged = lag_matrix(&[1, 2, 3], [0], 0, 0).unwrap();
+        assert_eq!(
+            block_bootstrap(&lagged, 0, 1, 0).unwrap_err(),
+            LagError::InvalidLength
+        );
+        assert_eq!(
+            block_bootstrap(&lagged, 4, 1, 0).unwrap_err(),
+            LagError::InvalidLength
+        );
+    }
+}
@@ -0,0 +1,157 @@
+use crate::{LagError, LagMatrix};
+use core::ops::RangeInclusive;
+use num_traits::Float;
+
+/// Create a matrix of bucketed lag features from a time series.
+///
+/// Instead of one row per lag, every row holds the mean of the values within a bucket of
+/// lags; for example, buckets `1..=3` and `4..=12` produce the mean of the first three lags
+/// and the mean of the fourth to twelfth lag. This reduces the dimensionality for
+/// long-memory series while retaining information about distant lags. Time steps for which
+/// any lag of a bucket would reach before the start of the series are filled with the
+/// placeholder value.
+///
+/// All bucket means are computed in a single pass over the data using running sums.
+/// The resulting matrix reports the largest lag of each bucket as its lag.
+///
+/// ## Arguments
+/// * `data` - The time series data to create bucketed lags of.
+/// * `buckets` - The inclusive ranges of lags to aggregate, one per row.
+/// * `fill` - The value to use to fill in lagged gaps.
+/// * `stride` - The number of elements between rows in the resulting vector.
+///   If set to `0` or `data.len()`, no padding is introduced. Values larger than
+///   `data.len()` creates padding entries set to the `fill` value.
+///
+/// ## Returns
+/// The matrix of bucketed lags, or an error.
+///
+/// ## Example
+/// ```
+/// use timelag::lag_matrix_bucketed;
+///
+/// let data = [1.0, 2.0, 3.0, 4.0, 5.0];
+/// let fill = f64::INFINITY;
+///
+/// let bucketed = lag_matrix_bucketed(&data, [0..=0, 1..=2], fill, 0).unwrap();
+///
+/// assert_eq!(
+///     bucketed,
+///     &[
+///          1.0,  2.0, 3.0, 4.0, 5.0, // lag zero
+///         fill, fill, 1.5, 2.5, 3.5, // mean of lags one and two
+///     ]
+/// );
+/// ```
+pub fn lag_matrix_bucketed<T, R>(
+    data: &[T],
+    buckets: R,
+    fill: T,
+    mut stride: usize,
+) -> Result<LagMatrix<T>, LagError>
+where
+    T: Float,
+    R: IntoIterator<Item = RangeInclusive<usize>>,
+{
+    let buckets = Vec::from_iter(buckets);
+    if buckets.is_empty() || buckets.iter().any(|bucket| bucket.is_empty()) {
+        return Err(LagError::InvalidLags);
+    }
+
+    if data.is_empty() {
+        return Err(LagError::EmptyData);
+    }
+
+    let data_rows = data.len();
+    if buckets.iter().any(|bucket| *bucket.end() > data_rows) {
+        return Err(LagError::LagExceedsValueCount);
+    }
+
+    if stride == 0 {
+        stride = data_rows;
+    }
+
+    if stride < data_rows {
+        return Err(LagError::InvalidStride);
+    }
+
+    let counts = buckets
+        .iter()
+        .map(|bucket| T::from(bucket.end() - bucket.start() + 1))
+        .collect::<Option<Vec<_>>>()
+        .ok_or(LagError::InvalidLags)?;
+
+    let num_lags = buckets.len();
+    let mut lagged = vec![fill; stride * num_lags];
+    let mut sums = vec![T::zero(); num_lags];
+
+    for t in 0..data_rows {
+        for (row, bucket) in buckets.iter().enumerate() {
+            let (start, end) = (*bucket.start(), *bucket.end());
+
+            // Slide the window of values data[t - end ..= t - start].
+            if t >= start {
+                sums[row] = sums[row] + data[t - start];
+            }
+            if t > end {
+                sums[row] = sums[row] - data[t - end - 1];
+            }
+
+            if t >= end {
+                lagged[row * stride + t] = sums[row] / counts[row];
+            }
+        }
+    }
+
+    let lags = buckets
+        .iter()
+        .map(|bucket| *bucket.end() as isize)
+        .collect();
+    Ok(LagMatrix::new(lagged, true, data_rows, 1, lags, stride))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_bucketed_matches_naive_means() {
+        let data: Vec<f64> = (0..30).map(|i| ((i * 37) % 11) as f64).collect();
+        let buckets = [1..=3, 4..=12, 13..=24, 2..=2];
+        let fill = f64::NEG_INFINITY;
+
+        let bucketed = lag_matrix_bucketed(&data, buckets.clone(), fill, 32).unwrap();
+        assert_eq!(bucketed.num_rows(), 4);
+        assert_eq!(bucketed.num_cols(), 30);
+        assert_eq!(bucketed.row_stride(), 32);
+
+        for (row, bucket) in buckets.iter().enumerate() {
+            for t in 0..data.len() {
+                let actual = bucketed[row * 32 + t];
+                if t < *bucket.end() {
+                    assert_eq!(actual, fill);
+                } else {
+                    let values: Vec<_> = bucket.clone().map(|lag| data[t - lag]).collect();
+                    let expected = values.iter().sum::<f64>() / values.len() as f64;
+                    assert!((actual - expected).abs() < 1e-9);
+                }
+            }
+
+            // The stride padding remains filled.
+            assert_eq!(bucketed[row * 32 + 30], fill);
+        }
+    }
+
+    #[test]
+    #[allow(clippy::reversed_empty_ranges)]
+    fn test_bucketed_invalid_buckets() {
+        let data = [1.0, 2.0];
+        assert_eq!(
+            lag_matrix_bucketed(&data, [2..=1], 0.0, 0).unwrap_err(),
+            LagError::InvalidLags
+        );
+        assert_eq!(
+            lag_matrix_bucketed(&data, [1..=3], 0.0, 0).unwrap_err(),
+            LagError::LagExceedsValueCount
+        );
+    }
+}
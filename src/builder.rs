@@ -0,0 +1,724 @@
+use crate::parallel::lag_matrix_2d_parallel;
+use crate::strided::lag_matrix_2d_strided;
+use crate::{
+    collect_lags, lag_matrix_2d, IntoLag, LagError, LagMatrix, LagPlan, MatrixLayout, OutputLayout,
+    ParallelConfig,
+};
+use std::borrow::Cow;
+
+/// Configures the creation of lag matrices.
+///
+/// ## Example
+/// ```
+/// use timelag::LagMatrixBuilder;
+///
+/// let data = [1.0, 2.0, 3.0, 4.0];
+/// let fill = f64::INFINITY;
+///
+/// // One row per time step, with all lags of a time step next to each other.
+/// let lagged = LagMatrixBuilder::new(fill)
+///     .unit_stride(true)
+///     .build(&data, 0..=1)
+///     .unwrap();
+///
+/// assert_eq!(lagged.num_rows(), 4);
+/// assert_eq!(lagged.lda(), 2);
+/// assert_eq!(
+///     lagged,
+///     &[
+///         1.0, fill,
+///         2.0, 1.0,
+///         3.0, 2.0,
+///         4.0, 3.0,
+///     ]
+/// );
+/// ```
+#[derive(Debug, Clone, PartialEq)]
+pub struct LagMatrixBuilder<T> {
+    fill: T,
+    row_stride: usize,
+    col_stride: usize,
+    output_layout: Option<OutputLayout>,
+    exclude_zero_lag: bool,
+    trim_incomplete: bool,
+    parallel: Option<Parallel<T>>,
+    lag_fills: Option<Vec<T>>,
+}
+
+/// A matrix validated by [`LagMatrixBuilder::plan`] whose buffer is yet to be filled.
+pub(crate) struct PlannedMatrix<T> {
+    pub(crate) plan: LagPlan,
+    pub(crate) lagged: Vec<T>,
+    fills: Option<Vec<T>>,
+}
+
+/// The kernel filling a matrix on multiple threads; see [`lag_matrix_2d_parallel`].
+type ParallelKernel<T> =
+    fn(&[T], MatrixLayout, Vec<isize>, T, usize, &ParallelConfig) -> Result<LagMatrix<T>, LagError>;
+
+/// The parallel configuration of a builder, together with the kernel instantiated for the
+/// element type when it was set, i.e. while `T: Send + Sync` was known to hold.
+#[derive(Clone)]
+struct Parallel<T> {
+    config: ParallelConfig,
+    kernel: ParallelKernel<T>,
+}
+
+impl<T> core::fmt::Debug for Parallel<T> {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        self.config.fmt(f)
+    }
+}
+
+impl<T> PartialEq for Parallel<T> {
+    fn eq(&self, other: &Self) -> bool {
+        self.config == other.config
+    }
+}
+
+/// Fills the matrix on multiple threads, capturing the `Send + Sync` bound.
+fn parallel_kernel<T: Copy + Send + Sync>(
+    data_matrix: &[T],
+    layout: MatrixLayout,
+    lags: Vec<isize>,
+    fill: T,
+    row_stride: usize,
+    config: &ParallelConfig,
+) -> Result<LagMatrix<T>, LagError> {
+    lag_matrix_2d_parallel(data_matrix, layout, lags, None, fill, row_stride, config)
+}
+
+impl<T> LagMatrixBuilder<T>
+where
+    T: Copy,
+{
+    /// Creates a builder using the specified value to fill in lagged gaps.
+    pub const fn new(fill: T) -> Self {
+        Self {
+            fill,
+            row_stride: 0,
+            col_stride: 1,
+            output_layout: None,
+            exclude_zero_lag: false,
+            trim_incomplete: false,
+            parallel: None,
+            lag_fills: None,
+        }
+    }
+
+    /// Sets the number of elements between subsequent rows of the resulting matrix.
+    ///
+    /// If set to `0` (the default), no padding is introduced. Larger values create
+    /// padding entries set to the fill value.
+    pub const fn row_stride(mut self, row_stride: usize) -> Self {
+        self.row_stride = row_stride;
+        self
+    }
+
+    /// Sets the number of elements between subsequent columns of the matrix written by
+    /// [`build_into`](Self::build_into) or [`build_2d_into`](Self::build_2d_into), e.g. the
+    /// leading dimension of a column-major BLAS or LAPACK work buffer.
+    ///
+    /// The default of `1` stores the columns of a row next to each other. Any other value
+    /// requires writing into a buffer; [`build`](Self::build) and [`build_2d`](Self::build_2d)
+    /// fail with [`LagError::InvalidStride`] since a [`LagMatrix`] has unit column stride.
+    ///
+    /// ## Example
+    /// ```
+    /// use timelag::{LagMatrixBuilder, MatrixLayout};
+    ///
+    /// let data = [1, 2, 3];
+    ///
+    /// // One row per time step, stored column by column with a leading dimension of four.
+    /// let mut buffer = [-1; 8];
+    /// let shape = LagMatrixBuilder::new(0)
+    ///     .row_stride(1)
+    ///     .col_stride(4)
+    ///     .build_2d_into(&mut buffer, &data, MatrixLayout::ColumnMajor(3), 0..=1)
+    ///     .unwrap();
+    ///
+    /// assert_eq!(shape, (3, 2));
+    /// assert_eq!(
+    ///     buffer,
+    ///     [
+    ///         1, 2, 3, -1, // first column
+    ///         0, 1, 2, -1, // second column
+    ///     ]
+    /// );
+    /// ```
+    pub const fn col_stride(mut self, col_stride: usize) -> Self {
+        self.col_stride = col_stride;
+        self
+    }
+
+    /// Guarantees that the values of each time step are contiguous, i.e. that the feature axis
+    /// of the `observations × features` design matrix has unit stride.
+    ///
+    /// When enabled, the result is always column-major with one row per time step, regardless of
+    /// the input layout. The matrix can then be passed to row-major BLAS and LAPACK routines
+    /// directly using its [`ptr`](LagMatrix::ptr), [`num_rows`](LagMatrix::num_rows),
+    /// [`num_cols`](LagMatrix::num_cols) and [`lda`](LagMatrix::lda).
+    ///
+    /// This is a shorthand for [`output_layout`](Self::output_layout) with
+    /// [`OutputLayout::LagsAlongColumns`]; disabling it restores the default.
+    pub const fn unit_stride(mut self, enabled: bool) -> Self {
+        self.output_layout = if enabled {
+            Some(OutputLayout::LagsAlongColumns)
+        } else {
+            None
+        };
+        self
+    }
+
+    /// Sets the orientation of the resulting matrix, regardless of the input layout;
+    /// see [`lag_matrix_2d_oriented`](crate::lag_matrix_2d_oriented).
+    ///
+    /// By default, single series are lagged along the rows and data matrices keep the
+    /// orientation of their layout.
+    ///
+    /// ## Example
+    /// ```
+    /// use timelag::{LagMatrixBuilder, MatrixLayout, OutputLayout};
+    ///
+    /// let data = [1, 10, 2, 20, 3, 30];
+    /// let lagged = LagMatrixBuilder::new(0)
+    ///     .output_layout(OutputLayout::LagsAlongRows)
+    ///     .build_2d(&data, MatrixLayout::ColumnMajor(3), [1])
+    ///     .unwrap();
+    ///
+    /// assert!(lagged.is_row_major());
+    /// assert_eq!(lagged, &[0, 1, 2, 0, 10, 20]);
+    /// ```
+    pub const fn output_layout(mut self, layout: OutputLayout) -> Self {
+        self.output_layout = Some(layout);
+        self
+    }
+
+    /// Omits the zero lag, i.e. the copy of the original series, from the requested lags.
+    ///
+    /// This matches MATLAB's `lagmatrix`, which only contains the requested lags, when porting
+    /// code that passes ranges such as `0..=3`. Requesting only the zero lag then fails with
+    /// [`LagError::InvalidLags`].
+    ///
+    /// ## Example
+    /// ```
+    /// use timelag::LagMatrixBuilder;
+    ///
+    /// let data = [1.0, 2.0, 3.0];
+    /// let lagged = LagMatrixBuilder::new(f64::NAN)
+    ///     .exclude_zero_lag(true)
+    ///     .build(&data, 0..=2)
+    ///     .unwrap();
+    ///
+    /// assert_eq!(lagged.lags(), &[1, 2]);
+    /// ```
+    pub const fn exclude_zero_lag(mut self, enabled: bool) -> Self {
+        self.exclude_zero_lag = enabled;
+        self
+    }
+
+    /// Drops the time steps that contain lagged gaps, keeping only complete cases;
+    /// see [`LagMatrix::trim_incomplete`].
+    ///
+    /// ## Example
+    /// ```
+    /// use timelag::LagMatrixBuilder;
+    ///
+    /// let data = [1.0, 2.0, 3.0, 4.0];
+    /// let lagged = LagMatrixBuilder::new(f64::NAN)
+    ///     .trim_incomplete(true)
+    ///     .build(&data, 0..=1)
+    ///     .unwrap();
+    ///
+    /// assert_eq!(lagged.series_length(), 3);
+    /// assert_eq!(lagged, &[2.0, 3.0, 4.0, 1.0, 2.0, 3.0]);
+    /// ```
+    pub const fn trim_incomplete(mut self, enabled: bool) -> Self {
+        self.trim_incomplete = enabled;
+        self
+    }
+
+    /// Uses a separate fill value for the gaps of each lag, e.g. the series mean for short lags
+    /// and a seasonal default for long ones.
+    ///
+    /// The values are aligned with the lags passed to [`build`](Self::build) or
+    /// [`build_2d`](Self::build_2d), which fail with [`LagError::InvalidLength`] if the counts
+    /// differ. Row padding entries keep the fill value passed to [`new`](Self::new).
+    ///
+    /// ## Example
+    /// ```
+    /// use timelag::LagMatrixBuilder;
+    ///
+    /// let data = [1.0, 2.0, 3.0];
+    /// let lagged = LagMatrixBuilder::new(f64::NAN)
+    ///     .lag_fills(vec![2.0, -1.0])
+    ///     .build(&data, [1, 2])
+    ///     .unwrap();
+    ///
+    /// assert_eq!(
+    ///     lagged,
+    ///     &[
+    ///         2.0, 1.0, 2.0, // first lag
+    ///         -1.0, -1.0, 1.0, // second lag
+    ///     ]
+    /// );
+    /// ```
+    pub fn lag_fills(mut self, fills: Vec<T>) -> Self {
+        self.lag_fills = Some(fills);
+        self
+    }
+
+    /// Creates a lag matrix of a single time series; see [`lag_matrix`](crate::lag_matrix).
+    pub fn build<L, R>(&self, data: &[T], lags: R) -> Result<LagMatrix<T>, LagError>
+    where
+        L: IntoLag,
+        R: IntoIterator<Item = L>,
+    {
+        let output = self.output_layout.unwrap_or_default();
+        self.create(data, output.input_layout(data.len()), lags)
+    }
+
+    /// Creates a lag matrix of multiple time series; see [`lag_matrix_2d`].
+    pub fn build_2d<L, R>(
+        &self,
+        data_matrix: &[T],
+        layout: MatrixLayout,
+        lags: R,
+    ) -> Result<LagMatrix<T>, LagError>
+    where
+        L: IntoLag,
+        R: IntoIterator<Item = L>,
+    {
+        let (data_matrix, layout) = self.convert(data_matrix, layout)?;
+        self.create(&data_matrix, layout, lags)
+    }
+
+    /// The orientation set via [`output_layout`](Self::output_layout), if any.
+    #[inline(always)]
+    pub(crate) const fn output(&self) -> Option<OutputLayout> {
+        self.output_layout
+    }
+
+    /// Converts the data matrix into the layout of the output, borrowing it if it already matches.
+    pub(crate) fn convert<'a>(
+        &self,
+        data_matrix: &'a [T],
+        layout: MatrixLayout,
+    ) -> Result<(Cow<'a, [T]>, MatrixLayout), LagError> {
+        match self.output_layout {
+            Some(output) => output.convert(data_matrix, layout),
+            None => Ok((Cow::Borrowed(data_matrix), layout)),
+        }
+    }
+
+    /// Validates the matrix of a data matrix converted via [`convert`](Self::convert), and
+    /// allocates its buffer pre-filled with the fill value, but without copying any values.
+    ///
+    /// The buffer is filled via [`LagPlan::fill_steps`] and turned into the matrix by
+    /// [`finish`](Self::finish), e.g. to fill several matrices in one scan of the input.
+    pub(crate) fn plan<L, R>(
+        &self,
+        data_matrix: &[T],
+        layout: MatrixLayout,
+        lags: R,
+    ) -> Result<PlannedMatrix<T>, LagError>
+    where
+        L: IntoLag,
+        R: IntoIterator<Item = L>,
+    {
+        if self.col_stride != 1 {
+            return Err(LagError::InvalidStride);
+        }
+
+        let (lags, fills) = self.select_lags(lags)?;
+        let plan = LagPlan::new(data_matrix, layout, lags, None, self.row_stride)?;
+        let lagged = vec![self.fill; plan.unit_len() * plan.num_units()];
+        Ok(PlannedMatrix {
+            plan,
+            lagged,
+            fills,
+        })
+    }
+
+    /// Creates the matrix of a filled plan; see [`plan`](Self::plan).
+    pub(crate) fn finish(&self, planned: PlannedMatrix<T>) -> Result<LagMatrix<T>, LagError> {
+        let matrix = planned.plan.into_matrix(planned.lagged);
+        self.post_process(matrix, planned.fills)
+    }
+
+    /// Applies the post-processing options to a matrix, i.e. the fills per lag and the
+    /// trimming of incomplete time steps.
+    fn post_process(
+        &self,
+        mut matrix: LagMatrix<T>,
+        fills: Option<Vec<T>>,
+    ) -> Result<LagMatrix<T>, LagError> {
+        if let Some(fills) = fills {
+            matrix.fill_gaps(|lag_index, _, _| fills[lag_index]);
+        }
+
+        if self.trim_incomplete {
+            matrix.trim_incomplete()
+        } else {
+            Ok(matrix)
+        }
+    }
+
+    /// Writes a lag matrix of a single time series into a preallocated buffer;
+    /// see [`build_2d_into`](Self::build_2d_into).
+    pub fn build_into<L, R>(
+        &self,
+        buffer: &mut [T],
+        data: &[T],
+        lags: R,
+    ) -> Result<(usize, usize), LagError>
+    where
+        L: IntoLag,
+        R: IntoIterator<Item = L>,
+    {
+        let output = self.output_layout.unwrap_or_default();
+        self.create_into(buffer, data, output.input_layout(data.len()), lags)
+    }
+
+    /// Writes a lag matrix of multiple time series into a preallocated buffer, e.g. a BLAS
+    /// or LAPACK work buffer, without allocating the matrix.
+    ///
+    /// The matrix has the same rows and columns as the one created by
+    /// [`build_2d`](Self::build_2d), but the element at `row` and `col` is written to
+    /// `row * row_stride + col * col_stride`; see [`col_stride`](Self::col_stride). A row
+    /// stride of `0` places the rows right after each other, while a row stride of `1` and
+    /// a column stride of at least the number of rows stores the matrix in column-major
+    /// (Fortran) order. Elements of the buffer between the rows or columns are left
+    /// untouched, and the matrix is always written on the calling thread.
+    ///
+    /// ## Returns
+    /// The number of rows and columns, or an error:
+    /// * [`LagError::InvalidStride`] if the column stride is zero, or the rows and columns
+    ///   would overlap or exceed the address space,
+    /// * [`LagError::InvalidLength`] if the buffer is too short,
+    /// * any error of [`build_2d`](Self::build_2d).
+    pub fn build_2d_into<L, R>(
+        &self,
+        buffer: &mut [T],
+        data_matrix: &[T],
+        layout: MatrixLayout,
+        lags: R,
+    ) -> Result<(usize, usize), LagError>
+    where
+        L: IntoLag,
+        R: IntoIterator<Item = L>,
+    {
+        let (data_matrix, layout) = self.convert(data_matrix, layout)?;
+        self.create_into(buffer, &data_matrix, layout, lags)
+    }
+
+    /// Writes the matrix into the buffer, applying the post-processing options on the fly.
+    fn create_into<L, R>(
+        &self,
+        buffer: &mut [T],
+        data_matrix: &[T],
+        layout: MatrixLayout,
+        lags: R,
+    ) -> Result<(usize, usize), LagError>
+    where
+        L: IntoLag,
+        R: IntoIterator<Item = L>,
+    {
+        let (lags, fills) = self.select_lags(lags)?;
+        let lag = lags
+            .iter()
+            .copied()
+            .max()
+            .unwrap_or(0)
+            .max(0)
+            .unsigned_abs();
+        let lead = lags
+            .iter()
+            .copied()
+            .min()
+            .unwrap_or(0)
+            .min(0)
+            .unsigned_abs();
+        let trim = self.trim_incomplete;
+        let steps = |length: usize| match trim {
+            true => lag.min(length)..length.saturating_sub(lead),
+            false => 0..length,
+        };
+        let fill = |lag_index: usize| fills.as_ref().map_or(self.fill, |fills| fills[lag_index]);
+
+        let strides = (self.row_stride, self.col_stride);
+        lag_matrix_2d_strided(
+            buffer,
+            data_matrix,
+            layout,
+            lags,
+            fill,
+            steps,
+            strides.0,
+            strides.1,
+        )
+    }
+
+    /// Collects the requested lags without the excluded zero lag, together with their fills.
+    fn select_lags<L, R>(&self, lags: R) -> Result<(Vec<isize>, Option<Vec<T>>), LagError>
+    where
+        L: IntoLag,
+        R: IntoIterator<Item = L>,
+    {
+        let requested = collect_lags(lags)?;
+        let keep = |lag: isize| !self.exclude_zero_lag || lag != 0;
+        let fills = match &self.lag_fills {
+            Some(fills) if fills.len() != requested.len() => return Err(LagError::InvalidLength),
+            Some(fills) => Some(
+                requested
+                    .iter()
+                    .zip(fills)
+                    .filter(|(&lag, _)| keep(lag))
+                    .map(|(_, &fill)| fill)
+                    .collect(),
+            ),
+            None => None,
+        };
+        let lags = requested.into_iter().filter(|&lag| keep(lag)).collect();
+        Ok((lags, fills))
+    }
+
+    /// Creates the matrix and applies the post-processing options.
+    fn create<L, R>(
+        &self,
+        data_matrix: &[T],
+        layout: MatrixLayout,
+        lags: R,
+    ) -> Result<LagMatrix<T>, LagError>
+    where
+        L: IntoLag,
+        R: IntoIterator<Item = L>,
+    {
+        if self.col_stride != 1 {
+            return Err(LagError::InvalidStride);
+        }
+
+        let (lags, fills) = self.select_lags(lags)?;
+        let (fill, row_stride) = (self.fill, self.row_stride);
+        let matrix = match &self.parallel {
+            Some(parallel) => (parallel.kernel)(
+                data_matrix,
+                layout,
+                lags,
+                fill,
+                row_stride,
+                &parallel.config,
+            ),
+            None => lag_matrix_2d(data_matrix, layout, lags, fill, row_stride),
+        }?;
+        self.post_process(matrix, fills)
+    }
+}
+
+impl<T> LagMatrixBuilder<T>
+where
+    T: Copy + Send + Sync,
+{
+    /// Fills the matrix on multiple threads, limited by the specified configuration.
+    ///
+    /// By default, matrices are created on the calling thread, such that only this option
+    /// requires the element type to be shareable between threads.
+    pub const fn parallel(mut self, config: ParallelConfig) -> Self {
+        self.parallel = Some(Parallel {
+            config,
+            kernel: parallel_kernel::<T>,
+        });
+        self
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::lag_matrix;
+
+    #[test]
+    #[rustfmt::skip]
+    fn test_unit_stride_row_major_input() {
+        let data = [
+            1, 2, 3,
+            10, 20, 30,
+        ];
+
+        let builder = LagMatrixBuilder::new(0).unit_stride(true).row_stride(5);
+        let lagged = builder.build_2d(&data, MatrixLayout::RowMajor(3), [0, 1]).unwrap();
+
+        assert!(lagged.is_column_major());
+        assert_eq!(lagged.lda(), 5);
+        assert_eq!(lagged, &[
+            1, 10, 0,  0, 0,
+            2, 20, 1, 10, 0,
+            3, 30, 2, 20, 0,
+        ]);
+    }
+
+    #[test]
+    fn test_exclude_zero_lag_only() {
+        let builder = LagMatrixBuilder::new(0).exclude_zero_lag(true);
+        assert_eq!(
+            builder.build(&[1, 2], [0]).unwrap_err(),
+            LagError::InvalidLags
+        );
+        assert_eq!(
+            builder
+                .build_2d(&[1, 2, 3, 4], MatrixLayout::RowMajor(2), [-1, 0])
+                .unwrap()
+                .lags(),
+            &[-1]
+        );
+    }
+
+    #[test]
+    #[rustfmt::skip]
+    fn test_trim_incomplete_unit_stride() {
+        let data = [
+            1, 2, 3,
+            10, 20, 30,
+        ];
+
+        let builder = LagMatrixBuilder::new(0).unit_stride(true).trim_incomplete(true);
+        let lagged = builder.build_2d(&data, MatrixLayout::RowMajor(3), [-1, 1]).unwrap();
+
+        assert_eq!(lagged.time_offset(), 1);
+        assert_eq!(lagged, &[3, 30, 1, 10]);
+    }
+
+    #[test]
+    fn test_parallel_matches_sequential() {
+        let data: Vec<i32> = (0..64).collect();
+        let config = ParallelConfig {
+            threads: 4,
+            min_chunk_elems: 8,
+        };
+
+        for unit_stride in [false, true] {
+            let builder = LagMatrixBuilder::new(-1).unit_stride(unit_stride);
+            assert_eq!(
+                builder
+                    .clone()
+                    .parallel(config)
+                    .build(&data, 0..=5)
+                    .unwrap(),
+                builder.build(&data, 0..=5).unwrap()
+            );
+        }
+    }
+
+    #[test]
+    #[rustfmt::skip]
+    fn test_lag_fills_are_aligned_with_requested_lags() {
+        let data = [1, 2, 3];
+        let builder = LagMatrixBuilder::new(0)
+            .exclude_zero_lag(true)
+            .unit_stride(true)
+            .row_stride(3)
+            .lag_fills(vec![7, -1, -2]);
+
+        assert_eq!(builder.build(&data, [0, 1, -1]).unwrap(), &[
+            -1, 2, 0,
+             1, 3, 0,
+             2, -2, 0,
+        ]);
+        assert_eq!(
+            builder.build(&data, [1, 2]).unwrap_err(),
+            LagError::InvalidLength
+        );
+    }
+
+    #[test]
+    fn test_default_matches_free_functions() {
+        let data = [1, 2, 3, 4];
+        let builder = LagMatrixBuilder::new(-1).row_stride(6);
+        assert_eq!(
+            builder.build(&data, 0..=2).unwrap(),
+            lag_matrix(&data, 0..=2, -1, 6).unwrap()
+        );
+        assert_eq!(
+            builder
+                .build_2d(&data, MatrixLayout::RowMajor(2), [1])
+                .unwrap(),
+            lag_matrix_2d(&data, MatrixLayout::RowMajor(2), [1], -1, 6).unwrap()
+        );
+    }
+
+    #[test]
+    fn test_build_into_matches_build() {
+        let data = [1, 10, 2, 20, 3, 30, 4, 40];
+        let layout = MatrixLayout::ColumnMajor(4);
+        let builder = LagMatrixBuilder::new(0)
+            .exclude_zero_lag(true)
+            .trim_incomplete(true)
+            .lag_fills(vec![-1, -2, -3]);
+
+        let expected = builder.build_2d(&data, layout, [0, 2, -1]).unwrap();
+        let mut buffer = vec![-9; expected.len()];
+        let shape = builder
+            .build_2d_into(&mut buffer, &data, layout, [0, 2, -1])
+            .unwrap();
+        assert_eq!(shape, (expected.num_rows(), expected.num_cols()));
+        assert_eq!(&buffer[..], &expected[..]);
+
+        let builder = LagMatrixBuilder::new(0).trim_incomplete(true);
+        let expected = builder.build(&data, [1]).unwrap();
+        let mut buffer = vec![-9; 16];
+        let builder = builder.row_stride(1).col_stride(2);
+        assert_eq!(builder.build_into(&mut buffer, &data, [1]), Ok((1, 7)));
+        let written: Vec<i32> = buffer.iter().copied().step_by(2).take(7).collect();
+        assert_eq!(&written[..], &expected[..]);
+        assert_eq!(buffer[1], -9);
+        assert_eq!(builder.build(&data, [1]), Err(LagError::InvalidStride));
+    }
+
+    #[test]
+    fn test_build_into_invalid_strides() {
+        let data = [1, 2, 3];
+        let layout = MatrixLayout::RowMajor(3);
+        let mut buffer = [0; 16];
+        for (row_stride, col_stride) in [(1, 0), (2, 1), (1, 1), (3, 2)] {
+            let builder = LagMatrixBuilder::new(0)
+                .row_stride(row_stride)
+                .col_stride(col_stride);
+            assert_eq!(
+                builder.build_2d_into(&mut buffer, &data, layout, 0..=1),
+                Err(LagError::InvalidStride)
+            );
+        }
+
+        let builder = LagMatrixBuilder::new(0).row_stride(6);
+        assert_eq!(
+            builder.build_2d_into(&mut buffer[..8], &data, layout, 0..=1),
+            Err(LagError::InvalidLength)
+        );
+        let builder = LagMatrixBuilder::new(0).row_stride(1).col_stride(2);
+        assert!(builder
+            .build_2d_into(&mut buffer, &data, layout, 0..=1)
+            .is_ok());
+        let builder = LagMatrixBuilder::new(0).col_stride(usize::MAX / 2);
+        assert_eq!(
+            builder.build_2d_into(&mut buffer, &data, layout, 0..=1),
+            Err(LagError::InvalidStride)
+        );
+    }
+
+    #[test]
+    fn test_sequential_build_does_not_require_sync() {
+        // Raw pointers are neither `Send` nor `Sync`.
+        let values = [1, 2, 3];
+        let data: Vec<*const i32> = values.iter().map(|value| value as *const i32).collect();
+        let lagged = LagMatrixBuilder::new(core::ptr::null())
+            .build(&data, [1])
+            .unwrap();
+
+        assert!(lagged[0].is_null());
+        assert_eq!(lagged[1], data[0]);
+        assert_eq!(
+            LagMatrixBuilder::new(0).parallel(ParallelConfig::default()),
+            LagMatrixBuilder::new(0).parallel(ParallelConfig::default())
+        );
+    }
+}
@@ -0,0 +1,192 @@
+use crate::LagMatrix;
+use core::fmt::{Display, Formatter};
+use core::ops::Range;
+use num_traits::ToBytes;
+
+/// The FNV-1a offset basis.
+const FNV_OFFSET: u64 = 0xcbf2_9ce4_8422_2325;
+
+/// The FNV-1a prime.
+const FNV_PRIME: u64 = 0x0000_0100_0000_01b3;
+
+impl<T, S> LagMatrix<T, S> {
+    /// The per-row checksums attached via [`with_row_checksums`](Self::with_row_checksums), if any.
+    ///
+    /// Operations that modify the matrix, e.g. adding static columns, discard the checksums.
+    #[inline(always)]
+    pub fn row_checksums(&self) -> Option<&[u64]> {
+        self.row_checksums.as_deref()
+    }
+}
+
+impl<T, S> LagMatrix<T, S>
+where
+    T: ToBytes,
+    S: AsRef<[T]>,
+{
+    /// Computes a checksum of the logical values of the specified rows.
+    ///
+    /// The checksum is the 64-bit FNV-1a hash over the little-endian bytes of the values,
+    /// row by row, such that it is identical on every platform. Row padding is not included.
+    ///
+    /// ## Returns
+    /// The checksum, or `None` if the rows are out of range.
+    pub fn checksum_region(&self, rows: Range<usize>) -> Option<u64> {
+        if rows.start > rows.end || rows.end > self.num_rows {
+            return None;
+        }
+
+        Some(
+            rows.map(|row| self.row_values(row))
+                .fold(FNV_OFFSET, |hash, values| fnv1a(hash, values)),
+        )
+    }
+
+    /// Attaches a checksum of every row, such that long-lived matrices, e.g. cached in
+    /// shared memory or files, can be validated cheaply via [`verify_rows`](Self::verify_rows).
+    ///
+    /// ## Example
+    /// ```
+    /// use timelag::lag_matrix;
+    ///
+    /// let lagged = lag_matrix(&[1.0, 2.0, 3.0], 0..=2, 0.0, 0)
+    ///     .unwrap()
+    ///     .with_row_checksums();
+    ///
+    /// assert_eq!(lagged.row_checksums().map(<[u64]>::len), Some(3));
+    /// assert_eq!(lagged.verify_rows(0..3), Ok(()));
+    /// ```
+    pub fn with_row_checksums(mut self) -> Self {
+        let checksums = (0..self.num_rows)
+            .map(|row| fnv1a(FNV_OFFSET, self.row_values(row)))
+            .collect();
+        self.row_checksums = Some(checksums);
+        self
+    }
+
+    /// Verifies the specified rows against the checksums attached via
+    /// [`with_row_checksums`](Self::with_row_checksums).
+    ///
+    /// ## Returns
+    /// `Ok(())` if all rows match, or the reason of the first failure.
+    pub fn verify_rows(&self, rows: Range<usize>) -> Result<(), IntegrityError> {
+        let Some(checksums) = &self.row_checksums else {
+            return Err(IntegrityError::MissingChecksums);
+        };
+
+        let Some(expected) = checksums.get(rows.clone()) else {
+            return Err(IntegrityError::OutOfRange);
+        };
+
+        for (row, &expected) in rows.zip(expected) {
+            if fnv1a(FNV_OFFSET, self.row_values(row)) != expected {
+                return Err(IntegrityError::Mismatch { row });
+            }
+        }
+        Ok(())
+    }
+
+    /// The logical values of a row, i.e. excluding row padding.
+    fn row_values(&self, row: usize) -> &[T] {
+        let start = row * self.row_stride;
+        &self.data.as_ref()[start..start + self.num_cols]
+    }
+}
+
+/// Continues an FNV-1a hash with the little-endian bytes of the values.
+fn fnv1a<T: ToBytes>(hash: u64, values: &[T]) -> u64 {
+    values.iter().fold(hash, |hash, value| {
+        value
+            .to_le_bytes()
+            .as_ref()
+            .iter()
+            .fold(hash, |hash, &byte| {
+                (hash ^ u64::from(byte)).wrapping_mul(FNV_PRIME)
+            })
+    })
+}
+
+/// A reason why rows of a lag matrix could not be verified; see [`LagMatrix::verify_rows`].
+#[derive(Debug, Copy, Clone, Eq, PartialEq)]
+pub enum IntegrityError {
+    /// The matrix has no checksums attached.
+    MissingChecksums,
+    /// The rows are out of range.
+    OutOfRange,
+    /// The values of a row do not match its checksum.
+    Mismatch {
+        /// The first row that does not match.
+        row: usize,
+    },
+}
+
+impl std::error::Error for IntegrityError {}
+
+impl Display for IntegrityError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            IntegrityError::MissingChecksums => write!(f, "The matrix has no row checksums"),
+            IntegrityError::OutOfRange => write!(f, "The rows are out of range"),
+            IntegrityError::Mismatch { row } => {
+                write!(f, "The values of row {row} do not match its checksum")
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{block_bootstrap, lag_matrix_2d, MatrixLayout};
+
+    #[test]
+    fn test_checksums_detect_corruption() {
+        let data: Vec<f64> = (0..20).map(f64::from).collect();
+        let lagged = lag_matrix_2d(&data, MatrixLayout::ColumnMajor(10), 0..=2, 0.0, 8)
+            .unwrap()
+            .with_row_checksums();
+
+        let mut values: Vec<f64> = lagged.clone().into();
+        values[5 * 8 + 3] += 1.0;
+        let mut corrupted = lagged.clone();
+        corrupted.data = values;
+
+        assert_eq!(lagged.verify_rows(0..10), Ok(()));
+        assert_eq!(corrupted.verify_rows(0..5), Ok(()));
+        assert_eq!(
+            corrupted.verify_rows(0..10),
+            Err(IntegrityError::Mismatch { row: 5 })
+        );
+        assert_eq!(lagged.verify_rows(8..11), Err(IntegrityError::OutOfRange));
+    }
+
+    #[test]
+    fn test_checksum_region() {
+        let data = [1u32, 2, 3, 4];
+        let padded = lag_matrix_2d(&data, MatrixLayout::RowMajor(4), [0, 1], 0, 6).unwrap();
+        let unpadded = lag_matrix_2d(&data, MatrixLayout::RowMajor(4), [0, 1], 0, 0).unwrap();
+
+        // Row padding does not contribute.
+        assert_eq!(padded.checksum_region(0..2), unpadded.checksum_region(0..2));
+        assert_ne!(padded.checksum_region(0..1), padded.checksum_region(1..2));
+        assert_eq!(padded.checksum_region(1..3), None);
+
+        // The FNV-1a hash of no bytes is the offset basis.
+        assert_eq!(padded.checksum_region(1..1), Some(FNV_OFFSET));
+    }
+
+    #[test]
+    fn test_modifications_discard_checksums() {
+        let data: Vec<i64> = (0..8).collect();
+        let lagged = lag_matrix_2d(&data, MatrixLayout::ColumnMajor(8), [0], 0, 0)
+            .unwrap()
+            .with_row_checksums();
+
+        let replicate = block_bootstrap(&lagged, 2, 1, 7).unwrap().next().unwrap();
+        assert_eq!(replicate.row_checksums(), None);
+        assert_eq!(
+            lagged.with_static_columns(&[1]).verify_rows(0..1),
+            Err(IntegrityError::MissingChecksums)
+        );
+    }
+}
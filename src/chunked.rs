@@ -0,0 +1,190 @@
+use crate::{collect_lags, IntoLag, LagError, LagMatrix};
+
+/// Lags a long time series chunk by chunk, carrying the history over chunk boundaries.
+///
+/// Each chunk yields the rows of the full column-major lag matrix that correspond to its
+/// time steps. The last values of previous chunks are kept so that lags reaching back across
+/// a chunk boundary are populated rather than filled; only the first time steps of the whole
+/// series are padded. Concatenating the chunks results in the same matrix as passing the
+/// entire series to [`lag_matrix_2d`](crate::lag_matrix_2d) with a
+/// [`ColumnMajor`](crate::MatrixLayout::ColumnMajor) layout.
+///
+/// ## Example
+/// ```
+/// use timelag::ChunkedLagger;
+///
+/// let mut lagger = ChunkedLagger::new([0, 2], f64::INFINITY).unwrap();
+///
+/// let first = lagger.push_chunk(&[1.0, 2.0]).unwrap();
+/// assert_eq!(first, &[1.0, f64::INFINITY, 2.0, f64::INFINITY]);
+///
+/// // The second lag of the next chunk refers back into the first one.
+/// let second = lagger.push_chunk(&[3.0, 4.0]).unwrap();
+/// assert_eq!(second, &[3.0, 1.0, 4.0, 2.0]);
+/// assert_eq!(second.time_offset(), 2);
+/// ```
+#[derive(Debug, Clone)]
+pub struct ChunkedLagger<T> {
+    lags: Vec<usize>,
+    max_lag: usize,
+    fill: T,
+    history: Vec<T>,
+    position: usize,
+}
+
+impl<T> ChunkedLagger<T>
+where
+    T: Copy,
+{
+    /// Creates a new lagger for the specified lags.
+    ///
+    /// ## Arguments
+    /// * `lags` - The lags to produce; leads are not supported since they require
+    ///   values of future chunks.
+    /// * `fill` - The value to use to fill in lagged gaps at the start of the series.
+    ///
+    /// ## Returns
+    /// The lagger, or [`LagError::InvalidLags`] if no lags or any leads were specified.
+    pub fn new<L, R>(lags: R, fill: T) -> Result<Self, LagError>
+    where
+        L: IntoLag,
+        R: IntoIterator<Item = L>,
+    {
+        let lags = collect_lags(lags)?
+            .into_iter()
+            .map(|lag| usize::try_from(lag).map_err(|_| LagError::InvalidLags))
+            .collect::<Result<Vec<_>, _>>()?;
+
+        let Some(&max_lag) = lags.iter().max() else {
+            return Err(LagError::InvalidLags);
+        };
+
+        Ok(Self {
+            lags,
+            max_lag,
+            fill,
+            history: Vec::with_capacity(max_lag),
+            position: 0,
+        })
+    }
+
+    /// The lags produced by this lagger.
+    #[inline(always)]
+    pub fn lags(&self) -> &[usize] {
+        &self.lags
+    }
+
+    /// The number of values consumed so far, i.e. the time step of the next chunk's first value.
+    #[inline(always)]
+    pub const fn position(&self) -> usize {
+        self.position
+    }
+
+    /// Lags the next chunk of the series.
+    ///
+    /// ## Returns
+    /// A column-major matrix with one row per value of the chunk, whose
+    /// [`time_offset`](LagMatrix::time_offset) is the position of the chunk in the series,
+    /// or [`LagError::EmptyData`] if the chunk is empty.
+    pub fn push_chunk(&mut self, chunk: &[T]) -> Result<LagMatrix<T>, LagError> {
+        if chunk.is_empty() {
+            return Err(LagError::EmptyData);
+        }
+
+        let history = self.history.len();
+        let mut data = Vec::with_capacity(chunk.len() * self.lags.len());
+        for t in 0..chunk.len() {
+            data.extend(self.lags.iter().map(|&lag| {
+                if lag <= t {
+                    chunk[t - lag]
+                } else if lag - t <= history {
+                    self.history[history - (lag - t)]
+                } else {
+                    self.fill
+                }
+            }));
+        }
+
+        let offset = self.position;
+        self.position += chunk.len();
+        self.retain_history(chunk);
+
+        let lags = self.lags.iter().map(|&lag| lag as isize).collect();
+        let stride = self.lags.len();
+        Ok(LagMatrix::new(data, false, chunk.len(), 1, lags, stride)
+            .with_window(offset, self.position))
+    }
+
+    /// Forgets the history, such that the next chunk starts a new series.
+    pub fn reset(&mut self) {
+        self.history.clear();
+        self.position = 0;
+    }
+
+    /// Keeps the last `max_lag` values seen for the next chunk.
+    fn retain_history(&mut self, chunk: &[T]) {
+        if chunk.len() >= self.max_lag {
+            self.history.clear();
+            self.history
+                .extend_from_slice(&chunk[chunk.len() - self.max_lag..]);
+        } else {
+            self.history.extend_from_slice(chunk);
+            let excess = self.history.len().saturating_sub(self.max_lag);
+            self.history.drain(..excess);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{lag_matrix_2d, MatrixLayout};
+
+    #[test]
+    fn test_chunks_match_full_matrix() {
+        let data: Vec<i32> = (1..=23).collect();
+        let full = lag_matrix_2d(&data, MatrixLayout::ColumnMajor(23), [0, 1, 5], -1, 0).unwrap();
+
+        // Chunks both shorter and longer than the largest lag.
+        let mut lagger = ChunkedLagger::new([0, 1, 5], -1).unwrap();
+        let mut chunked = Vec::new();
+        for chunk in [&data[..3], &data[3..4], &data[4..15], &data[15..]] {
+            chunked.extend_from_slice(&lagger.push_chunk(chunk).unwrap());
+        }
+
+        assert_eq!(full, chunked.as_slice());
+        assert_eq!(lagger.position(), 23);
+    }
+
+    #[test]
+    #[cfg(feature = "float")]
+    fn test_padding_is_relative_to_series() {
+        let mut lagger = ChunkedLagger::new([1], 0.0).unwrap();
+        let first = lagger.push_chunk(&[1.0, 2.0]).unwrap();
+        let second = lagger.push_chunk(&[3.0, 4.0]).unwrap();
+        assert!(first.gemm_ready().is_err());
+        assert_eq!(second.gemm_ready(), Ok((2, 1, 1)));
+
+        lagger.reset();
+        assert!(lagger.push_chunk(&[5.0]).unwrap().gemm_ready().is_err());
+    }
+
+    #[test]
+    fn test_invalid_lags() {
+        assert_eq!(
+            ChunkedLagger::new([0, -1], 0).unwrap_err(),
+            LagError::InvalidLags
+        );
+        assert_eq!(
+            ChunkedLagger::<i32>::new(Vec::<usize>::new(), 0).unwrap_err(),
+            LagError::InvalidLags
+        );
+        assert_eq!(
+            ChunkedLagger::new([1], 0)
+                .unwrap()
+                .push_chunk(&[])
+                .unwrap_err(),
+            LagError::EmptyData
+        );
+    }
+}
@@ -0,0 +1,248 @@
+use crate::{LagError, LagMatrix, LagMatrixView};
+
+impl<T, S> LagMatrix<T, S>
+where
+    T: Copy,
+    S: AsRef<[T]>,
+{
+    /// Appends the lags of another matrix of the same series, e.g. to combine the short-term
+    /// and the seasonal lags of separate runs into one feature block.
+    ///
+    /// The lags of this matrix are followed by those of the other matrix, i.e. the rows of
+    /// row-major and the columns of column-major matrices are stacked. Row weights are
+    /// discarded. The result has no row padding.
+    ///
+    /// ## Returns
+    /// The concatenated matrix, or
+    /// * [`LagError::InvalidLags`] if both matrices contain the same lag,
+    /// * [`LagError::InvalidChannel`] if the series or their directions differ,
+    /// * [`LagError::InvalidSeriesNames`] if the series names differ,
+    /// * [`LagError::InvalidStaticColumns`] if a matrix has static columns,
+    /// * [`LagError::InvalidMemoryLayout`] if the layouts differ,
+    /// * [`LagError::InvalidLength`] if the matrices cover different time steps.
+    ///
+    /// ## Example
+    /// ```
+    /// use timelag::lag_matrix;
+    ///
+    /// let data = [1, 2, 3, 4, 5];
+    /// let short = lag_matrix(&data, 0..=1, 0, 0).unwrap();
+    /// let seasonal = lag_matrix(&data, [4], 0, 0).unwrap();
+    ///
+    /// let lagged = short.concat_lags(&seasonal).unwrap();
+    /// assert_eq!(lagged.lags(), &[0, 1, 4]);
+    /// assert_eq!(lagged, lag_matrix(&data, [0, 1, 4], 0, 0).unwrap());
+    /// ```
+    pub fn concat_lags<U>(&self, other: &LagMatrix<T, U>) -> Result<LagMatrix<T>, LagError>
+    where
+        U: AsRef<[T]>,
+    {
+        if self.lags.iter().any(|lag| other.lags.contains(lag)) {
+            return Err(LagError::InvalidLags);
+        }
+        if self.series_count != other.series_count || self.directions != other.directions {
+            return Err(LagError::InvalidChannel);
+        }
+        if self.series_names != other.series_names {
+            return Err(LagError::InvalidSeriesNames);
+        }
+        self.check_compatible(other)?;
+
+        let (first, second) = (self.view(), other.view());
+        let features: Vec<_> = [&first, &second]
+            .into_iter()
+            .flat_map(|matrix| {
+                (0..matrix.num_lags).flat_map(move |lag_index| {
+                    (0..matrix.series_count).map(move |series| (matrix, lag_index, series))
+                })
+            })
+            .collect();
+        let lags = [self.lags.as_slice(), &other.lags].concat();
+
+        let mut matrix = self.stack_features(&features, self.series_count, lags);
+        matrix.directions = self.directions.clone();
+        matrix.series_names = self.series_names.clone();
+        Ok(matrix)
+    }
+
+    /// Appends the series of another matrix with the same lags, e.g. to combine the features
+    /// of separately lagged sensors.
+    ///
+    /// The series of the other matrix follow those of this matrix within every lag, such that
+    /// the result is laid out as if all series were lagged together. Series names are retained
+    /// if both matrices are named. Row weights are discarded. The result has no row padding.
+    ///
+    /// ## Returns
+    /// The concatenated matrix, or
+    /// * [`LagError::InvalidLags`] if the lags differ,
+    /// * [`LagError::InvalidStaticColumns`] if a matrix has static columns,
+    /// * [`LagError::InvalidMemoryLayout`] if the layouts differ,
+    /// * [`LagError::InvalidLength`] if the matrices cover different time steps.
+    ///
+    /// ## Example
+    /// ```
+    /// use timelag::{lag_matrix, lag_matrix_2d, MatrixLayout};
+    ///
+    /// let first = lag_matrix(&[1, 2, 3], 0..=1, 0, 0).unwrap();
+    /// let second = lag_matrix(&[10, 20, 30], 0..=1, 0, 0).unwrap();
+    ///
+    /// let lagged = first.concat_series(&second).unwrap();
+    /// assert_eq!(lagged.series_count(), 2);
+    /// assert_eq!(
+    ///     lagged,
+    ///     lag_matrix_2d(&[1, 2, 3, 10, 20, 30], MatrixLayout::RowMajor(3), 0..=1, 0, 0).unwrap()
+    /// );
+    /// ```
+    pub fn concat_series<U>(&self, other: &LagMatrix<T, U>) -> Result<LagMatrix<T>, LagError>
+    where
+        U: AsRef<[T]>,
+    {
+        if self.lags != other.lags {
+            return Err(LagError::InvalidLags);
+        }
+        self.check_compatible(other)?;
+
+        let (first, second) = (self.view(), other.view());
+        let matrices = [&first, &second];
+        let features: Vec<_> = (0..self.num_lags)
+            .flat_map(|lag_index| {
+                matrices.into_iter().flat_map(move |matrix| {
+                    (0..matrix.series_count).map(move |series| (matrix, lag_index, series))
+                })
+            })
+            .collect();
+        let series_count = self.series_count + other.series_count;
+
+        let mut matrix = self.stack_features(&features, series_count, self.lags.clone());
+        matrix.directions = [self.directions.as_slice(), &other.directions].concat();
+        matrix.series_names = match (&self.series_names, &other.series_names) {
+            (Some(first), Some(second)) => Some([first.as_slice(), second].concat()),
+            _ => None,
+        };
+        Ok(matrix)
+    }
+
+    /// Ensures that the matrices have the same orientation, cover the same time steps and
+    /// have no static columns.
+    fn check_compatible<U>(&self, other: &LagMatrix<T, U>) -> Result<(), LagError> {
+        if self.static_count > 0 || other.static_count > 0 {
+            return Err(LagError::InvalidStaticColumns);
+        }
+        if self.row_major != other.row_major {
+            return Err(LagError::InvalidMemoryLayout);
+        }
+        if self.series_length != other.series_length
+            || self.time_offset != other.time_offset
+            || self.source_length != other.source_length
+        {
+            return Err(LagError::InvalidLength);
+        }
+        Ok(())
+    }
+
+    /// Creates a matrix of the specified features, given by their matrix, lag index and series,
+    /// in the orientation and time window of this matrix.
+    fn stack_features(
+        &self,
+        features: &[(&LagMatrixView<'_, T>, usize, usize)],
+        series_count: usize,
+        lags: Vec<isize>,
+    ) -> LagMatrix<T> {
+        let series_length = self.series_length;
+        let value = |&(matrix, lag_index, series): &(&LagMatrixView<'_, T>, usize, usize),
+                     t: usize| {
+            matrix.data[matrix.offset_of(lag_index, series, t)]
+        };
+
+        let mut data = Vec::with_capacity(features.len() * series_length);
+        let row_stride = if self.row_major {
+            for feature in features {
+                data.extend((0..series_length).map(|t| value(feature, t)));
+            }
+            series_length
+        } else {
+            for t in 0..series_length {
+                data.extend(features.iter().map(|feature| value(feature, t)));
+            }
+            features.len()
+        };
+
+        let gaps: Vec<bool> = features
+            .iter()
+            .flat_map(|&(matrix, lag_index, series)| {
+                (0..series_length).map(move |t| matrix.is_interior_gap(lag_index, series, t))
+            })
+            .collect();
+
+        let mut matrix = LagMatrix::new(
+            data,
+            self.row_major,
+            series_length,
+            series_count,
+            lags,
+            row_stride,
+        )
+        .with_window(self.time_offset, self.source_length);
+        matrix.gaps = gaps.contains(&true).then_some(gaps);
+        matrix
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{lag_matrix_2d, LagError, MatrixLayout};
+
+    #[test]
+    #[rustfmt::skip]
+    fn test_concat_column_major() {
+        let data = [
+            1, 10,
+            2, 20,
+            3, 30,
+        ];
+        let layout = MatrixLayout::ColumnMajor(3);
+        let expected = lag_matrix_2d(&data, layout, [0, 1, -1], -1, 0).unwrap();
+
+        let short = lag_matrix_2d(&data, layout, [0, 1], -1, 7).unwrap();
+        let lead = lag_matrix_2d(&data, layout, [-1], -1, 0).unwrap();
+        assert_eq!(short.concat_lags(&lead).unwrap(), expected);
+
+        let first = lag_matrix_2d(&[1, 2, 3], layout, [0, 1, -1], -1, 0).unwrap();
+        let second = lag_matrix_2d(&[10, 20, 30], layout, [0, 1, -1], -1, 0).unwrap();
+        assert_eq!(first.concat_series(&second).unwrap(), expected);
+    }
+
+    #[test]
+    fn test_concat_rejects_mismatches() {
+        let layout = MatrixLayout::RowMajor(3);
+        let first = lag_matrix_2d(&[1, 2, 3], layout, 0..=1, 0, 0).unwrap();
+        assert_eq!(first.concat_lags(&first), Err(LagError::InvalidLags));
+        assert_eq!(
+            first.concat_series(&first.select_lags([1]).unwrap()),
+            Err(LagError::InvalidLags)
+        );
+        assert_eq!(
+            first.concat_series(&first.transpose()),
+            Err(LagError::InvalidMemoryLayout)
+        );
+
+        let longer = lag_matrix_2d(&[1, 2, 3, 4], MatrixLayout::RowMajor(4), 0..=1, 0, 0);
+        assert_eq!(
+            first.concat_series(&longer.unwrap()),
+            Err(LagError::InvalidLength)
+        );
+
+        let two = lag_matrix_2d(&[1, 2, 3, 4, 5, 6], layout, [2], 0, 0).unwrap();
+        assert_eq!(first.concat_lags(&two), Err(LagError::InvalidChannel));
+
+        let lag = first.select_lags([1]).unwrap();
+        let named = first.select_lags([0]).unwrap();
+        let named = named.with_series_names(["price"]).unwrap();
+        assert_eq!(named.concat_lags(&lag), Err(LagError::InvalidSeriesNames));
+        let fixed = first.clone().with_static_columns(&[7]);
+        assert_eq!(
+            fixed.concat_series(&first),
+            Err(LagError::InvalidStaticColumns)
+        );
+    }
+}
@@ -0,0 +1,160 @@
+use crate::LagMatrix;
+use core::fmt::{Display, Formatter};
+
+impl<T, S> Display for LagMatrix<T, S>
+where
+    T: Display,
+    S: AsRef<[T]>,
+{
+    /// Renders the matrix as an aligned grid, e.g. to debug lag or stride mistakes.
+    ///
+    /// The header and row labels name the features, see [`column_names`](LagMatrix::column_names),
+    /// and the time steps, which are counted from the [`time_offset`](LagMatrix::time_offset).
+    /// Lagged gaps are put in parentheses. The alternate format `{:#}` additionally shows the
+    /// row padding after a vertical bar. A precision, e.g. `{:.2}`, is applied to every value.
+    ///
+    /// ## Example
+    /// ```
+    /// use timelag::{lag_matrix_2d, MatrixLayout};
+    ///
+    /// let lagged = lag_matrix_2d(&[1, 2, 3], MatrixLayout::ColumnMajor(3), [0, 1], 0, 3).unwrap();
+    ///
+    /// assert_eq!(
+    ///     format!("{lagged}"),
+    ///     "    series0_lag0  series0_lag1\n\
+    ///      t0             1           (0)\n\
+    ///      t1             2             1\n\
+    ///      t2             3             2\n"
+    /// );
+    /// assert!(format!("{lagged:#}").starts_with("    series0_lag0  series0_lag1 | padding\n"));
+    /// ```
+    fn fmt(&self, f: &mut Formatter<'_>) -> core::fmt::Result {
+        let data = self.data.as_ref();
+        let features = self.features_per_step();
+        let value = |offset: usize, gap: bool| {
+            let value = match f.precision() {
+                Some(precision) => format!("{:.*}", precision, data[offset]),
+                None => data[offset].to_string(),
+            };
+            if gap {
+                format!("({value})")
+            } else {
+                value
+            }
+        };
+
+        let names = self.column_names();
+        let steps: Vec<String> = (0..self.series_length)
+            .map(|t| format!("t{}", self.time_offset + t))
+            .collect();
+        let (header, labels) = if self.row_major {
+            (&steps, &names)
+        } else {
+            (&names, &steps)
+        };
+
+        let mut cells = Vec::with_capacity(self.num_rows);
+        let mut padding = Vec::with_capacity(self.num_rows);
+        for row in 0..self.num_rows {
+            let start = row * self.row_stride;
+            cells.push(
+                (0..self.num_cols)
+                    .map(|col| {
+                        let (feature, t) = if self.row_major {
+                            (row, col)
+                        } else {
+                            (col, row)
+                        };
+                        let gap = feature < features
+                            && self.is_padding(
+                                feature / self.series_count,
+                                feature % self.series_count,
+                                t,
+                            );
+                        value(start + col, gap)
+                    })
+                    .collect::<Vec<_>>(),
+            );
+            if f.alternate() {
+                let end = (start + self.row_stride).min(data.len());
+                padding.push(
+                    (start + self.num_cols..end)
+                        .map(|offset| value(offset, false))
+                        .collect::<Vec<_>>(),
+                );
+            }
+        }
+
+        let label_width = labels.iter().map(String::len).max().unwrap_or(0);
+        let widths: Vec<usize> = (0..self.num_cols)
+            .map(|col| {
+                cells
+                    .iter()
+                    .map(|row| row[col].len())
+                    .chain(header.get(col).map(String::len))
+                    .max()
+                    .unwrap_or(0)
+            })
+            .collect();
+        let has_padding = padding.iter().any(|row| !row.is_empty());
+
+        write!(f, "{:label_width$}", "")?;
+        for (name, width) in header.iter().zip(&widths) {
+            write!(f, "  {name:>width$}")?;
+        }
+        if has_padding {
+            write!(f, " | padding")?;
+        }
+        writeln!(f)?;
+
+        for (row, values) in cells.iter().enumerate() {
+            write!(f, "{:label_width$}", labels[row])?;
+            for (value, width) in values.iter().zip(&widths) {
+                write!(f, "  {value:>width$}")?;
+            }
+            if has_padding {
+                write!(f, " |")?;
+                for value in &padding[row] {
+                    write!(f, " {value}")?;
+                }
+            }
+            writeln!(f)?;
+        }
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{lag_matrix, lag_matrix_2d, MatrixLayout};
+
+    #[test]
+    fn test_row_major_with_padding_and_precision() {
+        let lagged = lag_matrix(&[1.0, 2.0, 3.0], [0, 2], f64::NAN, 4)
+            .unwrap()
+            .with_series_names(["x"])
+            .unwrap();
+
+        assert_eq!(
+            format!("{lagged:#.1}"),
+            "           t0     t1   t2 | padding\n\
+             x_lag0    1.0    2.0  3.0 | NaN\n\
+             x_lag2  (NaN)  (NaN)  1.0 | NaN\n"
+        );
+    }
+
+    #[test]
+    fn test_static_columns_are_labeled() {
+        let lagged = lag_matrix_2d(&[1, 2], MatrixLayout::ColumnMajor(2), [1], -1, 0)
+            .unwrap()
+            .with_static_columns(&[7]);
+
+        assert_eq!(
+            lagged.to_string(),
+            "    series0_lag1  static0\n\
+             t0          (-1)        7\n\
+             t1             1        7\n"
+        );
+    }
+}
@@ -0,0 +1,131 @@
+use crate::{DistanceMetric, LagError};
+use num_traits::Float;
+
+/// Computes the dynamic time warping distance between two windows, e.g. lag rows or time
+/// steps of a lag matrix, within a Sakoe–Chiba band.
+///
+/// The cost of aligning two values is their squared difference, and the distance is the
+/// square root of the cheapest alignment; without warping it equals the Euclidean distance.
+/// A band of `w` only aligns the indices `i` and `j` if `|i - j| <= w`; it is widened to the
+/// difference in length of the windows, such that an alignment always exists. Only two rows
+/// of the cost matrix are kept in memory.
+///
+/// ## Arguments
+/// * `a` - The first window.
+/// * `b` - The second window.
+/// * `band` - The maximum warping distance, or `None` for an unconstrained alignment.
+///
+/// ## Returns
+/// The distance, or [`LagError::EmptyData`] if either window is empty.
+///
+/// ## Example
+/// ```
+/// use timelag::dtw_distance;
+///
+/// let a = [0.0, 1.0, 2.0, 1.0, 0.0, 0.0];
+/// let b = [0.0, 0.0, 1.0, 2.0, 1.0, 0.0];
+///
+/// // The shifted peak aligns perfectly when warping by one step ...
+/// assert_eq!(dtw_distance(&a, &b, Some(1)).unwrap(), 0.0);
+///
+/// // ... and not at all without warping.
+/// assert_eq!(dtw_distance(&a, &b, Some(0)).unwrap(), 2.0);
+/// ```
+pub fn dtw_distance<T: Float>(a: &[T], b: &[T], band: Option<usize>) -> Result<T, LagError> {
+    if a.is_empty() || b.is_empty() {
+        return Err(LagError::EmptyData);
+    }
+
+    let band = band.unwrap_or(usize::MAX).max(a.len().abs_diff(b.len()));
+    let mut previous = vec![T::infinity(); b.len() + 1];
+    let mut current = vec![T::infinity(); b.len() + 1];
+    previous[0] = T::zero();
+
+    for (i, &x) in a.iter().enumerate() {
+        current.fill(T::infinity());
+        let start = i.saturating_sub(band);
+        let end = i.saturating_add(band).saturating_add(1).min(b.len());
+        for j in start..end {
+            let cost = (x - b[j]) * (x - b[j]);
+            let best = previous[j].min(previous[j + 1]).min(current[j]);
+            current[j + 1] = cost + best;
+        }
+        core::mem::swap(&mut previous, &mut current);
+    }
+
+    Ok(previous[b.len()].sqrt())
+}
+
+/// The banded [dynamic time warping distance](dtw_distance), e.g. for similarity searches
+/// with [`knn_lag_query`](crate::knn_lag_query).
+///
+/// The rows are compared in the order of their cells, i.e. lag by lag; this is meaningful
+/// for matrices of a single series with consecutive lags.
+///
+/// ## Example
+/// ```
+/// use timelag::{knn_lag_query, lag_matrix, BandedDtw};
+///
+/// let data = [0.0, 1.0, 2.0, 1.0, 0.0, 0.0, 0.0];
+/// let matrix = lag_matrix(&data, 0..=3, f64::NAN, 0).unwrap();
+///
+/// // The query "0, 1, 2, 2" (oldest first), most recent lag first.
+/// let query = [2.0, 2.0, 1.0, 0.0];
+/// let neighbors = knn_lag_query(&matrix, &query, 1, BandedDtw::new(1)).unwrap();
+/// assert_eq!(neighbors[0].index, 3);
+/// ```
+#[derive(Debug, Copy, Clone, Eq, PartialEq, Hash)]
+pub struct BandedDtw {
+    band: usize,
+}
+
+impl BandedDtw {
+    /// Creates the distance with the specified maximum warping distance.
+    #[inline(always)]
+    pub const fn new(band: usize) -> Self {
+        Self { band }
+    }
+
+    /// The maximum warping distance.
+    #[inline(always)]
+    pub const fn band(&self) -> usize {
+        self.band
+    }
+}
+
+impl<T: Float> DistanceMetric<T> for BandedDtw {
+    /// The distance; infinite if either row is empty.
+    fn distance(&self, a: &[T], b: &[T]) -> T {
+        dtw_distance(a, b, Some(self.band)).unwrap_or_else(|_| T::infinity())
+    }
+
+    /// Scales the accumulated squared differences linearly.
+    fn extrapolate(&self, distance: T, coverage: T) -> T {
+        distance / coverage.sqrt()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_without_warping_equals_euclidean() {
+        let a = [1.0, 4.0, 2.0];
+        let b = [2.0, 2.0, 4.0];
+        let euclidean: f64 = crate::Metric::Euclidean.distance(&a, &b);
+        assert_eq!(dtw_distance(&a, &b, Some(0)).unwrap(), euclidean);
+        assert!(dtw_distance(&a, &b, None).unwrap() < euclidean);
+    }
+
+    #[test]
+    fn test_band_is_widened_to_length_difference() {
+        // Every value of the longer window must be aligned with the single one.
+        let distance = dtw_distance(&[1.0, 2.0, 3.0], &[2.0f64], Some(0)).unwrap();
+        assert_eq!(distance, 2f64.sqrt());
+        assert_eq!(
+            dtw_distance::<f64>(&[], &[1.0], None),
+            Err(LagError::EmptyData)
+        );
+    }
+}
@@ -0,0 +1,97 @@
+use crate::{lag_matrix_2d, LagError, LagMatrix, MatrixLayout};
+
+/// Creates the delay embedding of a time series.
+///
+/// Every time step `t` of the result holds the delay vector
+/// `[x(t), x(t - τ), x(t - 2τ), …, x(t - (m - 1)·τ)]` for the embedding dimension `m`
+/// and delay `τ`. The result is laid out column-major, i.e. with one delay vector per row,
+/// and time steps whose delay vector would reach before the start of the series are
+/// filled with the `fill` value.
+///
+/// ## Arguments
+/// * `data` - The time series data to embed.
+/// * `dimension` - The embedding dimension `m`, i.e. the number of values per delay vector.
+/// * `delay` - The delay `τ` between subsequent values of each delay vector.
+/// * `fill` - The value to use to fill in lagged gaps.
+///
+/// ## Returns
+/// The embedding, or an error if the dimension is zero or the embedding spans
+/// more than the available data.
+///
+/// ## Example
+/// ```
+/// use timelag::delay_embedding;
+///
+/// let data = [1.0, 2.0, 3.0, 4.0, 5.0];
+/// let fill = f64::INFINITY;
+///
+/// let embedding = delay_embedding(&data, 2, 2, fill).unwrap();
+///
+/// assert_eq!(
+///     embedding,
+///     &[
+///         1.0, fill,
+///         2.0, fill,
+///         3.0, 1.0,
+///         4.0, 2.0,
+///         5.0, 3.0,
+///     ]
+/// );
+/// ```
+pub fn delay_embedding<T: Copy>(
+    data: &[T],
+    dimension: usize,
+    delay: usize,
+    fill: T,
+) -> Result<LagMatrix<T>, LagError> {
+    if dimension == 0 {
+        return Err(LagError::InvalidLags);
+    }
+
+    let span = (dimension - 1)
+        .checked_mul(delay)
+        .ok_or(LagError::LagExceedsValueCount)?;
+    if span > data.len() {
+        return Err(LagError::LagExceedsValueCount);
+    }
+
+    let lags = (0..dimension).map(|k| k * delay);
+    lag_matrix_2d(data, MatrixLayout::ColumnMajor(data.len()), lags, fill, 0)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    #[rustfmt::skip]
+    fn test_delay_embedding() {
+        let data = [1, 2, 3, 4];
+        let embedding = delay_embedding(&data, 3, 1, 0).unwrap();
+
+        assert_eq!(embedding.num_rows(), 4);
+        assert_eq!(embedding.num_cols(), 3);
+        assert!(embedding.is_column_major());
+        assert_eq!(
+            embedding,
+            &[
+                1, 0, 0,
+                2, 1, 0,
+                3, 2, 1,
+                4, 3, 2,
+            ]
+        );
+    }
+
+    #[test]
+    fn test_delay_embedding_too_long() {
+        assert_eq!(
+            delay_embedding(&[1, 2, 3], 3, 2, 0).unwrap_err(),
+            LagError::LagExceedsValueCount
+        );
+        assert_eq!(
+            delay_embedding(&[1, 2, 3], 0, 1, 0).unwrap_err(),
+            LagError::InvalidLags
+        );
+    }
+}
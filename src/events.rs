@@ -0,0 +1,95 @@
+use crate::LagError;
+
+/// Computes the number of time steps since the most recent event for every time step,
+/// e.g. the days since the last login for churn prediction.
+///
+/// Time step `t` holds `0` if an event occurs at `t` itself, such that the values align with
+/// the rows of a lag matrix including the zero lag. Time steps before the first event hold
+/// `None`, as their distance is unknown.
+///
+/// ## Example
+/// ```
+/// use timelag::steps_since_event;
+///
+/// let events = [false, true, false, false, true];
+/// assert_eq!(
+///     steps_since_event(&events),
+///     [None, Some(0), Some(1), Some(2), Some(0)]
+/// );
+/// ```
+pub fn steps_since_event(events: &[bool]) -> Vec<Option<usize>> {
+    let mut last = None;
+    events
+        .iter()
+        .enumerate()
+        .map(|(t, &event)| {
+            if event {
+                last = Some(t);
+            }
+            last.map(|last| t - last)
+        })
+        .collect()
+}
+
+/// Counts the events in the trailing window of every time step, e.g. the failures of a
+/// machine within the last week.
+///
+/// The window of time step `t` holds the time steps `t + 1 - window..=t`, i.e. the lags
+/// `0..window`. The time steps whose windows reach before the start of the series hold
+/// `None`, like the lagged gaps of [`lag_matrix`](crate::lag_matrix).
+///
+/// ## Returns
+/// The counts, or [`LagError::InvalidLength`] if the window is empty.
+///
+/// ## Example
+/// ```
+/// use timelag::event_counts;
+///
+/// let events = [true, false, true, true, false];
+/// assert_eq!(
+///     event_counts(&events, 3).unwrap(),
+///     [None, None, Some(2), Some(2), Some(2)]
+/// );
+/// ```
+pub fn event_counts(events: &[bool], window: usize) -> Result<Vec<Option<usize>>, LagError> {
+    if window == 0 {
+        return Err(LagError::InvalidLength);
+    }
+
+    let mut count = 0;
+    Ok(events
+        .iter()
+        .enumerate()
+        .map(|(t, &event)| {
+            count += usize::from(event);
+            if t >= window {
+                count -= usize::from(events[t - window]);
+            }
+            (t + 1 >= window).then_some(count)
+        })
+        .collect())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_counts_match_direct_sums() {
+        let events: Vec<bool> = (0..50).map(|t| t * t % 7 < 3).collect();
+        let counts = event_counts(&events, 5).unwrap();
+        for (t, count) in counts.into_iter().enumerate() {
+            let expected = (t >= 4).then(|| events[t - 4..=t].iter().filter(|&&e| e).count());
+            assert_eq!(count, expected);
+        }
+
+        assert_eq!(event_counts(&events, 0), Err(LagError::InvalidLength));
+        assert_eq!(event_counts(&events[..2], 3).unwrap(), [None, None]);
+    }
+
+    #[test]
+    fn test_steps_since_event_without_events() {
+        assert_eq!(steps_since_event(&[false; 3]), [None; 3]);
+        assert!(steps_since_event(&[]).is_empty());
+    }
+}
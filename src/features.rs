@@ -0,0 +1,133 @@
+use crate::{
+    collect_lags, IntoLag, LagError, LagMatrix, LagMatrixBuilder, MatrixLayout, OutputLayout,
+};
+use num_traits::Float;
+
+/// Starts lagging a single series into a feature matrix with one row per time step and
+/// one column per lag, e.g. the design matrix of a regression or a tabular model.
+///
+/// Lagged gaps are filled with NaN, and the matrix is built once one of
+/// [`build`](Features::build), [`to_vec`](Features::to_vec) or `to_array2` is called. For
+/// anything beyond this common case, see [`LagMatrixBuilder`] and [`lag_matrix`](crate::lag_matrix).
+///
+/// ## Example
+/// ```
+/// let data = [1.0, 2.0, 3.0, 4.0];
+/// let features = timelag::features(&data).lags(1..=2).trimmed().to_vec().unwrap();
+///
+/// assert_eq!(
+///     features,
+///     [
+///         2.0, 1.0, // time step 2
+///         3.0, 2.0, // time step 3
+///     ]
+/// );
+/// ```
+pub fn features<T: Float>(data: &[T]) -> Features<'_, T> {
+    Features {
+        data,
+        builder: LagMatrixBuilder::new(T::nan()).output_layout(OutputLayout::LagsAlongColumns),
+        lags: Err(LagError::InvalidLags),
+    }
+}
+
+/// A feature matrix of a single series in the making; see [`features`].
+#[derive(Debug, Clone, PartialEq)]
+pub struct Features<'a, T> {
+    data: &'a [T],
+    builder: LagMatrixBuilder<T>,
+    lags: Result<Vec<isize>, LagError>,
+}
+
+impl<T> Features<'_, T>
+where
+    T: Float,
+{
+    /// Sets the lags to create; negative lags create leads, see [`IntoLag`].
+    ///
+    /// Building fails with [`LagError::InvalidLags`] if no lags are set.
+    pub fn lags<L: IntoLag, R: IntoIterator<Item = L>>(mut self, lags: R) -> Self {
+        self.lags = collect_lags(lags);
+        self
+    }
+
+    /// Keeps only the time steps for which all lags are available, i.e. drops the rows
+    /// holding NaN fills.
+    pub fn trimmed(mut self) -> Self {
+        self.builder = self.builder.trim_incomplete(true);
+        self
+    }
+
+    /// Creates the lag matrix, which is column-major, i.e. has one row per time step.
+    ///
+    /// ## Returns
+    /// The matrix, or an error, e.g. [`LagError::LagExceedsValueCount`] if a lag is longer
+    /// than the series.
+    pub fn build(&self) -> Result<LagMatrix<T>, LagError> {
+        let lags = self.lags.clone()?;
+        let layout = MatrixLayout::RowMajor(self.data.len());
+        self.builder.build_2d(self.data, layout, lags)
+    }
+
+    /// Creates the values of the feature matrix, row by row.
+    pub fn to_vec(&self) -> Result<Vec<T>, LagError> {
+        self.build().map(LagMatrix::into_vec)
+    }
+
+    /// Creates the feature matrix as an array of shape (time steps, lags).
+    ///
+    /// ## Example
+    /// ```
+    /// let data: Vec<f64> = (0..100).map(f64::from).collect();
+    /// let features = timelag::features(&data).lags(1..=24).trimmed().to_array2().unwrap();
+    ///
+    /// assert_eq!(features.shape(), &[76, 24]);
+    /// assert_eq!(features[[0, 0]], 23.0);
+    /// ```
+    #[cfg(feature = "ndarray")]
+    #[cfg_attr(docsrs, doc(cfg(feature = "ndarray")))]
+    pub fn to_array2(&self) -> Result<ndarray::Array2<T>, LagError> {
+        let (array, _) = self.build()?.into_array2();
+        Ok(array)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::lag_matrix_2d_oriented;
+
+    #[test]
+    fn test_matches_oriented_matrix() {
+        let data = [1.0, 2.0, 3.0, 4.0, 5.0];
+        let features = features(&data).lags([0, 2, -1]).build().unwrap();
+        let expected = lag_matrix_2d_oriented(
+            &data,
+            MatrixLayout::RowMajor(5),
+            [0, 2, -1],
+            f64::NAN,
+            0,
+            OutputLayout::LagsAlongColumns,
+        )
+        .unwrap();
+
+        assert!(features.is_column_major());
+        assert_eq!(features.num_rows(), 5);
+        assert_eq!(features.num_cols(), 3);
+        let same = |a: &f64, b: &f64| a == b || (a.is_nan() && b.is_nan());
+        assert!(features
+            .iter()
+            .zip(expected.iter())
+            .all(|(a, b)| same(a, b)));
+    }
+
+    #[test]
+    fn test_errors() {
+        let data = [1.0f32, 2.0];
+        assert_eq!(features(&data).to_vec(), Err(LagError::InvalidLags));
+        assert_eq!(
+            features(&data).lags([3]).to_vec(),
+            Err(LagError::LagExceedsValueCount)
+        );
+    }
+}
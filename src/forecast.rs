@@ -0,0 +1,203 @@
+use crate::{
+    lag_matrix_2d, lag_matrix_2d_directed, to_column_major, IntoLag, LagDirection, LagError,
+    LagMatrix, MatrixLayout,
+};
+
+/// The aligned input blocks of a temporal-fusion style forecasting model; see [`forecast_blocks`].
+///
+/// All blocks are laid out with one time step per row, such that row `t` of every block
+/// describes the same point in time.
+#[derive(Debug, Clone, PartialEq)]
+pub struct ForecastBlocks<T> {
+    past: LagMatrix<T>,
+    known_future: Option<LagMatrix<T>>,
+    static_covariates: Vec<T>,
+    static_count: usize,
+}
+
+impl<T> ForecastBlocks<T> {
+    /// The lagged values of the observed (past) series.
+    #[inline(always)]
+    pub fn past(&self) -> &LagMatrix<T> {
+        &self.past
+    }
+
+    /// The led values of the known-future covariates, if any were provided.
+    #[inline(always)]
+    pub fn known_future(&self) -> Option<&LagMatrix<T>> {
+        self.known_future.as_ref()
+    }
+
+    /// The static covariates, repeated for every time step.
+    ///
+    /// The values of time step `t` start at index `t * static_count()`.
+    #[inline(always)]
+    pub fn static_covariates(&self) -> &[T] {
+        &self.static_covariates
+    }
+
+    /// The number of static covariates per time step.
+    #[inline(always)]
+    pub const fn static_count(&self) -> usize {
+        self.static_count
+    }
+
+    /// The number of time steps, i.e. the number of rows of every block.
+    #[inline(always)]
+    pub const fn num_steps(&self) -> usize {
+        self.past.series_length()
+    }
+
+    /// Splits the blocks into the past, known-future and static covariate blocks.
+    pub fn into_parts(self) -> (LagMatrix<T>, Option<LagMatrix<T>>, Vec<T>) {
+        (self.past, self.known_future, self.static_covariates)
+    }
+}
+
+/// Creates the aligned past, known-future and static covariate blocks used by
+/// temporal-fusion style forecasting models.
+///
+/// * The _past_ block holds the observed series at the requested lags.
+/// * The _known-future_ block holds covariates that are known in advance (e.g. calendar
+///   features or weather forecasts) at the requested leads, padded at the end.
+/// * The _static_ block repeats the static covariates for every time step.
+///
+/// Regardless of the input layout, every block is produced with one time step per row.
+///
+/// ## Arguments
+/// * `past` - The matrix of observed time series.
+/// * `known_future` - The matrix of known-future covariates; may be empty.
+/// * `static_covariates` - The covariates constant over time; may be empty.
+/// * `layout` - The layout of both `past` and `known_future`, including the series length.
+/// * `past_lags` - The lags to create for the observed series.
+/// * `future_leads` - The leads to create for the known-future covariates.
+/// * `fill` - The value to use to fill in lagged gaps.
+///
+/// ## Returns
+/// The forecast blocks, or an error.
+///
+/// ## Example
+/// ```
+/// use timelag::{forecast_blocks, MatrixLayout};
+///
+/// let sales = [10.0, 11.0, 12.0, 13.0];
+/// let holiday = [0.0, 1.0, 0.0, 0.0];
+/// let store_size = [50.0];
+/// let fill = f64::NAN;
+///
+/// let blocks = forecast_blocks(&sales, &holiday, &store_size, MatrixLayout::ColumnMajor(4), 1..=2, 0..=1, fill)
+///     .unwrap();
+///
+/// // At time step 2, the past lags are 11 and 10 ...
+/// assert_eq!(blocks.past()[2 * 2..3 * 2], [11.0, 10.0]);
+/// // ... the holiday flags for steps 2 and 3 are known ...
+/// assert_eq!(blocks.known_future().unwrap()[2 * 2..3 * 2], [0.0, 0.0]);
+/// // ... and the store size is constant.
+/// assert_eq!(blocks.static_covariates()[2], 50.0);
+/// ```
+pub fn forecast_blocks<T, P, F, L, M>(
+    past: &[T],
+    known_future: &[T],
+    static_covariates: &[T],
+    layout: MatrixLayout,
+    past_lags: P,
+    future_leads: F,
+    fill: T,
+) -> Result<ForecastBlocks<T>, LagError>
+where
+    T: Copy,
+    P: IntoIterator<Item = L>,
+    F: IntoIterator<Item = M>,
+    L: IntoLag,
+    M: IntoLag,
+{
+    let series_length = layout.len();
+    let column_major = MatrixLayout::ColumnMajor(series_length);
+
+    let past = to_column_major(past, layout)?;
+    let past = lag_matrix_2d(&past, column_major, past_lags, fill, 0)?;
+
+    let known_future = if known_future.is_empty() {
+        None
+    } else {
+        let known_future = to_column_major(known_future, layout)?;
+        let directions = vec![LagDirection::Lead; known_future.len() / series_length];
+        Some(lag_matrix_2d_directed(
+            &known_future,
+            column_major,
+            future_leads,
+            &directions,
+            fill,
+            0,
+        )?)
+    };
+
+    let static_count = static_covariates.len();
+    let static_covariates = static_covariates.repeat(series_length);
+
+    Ok(ForecastBlocks {
+        past,
+        known_future,
+        static_covariates,
+        static_count,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    #[rustfmt::skip]
+    fn test_forecast_blocks_row_major_input() {
+        let past = [
+            1, 2, 3,
+            10, 20, 30,
+        ];
+        let future = [7, 8, 9];
+
+        let blocks = forecast_blocks(&past, &future, &[5, 6], MatrixLayout::RowMajor(3), [1], [1, 2], 0).unwrap();
+        assert_eq!(blocks.num_steps(), 3);
+
+        let past = blocks.past();
+        assert!(past.is_column_major());
+        assert_eq!(past, &[
+            0, 0,
+            1, 10,
+            2, 20,
+        ]);
+
+        let future = blocks.known_future().unwrap();
+        assert_eq!(future.directions(), &[LagDirection::Lead]);
+        assert_eq!(future, &[
+            8, 9,
+            9, 0,
+            0, 0,
+        ]);
+
+        assert_eq!(blocks.static_count(), 2);
+        assert_eq!(blocks.static_covariates(), &[5, 6, 5, 6, 5, 6]);
+    }
+
+    #[test]
+    fn test_forecast_blocks_without_covariates() {
+        let blocks =
+            forecast_blocks(&[1, 2], &[], &[], MatrixLayout::ColumnMajor(2), [0], [0], 0).unwrap();
+        assert!(blocks.known_future().is_none());
+        assert!(blocks.static_covariates().is_empty());
+    }
+
+    #[test]
+    fn test_forecast_blocks_length_mismatch() {
+        let result = forecast_blocks(
+            &[1, 2],
+            &[1, 2, 3],
+            &[],
+            MatrixLayout::ColumnMajor(2),
+            [0],
+            [0],
+            0,
+        );
+        assert_eq!(result.unwrap_err(), LagError::InvalidLength);
+    }
+}
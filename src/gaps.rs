@@ -0,0 +1,187 @@
+use crate::LagMatrix;
+use core::ops::Range;
+
+impl<T, S> LagMatrix<T, S> {
+    /// Determines whether the matrix has lagged gaps within the series, e.g. at the
+    /// changepoints of [`lag_matrix_2d_segmented`](crate::lag_matrix_2d_segmented), in
+    /// addition to those at its start and end.
+    ///
+    /// The time steps holding such gaps are excluded from
+    /// [`valid_length`](Self::valid_length) and [`iter_complete`](Self::iter_complete), and
+    /// [`trim_incomplete`](Self::trim_incomplete) rejects matrices that retain such gaps.
+    ///
+    /// ## Example
+    /// ```
+    /// use timelag::{lag_matrix_segmented, lag_matrix};
+    ///
+    /// let data = [1, 2, 3, 10, 20];
+    /// let lagged = lag_matrix_segmented(&data, 0..=1, 0, 0, &[3]).unwrap();
+    ///
+    /// assert!(lagged.has_interior_gaps());
+    /// assert_eq!(lagged.valid_length(), 3);
+    /// assert!(!lag_matrix(&data, 0..=1, 0, 0).unwrap().has_interior_gaps());
+    /// ```
+    #[inline(always)]
+    pub fn has_interior_gaps(&self) -> bool {
+        self.gaps.is_some()
+    }
+
+    /// The index of the value of series `series` at lag index `lag_index` and time step `t`
+    /// in the gap mask.
+    #[inline(always)]
+    pub(crate) const fn gap_index(&self, lag_index: usize, series: usize, t: usize) -> usize {
+        (lag_index * self.series_count + series) * self.series_length + t
+    }
+
+    /// Determines whether the value of series `series` at lag index `lag_index` and time step `t`
+    /// is a gap within the series.
+    #[inline(always)]
+    pub(crate) fn is_interior_gap(&self, lag_index: usize, series: usize, t: usize) -> bool {
+        self.gaps
+            .as_ref()
+            .is_some_and(|gaps| gaps[self.gap_index(lag_index, series, t)])
+    }
+
+    /// Marks the value of series `series` at lag index `lag_index` and time step `t` as a gap
+    /// within the series, unless it already is padding at the start or end of the series.
+    pub(crate) fn mark_gap(&mut self, lag_index: usize, series: usize, t: usize) {
+        if self.is_padding(lag_index, series, t) {
+            return;
+        }
+
+        let len = self.features_per_step() * self.series_length;
+        let index = self.gap_index(lag_index, series, t);
+        self.gaps.get_or_insert_with(|| vec![false; len])[index] = true;
+    }
+
+    /// Replaces all lagged gaps of the matrix by the gap mask, such that no time step is
+    /// padding by its position alone, e.g. for resampled time steps that no longer follow
+    /// the source series.
+    ///
+    /// The time window is widened by the largest lag and lead, which places the first and
+    /// the last time step far enough from the ends of the source series.
+    pub(crate) fn replace_gaps(&mut self, gaps: Vec<bool>) {
+        let (lag, lead) = self.max_shifts();
+        self.time_offset = lag;
+        self.source_length = lag + self.series_length + lead;
+        self.gaps = gaps.contains(&true).then_some(gaps);
+    }
+
+    /// Flags the time steps holding a gap within the series, or `None` if there are none.
+    pub(crate) fn gap_steps(&self) -> Option<Vec<bool>> {
+        let gaps = self.gaps.as_ref()?;
+        let mut steps = vec![false; self.series_length];
+        for feature in gaps.chunks_exact(self.series_length) {
+            for (step, &gap) in steps.iter_mut().zip(feature) {
+                *step |= gap;
+            }
+        }
+        Some(steps)
+    }
+
+    /// The number of time steps that hold a gap within the series, but are neither among the
+    /// incomplete steps at the start nor at the end of the matrix.
+    pub(crate) fn interior_gap_steps(&self) -> usize {
+        let Some(steps) = self.gap_steps() else {
+            return 0;
+        };
+
+        let (leading, trailing) = self.incomplete_steps();
+        steps[leading..self.series_length - trailing]
+            .iter()
+            .filter(|&&gap| gap)
+            .count()
+    }
+
+    /// The gap mask of the specified lags, in the specified order.
+    pub(crate) fn select_gaps(&self, indices: &[usize]) -> Option<Vec<bool>> {
+        let block = self.series_count * self.series_length;
+        let gaps = self.gaps.as_ref()?;
+        let selected: Vec<bool> = indices
+            .iter()
+            .flat_map(|&index| &gaps[index * block..(index + 1) * block])
+            .copied()
+            .collect();
+        selected.contains(&true).then_some(selected)
+    }
+
+    /// The gap mask of the time steps `range`.
+    pub(crate) fn slice_gaps(&self, range: Range<usize>) -> Option<Vec<bool>> {
+        let gaps = self.gaps.as_ref()?;
+        let sliced: Vec<bool> = gaps
+            .chunks_exact(self.series_length)
+            .flat_map(|feature| &feature[range.clone()])
+            .copied()
+            .collect();
+        sliced.contains(&true).then_some(sliced)
+    }
+
+    /// The gap mask of this matrix followed in time by that of another matrix of the same
+    /// features.
+    pub(crate) fn concat_gaps<U>(&self, other: &LagMatrix<T, U>) -> Option<Vec<bool>> {
+        if self.gaps.is_none() && other.gaps.is_none() {
+            return None;
+        }
+
+        let features = self.features_per_step();
+        Some(
+            (0..features)
+                .flat_map(|feature| {
+                    let (lag_index, series) =
+                        (feature / self.series_count, feature % self.series_count);
+                    let first = (0..self.series_length)
+                        .map(move |t| self.is_interior_gap(lag_index, series, t));
+                    let second = (0..other.series_length)
+                        .map(move |t| other.is_interior_gap(lag_index, series, t));
+                    first.chain(second)
+                })
+                .collect(),
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{lag_matrix_2d, lag_matrix_2d_segmented, LagError, MatrixLayout};
+
+    #[test]
+    fn test_gaps_follow_derived_matrices() {
+        let data = [1, 2, 3, 4, 5, 6];
+        for layout in [MatrixLayout::RowMajor(6), MatrixLayout::ColumnMajor(6)] {
+            let lagged = lag_matrix_2d_segmented(&data, layout, [0, 1], 0, 0, &[3]).unwrap();
+            assert_eq!(lagged.valid_length(), 4);
+            assert!(lagged.is_interior_gap(1, 0, 3));
+            assert!(!lagged.is_interior_gap(1, 0, 0));
+
+            assert!(lagged.transpose().is_interior_gap(1, 0, 3));
+            assert!(!lagged.select_lags([0]).unwrap().has_interior_gaps());
+            assert!(lagged.select_lags([1]).unwrap().is_interior_gap(0, 0, 3));
+
+            let window = lagged.slice_time(2..5).unwrap();
+            assert!(window.is_interior_gap(1, 0, 1));
+            assert_eq!(window.valid_length(), 2);
+            assert!(!lagged.slice_time(4..6).unwrap().has_interior_gaps());
+
+            let plain = lag_matrix_2d(&data, layout, [2], 0, 0).unwrap();
+            let combined = lagged.concat_lags(&plain).unwrap();
+            assert!(combined.is_interior_gap(1, 0, 3));
+            assert!(!combined.is_interior_gap(2, 0, 3));
+        }
+    }
+
+    #[test]
+    fn test_trim_incomplete_rejects_interior_gaps() {
+        let data = [1.0, 2.0, 3.0, 4.0];
+        let layout = MatrixLayout::ColumnMajor(4);
+        let lagged = lag_matrix_2d_segmented(&data, layout, [0, 1], 0.0, 0, &[2]).unwrap();
+        assert_eq!(
+            lagged.clone().trim_incomplete(),
+            Err(LagError::InteriorGaps)
+        );
+
+        // Slicing off the gaps makes the matrix trimmable again.
+        let window = lagged.slice_time(0..2).unwrap();
+        assert!(!window.has_interior_gaps());
+        assert_eq!(window.trim_incomplete().unwrap().time_offset(), 1);
+    }
+}
@@ -0,0 +1,184 @@
+use crate::LagMatrix;
+use core::fmt::{Display, Formatter};
+use num_traits::Float;
+
+impl<T, S> LagMatrix<T, S>
+where
+    T: Float,
+    S: AsRef<[T]>,
+{
+    /// Validates that the matrix can be handed to a BLAS `gemm` or LAPACK solver as-is.
+    ///
+    /// The matrix is interpreted as a row-major `m × n` matrix with leading dimension `lda`,
+    /// i.e. [`num_rows`](Self::num_rows) × [`num_cols`](Self::num_cols) with
+    /// [`lda`](Self::lda). Besides the layout, every value of the logical region is checked:
+    /// lagged gaps and non-finite values (NaN or infinity) would silently corrupt the result,
+    /// e.g. produce garbage regression coefficients, and are reported with their position.
+    ///
+    /// ## Returns
+    /// The `(m, n, lda)` triple, or a [`GemmError`] describing the first problem found.
+    ///
+    /// ## Example
+    /// ```
+    /// use timelag::{lag_matrix_2d, GemmError, MatrixLayout};
+    ///
+    /// let data = [1.0, 2.0, 3.0];
+    /// let lagged = lag_matrix_2d(&data, MatrixLayout::ColumnMajor(3), [0], f64::NAN, 2).unwrap();
+    /// assert_eq!(lagged.gemm_ready(), Ok((3, 1, 2)));
+    ///
+    /// let lagged = lag_matrix_2d(&data, MatrixLayout::ColumnMajor(3), [1], f64::NAN, 0).unwrap();
+    /// assert_eq!(lagged.gemm_ready(), Err(GemmError::Padding { row: 0, col: 0 }));
+    /// ```
+    pub fn gemm_ready(&self) -> Result<(usize, usize, usize), GemmError> {
+        let (m, n, lda) = (self.num_rows, self.num_cols, self.row_stride);
+        if m == 0 || n == 0 {
+            return Err(GemmError::Empty);
+        }
+
+        if lda < n {
+            return Err(GemmError::InvalidLeadingDimension { n, lda });
+        }
+
+        let data = self.data.as_ref();
+        let required = (m - 1) * lda + n;
+        if data.len() < required {
+            return Err(GemmError::TooShort {
+                len: data.len(),
+                required,
+            });
+        }
+
+        if data.as_ptr().align_offset(align_of::<T>()) != 0 {
+            return Err(GemmError::Misaligned);
+        }
+
+        let position = |offset: usize| (offset / lda, offset % lda);
+
+        // Lagged gaps may hold arbitrary (finite) fill values and are checked first.
+        for lag_index in 0..self.num_lags {
+            for series in 0..self.series_count {
+                for t in 0..self.series_length {
+                    if self.is_padding(lag_index, series, t) {
+                        let (row, col) = position(self.offset_of(lag_index, series, t));
+                        return Err(GemmError::Padding { row, col });
+                    }
+                }
+            }
+        }
+
+        for (row, values) in data.chunks(lda).take(m).enumerate() {
+            if let Some(col) = values[..n].iter().position(|value| !value.is_finite()) {
+                return Err(GemmError::NonFinite { row, col });
+            }
+        }
+
+        Ok((m, n, lda))
+    }
+}
+
+/// A reason why a lag matrix cannot be handed to a BLAS or LAPACK routine;
+/// see [`LagMatrix::gemm_ready`].
+#[derive(Debug, Copy, Clone, Eq, PartialEq)]
+pub enum GemmError {
+    /// The matrix has no rows or no columns.
+    Empty,
+    /// The leading dimension is smaller than the number of columns.
+    InvalidLeadingDimension {
+        /// The number of columns.
+        n: usize,
+        /// The leading dimension.
+        lda: usize,
+    },
+    /// The buffer is shorter than required by the shape and leading dimension.
+    TooShort {
+        /// The length of the buffer.
+        len: usize,
+        /// The required length of the buffer.
+        required: usize,
+    },
+    /// The buffer is not aligned to the element type.
+    Misaligned,
+    /// A lagged gap lies within the logical region.
+    Padding {
+        /// The row of the gap.
+        row: usize,
+        /// The column of the gap.
+        col: usize,
+    },
+    /// A NaN or infinite value lies within the logical region.
+    NonFinite {
+        /// The row of the value.
+        row: usize,
+        /// The column of the value.
+        col: usize,
+    },
+}
+
+impl std::error::Error for GemmError {}
+
+impl Display for GemmError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            GemmError::Empty => write!(f, "The matrix has no rows or no columns"),
+            GemmError::InvalidLeadingDimension { n, lda } => write!(
+                f,
+                "The leading dimension {lda} is smaller than the number of columns {n}"
+            ),
+            GemmError::TooShort { len, required } => write!(
+                f,
+                "The buffer holds {len} elements but the layout requires {required}"
+            ),
+            GemmError::Misaligned => write!(f, "The buffer is not aligned to the element type"),
+            GemmError::Padding { row, col } => write!(
+                f,
+                "The value at row {row}, column {col} is a lagged gap; trim incomplete rows first"
+            ),
+            GemmError::NonFinite { row, col } => {
+                write!(f, "The value at row {row}, column {col} is not finite")
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{lag_matrix, lag_matrix_2d, lag_matrix_segmented, GemmError, MatrixLayout};
+
+    #[test]
+    fn test_gemm_ready_row_major() {
+        let data = [1.0, 2.0, 3.0];
+        let lagged = lag_matrix(&data, [0], 0.0, 4).unwrap();
+        assert_eq!(lagged.gemm_ready(), Ok((1, 3, 4)));
+    }
+
+    #[test]
+    fn test_gemm_ready_reports_non_finite() {
+        let data = [1.0, 2.0, f64::NAN, 4.0];
+        let lagged = lag_matrix_2d(&data, MatrixLayout::ColumnMajor(2), [0], 0.0, 0).unwrap();
+        assert_eq!(
+            lagged.gemm_ready(),
+            Err(GemmError::NonFinite { row: 1, col: 0 })
+        );
+    }
+
+    #[test]
+    fn test_gemm_ready_reports_lead_padding() {
+        let data = [1.0, 2.0, 3.0];
+        let lagged = lag_matrix(&data, [0, -1], 0.0, 0).unwrap();
+        assert_eq!(
+            lagged.gemm_ready(),
+            Err(GemmError::Padding { row: 1, col: 2 })
+        );
+    }
+
+    #[test]
+    fn test_gemm_ready_reports_changepoint_gaps() {
+        let data = [1.0, 2.0, 3.0, 10.0, 20.0];
+        let lagged = lag_matrix_segmented(&data, 0..=1, 0.0, 0, &[3]).unwrap();
+        let window = lagged.slice_time(1..5).unwrap();
+        assert_eq!(
+            window.gemm_ready(),
+            Err(GemmError::Padding { row: 1, col: 2 })
+        );
+    }
+}
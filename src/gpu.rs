@@ -0,0 +1,503 @@
+use crate::{collect_lags, IntoLag, LagError, LagMatrix, LagPlan, MatrixLayout};
+use core::fmt::{Display, Formatter};
+use core::marker::PhantomData;
+use std::sync::mpsc;
+use wgpu::util::{BufferInitDescriptor, DeviceExt};
+
+/// The number of invocations per workgroup of the lag shader.
+const WORKGROUP_SIZE: u32 = 64;
+
+/// Fills one element of the lag matrix per invocation; see [`GpuLagger::lag_matrix_2d`].
+const SHADER: &str = r"
+struct Params {
+    len: u32,
+    row_major: u32,
+    series_length: u32,
+    num_series: u32,
+    num_features: u32,
+    row_stride: u32,
+    fill: u32,
+    threads_per_row: u32,
+}
+
+@group(0) @binding(0) var<uniform> params: Params;
+@group(0) @binding(1) var<storage, read> shifts: array<i32>;
+@group(0) @binding(2) var<storage, read> data: array<u32>;
+@group(0) @binding(3) var<storage, read_write> lagged: array<u32>;
+
+@compute @workgroup_size(64)
+fn main(@builtin(global_invocation_id) id: vec3<u32>) {
+    let index = id.y * params.threads_per_row + id.x;
+    if (index >= params.len) {
+        return;
+    }
+
+    var feature = index % params.row_stride;
+    var t = index / params.row_stride;
+    if (params.row_major != 0u) {
+        feature = index / params.row_stride;
+        t = index % params.row_stride;
+    }
+
+    if (t >= params.series_length || feature >= params.num_features) {
+        lagged[index] = params.fill;
+        return;
+    }
+
+    let series = feature % params.num_series;
+    let source = i32(t) - shifts[feature];
+    if (source < 0 || source >= i32(params.series_length)) {
+        lagged[index] = params.fill;
+    } else if (params.row_major != 0u) {
+        lagged[index] = data[series * params.series_length + u32(source)];
+    } else {
+        lagged[index] = data[u32(source) * params.num_series + series];
+    }
+}
+";
+
+/// A value that can be lagged on the GPU by a [`GpuLagger`].
+///
+/// The trait is sealed and implemented for `f32`, `i32` and `u32`, the 32-bit scalars of
+/// WGSL; values are copied bit by bit.
+pub trait GpuElement: Copy + sealed::Sealed {
+    /// The bits of the value.
+    fn to_bits(self) -> u32;
+
+    /// The value of the specified bits.
+    fn from_bits(bits: u32) -> Self;
+}
+
+mod sealed {
+    /// Restricts [`GpuElement`](super::GpuElement) to the types of this crate.
+    pub trait Sealed {}
+}
+
+impl sealed::Sealed for f32 {}
+impl sealed::Sealed for i32 {}
+impl sealed::Sealed for u32 {}
+
+impl GpuElement for f32 {
+    #[inline(always)]
+    fn to_bits(self) -> u32 {
+        f32::to_bits(self)
+    }
+
+    #[inline(always)]
+    fn from_bits(bits: u32) -> Self {
+        f32::from_bits(bits)
+    }
+}
+
+impl GpuElement for i32 {
+    #[inline(always)]
+    fn to_bits(self) -> u32 {
+        self as u32
+    }
+
+    #[inline(always)]
+    fn from_bits(bits: u32) -> Self {
+        bits as i32
+    }
+}
+
+impl GpuElement for u32 {
+    #[inline(always)]
+    fn to_bits(self) -> u32 {
+        self
+    }
+
+    #[inline(always)]
+    fn from_bits(bits: u32) -> Self {
+        bits
+    }
+}
+
+/// A reason why a lag matrix cannot be created on the GPU.
+#[derive(Debug, Clone)]
+pub enum GpuError {
+    /// No GPU adapter is available.
+    NoAdapter(wgpu::RequestAdapterError),
+    /// The adapter refused to create a device.
+    Device(wgpu::RequestDeviceError),
+    /// The lags or the data are invalid; see [`lag_matrix_2d`](crate::lag_matrix_2d).
+    Lag(LagError),
+    /// A buffer of the specified size in bytes exceeds the limits of the device.
+    ExceedsDeviceLimits {
+        /// The size of the buffer in bytes.
+        size: u64,
+        /// The largest buffer size supported by the device.
+        max: u64,
+    },
+    /// A shift does not fit into the signed 32-bit integers of the shader.
+    ShiftOutOfRange,
+    /// The matrix could not be read back from the GPU.
+    Readback(String),
+}
+
+impl std::error::Error for GpuError {}
+
+impl Display for GpuError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> core::fmt::Result {
+        match self {
+            GpuError::NoAdapter(error) => write!(f, "No GPU adapter is available: {error}"),
+            GpuError::Device(error) => write!(f, "The GPU device could not be created: {error}"),
+            GpuError::Lag(error) => write!(f, "{error}"),
+            GpuError::ExceedsDeviceLimits { size, max } => write!(
+                f,
+                "A buffer of {size} bytes exceeds the device limit of {max} bytes"
+            ),
+            GpuError::ShiftOutOfRange => write!(f, "A shift does not fit into 32 bits"),
+            GpuError::Readback(error) => write!(f, "The matrix could not be read back: {error}"),
+        }
+    }
+}
+
+impl From<LagError> for GpuError {
+    fn from(error: LagError) -> Self {
+        GpuError::Lag(error)
+    }
+}
+
+/// Creates lag matrices with a compute shader on a GPU, e.g. for outputs too large to fill
+/// quickly on the CPU.
+///
+/// Every element of the matrix is written by one invocation of the shader, which either
+/// copies the shifted value or writes the fill value into a storage buffer. The result
+/// stays on the GPU as a [`GpuLagMatrix`] until it is read back, such that frameworks
+/// sharing the [`device`](Self::device) can consume the buffer directly.
+///
+/// The matrix is limited to the largest storage buffer of the device, which the lagger
+/// requests from the adapter in [`new`](Self::new).
+///
+/// ## Example
+/// ```no_run
+/// use timelag::{GpuLagger, MatrixLayout};
+///
+/// let data: Vec<f32> = (0..1000).map(|x| x as f32).collect();
+/// let layout = MatrixLayout::RowMajor(1000);
+///
+/// let gpu = GpuLagger::new().unwrap();
+/// let lagged = gpu.lag_matrix_2d(&data, layout, 0..=24, f32::NAN, 0).unwrap();
+///
+/// let matrix = lagged.to_matrix().unwrap();
+/// assert_eq!(matrix.num_rows(), 25);
+/// assert_eq!(matrix.get(3, 10), Some(&7.0));
+/// ```
+#[derive(Debug, Clone)]
+pub struct GpuLagger {
+    device: wgpu::Device,
+    queue: wgpu::Queue,
+    pipeline: wgpu::ComputePipeline,
+}
+
+impl GpuLagger {
+    /// Creates a lagger on the default adapter, requesting its largest buffers.
+    ///
+    /// ## Returns
+    /// The lagger, or [`GpuError::NoAdapter`] if there is no GPU, or [`GpuError::Device`]
+    /// if the device cannot be created.
+    pub fn new() -> Result<Self, GpuError> {
+        let instance = wgpu::Instance::new(wgpu::InstanceDescriptor::new_without_display_handle());
+        let options = wgpu::RequestAdapterOptions::default();
+        let adapter =
+            pollster::block_on(instance.request_adapter(&options)).map_err(GpuError::NoAdapter)?;
+
+        let descriptor = wgpu::DeviceDescriptor {
+            label: Some("timelag"),
+            required_limits: adapter.limits(),
+            ..Default::default()
+        };
+        let (device, queue) =
+            pollster::block_on(adapter.request_device(&descriptor)).map_err(GpuError::Device)?;
+        Ok(Self::from_device(device, queue))
+    }
+
+    /// Creates a lagger on an existing device, e.g. that of a framework consuming the
+    /// matrices on the GPU.
+    pub fn from_device(device: wgpu::Device, queue: wgpu::Queue) -> Self {
+        let module = device.create_shader_module(wgpu::ShaderModuleDescriptor {
+            label: Some("timelag::lag_matrix_2d"),
+            source: wgpu::ShaderSource::Wgsl(SHADER.into()),
+        });
+        let pipeline = device.create_compute_pipeline(&wgpu::ComputePipelineDescriptor {
+            label: Some("timelag::lag_matrix_2d"),
+            layout: None,
+            module: &module,
+            entry_point: Some("main"),
+            compilation_options: Default::default(),
+            cache: None,
+        });
+
+        Self {
+            device,
+            queue,
+            pipeline,
+        }
+    }
+
+    /// The device the matrices are created on.
+    pub fn device(&self) -> &wgpu::Device {
+        &self.device
+    }
+
+    /// The queue the matrices are created with.
+    pub fn queue(&self) -> &wgpu::Queue {
+        &self.queue
+    }
+
+    /// Creates a lag matrix on the GPU like [`lag_matrix_2d`](crate::lag_matrix_2d).
+    ///
+    /// ## Arguments
+    /// * `data_matrix` - The matrix of multiple series to lag.
+    /// * `layout` - The layout and series length of the data matrix.
+    /// * `lags` - The lags to create; negative lags create leads.
+    /// * `fill` - The value to use to fill in lagged gaps and the padding of the rows.
+    /// * `row_stride` - The number of elements along a row, or `0` for no padding.
+    ///
+    /// ## Returns
+    /// The matrix in a buffer of the device, or an error if the input is invalid or the
+    /// matrix exceeds the limits of the device.
+    pub fn lag_matrix_2d<T: GpuElement, L: IntoLag, R: IntoIterator<Item = L>>(
+        &self,
+        data_matrix: &[T],
+        layout: MatrixLayout,
+        lags: R,
+        fill: T,
+        row_stride: usize,
+    ) -> Result<GpuLagMatrix<T>, GpuError> {
+        let plan = LagPlan::new(data_matrix, layout, collect_lags(lags)?, None, row_stride)?;
+        let len = plan.unit_len() * plan.num_units();
+        let shifts = plan
+            .shifts()
+            .into_iter()
+            .map(|shift| i32::try_from(shift).map_err(|_| GpuError::ShiftOutOfRange))
+            .collect::<Result<Vec<_>, _>>()?;
+
+        let limits = self.device.limits();
+        let max = limits
+            .max_storage_buffer_binding_size
+            .min(limits.max_buffer_size);
+        let size = (len.max(data_matrix.len()) * size_of::<u32>()) as u64;
+        if size > max || len > u32::MAX as usize {
+            return Err(GpuError::ExceedsDeviceLimits { size, max });
+        }
+
+        let workgroups = (len as u32).div_ceil(WORKGROUP_SIZE);
+        let x = workgroups.min(limits.max_compute_workgroups_per_dimension);
+        let y = workgroups.div_ceil(x);
+        if y > limits.max_compute_workgroups_per_dimension {
+            return Err(GpuError::ExceedsDeviceLimits { size, max });
+        }
+
+        let params = [
+            len as u32,
+            u32::from(plan.row_major),
+            plan.series_length as u32,
+            plan.num_series as u32,
+            (plan.lags.len() * plan.num_series) as u32,
+            plan.row_stride as u32,
+            fill.to_bits(),
+            x * WORKGROUP_SIZE,
+        ];
+        let shifts = shifts.iter().map(|&shift| shift as u32);
+        let data = data_matrix.iter().map(|&value| value.to_bits());
+        let params = self.upload("params", params, wgpu::BufferUsages::UNIFORM);
+        let shifts = self.upload("shifts", shifts, wgpu::BufferUsages::STORAGE);
+        let data = self.upload("data", data, wgpu::BufferUsages::STORAGE);
+        let buffer = self.device.create_buffer(&wgpu::BufferDescriptor {
+            label: Some("timelag::GpuLagMatrix"),
+            size: (len * size_of::<u32>()) as u64,
+            usage: wgpu::BufferUsages::STORAGE | wgpu::BufferUsages::COPY_SRC,
+            mapped_at_creation: false,
+        });
+
+        let entries = [&params, &shifts, &data, &buffer]
+            .into_iter()
+            .enumerate()
+            .map(|(binding, buffer)| wgpu::BindGroupEntry {
+                binding: binding as u32,
+                resource: buffer.as_entire_binding(),
+            })
+            .collect::<Vec<_>>();
+        let bind_group = self.device.create_bind_group(&wgpu::BindGroupDescriptor {
+            label: None,
+            layout: &self.pipeline.get_bind_group_layout(0),
+            entries: &entries,
+        });
+
+        let mut encoder = self
+            .device
+            .create_command_encoder(&wgpu::CommandEncoderDescriptor::default());
+        {
+            let mut pass = encoder.begin_compute_pass(&wgpu::ComputePassDescriptor::default());
+            pass.set_pipeline(&self.pipeline);
+            pass.set_bind_group(0, &bind_group, &[]);
+            pass.dispatch_workgroups(x, y, 1);
+        }
+        self.queue.submit([encoder.finish()]);
+
+        Ok(GpuLagMatrix {
+            buffer,
+            device: self.device.clone(),
+            queue: self.queue.clone(),
+            plan,
+            values: PhantomData,
+        })
+    }
+
+    /// Creates a buffer holding the specified words.
+    fn upload(
+        &self,
+        label: &str,
+        words: impl IntoIterator<Item = u32>,
+        usage: wgpu::BufferUsages,
+    ) -> wgpu::Buffer {
+        let contents: Vec<u8> = words.into_iter().flat_map(u32::to_ne_bytes).collect();
+        self.device.create_buffer_init(&BufferInitDescriptor {
+            label: Some(label),
+            contents: &contents,
+            usage,
+        })
+    }
+}
+
+/// A lag matrix in a storage buffer of a GPU; see [`GpuLagger::lag_matrix_2d`].
+///
+/// The buffer holds the values of the [`LagMatrix`] returned by
+/// [`to_matrix`](Self::to_matrix) as 32-bit words in the same order, and can be copied
+/// from or bound as storage by other shaders.
+#[derive(Debug)]
+pub struct GpuLagMatrix<T> {
+    buffer: wgpu::Buffer,
+    device: wgpu::Device,
+    queue: wgpu::Queue,
+    plan: LagPlan,
+    values: PhantomData<T>,
+}
+
+impl<T> GpuLagMatrix<T>
+where
+    T: GpuElement,
+{
+    /// The buffer holding the values of the matrix.
+    pub fn buffer(&self) -> &wgpu::Buffer {
+        &self.buffer
+    }
+
+    /// The number of values of the matrix, including the padding of the rows.
+    pub fn len(&self) -> usize {
+        self.plan.unit_len() * self.plan.num_units()
+    }
+
+    /// Determines whether the matrix holds no values, which is never the case.
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// Reads the matrix back from the GPU, waiting for the shader to complete.
+    ///
+    /// ## Returns
+    /// The matrix, or [`GpuError::Readback`] if the buffer cannot be mapped.
+    pub fn to_matrix(&self) -> Result<LagMatrix<T>, GpuError> {
+        let size = self.buffer.size();
+        let staging = self.device.create_buffer(&wgpu::BufferDescriptor {
+            label: Some("timelag::GpuLagMatrix::to_matrix"),
+            size,
+            usage: wgpu::BufferUsages::MAP_READ | wgpu::BufferUsages::COPY_DST,
+            mapped_at_creation: false,
+        });
+
+        let mut encoder = self
+            .device
+            .create_command_encoder(&wgpu::CommandEncoderDescriptor::default());
+        encoder.copy_buffer_to_buffer(&self.buffer, 0, &staging, 0, size);
+        self.queue.submit([encoder.finish()]);
+
+        let (sender, receiver) = mpsc::channel();
+        staging.map_async(wgpu::MapMode::Read, .., move |result| {
+            let _ = sender.send(result);
+        });
+        let readback = |error: &dyn Display| GpuError::Readback(error.to_string());
+        self.device
+            .poll(wgpu::PollType::wait_indefinitely())
+            .map_err(|error| readback(&error))?;
+        receiver
+            .recv()
+            .map_err(|error| readback(&error))?
+            .map_err(|error| readback(&error))?;
+
+        let values = {
+            let view = staging
+                .get_mapped_range(..)
+                .map_err(|error| readback(&error))?;
+            view.chunks_exact(size_of::<u32>())
+                .map(|word| T::from_bits(u32::from_ne_bytes(word.try_into().unwrap())))
+                .collect()
+        };
+        staging.unmap();
+        Ok(self.plan.clone().into_matrix(values))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::lag_matrix_2d;
+
+    /// The lagger of the default adapter, or `None` on machines without a GPU, where the
+    /// tests are skipped.
+    fn lagger() -> Option<GpuLagger> {
+        match GpuLagger::new() {
+            Ok(lagger) => Some(lagger),
+            Err(GpuError::NoAdapter(_)) => None,
+            Err(error) => panic!("{error}"),
+        }
+    }
+
+    #[test]
+    fn test_matches_cpu_matrices() {
+        let Some(gpu) = lagger() else {
+            return;
+        };
+
+        let data: Vec<f32> = (0..60).map(|x| x as f32 * 0.5).collect();
+        let ints: Vec<i32> = (0..60).map(|x| 30 - x).collect();
+        for layout in [MatrixLayout::RowMajor(20), MatrixLayout::ColumnMajor(20)] {
+            for (lags, stride) in [(vec![0, 1, 5], 0), (vec![3, -2, 0, 19], 13), (vec![-4], 0)] {
+                let stride = match layout {
+                    MatrixLayout::RowMajor(_) => stride * 2,
+                    MatrixLayout::ColumnMajor(_) => stride,
+                };
+                let expected = lag_matrix_2d(&data, layout, lags.clone(), -1.0, stride).unwrap();
+                let actual = gpu
+                    .lag_matrix_2d(&data, layout, lags.clone(), -1.0, stride)
+                    .unwrap();
+                assert_eq!(actual.len(), expected.len());
+                assert_eq!(actual.to_matrix().unwrap(), expected, "{layout:?} {lags:?}");
+
+                let expected = lag_matrix_2d(&ints, layout, lags.clone(), i32::MIN, stride);
+                let actual = gpu.lag_matrix_2d(&ints, layout, lags, i32::MIN, stride);
+                assert_eq!(actual.unwrap().to_matrix().unwrap(), expected.unwrap());
+            }
+        }
+    }
+
+    #[test]
+    fn test_rejects_invalid_input() {
+        let Some(gpu) = lagger() else {
+            return;
+        };
+
+        let data = [1u32, 2, 3];
+        let layout = MatrixLayout::RowMajor(3);
+        assert!(matches!(
+            gpu.lag_matrix_2d(&data, layout, [4], 0, 0),
+            Err(GpuError::Lag(LagError::LagExceedsValueCount))
+        ));
+        assert!(matches!(
+            gpu.lag_matrix_2d(&data, layout, [0, 1], 0, 2),
+            Err(GpuError::Lag(LagError::InvalidStride))
+        ));
+    }
+}
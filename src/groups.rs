@@ -0,0 +1,139 @@
+use crate::{
+    collect_lags, lag_matrix_2d_impl, shift_of, IntoLag, LagError, LagMatrix, MatrixLayout,
+};
+use std::collections::HashMap;
+use std::hash::Hash;
+
+/// Create a time-lagged matrix of panel data, lagging the values of every group (e.g. a
+/// stock ticker or a machine id) on their own.
+///
+/// The values are observations of multiple entities in time order, where `group_ids` assigns
+/// every value to its entity. The groups do not need to be contiguous: a lag of `k` refers to
+/// the `k`-th previous value of the same group, and a lead to the `k`-th next one, similar to
+/// `groupby().shift()` in pandas. Lagged values that do not exist in their group are set to
+/// the fill value, such that no value ever leaks across group boundaries.
+///
+/// The gaps of the groups are tracked as gaps within the series, such that
+/// [`valid_length`](LagMatrix::valid_length) and [`iter_complete`](LagMatrix::iter_complete)
+/// skip the time steps that lack a value of their group; see
+/// [`has_interior_gaps`](LagMatrix::has_interior_gaps).
+///
+/// ## Arguments
+/// * `values` - The values of all groups, in time order.
+/// * `group_ids` - The group of every value.
+/// * `lags` - The lags to create; negative lags create leads, see [`IntoLag`].
+/// * `fill` - The value to use to fill in lagged gaps.
+/// * `row_stride` - The number of elements along a row of the matrix, see
+///   [`lag_matrix`](crate::lag_matrix).
+///
+/// ## Returns
+/// The matrix with one row per lag, or an error, e.g. [`LagError::InvalidLength`] if the
+/// number of group ids differs from the number of values.
+///
+/// ## Example
+/// ```
+/// use timelag::lag_by_group;
+///
+/// let values = [1, 10, 2, 20, 3, 4];
+/// let groups = ["a", "b", "a", "b", "a", "a"];
+/// let lagged = lag_by_group(&values, &groups, [1], 0, 0).unwrap();
+///
+/// assert_eq!(lagged, &[0, 0, 1, 10, 2, 3]);
+/// ```
+pub fn lag_by_group<T, G, L, R>(
+    values: &[T],
+    group_ids: &[G],
+    lags: R,
+    fill: T,
+    row_stride: usize,
+) -> Result<LagMatrix<T>, LagError>
+where
+    T: Copy,
+    G: Eq + Hash,
+    L: IntoLag,
+    R: IntoIterator<Item = L>,
+{
+    if values.len() != group_ids.len() {
+        return Err(LagError::InvalidLength);
+    }
+
+    let mut matrix = lag_matrix_2d_impl(
+        values,
+        MatrixLayout::RowMajor(values.len()),
+        collect_lags(lags)?,
+        None,
+        fill,
+        row_stride,
+    )?;
+
+    let mut groups: HashMap<&G, Vec<usize>> = HashMap::new();
+    for (t, group) in group_ids.iter().enumerate() {
+        groups.entry(group).or_default().push(t);
+    }
+
+    for lag_index in 0..matrix.num_lags {
+        let shift = shift_of(matrix.lags[lag_index], matrix.directions[0]);
+        for steps in groups.values() {
+            for (position, &t) in steps.iter().enumerate() {
+                let source = position
+                    .checked_add_signed(-shift)
+                    .and_then(|source| steps.get(source));
+                let offset = matrix.offset_of(lag_index, 0, t);
+                match source {
+                    Some(&source) => matrix.data[offset] = values[source],
+                    None => {
+                        matrix.data[offset] = fill;
+                        matrix.mark_gap(lag_index, 0, t);
+                    }
+                }
+            }
+        }
+    }
+
+    Ok(matrix)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::lag_matrix;
+
+    #[test]
+    #[rustfmt::skip]
+    fn test_interleaved_groups_with_leads() {
+        let values = [1, 10, 2, 100, 20, 3];
+        let groups = [0, 1, 0, 2, 1, 0];
+        let lagged = lag_by_group(&values, &groups, [0, 2, -1], -1, 0).unwrap();
+        assert_eq!(lagged, &[
+             1, 10,  2, 100, 20,  3,
+            -1, -1, -1,  -1, -1,  1,
+             2, 20,  3,  -1, -1, -1,
+        ]);
+    }
+
+    #[test]
+    fn test_group_starts_are_incomplete() {
+        let values = [1, 10, 2, 20, 3];
+        let groups = ["a", "b", "a", "b", "a"];
+        let lagged = lag_by_group(&values, &groups, 0..=1, 0, 0).unwrap();
+
+        assert!(lagged.has_interior_gaps());
+        assert_eq!(lagged.valid_length(), 3);
+        let steps: Vec<usize> = lagged.iter_complete().map(|(t, _)| t).collect();
+        assert_eq!(steps, [2, 3, 4]);
+        assert_eq!(lagged.trim_incomplete(), Err(LagError::InteriorGaps));
+    }
+
+    #[test]
+    fn test_single_group_matches_lag_matrix() {
+        let values = [1.0, 2.0, 3.0, 4.0];
+        let expected = lag_matrix(&values, 0..=2, 0.0, 6).unwrap();
+        let lagged = lag_by_group(&values, &["x"; 4], 0..=2, 0.0, 6).unwrap();
+        assert_eq!(lagged, expected);
+
+        assert_eq!(
+            lag_by_group(&values, &["x"; 3], [1], 0.0, 0),
+            Err(LagError::InvalidLength)
+        );
+    }
+}
@@ -0,0 +1,539 @@
+use crate::LagMatrix;
+use core::iter::{FusedIterator, StepBy, Take};
+use core::ops::{Index, Range};
+use core::slice::{Chunks, Iter};
+
+impl<T, S> LagMatrix<T, S>
+where
+    S: AsRef<[T]>,
+{
+    /// Iterates the rows of the matrix, excluding row padding.
+    ///
+    /// In row-major matrices, every row holds a lagged series; in column-major matrices,
+    /// every row holds the lagged values of a time step.
+    ///
+    /// ## Example
+    /// ```
+    /// use timelag::lag_matrix;
+    ///
+    /// let lagged = lag_matrix(&[1, 2, 3], 0..=1, 0, 5).unwrap();
+    ///
+    /// let rows: Vec<&[i32]> = lagged.rows().collect();
+    /// assert_eq!(rows, [&[1, 2, 3][..], &[0, 1, 2][..]]);
+    /// ```
+    pub fn rows(&self) -> Rows<'_, T> {
+        Rows {
+            chunks: self
+                .data
+                .as_ref()
+                .chunks(self.row_stride.max(1))
+                .take(self.num_rows),
+            num_cols: self.num_cols,
+        }
+    }
+
+    /// Copies the rows of the matrix, excluding row padding, into nested vectors,
+    /// e.g. for tools that do not use ndarray.
+    ///
+    /// ## Example
+    /// ```
+    /// use timelag::lag_matrix;
+    ///
+    /// let lagged = lag_matrix(&[1, 2, 3], 0..=1, 0, 5).unwrap();
+    ///
+    /// assert_eq!(lagged.to_nested(), [[1, 2, 3], [0, 1, 2]]);
+    /// ```
+    pub fn to_nested(&self) -> Vec<Vec<T>>
+    where
+        T: Clone,
+    {
+        self.rows().map(<[T]>::to_vec).collect()
+    }
+
+    /// Iterates the columns of the matrix, each as an iterator stepping over the row stride.
+    ///
+    /// ## Example
+    /// ```
+    /// use timelag::{lag_matrix_2d, MatrixLayout};
+    ///
+    /// let lagged = lag_matrix_2d(&[1, 2, 3], MatrixLayout::ColumnMajor(3), 0..=1, 0, 4).unwrap();
+    ///
+    /// let columns: Vec<Vec<i32>> = lagged.cols().map(|col| col.copied().collect()).collect();
+    /// assert_eq!(columns, [[1, 2, 3], [0, 1, 2]]);
+    /// ```
+    pub fn cols(&self) -> Columns<'_, T> {
+        Columns {
+            data: self.data.as_ref(),
+            cols: 0..self.num_cols,
+            row_stride: self.row_stride.max(1),
+        }
+    }
+
+    /// Iterates the time steps without fill values, e.g. to fit a regression on complete
+    /// cases only, together with their index in `0..series_length`.
+    ///
+    /// The values of every time step are ordered like a row of a column-major matrix, i.e.
+    /// by lag and then by series, followed by the static columns, if any. Time steps with
+    /// gaps within the series are skipped; see [`has_interior_gaps`](Self::has_interior_gaps).
+    ///
+    /// ## Example
+    /// ```
+    /// use timelag::lag_matrix;
+    ///
+    /// let lagged = lag_matrix(&[1, 2, 3, 4], 0..=2, 0, 0).unwrap();
+    ///
+    /// let steps: Vec<(usize, Vec<i32>)> = lagged
+    ///     .iter_complete()
+    ///     .map(|(t, values)| (t, values.copied().collect()))
+    ///     .collect();
+    /// assert_eq!(steps, [(2, vec![3, 2, 1]), (3, vec![4, 3, 2])]);
+    /// ```
+    pub fn iter_complete(&self) -> CompleteSteps<'_, T> {
+        let (leading, trailing) = self.incomplete_steps();
+        let steps = leading..self.series_length - trailing;
+        CompleteSteps {
+            data: self.data.as_ref(),
+            remaining: steps.len() - self.interior_gap_steps(),
+            steps,
+            gaps: self.gap_steps(),
+            row_stride: self.row_stride,
+            num_cols: self.num_cols,
+            row_major: self.row_major,
+        }
+    }
+
+    /// The values of the specified series at the specified lag, indexable by time step
+    /// regardless of the orientation of the matrix, e.g. as created by
+    /// [`lag_matrix_2d`](crate::lag_matrix_2d).
+    ///
+    /// ## Returns
+    /// The values, or `None` if the series or the lag is not part of the matrix.
+    ///
+    /// ## Example
+    /// ```
+    /// use timelag::{lag_matrix_2d, MatrixLayout};
+    ///
+    /// let data = [
+    ///     1, 10,
+    ///     2, 20,
+    ///     3, 30,
+    /// ];
+    /// let lagged = lag_matrix_2d(&data, MatrixLayout::ColumnMajor(3), 0..=1, 0, 0).unwrap();
+    /// let values = lagged.lag_of(1, 1).unwrap();
+    ///
+    /// assert_eq!(values.len(), 3);
+    /// assert_eq!(values[2], 20);
+    /// assert_eq!(values.as_slice(), None); // interleaved with the other columns
+    /// assert_eq!(values.to_vec(), [0, 10, 20]);
+    /// ```
+    pub fn lag_of(&self, series: usize, lag: isize) -> Option<LagValues<'_, T>> {
+        if series >= self.series_count {
+            return None;
+        }
+        let lag_index = self.lags.iter().position(|&l| l == lag)?;
+        let start = self.offset_of(lag_index, series, 0);
+        let stride = if self.row_major {
+            1
+        } else {
+            self.row_stride.max(1)
+        };
+        let end = match self.series_length {
+            0 => start,
+            len => start + (len - 1) * stride + 1,
+        };
+        Some(LagValues {
+            data: &self.data.as_ref()[start..end],
+            stride,
+        })
+    }
+}
+
+/// The values of a series at a lag in time order; see [`LagMatrix::lag_of`].
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub struct LagValues<'a, T> {
+    /// The elements from the first to the last value, inclusive.
+    data: &'a [T],
+    stride: usize,
+}
+
+impl<'a, T> LagValues<'a, T> {
+    /// The number of time steps.
+    #[inline(always)]
+    pub fn len(&self) -> usize {
+        self.data.len().div_ceil(self.stride)
+    }
+
+    /// Determines whether there are no time steps.
+    #[inline(always)]
+    pub fn is_empty(&self) -> bool {
+        self.data.is_empty()
+    }
+
+    /// The value at the specified time step, or `None` if it is out of range.
+    #[inline(always)]
+    pub fn get(&self, t: usize) -> Option<&'a T> {
+        self.data.get(t.checked_mul(self.stride)?)
+    }
+
+    /// The values as a slice, or `None` if they are not contiguous, e.g. in column-major
+    /// matrices of more than one column.
+    #[inline(always)]
+    pub fn as_slice(&self) -> Option<&'a [T]> {
+        (self.stride == 1).then_some(self.data)
+    }
+
+    /// Iterates the values.
+    #[inline(always)]
+    pub fn iter(&self) -> StepBy<Iter<'a, T>> {
+        self.data.iter().step_by(self.stride)
+    }
+
+    /// Copies the values into a vector.
+    pub fn to_vec(&self) -> Vec<T>
+    where
+        T: Clone,
+    {
+        self.iter().cloned().collect()
+    }
+}
+
+impl<T> Index<usize> for LagValues<'_, T> {
+    type Output = T;
+
+    #[inline(always)]
+    fn index(&self, t: usize) -> &Self::Output {
+        self.get(t).expect("the time step is in range")
+    }
+}
+
+impl<'a, T> IntoIterator for LagValues<'a, T> {
+    type Item = &'a T;
+    type IntoIter = StepBy<Iter<'a, T>>;
+
+    #[inline(always)]
+    fn into_iter(self) -> Self::IntoIter {
+        self.iter()
+    }
+}
+
+/// An iterator over the rows of a [`LagMatrix`]; see [`LagMatrix::rows`].
+#[derive(Debug, Clone)]
+pub struct Rows<'a, T> {
+    chunks: Take<Chunks<'a, T>>,
+    num_cols: usize,
+}
+
+impl<'a, T> Iterator for Rows<'a, T> {
+    type Item = &'a [T];
+
+    #[inline(always)]
+    fn next(&mut self) -> Option<Self::Item> {
+        self.chunks.next().map(|row| &row[..self.num_cols])
+    }
+
+    #[inline(always)]
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        self.chunks.size_hint()
+    }
+}
+
+impl<T> DoubleEndedIterator for Rows<'_, T> {
+    #[inline(always)]
+    fn next_back(&mut self) -> Option<Self::Item> {
+        self.chunks.next_back().map(|row| &row[..self.num_cols])
+    }
+}
+
+impl<T> ExactSizeIterator for Rows<'_, T> {}
+
+impl<T> FusedIterator for Rows<'_, T> {}
+
+/// An iterator over the columns of a [`LagMatrix`]; see [`LagMatrix::cols`].
+#[derive(Debug, Clone)]
+pub struct Columns<'a, T> {
+    data: &'a [T],
+    cols: Range<usize>,
+    row_stride: usize,
+}
+
+impl<'a, T> Columns<'a, T> {
+    /// The values of the specified column.
+    fn column(&self, col: usize) -> StepBy<Iter<'a, T>> {
+        self.data[col..].iter().step_by(self.row_stride)
+    }
+}
+
+impl<'a, T> Iterator for Columns<'a, T> {
+    type Item = StepBy<Iter<'a, T>>;
+
+    #[inline(always)]
+    fn next(&mut self) -> Option<Self::Item> {
+        self.cols.next().map(|col| self.column(col))
+    }
+
+    #[inline(always)]
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        self.cols.size_hint()
+    }
+}
+
+impl<T> DoubleEndedIterator for Columns<'_, T> {
+    #[inline(always)]
+    fn next_back(&mut self) -> Option<Self::Item> {
+        self.cols.next_back().map(|col| self.column(col))
+    }
+}
+
+impl<T> ExactSizeIterator for Columns<'_, T> {}
+
+impl<T> FusedIterator for Columns<'_, T> {}
+
+impl<'a, T, S> IntoIterator for &'a LagMatrix<T, S>
+where
+    S: AsRef<[T]>,
+{
+    type Item = &'a [T];
+    type IntoIter = Rows<'a, T>;
+
+    /// Iterates the rows of the matrix; see [`LagMatrix::rows`].
+    #[inline(always)]
+    fn into_iter(self) -> Self::IntoIter {
+        self.rows()
+    }
+}
+
+impl<T> IntoIterator for LagMatrix<T> {
+    type Item = Vec<T>;
+    type IntoIter = IntoRows<T>;
+
+    /// Converts the matrix into an iterator over its rows, excluding row padding.
+    ///
+    /// ## Example
+    /// ```
+    /// use timelag::lag_matrix;
+    ///
+    /// let lagged = lag_matrix(&[1, 2, 3], 0..=1, 0, 5).unwrap();
+    ///
+    /// let rows: Vec<Vec<i32>> = lagged.into_iter().collect();
+    /// assert_eq!(rows, [[1, 2, 3], [0, 1, 2]]);
+    /// ```
+    fn into_iter(self) -> Self::IntoIter {
+        IntoRows {
+            values: self.data.into_iter(),
+            remaining: self.num_rows,
+            num_cols: self.num_cols,
+            padding: self.row_stride - self.num_cols,
+        }
+    }
+}
+
+/// An iterator over the owned rows of a [`LagMatrix`], excluding row padding.
+#[derive(Debug, Clone)]
+pub struct IntoRows<T> {
+    values: std::vec::IntoIter<T>,
+    remaining: usize,
+    num_cols: usize,
+    padding: usize,
+}
+
+impl<T> Iterator for IntoRows<T> {
+    type Item = Vec<T>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.remaining == 0 {
+            return None;
+        }
+        self.remaining -= 1;
+
+        let row = self.values.by_ref().take(self.num_cols).collect();
+        if self.padding > 0 {
+            self.values.nth(self.padding - 1);
+        }
+        Some(row)
+    }
+
+    #[inline(always)]
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        (self.remaining, Some(self.remaining))
+    }
+}
+
+impl<T> ExactSizeIterator for IntoRows<T> {}
+
+impl<T> FusedIterator for IntoRows<T> {}
+
+/// An iterator over the time steps of a [`LagMatrix`] without fill values;
+/// see [`LagMatrix::iter_complete`].
+#[derive(Debug, Clone)]
+pub struct CompleteSteps<'a, T> {
+    data: &'a [T],
+    steps: Range<usize>,
+    gaps: Option<Vec<bool>>,
+    remaining: usize,
+    row_stride: usize,
+    num_cols: usize,
+    row_major: bool,
+}
+
+impl<'a, T> CompleteSteps<'a, T> {
+    /// The values of the specified time step.
+    fn step(&self, t: usize) -> (usize, StepBy<Iter<'a, T>>) {
+        let values = if self.row_major {
+            self.data[t..].iter().step_by(self.row_stride)
+        } else {
+            let start = t * self.row_stride;
+            self.data[start..start + self.num_cols].iter().step_by(1)
+        };
+        (t, values)
+    }
+}
+
+impl<'a, T> Iterator for CompleteSteps<'a, T> {
+    type Item = (usize, StepBy<Iter<'a, T>>);
+
+    #[inline(always)]
+    fn next(&mut self) -> Option<Self::Item> {
+        let gaps = self.gaps.as_deref();
+        let t = self.steps.find(|&t| !gaps.is_some_and(|gaps| gaps[t]))?;
+        self.remaining -= 1;
+        Some(self.step(t))
+    }
+
+    #[inline(always)]
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        (self.remaining, Some(self.remaining))
+    }
+}
+
+impl<T> DoubleEndedIterator for CompleteSteps<'_, T> {
+    #[inline(always)]
+    fn next_back(&mut self) -> Option<Self::Item> {
+        let gaps = self.gaps.as_deref();
+        let t = self.steps.rfind(|&t| !gaps.is_some_and(|gaps| gaps[t]))?;
+        self.remaining -= 1;
+        Some(self.step(t))
+    }
+}
+
+impl<T> ExactSizeIterator for CompleteSteps<'_, T> {}
+
+impl<T> FusedIterator for CompleteSteps<'_, T> {}
+
+#[cfg(test)]
+mod tests {
+    use crate::{
+        lag_matrix_2d, lag_matrix_2d_directed, to_column_major, LagDirection, LagMatrix,
+        MatrixLayout,
+    };
+
+    #[test]
+    fn test_rows_and_cols_agree_with_get() {
+        let data = [1, 10, 2, 20, 3, 30];
+        for layout in [MatrixLayout::ColumnMajor(3), MatrixLayout::RowMajor(3)] {
+            let lagged = lag_matrix_2d(&data, layout, [0, 2], -1, 7).unwrap();
+            assert_eq!(lagged.rows().len(), lagged.num_rows());
+            assert_eq!(lagged.cols().len(), lagged.num_cols());
+
+            for (row, values) in lagged.rows().enumerate() {
+                let expected: Vec<i32> = (0..lagged.num_cols())
+                    .map(|col| lagged[(row, col)])
+                    .collect();
+                assert_eq!(values, expected);
+            }
+
+            for (col, values) in lagged.cols().enumerate() {
+                assert_eq!(values.len(), lagged.num_rows());
+                for (row, &value) in values.enumerate() {
+                    assert_eq!(value, lagged[(row, col)]);
+                }
+            }
+
+            let mut last = lagged.cols().next_back().unwrap();
+            assert_eq!(
+                last.next_back(),
+                lagged.get(lagged.num_rows() - 1, lagged.num_cols() - 1)
+            );
+        }
+    }
+
+    #[test]
+    fn test_lag_of_agrees_with_lag_series() {
+        let data = [1, 10, 2, 20, 3, 30];
+        for layout in [MatrixLayout::ColumnMajor(3), MatrixLayout::RowMajor(3)] {
+            let lagged = lag_matrix_2d(&data, layout, [0, 2, -1], -1, 8).unwrap();
+            for series in 0..2 {
+                for lag in [0, 2, -1] {
+                    let values = lagged.lag_of(series, lag).unwrap();
+                    let expected: Vec<i32> =
+                        lagged.lag_series(series, lag).unwrap().copied().collect();
+                    assert_eq!(values.len(), 3);
+                    assert_eq!(values.to_vec(), expected);
+                    assert_eq!(values.get(3), None);
+                    assert!((0..3).all(|t| values[t] == expected[t]));
+                    assert_eq!(values.as_slice().is_some(), lagged.is_row_major());
+                }
+            }
+            assert!(lagged.lag_of(2, 0).is_none());
+            assert!(lagged.lag_of(0, 1).is_none());
+        }
+    }
+
+    #[test]
+    fn test_into_iterator_strips_padding() {
+        let data = [1, 10, 2, 20, 3, 30];
+        let lagged = lag_matrix_2d(&data, MatrixLayout::ColumnMajor(3), [0, 1], -1, 6).unwrap();
+
+        let mut borrowed = Vec::new();
+        for row in &lagged {
+            borrowed.push(row.to_vec());
+        }
+
+        assert_eq!(lagged.to_nested(), borrowed);
+        let owned: Vec<Vec<i32>> = lagged.into_iter().collect();
+        assert_eq!(owned, borrowed);
+        assert_eq!(owned, [[1, 10, -1, -1], [2, 20, 1, 10], [3, 30, 2, 20]]);
+    }
+
+    #[test]
+    fn test_iter_complete_in_both_layouts() {
+        let data: Vec<i32> = (0..12).collect();
+        let directions = [LagDirection::Lag, LagDirection::Lead];
+        let row_major =
+            lag_matrix_2d_directed(&data, MatrixLayout::RowMajor(6), [0, 2], &directions, -1, 9)
+                .unwrap();
+
+        let data = to_column_major(&data, MatrixLayout::RowMajor(6)).unwrap();
+        let column_major = lag_matrix_2d_directed(
+            &data,
+            MatrixLayout::ColumnMajor(6),
+            [0, 2],
+            &directions,
+            -1,
+            5,
+        )
+        .unwrap();
+
+        // The rows of the trimmed column-major matrix are the complete time steps.
+        let trimmed = column_major.clone().trim_incomplete().unwrap();
+        for lagged in [&row_major, &column_major] {
+            let steps: Vec<(usize, Vec<i32>)> = lagged
+                .iter_complete()
+                .map(|(t, values)| (t, values.copied().collect()))
+                .collect();
+
+            assert_eq!(steps.len(), 2);
+            for ((t, values), (expected_t, row)) in steps.into_iter().zip((2..).zip(&trimmed)) {
+                assert_eq!(t, expected_t);
+                assert_eq!(values, row);
+            }
+        }
+    }
+
+    #[test]
+    fn test_empty_matrix() {
+        let lagged = LagMatrix::<f64>::default();
+        assert_eq!(lagged.rows().count(), 0);
+        assert_eq!(lagged.cols().count(), 0);
+        assert_eq!(lagged.into_iter().len(), 0);
+    }
+}
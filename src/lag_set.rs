@@ -0,0 +1,209 @@
+use crate::LagError;
+use core::ops::{Deref, RangeInclusive};
+
+/// A selection of lags.
+///
+/// Lag sets can be passed to every function accepting lags, as they implement
+/// [`IntoIterator<Item = usize>`].
+///
+/// ## Example
+/// ```
+/// use timelag::{lag_matrix, LagSet};
+///
+/// let lags = LagSet::exponential(2, 4).unwrap();
+/// assert_eq!(lags.as_slice(), &[1, 2, 4, 8]);
+/// assert_eq!(lags.max_lag(), Some(8));
+///
+/// let data: Vec<f64> = (0..16).map(f64::from).collect();
+/// let lagged = lag_matrix(&data, &lags, f64::NAN, 0).unwrap();
+/// assert_eq!(lagged.num_rows(), 4);
+/// ```
+#[derive(Debug, Clone, Default, PartialEq, Eq, Hash)]
+pub struct LagSet {
+    lags: Vec<usize>,
+}
+
+impl LagSet {
+    /// Creates a lag set from the specified lags, retaining their order.
+    pub fn new<R: IntoIterator<Item = usize>>(lags: R) -> Self {
+        Self {
+            lags: Vec::from_iter(lags),
+        }
+    }
+
+    /// Creates exponentially spaced lags `1, base, base², …` with `count` elements.
+    ///
+    /// ## Returns
+    /// The lag set, or [`LagError::InvalidLags`] if `base` is less than two or the
+    /// largest lag does not fit into a `usize`.
+    pub fn exponential(base: usize, count: usize) -> Result<Self, LagError> {
+        if base < 2 {
+            return Err(LagError::InvalidLags);
+        }
+
+        let mut lags = Vec::with_capacity(count);
+        let mut lag: usize = 1;
+        for i in 0..count {
+            if i > 0 {
+                lag = lag.checked_mul(base).ok_or(LagError::InvalidLags)?;
+            }
+            lags.push(lag);
+        }
+
+        Ok(Self { lags })
+    }
+
+    /// The lags of this set.
+    #[inline(always)]
+    pub fn as_slice(&self) -> &[usize] {
+        &self.lags
+    }
+
+    /// The number of lags in this set.
+    #[inline(always)]
+    pub fn len(&self) -> usize {
+        self.lags.len()
+    }
+
+    /// Determines whether this set contains no lags.
+    #[inline(always)]
+    pub fn is_empty(&self) -> bool {
+        self.lags.is_empty()
+    }
+
+    /// The smallest lag of this set, if any.
+    pub fn min_lag(&self) -> Option<usize> {
+        self.lags.iter().copied().min()
+    }
+
+    /// The largest lag of this set, if any. This is the number of observations
+    /// consumed before all lags have data.
+    pub fn max_lag(&self) -> Option<usize> {
+        self.lags.iter().copied().max()
+    }
+
+    /// Partitions the range up to the largest lag into contiguous buckets ending at each lag.
+    ///
+    /// The lags are visited in ascending order; the first bucket contains only the smallest lag
+    /// and every following bucket spans the lags after the previous lag up to and including the
+    /// current one. The buckets can be passed to [`lag_matrix_bucketed`](crate::lag_matrix_bucketed)
+    /// to aggregate long-range lags.
+    ///
+    /// ## Example
+    /// ```
+    /// use timelag::LagSet;
+    ///
+    /// let lags = LagSet::exponential(2, 4).unwrap();
+    /// assert_eq!(lags.buckets(), vec![1..=1, 2..=2, 3..=4, 5..=8]);
+    /// ```
+    pub fn buckets(&self) -> Vec<RangeInclusive<usize>> {
+        let mut lags = self.lags.clone();
+        lags.sort_unstable();
+        lags.dedup();
+
+        let mut previous = None;
+        lags.into_iter()
+            .map(|lag| {
+                let start = previous.map_or(lag, |previous: usize| previous + 1);
+                previous = Some(lag);
+                start..=lag
+            })
+            .collect()
+    }
+
+    /// Converts this lag set into a vector of lags.
+    #[inline(always)]
+    pub fn into_vec(self) -> Vec<usize> {
+        self.lags
+    }
+}
+
+impl Deref for LagSet {
+    type Target = [usize];
+
+    #[inline(always)]
+    fn deref(&self) -> &Self::Target {
+        &self.lags
+    }
+}
+
+impl From<Vec<usize>> for LagSet {
+    #[inline(always)]
+    fn from(lags: Vec<usize>) -> Self {
+        Self { lags }
+    }
+}
+
+impl From<LagSet> for Vec<usize> {
+    #[inline(always)]
+    fn from(value: LagSet) -> Self {
+        value.lags
+    }
+}
+
+impl FromIterator<usize> for LagSet {
+    fn from_iter<I: IntoIterator<Item = usize>>(iter: I) -> Self {
+        Self::new(iter)
+    }
+}
+
+impl IntoIterator for LagSet {
+    type Item = usize;
+    type IntoIter = std::vec::IntoIter<usize>;
+
+    #[inline(always)]
+    fn into_iter(self) -> Self::IntoIter {
+        self.lags.into_iter()
+    }
+}
+
+impl<'a> IntoIterator for &'a LagSet {
+    type Item = usize;
+    type IntoIter = core::iter::Copied<core::slice::Iter<'a, usize>>;
+
+    #[inline(always)]
+    fn into_iter(self) -> Self::IntoIter {
+        self.lags.iter().copied()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    #[cfg(feature = "float")]
+    use crate::lag_matrix_bucketed;
+
+    #[test]
+    fn test_exponential() {
+        let lags = LagSet::exponential(3, 4).unwrap();
+        assert_eq!(lags.as_slice(), &[1, 3, 9, 27]);
+        assert_eq!(lags.min_lag(), Some(1));
+        assert_eq!(lags.max_lag(), Some(27));
+        assert!(LagSet::exponential(2, 0).unwrap().is_empty());
+    }
+
+    #[test]
+    fn test_exponential_invalid() {
+        assert_eq!(LagSet::exponential(1, 3), Err(LagError::InvalidLags));
+        assert_eq!(LagSet::exponential(2, 100), Err(LagError::InvalidLags));
+    }
+
+    #[test]
+    fn test_buckets_unordered() {
+        let lags = LagSet::new([12, 1, 3, 3]);
+        assert_eq!(lags.buckets(), vec![1..=1, 2..=3, 4..=12]);
+    }
+
+    #[test]
+    #[cfg(feature = "float")]
+    fn test_exponential_buckets_aggregate() {
+        let data: Vec<f64> = (0..10).map(f64::from).collect();
+        let buckets = LagSet::exponential(2, 3).unwrap().buckets();
+
+        let aggregated = lag_matrix_bucketed(&data, buckets, f64::NAN, 0).unwrap();
+        assert_eq!(aggregated.num_rows(), 3);
+
+        // The mean of lags three and four at the last time step.
+        assert_eq!(aggregated[2 * 10 + 9], 5.5);
+    }
+}
@@ -5,10 +5,41 @@
 //!
 //! ## Crate Features
 //!
-//! * `ndarray` - Enables support for [ndarray](https://crates.io/crates/ndarray)'s `Array1` and `Array2` traits.
+//! * `float` - Enables the helpers specific to floating-point values, such as `features`,
+//!   scaling, winsorization, augmentation and synthetic series (enabled by default). Disable the
+//!   default features to compile the crate for integer-only targets.
+//! * `ndarray` - Enables support for [ndarray](https://crates.io/crates/ndarray)'s `Array1` and `Array2` traits, `Array3` exports of lag matrices, lagging into existing arrays, and lagging of `Array3` batches and `ArrayD` axes.
+//! * `futures` - Enables lagging of asynchronous [`Stream`](futures_core::Stream)s via `lag_stream`.
+//! * `stats` - Enables statistical analysis helpers such as the `matrix_profile` and `autocorrelation_masked`.
+//! * `fft` - Enables FFT based helpers such as the `periodogram`, the `lag_spectrum` of trailing windows and surrogate data generation via [rustfft](https://crates.io/crates/rustfft).
+//! * `dtw` - Enables the banded dynamic time warping distance `dtw_distance` for comparing
+//!   lagged windows, also usable as a metric for nearest-neighbor searches.
+//! * `io` - Enables the `lag_csv_column` pipeline that lags large CSV columns chunk by chunk,
+//!   and reading and writing matrices in NumPy's portable NPY format.
+//! * `parquet` - Enables the `ParquetSink` writing lagged chunks as row groups of a Parquet
+//!   file via [parquet](https://crates.io/crates/parquet) (implies `io`).
+//! * `shm` - Enables sharing matrices between processes via POSIX shared memory on Linux (implies `io` and `unsafe`).
+//! * `wgpu` - Enables the `GpuLagger` creating lag matrices with a compute shader via
+//!   [wgpu](https://crates.io/crates/wgpu), keeping them on the GPU or reading them back.
+//! * `debug-verify` - Cross-checks every value written during construction against the naive
+//!   definition of the lag in debug builds, e.g. to catch regressions in the copy kernels.
+//! * `augurs` - Provides an autoregressive forecasting model implementing the traits of the
+//!   [augurs](https://crates.io/crates/augurs) ecosystem (implies `stats`).
 //!
 //! ## Example
 //!
+//! For the common case of a floating-point series lagged into a feature matrix with one
+//! row per time step:
+//!
+//! ```
+//! # #[cfg(feature = "float")] {
+//! let data = [1.0, 2.0, 3.0, 4.0];
+//! let features = timelag::features(&data).lags(1..=2).trimmed().to_vec().unwrap();
+//!
+//! assert_eq!(features, [2.0, 1.0, 3.0, 2.0]);
+//! # }
+//! ```
+//!
 //! For singular time series:
 //!
 //! ```
@@ -25,7 +56,7 @@
 //! let lagged = lag_matrix(&data, 0..=3, lag, 5).unwrap();
 //!
 //! // The function is also available via the CreateLagMatrix.
-//! // All methods take an IntoIterator<Item = usize> for the lags.
+//! // All methods take an IntoIterator<Item = impl IntoLag> for the lags.
 //! let other = data.lag_matrix([0, 1, 2, 3], lag, 5).unwrap();
 //!
 //! assert_eq!(
@@ -111,22 +142,239 @@
 // only enables the `doc_cfg` feature when
 // the `docsrs` configuration attribute is defined
 #![cfg_attr(docsrs, feature(doc_cfg))]
+// Forbid unsafe code throughout the crate unless the `unsafe` feature is enabled.
+#![cfg_attr(not(feature = "unsafe"), forbid(unsafe_code))]
 
-// Explicitly allow or forbid unsafe code depending on the feature selection.
-#[cfg_attr(feature = "unsafe", allow(unsafe_code))]
-#[cfg_attr(not(feature = "unsafe"), forbid(unsafe_code))]
 // Enable ndarray based on the feature.
 #[cfg(feature = "ndarray")]
 #[cfg_attr(docsrs, doc(cfg(feature = "ndarray")))]
 mod ndarray_support;
 
+#[cfg(feature = "stats")]
+#[cfg_attr(docsrs, doc(cfg(feature = "stats")))]
+mod acf;
+#[cfg(feature = "float")]
+#[cfg_attr(docsrs, doc(cfg(feature = "float")))]
+mod augment;
+#[cfg(feature = "augurs")]
+#[cfg_attr(docsrs, doc(cfg(feature = "augurs")))]
+mod augurs;
+mod bootstrap;
+#[cfg(feature = "float")]
+#[cfg_attr(docsrs, doc(cfg(feature = "float")))]
+mod buckets;
+mod builder;
+mod checksum;
+mod chunked;
+mod concat;
+mod display;
+#[cfg(feature = "dtw")]
+#[cfg_attr(docsrs, doc(cfg(feature = "dtw")))]
+mod dtw;
+mod embedding;
+mod events;
+#[cfg(feature = "float")]
+#[cfg_attr(docsrs, doc(cfg(feature = "float")))]
+mod features;
+mod forecast;
+mod gaps;
+#[cfg(feature = "float")]
+#[cfg_attr(docsrs, doc(cfg(feature = "float")))]
+mod gemm;
+#[cfg(feature = "wgpu")]
+#[cfg_attr(docsrs, doc(cfg(feature = "wgpu")))]
+mod gpu;
+mod groups;
+mod iter;
+mod lag_set;
+mod names;
+#[cfg(feature = "float")]
+#[cfg_attr(docsrs, doc(cfg(feature = "float")))]
+mod neighbors;
+#[cfg(feature = "io")]
+#[cfg_attr(docsrs, doc(cfg(feature = "io")))]
+mod npy;
+mod offsets;
+mod online;
+mod ordinal;
+mod orientation;
+mod parallel;
+#[cfg(feature = "parquet")]
+#[cfg_attr(docsrs, doc(cfg(feature = "parquet")))]
+mod parquet_sink;
+mod per_series;
+#[cfg(feature = "io")]
+#[cfg_attr(docsrs, doc(cfg(feature = "io")))]
+mod pipeline;
+mod prefetch;
+#[cfg(feature = "stats")]
+#[cfg_attr(docsrs, doc(cfg(feature = "stats")))]
+mod profile;
+#[cfg(feature = "float")]
+#[cfg_attr(docsrs, doc(cfg(feature = "float")))]
+mod pyramid;
+mod ragged;
+mod report;
+mod reverse;
+mod rng;
+#[cfg(feature = "float")]
+#[cfg_attr(docsrs, doc(cfg(feature = "float")))]
+mod scaling;
+mod schema;
+mod segmented;
+mod shape;
+#[cfg(all(feature = "shm", target_os = "linux"))]
+#[cfg_attr(docsrs, doc(cfg(all(feature = "shm", target_os = "linux"))))]
+mod shm;
+#[cfg(feature = "fft")]
+#[cfg_attr(docsrs, doc(cfg(feature = "fft")))]
+mod spectral;
+mod splits;
+#[cfg(feature = "futures")]
+#[cfg_attr(docsrs, doc(cfg(feature = "futures")))]
+mod stream;
+mod strided;
+mod supervised;
+#[cfg(feature = "fft")]
+#[cfg_attr(docsrs, doc(cfg(feature = "fft")))]
+mod surrogate;
+#[cfg(feature = "float")]
+#[cfg_attr(docsrs, doc(cfg(feature = "float")))]
+pub mod synthetic;
+#[cfg(feature = "stats")]
+#[cfg_attr(docsrs, doc(cfg(feature = "stats")))]
+mod toeplitz;
+mod tolerant;
+mod transform;
+pub mod v2;
+#[cfg(all(feature = "debug-verify", debug_assertions))]
+mod verify;
+#[cfg(feature = "float")]
+#[cfg_attr(docsrs, doc(cfg(feature = "float")))]
+mod weights;
+#[cfg(feature = "float")]
+#[cfg_attr(docsrs, doc(cfg(feature = "float")))]
+mod winsorize;
+mod writer;
+
 use core::borrow::Borrow;
 use core::fmt::{Display, Formatter};
-use core::ops::{Deref, Range};
-
+use core::iter::StepBy;
+use core::ops::{
+    Deref, Index, Range, RangeFrom, RangeFull, RangeInclusive, RangeTo, RangeToInclusive,
+};
+use num_traits::AsPrimitive;
+use std::borrow::Cow;
+use timelag_core::{
+    column_major_copies, exceeds_length, row_major_copies, shifted_ranges, source_step, LayoutError,
+};
+
+#[cfg(feature = "stats")]
+#[cfg_attr(docsrs, doc(cfg(feature = "stats")))]
+pub use acf::autocorrelation_masked;
+#[cfg(feature = "augurs")]
+#[cfg_attr(docsrs, doc(cfg(feature = "augurs")))]
+pub use augurs::{AutoregressiveModel, FittedAutoregressiveModel};
+pub use bootstrap::{block_bootstrap, BlockBootstrap};
+#[cfg(feature = "float")]
+#[cfg_attr(docsrs, doc(cfg(feature = "float")))]
+pub use buckets::lag_matrix_bucketed;
+pub use builder::LagMatrixBuilder;
+pub use checksum::IntegrityError;
+pub use chunked::ChunkedLagger;
+#[cfg(feature = "dtw")]
+#[cfg_attr(docsrs, doc(cfg(feature = "dtw")))]
+pub use dtw::{dtw_distance, BandedDtw};
+pub use embedding::delay_embedding;
+pub use events::{event_counts, steps_since_event};
+#[cfg(feature = "float")]
+#[cfg_attr(docsrs, doc(cfg(feature = "float")))]
+pub use features::{features, Features};
+pub use forecast::{forecast_blocks, ForecastBlocks};
+#[cfg(feature = "float")]
+#[cfg_attr(docsrs, doc(cfg(feature = "float")))]
+pub use gemm::GemmError;
+#[cfg(feature = "wgpu")]
+#[cfg_attr(docsrs, doc(cfg(feature = "wgpu")))]
+pub use gpu::{GpuElement, GpuError, GpuLagMatrix, GpuLagger};
+pub use groups::lag_by_group;
+pub use iter::{Columns, CompleteSteps, IntoRows, LagValues, Rows};
+pub use lag_set::LagSet;
+pub use names::lag_columns;
 #[cfg(feature = "ndarray")]
 #[cfg_attr(docsrs, doc(cfg(feature = "ndarray")))]
-pub use ndarray_support::LagMatrixFromArray;
+pub use ndarray_support::{lag_along_axis, lag_batch, lag_matrix_into, LagMatrixFromArray};
+#[cfg(feature = "float")]
+#[cfg_attr(docsrs, doc(cfg(feature = "float")))]
+pub use neighbors::{knn_lag_query, DistanceMetric, Metric, Neighbor};
+#[cfg(feature = "io")]
+#[cfg_attr(docsrs, doc(cfg(feature = "io")))]
+pub use npy::{read_npy, NpyElement, NpyMatrix};
+pub use online::{LagRow, MultiChannelLagger, OnlineLagger};
+pub use ordinal::{ordinal_patterns, OrdinalPatterns};
+pub use orientation::{lag_matrix_2d_oriented, OutputLayout};
+pub use parallel::ParallelConfig;
+#[cfg(feature = "parquet")]
+#[cfg_attr(docsrs, doc(cfg(feature = "parquet")))]
+pub use parquet_sink::{ParquetElement, ParquetSink};
+pub use per_series::{lag_matrix_2d_per_series, PerSeriesLagMatrix};
+#[cfg(feature = "io")]
+#[cfg_attr(docsrs, doc(cfg(feature = "io")))]
+pub use pipeline::{lag_csv_column, ChunkSink, CsvColumnReader, CsvError, CsvSink, PipelineError};
+pub use prefetch::lag_matrix_prefetched;
+#[cfg(feature = "stats")]
+#[cfg_attr(docsrs, doc(cfg(feature = "stats")))]
+pub use profile::{matrix_profile, MatrixProfile};
+#[cfg(feature = "float")]
+#[cfg_attr(docsrs, doc(cfg(feature = "float")))]
+pub use pyramid::{summary_pyramid, SummaryPyramid};
+pub use ragged::{lag_matrices, lag_matrix_ragged, RaggedAlignment};
+pub use report::{LagReport, LagWarning, TrimMode};
+pub use reverse::lag_matrix_newest_first;
+#[cfg(feature = "float")]
+#[cfg_attr(docsrs, doc(cfg(feature = "float")))]
+pub use scaling::{ScaleScope, Scaling};
+pub use schema::{build_all, LagSchema};
+pub use segmented::{lag_matrix_2d_segmented, lag_matrix_segmented};
+pub use shape::LagMatrixShape;
+#[cfg(all(feature = "shm", target_os = "linux"))]
+#[cfg_attr(docsrs, doc(cfg(all(feature = "shm", target_os = "linux"))))]
+pub use shm::SharedValues;
+#[cfg(feature = "fft")]
+#[cfg_attr(docsrs, doc(cfg(feature = "fft")))]
+pub use spectral::{lag_spectrum, periodogram, LagSpectrum, Periodogram};
+pub use splits::{Split, TimeSeriesSplit};
+#[cfg(feature = "futures")]
+#[cfg_attr(docsrs, doc(cfg(feature = "futures")))]
+pub use stream::{lag_stream, BatchedLagStream, LagStream, NoTicks};
+pub use supervised::{
+    supervised_dataset, supervised_dataset_multi, supervised_dataset_with_gap, Batch, Batches,
+    SupervisedDataset,
+};
+#[cfg(feature = "fft")]
+#[cfg_attr(docsrs, doc(cfg(feature = "fft")))]
+pub use surrogate::{lag_matrix_surrogates, surrogate, SurrogateMethod};
+/// The allocation-free kernels of this crate, also available as the `no_std` and
+/// dependency-free [`timelag-core`](https://crates.io/crates/timelag-core) crate.
+pub use timelag_core as kernels;
+pub use timelag_core::{LagOffsets, MatrixLayout};
+#[cfg(feature = "stats")]
+#[cfg_attr(docsrs, doc(cfg(feature = "stats")))]
+pub use toeplitz::{levinson_durbin, solve_toeplitz, LevinsonDurbin};
+pub use tolerant::{lag_matrix_2d_tolerant, PartialLagMatrix};
+pub use transform::{
+    Difference, FittedDifference, InverseTransform, Lag, Pipeline, SeriesTransform,
+};
+#[cfg(feature = "float")]
+#[cfg_attr(docsrs, doc(cfg(feature = "float")))]
+pub use transform::{Rolling, RollingStat};
+#[cfg(feature = "float")]
+#[cfg_attr(docsrs, doc(cfg(feature = "float")))]
+pub use weights::recency_weights;
+#[cfg(feature = "float")]
+#[cfg_attr(docsrs, doc(cfg(feature = "float")))]
+pub use winsorize::{lag_matrix_2d_winsorized, winsorize, Winsorization};
+pub use writer::RowFormat;
 
 /// The prelude.
 pub mod prelude {
@@ -138,9 +386,12 @@ pub mod prelude {
 }
 
 /// A matrix of time-lagged values.
+///
+/// The values are held in the storage `S`, which is an owned vector by default. Matrices over
+/// borrowed storage, see [`LagMatrixView`], provide the same accessors without owning the data.
 #[derive(Debug, Clone, Default, PartialEq)]
-pub struct LagMatrix<T> {
-    data: Vec<T>,
+pub struct LagMatrix<T, S = Vec<T>> {
+    data: S,
     num_rows: usize,
     num_cols: usize,
     series_length: usize,
@@ -148,9 +399,63 @@ pub struct LagMatrix<T> {
     num_lags: usize,
     row_stride: usize,
     row_major: bool,
+    lags: Vec<isize>,
+    directions: Vec<LagDirection>,
+    series_names: Option<Vec<String>>,
+    static_count: usize,
+    row_weights: Option<Vec<T>>,
+    time_offset: usize,
+    source_length: usize,
+    row_checksums: Option<Vec<u64>>,
+    gaps: Option<Vec<bool>>,
 }
 
-impl<T> LagMatrix<T> {
+impl<T, S> LagMatrix<T, S> {
+    /// Creates a matrix from lagged data, deriving the shape from the layout.
+    pub(crate) fn new(
+        data: S,
+        row_major: bool,
+        series_length: usize,
+        series_count: usize,
+        lags: Vec<isize>,
+        row_stride: usize,
+    ) -> Self {
+        let num_lags = lags.len();
+        let (num_rows, num_cols) = if row_major {
+            (series_count * num_lags, series_length)
+        } else {
+            (series_length, series_count * num_lags)
+        };
+
+        Self {
+            data,
+            num_rows,
+            num_cols,
+            series_length,
+            series_count,
+            num_lags,
+            row_stride,
+            row_major,
+            lags,
+            directions: vec![LagDirection::Lag; series_count],
+            series_names: None,
+            static_count: 0,
+            row_weights: None,
+            time_offset: 0,
+            source_length: series_length,
+            row_checksums: None,
+            gaps: None,
+        }
+    }
+
+    /// Places the matrix at the specified time step of a longer source series,
+    /// such that padding is determined relative to the source.
+    pub(crate) fn with_window(mut self, time_offset: usize, source_length: usize) -> Self {
+        self.time_offset = time_offset;
+        self.source_length = source_length;
+        self
+    }
+
     /// The number of logical rows in the matrix.
     /// This value is less than or equal to [`row_stride`].
     ///
@@ -180,6 +485,14 @@ impl<T> LagMatrix<T> {
         self.series_length
     }
 
+    /// The time step of the source series that corresponds to the first time step of the matrix.
+    /// This is zero unless the matrix covers only a part of the series, e.g. a chunk
+    /// produced by a [`ChunkedLagger`].
+    #[inline(always)]
+    pub const fn time_offset(&self) -> usize {
+        self.time_offset
+    }
+
     /// The number of lags represented in the matrix.
     /// This represents solely the number of different lag values used, but not their value.
     #[inline(always)]
@@ -208,6 +521,99 @@ impl<T> LagMatrix<T> {
         !self.row_major
     }
 
+    /// The lags represented in the matrix, in the order they were produced.
+    ///
+    /// Negative values denote leads; see [`IntoLag`]. Together with the
+    /// [`directions`](Self::directions), this determines which values are lagged gaps.
+    ///
+    /// ## Example
+    /// ```
+    /// use timelag::lag_matrix;
+    ///
+    /// let data = [1.0, 2.0, 3.0, 4.0];
+    /// let lags = vec![3, 1, -1];
+    ///
+    /// let lagged = lag_matrix(&data, &lags, f64::NAN, 0).unwrap();
+    /// assert_eq!(lagged.lags(), &[3, 1, -1]);
+    /// ```
+    #[inline(always)]
+    pub fn lags(&self) -> &[isize] {
+        &self.lags
+    }
+
+    /// The shift direction of each series.
+    #[inline(always)]
+    pub fn directions(&self) -> &[LagDirection] {
+        &self.directions
+    }
+
+    /// The shift direction of the specified series, or `None` if the series does not exist.
+    #[inline(always)]
+    pub fn direction(&self, series: usize) -> Option<LagDirection> {
+        self.directions.get(series).copied()
+    }
+
+    /// The number of static columns appended via [`with_static_columns`](Self::with_static_columns).
+    #[inline(always)]
+    pub const fn static_count(&self) -> usize {
+        self.static_count
+    }
+
+    /// The observation weights attached via [`with_row_weights`](Self::with_row_weights), if any.
+    ///
+    /// There is one weight per time step, i.e. per row of a column-major matrix.
+    #[inline(always)]
+    pub fn row_weights(&self) -> Option<&[T]> {
+        self.row_weights.as_deref()
+    }
+
+    /// The number of time steps without any lagged gaps, i.e. the complete cases.
+    ///
+    /// This equals [`series_length`](Self::series_length) after
+    /// [`trim_incomplete`](Self::trim_incomplete). Time steps with gaps within the series
+    /// are not counted; see [`has_interior_gaps`](Self::has_interior_gaps).
+    pub fn valid_length(&self) -> usize {
+        let (leading, trailing) = self.incomplete_steps();
+        self.series_length - leading - trailing - self.interior_gap_steps()
+    }
+
+    /// The largest lag and the largest lead of any series.
+    fn max_shifts(&self) -> (usize, usize) {
+        let (mut lag, mut lead) = (0, 0);
+        for &value in &self.lags {
+            for &direction in &self.directions {
+                let shift = shift_of(value, direction);
+                if shift >= 0 {
+                    lag = lag.max(shift.unsigned_abs());
+                } else {
+                    lead = lead.max(shift.unsigned_abs());
+                }
+            }
+        }
+        (lag, lead)
+    }
+
+    /// The number of incomplete time steps at the start and at the end of the matrix.
+    fn incomplete_steps(&self) -> (usize, usize) {
+        let (lag, lead) = self.max_shifts();
+        let leading = lag.saturating_sub(self.time_offset).min(self.series_length);
+        let end = self.time_offset + self.series_length;
+        let trailing = (end + lead)
+            .saturating_sub(self.source_length)
+            .min(self.series_length - leading);
+        (leading, trailing)
+    }
+
+    /// The leading dimension of the matrix in the sense of BLAS and LAPACK, i.e. the
+    /// [`row_stride`](Self::row_stride) of the row-major storage.
+    ///
+    /// Elements within a row are always contiguous, such that the matrix can be passed as
+    /// an `num_rows × num_cols` row-major matrix with leading dimension `lda`.
+    #[inline(always)]
+    pub const fn lda(&self) -> usize {
+        self.row_stride
+    }
+
     /// Obtains the matrix layout.
     pub const fn matrix_layout(&self) -> MatrixLayout {
         if self.row_major {
@@ -217,53 +623,1006 @@ impl<T> LagMatrix<T> {
         }
     }
 
+    /// The number of values per time step, i.e. one value per lag and series.
+    #[inline(always)]
+    pub(crate) const fn features_per_step(&self) -> usize {
+        self.num_lags * self.series_count
+    }
+
+    /// The offset of the value of series `series` at lag index `lag_index` and time step `t`.
+    #[inline(always)]
+    pub(crate) const fn offset_of(&self, lag_index: usize, series: usize, t: usize) -> usize {
+        self.offsets().offset_of(series, lag_index, t)
+    }
+
+    /// Determines whether the value of series `series` at lag index `lag_index` and time step `t` is padding,
+    /// including gaps within the series.
+    #[inline(always)]
+    pub(crate) fn is_padding(&self, lag_index: usize, series: usize, t: usize) -> bool {
+        let shift = shift_of(self.lags[lag_index], self.directions[series]);
+        let step = self.time_offset + t;
+        let padding = if shift >= 0 {
+            step < shift.unsigned_abs()
+        } else {
+            step + shift.unsigned_abs() >= self.source_length
+        };
+        padding || self.is_interior_gap(lag_index, series, t)
+    }
+
+    /// The time steps of series `series` at lag index `lag_index` that are padding.
+    pub(crate) fn padding_steps(&self, lag_index: usize, series: usize) -> Range<usize> {
+        let shift = shift_of(self.lags[lag_index], self.directions[series]);
+        let amount = shift.unsigned_abs();
+        if shift >= 0 {
+            0..amount
+                .saturating_sub(self.time_offset)
+                .min(self.series_length)
+        } else {
+            let start = self
+                .source_length
+                .saturating_sub(amount)
+                .saturating_sub(self.time_offset);
+            start.min(self.series_length)..self.series_length
+        }
+    }
+}
+
+impl<T> LagMatrix<T> {
+    /// Appends static covariates that are constant over time, replicating them for every time step.
+    ///
+    /// The values are placed after the lagged features; pass one value per series for
+    /// constant-per-series covariates, or any number of values for constant-per-matrix
+    /// covariates. The static value of covariate `k` at time step `t` is located at
+    /// `t * row_stride + num_cols - static_count + k` for column-major matrices and at
+    /// `(num_rows - static_count + k) * row_stride + t` for row-major matrices.
+    ///
+    /// For column-major matrices, every row grows by `values.len()` elements and any existing
+    /// row padding is retained after the new columns. For row-major matrices, one row per
+    /// value is appended; its padding entries are set to the value as well.
+    ///
+    /// ## Example
+    /// ```
+    /// use timelag::{lag_matrix_2d, MatrixLayout};
+    ///
+    /// let data = [1, 10, 2, 20, 3, 30];
+    /// let lagged = lag_matrix_2d(&data, MatrixLayout::ColumnMajor(3), [1], 0, 0)
+    ///     .unwrap()
+    ///     .with_static_columns(&[7, 8]);
+    ///
+    /// assert_eq!(lagged.num_cols(), 4);
+    /// assert_eq!(lagged.static_count(), 2);
+    /// assert_eq!(
+    ///     lagged,
+    ///     &[
+    ///         0,  0, 7, 8,
+    ///         1, 10, 7, 8,
+    ///         2, 20, 7, 8,
+    ///     ]
+    /// );
+    /// ```
+    pub fn with_static_columns(mut self, values: &[T]) -> Self
+    where
+        T: Copy,
+    {
+        if values.is_empty() {
+            return self;
+        }
+
+        if self.row_major {
+            self.data.reserve(values.len() * self.row_stride);
+            for &value in values {
+                self.data
+                    .extend(core::iter::repeat_n(value, self.row_stride));
+            }
+            self.num_rows += values.len();
+        } else {
+            let row_stride = self.row_stride + values.len();
+            let mut data = Vec::with_capacity(self.num_rows * row_stride);
+            for row in self.data.chunks_exact(self.row_stride) {
+                let (features, padding) = row.split_at(self.num_cols);
+                data.extend_from_slice(features);
+                data.extend_from_slice(values);
+                data.extend_from_slice(padding);
+            }
+            self.data = data;
+            self.num_cols += values.len();
+            self.row_stride = row_stride;
+        }
+
+        self.static_count += values.len();
+        self.row_checksums = None;
+        self
+    }
+
+    /// Attaches one weight per time step, e.g. for estimators that should down-weight old observations.
+    ///
+    /// See [`recency_weights`] for exponentially decaying weights.
+    ///
+    /// ## Returns
+    /// The weighted matrix, or [`LagError::InvalidLength`] if the number of weights
+    /// does not match the series length.
+    ///
+    /// ## Example
+    /// ```
+    /// use timelag::lag_matrix;
+    ///
+    /// let data = [1.0, 2.0, 3.0];
+    /// let lagged = lag_matrix(&data, 0..=1, f64::NAN, 0)
+    ///     .unwrap()
+    ///     .with_row_weights(vec![0.25, 0.5, 1.0])
+    ///     .unwrap();
+    ///
+    /// assert_eq!(lagged.row_weights(), Some(&[0.25, 0.5, 1.0][..]));
+    /// ```
+    pub fn with_row_weights(mut self, weights: Vec<T>) -> Result<Self, LagError> {
+        if weights.len() != self.series_length {
+            return Err(LagError::InvalidLength);
+        }
+
+        self.row_weights = Some(weights);
+        Ok(self)
+    }
+
+    /// Drops the time steps that contain lagged gaps instead of keeping them padded,
+    /// similar to `lagmat(..., trim='both')` in statsmodels.
+    ///
+    /// Lags render the first time steps incomplete and leads the last ones. The
+    /// [`time_offset`](Self::time_offset) of the result points to the first retained time step
+    /// of the original series. Row padding, static columns and row weights are retained.
+    ///
+    /// ## Returns
+    /// The trimmed matrix, or
+    /// * [`LagError::LagExceedsValueCount`] if no time step is complete,
+    /// * [`LagError::InteriorGaps`] if a retained time step has a gap within the series,
+    ///   see [`has_interior_gaps`](Self::has_interior_gaps).
+    ///
+    /// ## Example
+    /// ```
+    /// use timelag::lag_matrix_2d;
+    /// use timelag::MatrixLayout;
+    ///
+    /// let data = [1, 2, 3, 4];
+    /// let lagged = lag_matrix_2d(&data, MatrixLayout::ColumnMajor(4), [0, 2], 0, 0).unwrap();
+    /// assert_eq!(lagged.valid_length(), 2);
+    ///
+    /// let trimmed = lagged.trim_incomplete().unwrap();
+    /// assert_eq!(trimmed.num_rows(), 2);
+    /// assert_eq!(trimmed.time_offset(), 2);
+    /// assert_eq!(trimmed, &[3, 1, 4, 2]);
+    /// ```
+    pub fn trim_incomplete(mut self) -> Result<Self, LagError>
+    where
+        T: Copy,
+    {
+        let (leading, trailing) = self.incomplete_steps();
+        let length = self.series_length - leading - trailing;
+        if length == 0 {
+            return Err(LagError::LagExceedsValueCount);
+        }
+        if self.interior_gap_steps() > 0 {
+            return Err(LagError::InteriorGaps);
+        }
+
+        if leading == 0 && trailing == 0 {
+            return Ok(self);
+        }
+
+        if self.row_major {
+            let row_stride = self.row_stride - leading - trailing;
+            let mut data = Vec::with_capacity(self.num_rows * row_stride);
+            for row in self.data.chunks_exact(self.row_stride) {
+                let (values, padding) = row.split_at(self.series_length);
+                data.extend_from_slice(&values[leading..leading + length]);
+                data.extend_from_slice(padding);
+            }
+            self.data = data;
+            self.num_cols = length;
+            self.row_stride = row_stride;
+        } else {
+            let start = leading * self.row_stride;
+            self.data.truncate((leading + length) * self.row_stride);
+            self.data.drain(..start);
+            self.num_rows = length;
+        }
+
+        if let Some(weights) = &mut self.row_weights {
+            weights.truncate(leading + length);
+            weights.drain(..leading);
+        }
+
+        self.series_length = length;
+        self.time_offset += leading;
+        self.row_checksums = None;
+        self.gaps = None;
+        Ok(self)
+    }
+
+    /// Removes the row padding, such that the row stride equals the number of columns
+    /// and the values are stored densely, e.g. for serialization.
+    ///
+    /// The rows are moved in place; row checksums, if any, remain valid.
+    ///
+    /// ## Example
+    /// ```
+    /// use timelag::lag_matrix;
+    ///
+    /// let lagged = lag_matrix(&[1, 2, 3], 0..=1, 0, 5).unwrap().compact();
+    ///
+    /// assert_eq!(lagged.row_stride(), 3);
+    /// assert_eq!(lagged, &[1, 2, 3, 0, 1, 2]);
+    /// ```
+    pub fn compact(mut self) -> Self
+    where
+        T: Copy,
+    {
+        if self.row_stride == self.num_cols {
+            return self;
+        }
+
+        for row in 1..self.num_rows {
+            let start = row * self.row_stride;
+            self.data
+                .copy_within(start..start + self.num_cols, row * self.num_cols);
+        }
+        self.data.truncate(self.num_rows * self.num_cols);
+        self.row_stride = self.num_cols;
+        self
+    }
+
     /// Converts this [`LagMatrix`] into a vector.
     #[inline(always)]
     pub fn into_vec(self) -> Vec<T> {
         self.data
     }
+
+    /// Replaces every lagged gap by the value computed from its lag index, series and time step,
+    /// including the gaps within the series.
+    ///
+    /// Only the gaps are visited, such that this is cheap compared to creating the matrix.
+    pub(crate) fn fill_gaps<F>(&mut self, mut fill: F)
+    where
+        F: FnMut(usize, usize, usize) -> T,
+    {
+        for lag_index in 0..self.num_lags {
+            for series in 0..self.series_count {
+                let padding = self.padding_steps(lag_index, series);
+                let interior = (0..self.series_length)
+                    .filter(|&t| {
+                        !padding.contains(&t) && self.is_interior_gap(lag_index, series, t)
+                    })
+                    .collect::<Vec<_>>();
+                for t in padding.chain(interior) {
+                    let offset = self.offset_of(lag_index, series, t);
+                    self.data[offset] = fill(lag_index, series, t);
+                }
+            }
+        }
+    }
+}
+
+impl<T, S> LagMatrix<T, S>
+where
+    S: AsRef<[T]>,
+{
+    /// A raw pointer to the first element of the matrix, e.g. for BLAS and LAPACK interop.
+    ///
+    /// The pointer is valid for reads of `num_rows * lda` elements for as long as the matrix
+    /// is neither moved nor dropped.
+    #[inline(always)]
+    pub fn ptr(&self) -> *const T {
+        self.data.as_ref().as_ptr()
+    }
+
+    /// The value at the specified row and column, skipping row padding.
+    ///
+    /// This shadows [`slice::get`]; use `matrix[index]` or `matrix.as_ref().get(index)`
+    /// to access the underlying buffer by offset.
+    ///
+    /// ## Returns
+    /// The value, or `None` if the row or column is out of range.
+    ///
+    /// ## Example
+    /// ```
+    /// use timelag::lag_matrix;
+    ///
+    /// let lagged = lag_matrix(&[1, 2, 3], 0..=1, 0, 4).unwrap();
+    ///
+    /// assert_eq!(lagged.get(1, 2), Some(&2));
+    /// assert_eq!(lagged[(1, 0)], 0);
+    /// assert_eq!(lagged.get(1, 3), None); // padding
+    /// ```
+    #[inline(always)]
+    pub fn get(&self, row: usize, col: usize) -> Option<&T> {
+        if row < self.num_rows && col < self.num_cols {
+            Some(&self.data.as_ref()[row * self.row_stride + col])
+        } else {
+            None
+        }
+    }
+
+    /// The values of a row-major matrix's first series at the specified lag, e.g. as
+    /// created by [`lag_matrix`].
+    ///
+    /// ## Returns
+    /// The values, or `None` if the lag is not part of the matrix or the matrix is column-major,
+    /// in which case the values are not contiguous; see [`lag_series`](Self::lag_series).
+    ///
+    /// ## Example
+    /// ```
+    /// use timelag::lag_matrix;
+    ///
+    /// let lagged = lag_matrix(&[1, 2, 3, 4], [0, 2], 0, 0).unwrap();
+    ///
+    /// assert_eq!(lagged.lag(2), Some(&[0, 0, 1, 2][..]));
+    /// assert_eq!(lagged.lag(1), None);
+    /// ```
+    pub fn lag(&self, lag: isize) -> Option<&[T]> {
+        if !self.row_major {
+            return None;
+        }
+        let lag_index = self.lags.iter().position(|&l| l == lag)?;
+        let start = self.offset_of(lag_index, 0, 0);
+        Some(&self.data.as_ref()[start..start + self.series_length])
+    }
+
+    /// The values of the specified series at the specified lag, in time order; see
+    /// [`lag_of`](Self::lag_of) for indexed access.
+    ///
+    /// ## Returns
+    /// An iterator over the values, stepping over the row stride in column-major matrices,
+    /// or `None` if the series or the lag is not part of the matrix.
+    ///
+    /// ## Example
+    /// ```
+    /// use timelag::{lag_matrix_2d, MatrixLayout};
+    ///
+    /// let data = [
+    ///     1, 10,
+    ///     2, 20,
+    ///     3, 30,
+    /// ];
+    /// let lagged = lag_matrix_2d(&data, MatrixLayout::ColumnMajor(3), 0..=1, 0, 0).unwrap();
+    ///
+    /// let values: Vec<_> = lagged.lag_series(1, 1).unwrap().copied().collect();
+    /// assert_eq!(values, [0, 10, 20]);
+    /// ```
+    pub fn lag_series(
+        &self,
+        series: usize,
+        lag: isize,
+    ) -> Option<StepBy<core::slice::Iter<'_, T>>> {
+        if series >= self.series_count {
+            return None;
+        }
+        let lag_index = self.lags.iter().position(|&l| l == lag)?;
+        let start = self.offset_of(lag_index, series, 0);
+        let data = self.data.as_ref();
+
+        Some(if self.row_major {
+            data[start..start + self.series_length].iter().step_by(1)
+        } else {
+            data[start..].iter().step_by(self.row_stride)
+        })
+    }
+
+    /// Creates a matrix of the specified lags only, e.g. to keep the significant lags
+    /// after exploring many of them, without lagging the series again.
+    ///
+    /// The lags are placed in the specified order. Static columns, row weights and any
+    /// extra row padding are retained.
+    ///
+    /// ## Returns
+    /// The matrix, or [`LagError::InvalidLags`] if no lags were specified or a lag is not
+    /// part of the matrix.
+    ///
+    /// ## Example
+    /// ```
+    /// use timelag::lag_matrix;
+    ///
+    /// let lagged = lag_matrix(&[1, 2, 3, 4], 0..=3, 0, 0).unwrap();
+    /// let selected = lagged.select_lags([3, 1]).unwrap();
+    ///
+    /// assert_eq!(selected.lags(), &[3, 1]);
+    /// assert_eq!(selected, lag_matrix(&[1, 2, 3, 4], [3, 1], 0, 0).unwrap());
+    /// ```
+    pub fn select_lags<L: IntoLag, R: IntoIterator<Item = L>>(
+        &self,
+        lags: R,
+    ) -> Result<LagMatrix<T>, LagError>
+    where
+        T: Copy,
+    {
+        let lags = collect_lags(lags)?;
+        let indices = lags
+            .iter()
+            .map(|lag| self.lags.iter().position(|l| l == lag))
+            .collect::<Option<Vec<_>>>()
+            .ok_or(LagError::InvalidLags)?;
+        if indices.is_empty() {
+            return Err(LagError::InvalidLags);
+        }
+
+        let data = self.data.as_ref();
+        let series_count = self.series_count;
+        let features = self.features_per_step();
+
+        let (selected, row_stride) = if self.row_major {
+            let block = series_count * self.row_stride;
+            let mut selected = Vec::with_capacity(
+                (indices.len() * series_count + self.static_count) * self.row_stride,
+            );
+            for &index in &indices {
+                selected.extend_from_slice(&data[index * block..(index + 1) * block]);
+            }
+            selected.extend_from_slice(&data[self.num_lags * block..]);
+            (selected, self.row_stride)
+        } else {
+            let row_stride = self.row_stride - features + indices.len() * series_count;
+            let mut selected = Vec::with_capacity(self.num_rows * row_stride);
+            for row in data.chunks_exact(self.row_stride) {
+                for &index in &indices {
+                    selected
+                        .extend_from_slice(&row[index * series_count..(index + 1) * series_count]);
+                }
+                selected.extend_from_slice(&row[features..]);
+            }
+            (selected, row_stride)
+        };
+
+        let mut matrix = LagMatrix::new(
+            selected,
+            self.row_major,
+            self.series_length,
+            series_count,
+            lags,
+            row_stride,
+        )
+        .with_window(self.time_offset, self.source_length);
+        if self.row_major {
+            matrix.num_rows += self.static_count;
+        } else {
+            matrix.num_cols += self.static_count;
+        }
+        matrix.directions = self.directions.clone();
+        matrix.series_names = self.series_names.clone();
+        matrix.static_count = self.static_count;
+        matrix.row_weights = self.row_weights.clone();
+        matrix.gaps = self.select_gaps(&indices);
+        Ok(matrix)
+    }
+
+    /// Copies the time steps `range` of all lags and series, e.g. the training window of a
+    /// walk-forward backtest, without rebuilding the matrix.
+    ///
+    /// The [`time_offset`](Self::time_offset) of the result points to the first selected time
+    /// step, such that lagged gaps are still determined relative to the source series. Row
+    /// padding, static columns, series names and row weights are retained.
+    ///
+    /// ## Returns
+    /// The sliced matrix, or [`LagError::InvalidLength`] if the range is empty or exceeds
+    /// the series length.
+    ///
+    /// ## Example
+    /// ```
+    /// use timelag::lag_matrix;
+    ///
+    /// let lagged = lag_matrix(&[1, 2, 3, 4, 5], 0..=1, 0, 0).unwrap();
+    /// let window = lagged.slice_time(1..3).unwrap();
+    ///
+    /// assert_eq!(window.time_offset(), 1);
+    /// assert_eq!(window.valid_length(), 2);
+    /// assert_eq!(window, &[2, 3, 1, 2]);
+    /// ```
+    pub fn slice_time(&self, range: Range<usize>) -> Result<LagMatrix<T>, LagError>
+    where
+        T: Copy,
+    {
+        if range.is_empty() || range.end > self.series_length {
+            return Err(LagError::InvalidLength);
+        }
+
+        let data = self.data.as_ref();
+        let length = range.len();
+        let (sliced, row_stride) = if self.row_major {
+            let row_stride = self.row_stride - (self.series_length - length);
+            let mut sliced = Vec::with_capacity(self.num_rows * row_stride);
+            for row in data.chunks(self.row_stride).take(self.num_rows) {
+                sliced.extend_from_slice(&row[range.clone()]);
+                sliced.extend_from_slice(&row[self.series_length..]);
+            }
+            (sliced, row_stride)
+        } else {
+            let values = &data[range.start * self.row_stride..range.end * self.row_stride];
+            (values.to_vec(), self.row_stride)
+        };
+
+        let mut matrix = self.map_metadata(sliced);
+        if self.row_major {
+            matrix.num_cols = length;
+        } else {
+            matrix.num_rows = length;
+        }
+        matrix.series_length = length;
+        matrix.row_stride = row_stride;
+        matrix.time_offset = self.time_offset + range.start;
+        matrix.row_weights = self
+            .row_weights
+            .as_ref()
+            .map(|weights| weights[range.clone()].to_vec());
+        matrix.gaps = self.slice_gaps(range);
+        Ok(matrix)
+    }
+
+    /// Swaps the rows and columns of the matrix, converting between the row-major
+    /// "one row per lag" and the column-major "one row per time step" orientation.
+    ///
+    /// The result has no row padding. The metadata, e.g. the lags and row weights, is retained.
+    ///
+    /// ## Example
+    /// ```
+    /// use timelag::{lag_matrix, lag_matrix_2d, MatrixLayout};
+    ///
+    /// let data = [1.0, 2.0, 3.0];
+    /// let lagged = lag_matrix(&data, 0..=1, 0.0, 4).unwrap();
+    ///
+    /// let transposed = lagged.transpose();
+    /// assert!(transposed.is_column_major());
+    /// assert_eq!(transposed, lag_matrix_2d(&data, MatrixLayout::ColumnMajor(3), 0..=1, 0.0, 0).unwrap());
+    /// assert_eq!(transposed.transpose(), lag_matrix(&data, 0..=1, 0.0, 0).unwrap());
+    /// ```
+    pub fn transpose(&self) -> LagMatrix<T>
+    where
+        T: Copy,
+    {
+        let data = self.data.as_ref();
+        let transposed = (0..self.num_rows * self.num_cols)
+            .map(|index| {
+                let (row, col) = (index % self.num_rows, index / self.num_rows);
+                data[row * self.row_stride + col]
+            })
+            .collect();
+
+        let mut matrix = self.map_metadata(transposed);
+        matrix.num_rows = self.num_cols;
+        matrix.num_cols = self.num_rows;
+        matrix.row_stride = self.num_rows;
+        matrix.row_major = !self.row_major;
+        matrix.row_weights = self.row_weights.clone();
+        matrix
+    }
+
+    /// Applies a function to every element, including row padding, retaining the metadata.
+    ///
+    /// Row weights are discarded, as `f` maps values rather than weights; use
+    /// [`cast`](Self::cast) to convert numeric types including the weights.
+    ///
+    /// ## Example
+    /// ```
+    /// use timelag::lag_matrix;
+    ///
+    /// let lagged = lag_matrix(&[1, 2, 3], 0..=1, -1, 0).unwrap();
+    /// let flags = lagged.map(|value| value >= 0);
+    ///
+    /// assert_eq!(flags.lags(), &[0, 1]);
+    /// assert_eq!(flags, &[true, true, true, false, true, true]);
+    /// ```
+    pub fn map<U, F>(&self, f: F) -> LagMatrix<U>
+    where
+        T: Copy,
+        F: FnMut(T) -> U,
+    {
+        let mut mapped = self.map_metadata(self.data.as_ref().iter().copied().map(f).collect());
+        mapped.row_weights = None;
+        mapped
+    }
+
+    /// Converts every element and the row weights to another numeric type like the `as` operator,
+    /// e.g. to feed `f32` models from `f64` data.
+    ///
+    /// ## Example
+    /// ```
+    /// use timelag::lag_matrix;
+    ///
+    /// let lagged = lag_matrix(&[1.5_f64, 2.5, 3.5], 0..=1, f64::NAN, 0).unwrap();
+    /// let single = lagged.cast::<f32>();
+    ///
+    /// assert_eq!(single[..3], [1.5_f32, 2.5, 3.5]);
+    /// assert!(single[3].is_nan());
+    /// ```
+    pub fn cast<U>(&self) -> LagMatrix<U>
+    where
+        T: AsPrimitive<U>,
+        U: Copy + 'static,
+    {
+        let mut cast =
+            self.map_metadata(self.data.as_ref().iter().map(|value| value.as_()).collect());
+        cast.row_weights = self
+            .row_weights
+            .as_ref()
+            .map(|weights| weights.iter().map(|weight| weight.as_()).collect());
+        cast
+    }
+
+    /// Appends the time steps of a matrix built from the directly following chunk of the
+    /// series, e.g. to stitch monthly feature builds into one training matrix.
+    ///
+    /// The other matrix must continue this one in time, i.e. its
+    /// [`time_offset`](Self::time_offset) must equal the end of this matrix, as is the case
+    /// for consecutive chunks of a [`ChunkedLagger`]. Chunks lagged independently of each other
+    /// start at a time offset of zero and are rejected, since their first rows hold fill values
+    /// rather than the carried-over history. Static columns are taken from both matrices as they
+    /// are; row weights are concatenated. The result has no row padding.
+    ///
+    /// ## Returns
+    /// The concatenated matrix, or
+    /// * [`LagError::InvalidLags`] if the lags differ,
+    /// * [`LagError::InvalidChannel`] if the series or their directions differ,
+    /// * [`LagError::InvalidSeriesNames`] if the series names differ,
+    /// * [`LagError::InvalidStaticColumns`] if the static columns differ,
+    /// * [`LagError::InvalidMemoryLayout`] if the layouts differ,
+    /// * [`LagError::InvalidLength`] if the other matrix does not continue this one, if this
+    ///   matrix is missing leads at its end, or if only one of the matrices is weighted.
+    ///
+    /// ## Example
+    /// ```
+    /// use timelag::{lag_matrix_2d, ChunkedLagger, MatrixLayout};
+    ///
+    /// let mut lagger = ChunkedLagger::new([0, 1], 0).unwrap();
+    /// let january = lagger.push_chunk(&[1, 2]).unwrap();
+    /// let february = lagger.push_chunk(&[3, 4, 5]).unwrap();
+    ///
+    /// let lagged = january.concat_time(&february).unwrap();
+    /// assert_eq!(lagged, lag_matrix_2d(&[1, 2, 3, 4, 5], MatrixLayout::ColumnMajor(5), [0, 1], 0, 0).unwrap());
+    ///
+    /// // A chunk lagged on its own does not continue the series.
+    /// let mut fresh = ChunkedLagger::new([0, 1], 0).unwrap();
+    /// assert!(january.concat_time(&fresh.push_chunk(&[3, 4, 5]).unwrap()).is_err());
+    /// ```
+    pub fn concat_time<U>(&self, other: &LagMatrix<T, U>) -> Result<LagMatrix<T>, LagError>
+    where
+        T: Copy,
+        U: AsRef<[T]>,
+    {
+        if self.lags != other.lags {
+            return Err(LagError::InvalidLags);
+        }
+        if self.series_count != other.series_count || self.directions != other.directions {
+            return Err(LagError::InvalidChannel);
+        }
+        if self.series_names != other.series_names {
+            return Err(LagError::InvalidSeriesNames);
+        }
+        if self.static_count != other.static_count {
+            return Err(LagError::InvalidStaticColumns);
+        }
+        if self.row_major != other.row_major {
+            return Err(LagError::InvalidMemoryLayout);
+        }
+
+        let end = self.time_offset + self.series_length;
+        if other.time_offset != end || self.incomplete_steps().1 > 0 {
+            return Err(LagError::InvalidLength);
+        }
+
+        let row_weights = match (&self.row_weights, &other.row_weights) {
+            (Some(first), Some(second)) => Some([first.as_slice(), second].concat()),
+            (None, None) => None,
+            _ => return Err(LagError::InvalidLength),
+        };
+
+        let series_length = self.series_length + other.series_length;
+        let (data, row_stride) = if self.row_major {
+            let mut data = Vec::with_capacity(self.num_rows * series_length);
+            for (first, second) in self.rows().zip(other.rows()) {
+                data.extend_from_slice(first);
+                data.extend_from_slice(second);
+            }
+            (data, series_length)
+        } else {
+            let mut data = Vec::with_capacity(series_length * self.num_cols);
+            for row in self.rows().chain(other.rows()) {
+                data.extend_from_slice(row);
+            }
+            (data, self.num_cols)
+        };
+
+        let mut matrix = LagMatrix::new(
+            data,
+            self.row_major,
+            series_length,
+            self.series_count,
+            self.lags.clone(),
+            row_stride,
+        )
+        .with_window(self.time_offset, other.source_length);
+        if self.row_major {
+            matrix.num_rows += self.static_count;
+        } else {
+            matrix.num_cols += self.static_count;
+        }
+        matrix.directions = self.directions.clone();
+        matrix.series_names = self.series_names.clone();
+        matrix.static_count = self.static_count;
+        matrix.row_weights = row_weights;
+        matrix.gaps = self.concat_gaps(other);
+        Ok(matrix)
+    }
+
+    /// Creates a matrix of the specified values and the metadata of this matrix,
+    /// excluding row weights and checksums.
+    fn map_metadata<U>(&self, data: Vec<U>) -> LagMatrix<U> {
+        LagMatrix {
+            data,
+            num_rows: self.num_rows,
+            num_cols: self.num_cols,
+            series_length: self.series_length,
+            series_count: self.series_count,
+            num_lags: self.num_lags,
+            row_stride: self.row_stride,
+            row_major: self.row_major,
+            lags: self.lags.clone(),
+            directions: self.directions.clone(),
+            series_names: self.series_names.clone(),
+            static_count: self.static_count,
+            row_weights: None,
+            time_offset: self.time_offset,
+            source_length: self.source_length,
+            row_checksums: None,
+            gaps: self.gaps.clone(),
+        }
+    }
+
+    /// Borrows the matrix, e.g. to hand it to read-only consumers without cloning its data.
+    ///
+    /// The metadata, such as the lags, is copied.
+    pub fn view(&self) -> LagMatrixView<'_, T>
+    where
+        T: Clone,
+    {
+        LagMatrix {
+            data: self.data.as_ref(),
+            num_rows: self.num_rows,
+            num_cols: self.num_cols,
+            series_length: self.series_length,
+            series_count: self.series_count,
+            num_lags: self.num_lags,
+            row_stride: self.row_stride,
+            row_major: self.row_major,
+            lags: self.lags.clone(),
+            directions: self.directions.clone(),
+            series_names: self.series_names.clone(),
+            static_count: self.static_count,
+            row_weights: self.row_weights.clone(),
+            time_offset: self.time_offset,
+            source_length: self.source_length,
+            row_checksums: self.row_checksums.clone(),
+            gaps: self.gaps.clone(),
+        }
+    }
+
+    /// Converts the matrix into a matrix owning a copy of its data.
+    pub fn into_owned(self) -> LagMatrix<T>
+    where
+        T: Clone,
+    {
+        self.map_data(|data| data.as_ref().to_vec())
+    }
+
+    /// Replaces the storage of the matrix, keeping its metadata.
+    fn map_data<U>(self, f: impl FnOnce(S) -> U) -> LagMatrix<T, U> {
+        LagMatrix {
+            data: f(self.data),
+            num_rows: self.num_rows,
+            num_cols: self.num_cols,
+            series_length: self.series_length,
+            series_count: self.series_count,
+            num_lags: self.num_lags,
+            row_stride: self.row_stride,
+            row_major: self.row_major,
+            lags: self.lags,
+            directions: self.directions,
+            series_names: self.series_names,
+            static_count: self.static_count,
+            row_weights: self.row_weights,
+            time_offset: self.time_offset,
+            source_length: self.source_length,
+            row_checksums: self.row_checksums,
+            gaps: self.gaps,
+        }
+    }
+
+    /// Wraps an existing buffer of lagged values, e.g. one created by another process or
+    /// a memory-mapped file, without copying it.
+    ///
+    /// The buffer has to be laid out like the matrices created by [`lag_matrix_2d`] with
+    /// the specified shape, e.g. one obtained from [`into_parts`](LagMatrix::into_parts).
+    ///
+    /// ## Arguments
+    /// * `data` - The buffer holding the lagged values.
+    /// * `shape` - The shape and lag metadata of the matrix.
+    ///
+    /// ## Returns
+    /// The matrix, or [`LagError::InvalidLength`] if the buffer length does not match the shape.
+    ///
+    /// ## Example
+    /// ```
+    /// use timelag::{lag_matrix, LagMatrixShape, LagMatrixView, MatrixLayout};
+    ///
+    /// let buffer = [1, 2, 3, 0, 1, 2];
+    /// let shape = LagMatrixShape::new(MatrixLayout::RowMajor(3), 1, vec![0, 1], 0).unwrap();
+    /// let view = LagMatrixView::from_parts(&buffer[..], shape).unwrap();
+    ///
+    /// assert_eq!(view.num_rows(), 2);
+    /// assert_eq!(view.into_owned(), lag_matrix(&[1, 2, 3], 0..=1, 0, 0).unwrap());
+    /// ```
+    pub fn from_parts(data: S, shape: LagMatrixShape) -> Result<Self, LagError> {
+        if data.as_ref().len() != shape.len() {
+            return Err(LagError::InvalidLength);
+        }
+
+        let mut matrix = Self::new(
+            data,
+            matches!(shape.layout, MatrixLayout::RowMajor(_)),
+            shape.layout.len(),
+            shape.series_count,
+            shape.lags,
+            shape.row_stride,
+        )
+        .with_window(shape.time_offset, shape.source_length);
+        if matrix.row_major {
+            matrix.num_rows += shape.static_count;
+        } else {
+            matrix.num_cols += shape.static_count;
+        }
+        matrix.directions = shape.directions;
+        matrix.series_names = shape.series_names;
+        matrix.static_count = shape.static_count;
+        matrix.gaps = shape.gaps;
+        Ok(matrix)
+    }
+}
+
+impl<T> From<LagMatrix<T>> for Vec<T> {
+    #[inline(always)]
+    fn from(value: LagMatrix<T>) -> Self {
+        value.data
+    }
+}
+
+impl<T> From<LagMatrix<T>> for Box<[T]> {
+    #[inline(always)]
+    fn from(value: LagMatrix<T>) -> Self {
+        value.data.into_boxed_slice()
+    }
+}
+
+impl<T, S> Deref for LagMatrix<T, S>
+where
+    S: AsRef<[T]>,
+{
+    type Target = [T];
+
+    #[inline(always)]
+    fn deref(&self) -> &Self::Target {
+        self.data.as_ref()
+    }
+}
+
+impl<T, S> Index<(usize, usize)> for LagMatrix<T, S>
+where
+    S: AsRef<[T]>,
+{
+    type Output = T;
+
+    /// The value at the specified row and column; see [`LagMatrix::get`].
+    ///
+    /// ## Panics
+    /// Panics if the row or column is out of range.
+    #[inline(always)]
+    fn index(&self, (row, col): (usize, usize)) -> &Self::Output {
+        match self.get(row, col) {
+            Some(value) => value,
+            None => panic!(
+                "index ({row}, {col}) out of range for a {}x{} matrix",
+                self.num_rows, self.num_cols
+            ),
+        }
+    }
+}
+
+/// Forwards indexing by offset to the underlying buffer, as implementing
+/// `Index<(usize, usize)>` disables indexing through [`Deref`].
+macro_rules! impl_slice_index {
+    ($($index:ty => $output:ty),*) => {
+        $(
+            impl<T, S> Index<$index> for LagMatrix<T, S>
+            where
+                S: AsRef<[T]>,
+            {
+                type Output = $output;
+
+                #[inline(always)]
+                fn index(&self, index: $index) -> &Self::Output {
+                    &self.data.as_ref()[index]
+                }
+            }
+        )*
+    };
+}
+
+impl_slice_index!(
+    usize => T,
+    Range<usize> => [T],
+    RangeFrom<usize> => [T],
+    RangeTo<usize> => [T],
+    RangeFull => [T],
+    RangeInclusive<usize> => [T],
+    RangeToInclusive<usize> => [T]
+);
+
+impl<T, S> PartialEq<[T]> for LagMatrix<T, S>
+where
+    S: AsRef<[T]>,
+    T: PartialEq,
+{
+    fn eq(&self, other: &[T]) -> bool {
+        self.data.as_ref().iter().eq(other)
+    }
+}
+
+impl<O, T, S> PartialEq<O> for LagMatrix<T, S>
+where
+    O: AsRef<[T]>,
+    S: AsRef<[T]>,
+    T: PartialEq,
+{
+    #[inline(always)]
+    fn eq(&self, other: &O) -> bool {
+        self.data.as_ref().eq(other.as_ref())
+    }
 }
 
-impl<T> From<LagMatrix<T>> for Vec<T> {
+/// A [`LagMatrix`] borrowing its values, e.g. from an externally created buffer.
+pub type LagMatrixView<'a, T> = LagMatrix<T, &'a [T]>;
+
+/// A [`LagMatrix`] that either borrows or owns its values; see [`lag_matrix_2d_cow`].
+pub type LagMatrixCow<'a, T> = LagMatrix<T, Cow<'a, [T]>>;
+
+impl<T: Clone> LagMatrixCow<'_, T> {
+    /// Indicates whether the matrix borrows its values.
     #[inline(always)]
-    fn from(value: LagMatrix<T>) -> Self {
-        value.data
+    pub fn is_borrowed(&self) -> bool {
+        matches!(self.data, Cow::Borrowed(_))
     }
-}
 
-impl<T> From<LagMatrix<T>> for Box<[T]> {
-    #[inline(always)]
-    fn from(value: LagMatrix<T>) -> Self {
-        value.data.into_boxed_slice()
+    /// Mutable access to the values, copying them first if they are borrowed.
+    ///
+    /// Modifying the values discards the row checksums, if any.
+    pub fn to_mut(&mut self) -> &mut [T] {
+        self.row_checksums = None;
+        self.data.to_mut()
     }
 }
 
-impl<T> Deref for LagMatrix<T> {
-    type Target = [T];
-
+impl<T: Clone> From<LagMatrix<T>> for LagMatrixCow<'_, T> {
     #[inline(always)]
-    fn deref(&self) -> &Self::Target {
-        &self.data
+    fn from(value: LagMatrix<T>) -> Self {
+        value.map_data(Cow::Owned)
     }
 }
 
-impl<T> PartialEq<[T]> for LagMatrix<T>
-where
-    T: PartialEq,
-{
-    fn eq(&self, other: &[T]) -> bool {
-        self.data.iter().eq(other)
+impl<'a, T: Clone> From<LagMatrixView<'a, T>> for LagMatrixCow<'a, T> {
+    #[inline(always)]
+    fn from(value: LagMatrixView<'a, T>) -> Self {
+        value.map_data(Cow::Borrowed)
     }
 }
 
-impl<S, T> PartialEq<S> for LagMatrix<T>
-where
-    S: AsRef<[T]>,
-    T: PartialEq,
-{
+impl<T: Clone> From<LagMatrixCow<'_, T>> for LagMatrix<T> {
+    /// Converts the matrix, copying its values only if they are borrowed.
     #[inline(always)]
-    fn eq(&self, other: &S) -> bool {
-        self.data.eq(other.as_ref())
+    fn from(value: LagMatrixCow<'_, T>) -> Self {
+        value.map_data(Cow::into_owned)
     }
 }
 
@@ -278,11 +1637,11 @@ pub trait CreateLagMatrix<T> {
     /// index) elements will be dropped with each lag. Lagged versions are prepended with the placeholder.
     ///
     /// ## Arguments
-    /// * `lags` - The number of lagged versions to create.
+    /// * `lags` - The lags to create; negative lags create leads, see [`IntoLag`].
     /// * `fill` - The value to use to fill in lagged gaps.
     /// * `stride` - The number of elements between lagged versions in the resulting vector.
-    ///            If set to `0` or `data.len()`, no padding is introduced. Values larger than
-    ///            `data.len()` creates padding entries set to the `fill` value.
+    ///   If set to `0` or `data.len()`, no padding is introduced. Values larger than
+    ///   `data.len()` creates padding entries set to the `fill` value.
     ///
     /// ## Returns
     /// A vector containing lagged copies of the original data, or an error.
@@ -315,7 +1674,7 @@ pub trait CreateLagMatrix<T> {
     ///     ]
     /// );
     /// ```
-    fn lag_matrix<R: IntoIterator<Item = usize>>(
+    fn lag_matrix<L: IntoLag, R: IntoIterator<Item = L>>(
         &self,
         lags: R,
         fill: T,
@@ -331,12 +1690,12 @@ pub trait CreateLagMatrix<T> {
     /// dropped with each lag. Lagged versions are prepended with the placeholder.
     ///
     /// ## Arguments
-    /// * `lags` - The number of lagged versions to create.
+    /// * `lags` - The lags to create; negative lags create leads, see [`IntoLag`].
     /// * `layout` - The matrix layout, specifying column- or row-major order and the series length.
     /// * `fill` - The value to use to fill in lagged gaps.
     /// * `row_stride` - The number of elements along a row of the matrix.
-    ///            If set to `0` or `data.len()`, no padding is introduced. Values larger than
-    ///            `data.len()` creates padding entries set to the `fill` value.
+    ///   If set to `0` or `data.len()`, no padding is introduced. Values larger than
+    ///   `data.len()` creates padding entries set to the `fill` value.
     ///
     /// ## Returns
     /// A vector containing lagged copies of the original data, or an error.
@@ -417,7 +1776,7 @@ pub trait CreateLagMatrix<T> {
     ///     ]
     /// );
     /// ```
-    fn lag_matrix_2d<R: IntoIterator<Item = usize>>(
+    fn lag_matrix_2d<L: IntoLag, R: IntoIterator<Item = L>>(
         &self,
         layout: MatrixLayout,
         lags: R,
@@ -432,7 +1791,7 @@ where
     T: Copy,
 {
     #[inline(always)]
-    fn lag_matrix<R: IntoIterator<Item = usize>>(
+    fn lag_matrix<L: IntoLag, R: IntoIterator<Item = L>>(
         &self,
         lags: R,
         fill: T,
@@ -442,7 +1801,7 @@ where
     }
 
     #[inline(always)]
-    fn lag_matrix_2d<R: IntoIterator<Item = usize>>(
+    fn lag_matrix_2d<L: IntoLag, R: IntoIterator<Item = L>>(
         &self,
         layout: MatrixLayout,
         lags: R,
@@ -462,11 +1821,11 @@ where
 ///
 /// ## Arguments
 /// * `data` - The time series data to create lagged versions of.
-/// * `lags` - The number of lagged versions to create.
+/// * `lags` - The lags to create; negative lags create leads, see [`IntoLag`].
 /// * `fill` - The value to use to fill in lagged gaps.
 /// * `stride` - The number of elements between lagged versions in the resulting vector.
-///            If set to `0` or `data.len()`, no padding is introduced. Values larger than
-///            `data.len()` creates padding entries set to the `fill` value.
+///   If set to `0` or `data.len()`, no padding is introduced. Values larger than
+///   `data.len()` creates padding entries set to the `fill` value.
 ///
 /// ## Returns
 /// A vector containing lagged copies of the original data, or an error.
@@ -498,13 +1857,44 @@ where
 ///     ]
 /// );
 /// ```
-pub fn lag_matrix<T: Copy, R: IntoIterator<Item = usize>>(
+///
+/// Only the requested lags are produced, so the original series is omitted unless the zero
+/// lag is requested; as with MATLAB's `lagmatrix([1 2 3], 1:3)`:
+///
+/// ```
+/// # use timelag::lag_matrix;
+/// let fill = f64::NAN;
+/// let lagged = lag_matrix(&[1.0, 2.0, 3.0], 1..=3, fill, 0).unwrap();
+///
+/// assert_eq!(lagged.num_rows(), 3);
+/// assert_eq!(lagged.lags(), &[1, 2, 3]);
+/// ```
+///
+/// Negative lags create leads, which are padded at the end:
+///
+/// ```
+/// # use timelag::lag_matrix;
+/// let data = [1.0, 2.0, 3.0, 4.0];
+/// let fill = f64::INFINITY;
+///
+/// let lagged = lag_matrix(&data, [-1, 0, 1], fill, 0).unwrap();
+///
+/// assert_eq!(
+///     lagged,
+///     &[
+///          2.0, 3.0, 4.0, fill, // first lead
+///          1.0, 2.0, 3.0, 4.0,  // original data
+///         fill, 1.0, 2.0, 3.0,  // first lag
+///     ]
+/// );
+/// ```
+pub fn lag_matrix<T: Copy, L: IntoLag, R: IntoIterator<Item = L>>(
     data: &[T],
     lags: R,
     fill: T,
     mut stride: usize,
 ) -> Result<LagMatrix<T>, LagError> {
-    let lags = Vec::from_iter(lags);
+    let lags = collect_lags(lags)?;
     let num_lags = lags.len();
 
     if num_lags == 0 {
@@ -516,7 +1906,7 @@ pub fn lag_matrix<T: Copy, R: IntoIterator<Item = usize>>(
     }
 
     let data_rows = data.len();
-    if num_lags > data_rows {
+    if num_lags > data_rows || exceeds_length(&lags, data_rows) {
         return Err(LagError::LagExceedsValueCount);
     }
 
@@ -529,54 +1919,272 @@ pub fn lag_matrix<T: Copy, R: IntoIterator<Item = usize>>(
     }
 
     let mut lagged = vec![fill; stride * num_lags];
-    for (row, lag) in lags.into_iter().enumerate() {
-        let lagged_offset = row * stride + lag;
-        let lagged_rows = data_rows - lag;
-        let lagged_end = lagged_offset + lagged_rows;
-        let src = &data[0..lagged_rows];
-        lagged[lagged_offset..lagged_end].copy_from_slice(src);
-    }
-
-    let matrix = LagMatrix {
-        data: lagged,
-        num_rows: num_lags,
-        num_cols: data_rows,
-        series_length: data_rows,
-        row_stride: stride,
-        series_count: 1,
-        num_lags,
-        row_major: true,
-    };
+    shift_rows(data, &lags, stride, &mut lagged);
+
+    #[cfg(all(feature = "debug-verify", debug_assertions))]
+    verify_rows(data_rows, &lags, stride);
+
+    let matrix = LagMatrix::new(lagged, true, data_rows, 1, lags, stride);
 
     Ok(matrix)
 }
 
-/// Describes the layout of the data matrix.
-#[derive(Debug, Copy, Clone, Eq, PartialEq, Ord, PartialOrd)]
-pub enum MatrixLayout {
-    /// Data is laid out row-wise, i.e. reach row of the matrix contains a time series
-    /// and the columns represent points in time.
-    ///
-    /// The values represent the number of elements per row, i.e. the length of each time series.
-    RowMajor(usize),
-    /// Data is laid out column-wise, i.e. reach column of the matrix contains a time series
-    /// and the rows represent points in time.
-    ///
-    /// The values represent the number of elements per column, i.e. the length of each time series.
-    ColumnMajor(usize),
+/// Copies one lagged version of the series per row of `stride` elements into `lagged`,
+/// which is pre-filled with the fill value.
+fn shift_rows<T: Copy>(data: &[T], lags: &[isize], stride: usize, lagged: &mut [T]) {
+    for (row, &lag) in lags.iter().enumerate() {
+        let (src, dst) = shifted_ranges(lag, data.len());
+        let row_offset = row * stride;
+        lagged[row_offset + dst.start..row_offset + dst.end].copy_from_slice(&data[src]);
+    }
 }
 
-impl MatrixLayout {
-    pub fn len(&self) -> usize {
-        match self {
-            MatrixLayout::RowMajor(len) => *len,
-            MatrixLayout::ColumnMajor(len) => *len,
+/// Checks the kernel of [`lag_matrix`] against the naive definition by running it on the
+/// indices of the values; see [`LagPlan::verify`].
+#[cfg(all(feature = "debug-verify", debug_assertions))]
+fn verify_rows(data_len: usize, lags: &[isize], stride: usize) {
+    let mut lagged = vec![verify::GAP; stride * lags.len()];
+    shift_rows(&verify::indices(data_len), lags, stride, &mut lagged);
+    for (row, &lag) in lags.iter().enumerate() {
+        for t in 0..data_len {
+            verify::check(
+                "lag_matrix",
+                lagged[row * stride + t],
+                verify::naive_source(t, lag, data_len),
+                format_args!("lag {lag} at time step {t}"),
+            );
+        }
+    }
+}
+
+/// Create a time-lagged matrix of time series values with dilated lags.
+///
+/// Every lag `k` is multiplied by `step`, such that lags `1..=3` with a step of `7` produce
+/// the lags `7`, `14` and `21` without materializing any of the intermediate lags. This is
+/// useful for long series where only seasonally spaced lags are of interest. The result
+/// is identical to calling [`lag_matrix`] with the dilated lags.
+///
+/// ## Arguments
+/// * `data` - The time series data to create lagged versions of.
+/// * `lags` - The lags to create before dilation; negative lags create leads.
+/// * `step` - The dilation factor applied to every lag.
+/// * `fill` - The value to use to fill in lagged gaps.
+/// * `stride` - The number of elements between lagged versions in the resulting vector;
+///   see [`lag_matrix`].
+///
+/// ## Returns
+/// The lag matrix, or an error. [`LagError::InvalidLags`] is returned if `step` is zero
+/// or a dilated lag overflows.
+///
+/// ## Example
+/// ```
+/// # use timelag::lag_matrix_with_step;
+/// let data = [1.0, 2.0, 3.0, 4.0, 5.0];
+/// let fill = f64::INFINITY;
+///
+/// let lagged = lag_matrix_with_step(&data, 1..=2, 2, fill, 0).unwrap();
+///
+/// assert_eq!(lagged.lags(), &[2, 4]);
+/// assert_eq!(
+///     lagged,
+///     &[
+///         fill, fill,  1.0,  2.0, 3.0, // second lag
+///         fill, fill, fill, fill, 1.0, // fourth lag
+///     ]
+/// );
+/// ```
+pub fn lag_matrix_with_step<T: Copy, L: IntoLag, R: IntoIterator<Item = L>>(
+    data: &[T],
+    lags: R,
+    step: usize,
+    fill: T,
+    stride: usize,
+) -> Result<LagMatrix<T>, LagError> {
+    let step = isize::try_from(step)
+        .ok()
+        .filter(|&step| step > 0)
+        .ok_or(LagError::InvalidLags)?;
+
+    let lags = collect_lags(lags)?
+        .into_iter()
+        .map(|lag| lag.checked_mul(step).ok_or(LagError::InvalidLags))
+        .collect::<Result<Vec<_>, _>>()?;
+
+    lag_matrix(data, lags, fill, stride)
+}
+
+/// Create a time-lagged matrix of time series values, computing the value of every lagged gap
+/// with a closure.
+///
+/// The closure receives the series index (always `0`), the lag and the time step of the gap;
+/// see [`lag_matrix_2d_with`] for multiple series. Row padding entries are set to `T::default()`.
+///
+/// ## Example
+/// ```
+/// # use timelag::lag_matrix_with;
+/// let data = [4.0, 5.0, 6.0];
+///
+/// // Backfill from the first observed value.
+/// let lagged = lag_matrix_with(&data, 0..=1, |_, _, _| data[0], 0).unwrap();
+///
+/// assert_eq!(
+///     lagged,
+///     &[
+///         4.0, 5.0, 6.0, // original data
+///         4.0, 4.0, 5.0, // first lag
+///     ]
+/// );
+/// ```
+pub fn lag_matrix_with<T, L, R, F>(
+    data: &[T],
+    lags: R,
+    fill: F,
+    stride: usize,
+) -> Result<LagMatrix<T>, LagError>
+where
+    T: Copy + Default,
+    L: IntoLag,
+    R: IntoIterator<Item = L>,
+    F: FnMut(usize, isize, usize) -> T,
+{
+    lag_matrix_2d_with(data, MatrixLayout::RowMajor(data.len()), lags, fill, stride)
+}
+
+/// Create a time-lagged matrix of time series values, marking lagged gaps as `None`.
+///
+/// Unlike a sentinel fill value, `None` cannot be confused with an observation, which
+/// matters for integer series where every value is meaningful. Row padding entries are
+/// `None` as well. See [`lag_matrix`] for the arguments.
+///
+/// ## Example
+/// ```
+/// # use timelag::lag_matrix_opt;
+/// let data = [0, 1, 2];
+/// let lagged = lag_matrix_opt(&data, 0..=1, 0).unwrap();
+///
+/// assert_eq!(
+///     lagged,
+///     &[
+///         Some(0), Some(1), Some(2), // original data
+///         None, Some(0), Some(1), // first lag
+///     ]
+/// );
+/// ```
+pub fn lag_matrix_opt<T: Copy, L: IntoLag, R: IntoIterator<Item = L>>(
+    data: &[T],
+    lags: R,
+    stride: usize,
+) -> Result<LagMatrix<Option<T>>, LagError> {
+    lag_matrix_2d_opt(data, MatrixLayout::RowMajor(data.len()), lags, stride)
+}
+
+/// Create a time-lagged matrix of multiple time series values, marking lagged gaps as `None`.
+///
+/// See [`lag_matrix_opt`] and [`lag_matrix_2d`] for details.
+///
+/// ## Example
+/// ```
+/// # use timelag::{lag_matrix_2d_opt, MatrixLayout};
+/// let data = [
+///     1, 10,
+///     2, 20,
+/// ];
+/// let lagged = lag_matrix_2d_opt(&data, MatrixLayout::ColumnMajor(2), [1], 0).unwrap();
+///
+/// assert_eq!(
+///     lagged,
+///     &[
+///         None, None,
+///         Some(1), Some(10),
+///     ]
+/// );
+/// ```
+pub fn lag_matrix_2d_opt<T: Copy, L: IntoLag, R: IntoIterator<Item = L>>(
+    data_matrix: &[T],
+    layout: MatrixLayout,
+    lags: R,
+    row_stride: usize,
+) -> Result<LagMatrix<Option<T>>, LagError> {
+    let data_matrix: Vec<Option<T>> = data_matrix.iter().copied().map(Some).collect();
+    lag_matrix_2d_impl(
+        &data_matrix,
+        layout,
+        collect_lags(lags)?,
+        None,
+        None,
+        row_stride,
+    )
+}
+
+/// Create a time-lagged matrix of time series values, borrowing the data where possible.
+///
+/// See [`lag_matrix_2d_cow`] and [`lag_matrix`] for details.
+pub fn lag_matrix_cow<T: Copy, L: IntoLag, R: IntoIterator<Item = L>>(
+    data: &[T],
+    lags: R,
+    fill: T,
+    stride: usize,
+) -> Result<LagMatrixCow<'_, T>, LagError> {
+    lag_matrix_2d_cow(data, MatrixLayout::RowMajor(data.len()), lags, fill, stride)
+}
+
+/// Create a time-lagged matrix of multiple time series values, borrowing the data where possible.
+///
+/// If the matrix would be an exact copy of the data, i.e. for the zero lag alone and
+/// without extra row padding, the data is borrowed; otherwise, the matrix is created
+/// and owned like via [`lag_matrix_2d`], whose arguments are used here.
+///
+/// ## Example
+/// ```
+/// # use timelag::{lag_matrix_2d_cow, MatrixLayout};
+/// let data = [1.0, 2.0, 3.0, 4.0];
+///
+/// let lagged = lag_matrix_2d_cow(&data, MatrixLayout::ColumnMajor(4), [0], 0.0, 0).unwrap();
+/// assert!(lagged.is_borrowed());
+///
+/// let lagged = lag_matrix_2d_cow(&data, MatrixLayout::ColumnMajor(4), [0, 1], 0.0, 0).unwrap();
+/// assert!(!lagged.is_borrowed());
+/// assert_eq!(lagged.num_cols(), 2);
+/// ```
+pub fn lag_matrix_2d_cow<T: Copy, L: IntoLag, R: IntoIterator<Item = L>>(
+    data_matrix: &[T],
+    layout: MatrixLayout,
+    lags: R,
+    fill: T,
+    row_stride: usize,
+) -> Result<LagMatrixCow<'_, T>, LagError> {
+    let lags = collect_lags(lags)?;
+    if lags == [0] && !layout.is_empty() {
+        let series_count = data_matrix.len() / layout.len();
+        let borrowed = LagMatrixShape::new(layout, series_count, lags.clone(), row_stride)
+            .and_then(|shape| LagMatrix::from_parts(Cow::Borrowed(data_matrix), shape));
+        if let Ok(matrix) = borrowed {
+            return Ok(matrix);
         }
     }
 
-    pub fn is_empty(&self) -> bool {
-        self.len() == 0
+    lag_matrix_2d_impl(data_matrix, layout, lags, None, fill, row_stride).map(Into::into)
+}
+
+/// Converts a data matrix into the column-major (interleaved) layout.
+pub(crate) fn to_column_major<T: Copy>(
+    data: &[T],
+    layout: MatrixLayout,
+) -> Result<Vec<T>, LagError> {
+    let series_length = layout.len();
+    if series_length == 0 || !data.len().is_multiple_of(series_length) {
+        return Err(LagError::InvalidLength);
     }
+
+    Ok(match layout {
+        MatrixLayout::ColumnMajor(_) => data.to_vec(),
+        MatrixLayout::RowMajor(_) => {
+            let num_series = data.len() / series_length;
+            (0..data.len())
+                .map(|i| data[(i % num_series) * series_length + i / num_series])
+                .collect()
+        }
+    })
 }
 
 /// Create a time-lagged matrix of multiple time series.
@@ -589,12 +2197,12 @@ impl MatrixLayout {
 ///
 /// ## Arguments
 /// * `data_matrix` - The matrix of multiple time series data to create lagged versions of.
-/// * `lags` - The number of lagged versions to create.
+/// * `lags` - The lags to create; negative lags create leads, see [`IntoLag`].
 /// * `layout` - The matrix layout, specifying column- or row-major order and the series length.
 /// * `fill` - The value to use to fill in lagged gaps.
 /// * `row_stride` - The number of elements along a row of the matrix.
-///            If set to `0` or `data.len()`, no padding is introduced. Values larger than
-///            `data.len()` creates padding entries set to the `fill` value.
+///   If set to `0` or `data.len()`, no padding is introduced. Values larger than
+///   `data.len()` creates padding entries set to the `fill` value.
 ///
 /// ## Returns
 /// A vector containing lagged copies of the original data, or an error.
@@ -675,125 +2283,479 @@ impl MatrixLayout {
 ///     ]
 /// );
 /// ```
-pub fn lag_matrix_2d<T: Copy, R: IntoIterator<Item = usize>>(
+pub fn lag_matrix_2d<T: Copy, L: IntoLag, R: IntoIterator<Item = L>>(
     data_matrix: &[T],
     layout: MatrixLayout,
     lags: R,
     fill: T,
-    mut row_stride: usize,
+    row_stride: usize,
 ) -> Result<LagMatrix<T>, LagError> {
-    let lags = Vec::from_iter(lags);
-    let num_lags = lags.len();
+    lag_matrix_2d_impl(
+        data_matrix,
+        layout,
+        collect_lags(lags)?,
+        None,
+        fill,
+        row_stride,
+    )
+}
+
+/// The direction in which a time series is shifted.
+#[derive(Debug, Copy, Clone, Eq, PartialEq, Ord, PartialOrd, Hash, Default)]
+pub enum LagDirection {
+    /// The series is lagged, i.e. shifted into the future, such that every time step
+    /// holds a past value. Gaps are padded at the start of the series.
+    #[default]
+    Lag,
+    /// The series is led, i.e. shifted into the past, such that every time step
+    /// holds a future value. Gaps are padded at the end of the series.
+    Lead,
+}
+
+/// A value that can be used as a lag.
+///
+/// Positive values lag a series, such that time step `t` holds the past value `x(t - k)`
+/// and the gap is padded at the start. Negative values lead a series, such that time step
+/// `t` holds the future value `x(t + k)` and the gap is padded at the end, similar to
+/// negative lags in MATLAB's `lagmatrix`.
+pub trait IntoLag: Copy {
+    /// Converts the value into a signed lag, or `None` if it is out of range.
+    fn into_lag(self) -> Option<isize>;
+}
+
+macro_rules! impl_into_lag {
+    ($($t:ty),*) => {
+        $(
+            impl IntoLag for $t {
+                #[inline(always)]
+                fn into_lag(self) -> Option<isize> {
+                    isize::try_from(self).ok()
+                }
+            }
+        )*
+    };
+}
+
+impl_into_lag!(usize, u8, u16, u32, u64, isize, i8, i16, i32, i64);
+
+impl<L: IntoLag> IntoLag for &L {
+    #[inline(always)]
+    fn into_lag(self) -> Option<isize> {
+        (*self).into_lag()
+    }
+}
+
+/// Create a time-lagged matrix of multiple time series, leading some of the series.
+///
+/// This function behaves like [`lag_matrix_2d`], but shifts every series in the direction
+/// specified for it. Series with [`LagDirection::Lead`] hold the future value `x(t + k)` at
+/// lag `k` and are padded at their end, which is useful for covariates known in advance,
+/// such as calendar features or weather forecasts.
+///
+/// ## Arguments
+/// * `data_matrix` - The matrix of multiple time series data to create lagged versions of.
+/// * `layout` - The matrix layout, specifying column- or row-major order and the series length.
+/// * `lags` - The lags to create.
+/// * `directions` - The shift direction of each series.
+/// * `fill` - The value to use to fill in lagged gaps.
+/// * `row_stride` - The number of elements along a row of the matrix; see [`lag_matrix_2d`].
+///
+/// ## Returns
+/// The lag matrix, or an error. [`LagError::InvalidLength`] is returned if the number of
+/// directions does not match the number of series.
+///
+/// ## Example
+/// ```
+/// # use timelag::{lag_matrix_2d_directed, LagDirection, MatrixLayout};
+/// let data = [
+///      1.0,  2.0,  3.0,  4.0,
+///     -1.0, -2.0, -3.0, -4.0
+/// ];
+///
+/// let fill = f64::INFINITY;
+/// let directions = [LagDirection::Lag, LagDirection::Lead];
+///
+/// let lagged = lag_matrix_2d_directed(&data, MatrixLayout::RowMajor(4), 0..=1, &directions, fill, 0).unwrap();
+///
+/// assert_eq!(lagged.direction(1), Some(LagDirection::Lead));
+/// assert_eq!(
+///     lagged,
+///     &[
+///          1.0,  2.0,  3.0,  4.0, // original data
+///         -1.0, -2.0, -3.0, -4.0,
+///         fill,  1.0,  2.0,  3.0, // first lag
+///         -2.0, -3.0, -4.0, fill, // first lead
+///     ]
+/// );
+/// ```
+pub fn lag_matrix_2d_directed<T: Copy, L: IntoLag, R: IntoIterator<Item = L>>(
+    data_matrix: &[T],
+    layout: MatrixLayout,
+    lags: R,
+    directions: &[LagDirection],
+    fill: T,
+    row_stride: usize,
+) -> Result<LagMatrix<T>, LagError> {
+    lag_matrix_2d_impl(
+        data_matrix,
+        layout,
+        collect_lags(lags)?,
+        Some(directions),
+        fill,
+        row_stride,
+    )
+}
+
+/// Create a time-lagged matrix of multiple time series values, computing the value of every
+/// lagged gap with a closure.
+///
+/// This enables context-dependent imputation, e.g. backfilling from the first observed value,
+/// without a second pass over the output. Only the gaps are visited; row padding entries
+/// are set to `T::default()`.
+///
+/// ## Arguments
+/// * `data_matrix` - The matrix of multiple time series data to create lagged versions of.
+/// * `layout` - The matrix layout, specifying the number of elements per series.
+/// * `lags` - The lags to create; negative lags create leads.
+/// * `fill` - Computes the value of a gap from the series index, the lag and the time step.
+/// * `row_stride` - The number of elements along a row; see [`lag_matrix_2d`].
+///
+/// ## Example
+/// ```
+/// # use timelag::{lag_matrix_2d_with, MatrixLayout};
+/// let data = [
+///     1.0, 2.0, 3.0,
+///     7.0, 8.0, 9.0,
+/// ];
+///
+/// // Backfill each series from its first value.
+/// let lagged = lag_matrix_2d_with(&data, MatrixLayout::RowMajor(3), [2], |series, _, _| {
+///     data[series * 3]
+/// }, 0).unwrap();
+///
+/// assert_eq!(
+///     lagged,
+///     &[
+///         1.0, 1.0, 1.0,
+///         7.0, 7.0, 7.0,
+///     ]
+/// );
+/// ```
+pub fn lag_matrix_2d_with<T, L, R, F>(
+    data_matrix: &[T],
+    layout: MatrixLayout,
+    lags: R,
+    mut fill: F,
+    row_stride: usize,
+) -> Result<LagMatrix<T>, LagError>
+where
+    T: Copy + Default,
+    L: IntoLag,
+    R: IntoIterator<Item = L>,
+    F: FnMut(usize, isize, usize) -> T,
+{
+    let lags = collect_lags(lags)?;
+    let mut matrix = lag_matrix_2d_impl(data_matrix, layout, lags, None, T::default(), row_stride)?;
+
+    let lags = matrix.lags.clone();
+    matrix.fill_gaps(|lag_index, series, t| fill(series, lags[lag_index], t));
+    Ok(matrix)
+}
+
+fn lag_matrix_2d_impl<T: Copy>(
+    data_matrix: &[T],
+    layout: MatrixLayout,
+    lags: Vec<isize>,
+    directions: Option<&[LagDirection]>,
+    fill: T,
+    row_stride: usize,
+) -> Result<LagMatrix<T>, LagError> {
+    let plan = LagPlan::new(data_matrix, layout, lags, directions, row_stride)?;
+    let mut lagged = vec![fill; plan.unit_len() * plan.num_units()];
+    plan.fill(data_matrix, &mut lagged, 0..plan.num_units());
+    Ok(plan.into_matrix(lagged))
+}
+
+/// The validated shape of a lag matrix created by [`lag_matrix_2d_impl`].
+///
+/// The matrix is filled in units that can be processed independently: one block of rows
+/// per lag for row-major matrices, and one row per time step for column-major matrices.
+#[derive(Debug, Clone)]
+struct LagPlan {
+    row_major: bool,
+    series_length: usize,
+    num_series: usize,
+    row_stride: usize,
+    lags: Vec<isize>,
+    directions: Vec<LagDirection>,
+}
+
+impl LagPlan {
+    fn new<T>(
+        data_matrix: &[T],
+        layout: MatrixLayout,
+        lags: Vec<isize>,
+        directions: Option<&[LagDirection]>,
+        mut row_stride: usize,
+    ) -> Result<Self, LagError> {
+        let num_lags = lags.len();
+
+        if num_lags == 0 {
+            return Err(LagError::InvalidLags);
+        }
+
+        if data_matrix.is_empty() {
+            return Err(LagError::EmptyData);
+        }
 
-    if num_lags == 0 {
-        return Err(LagError::InvalidLags);
-    }
+        let series_length = layout.len();
+        if num_lags > series_length || exceeds_length(&lags, series_length) {
+            return Err(LagError::LagExceedsValueCount);
+        }
 
-    if data_matrix.is_empty() {
-        return Err(LagError::EmptyData);
-    }
+        let num_series = data_matrix.len() / series_length;
+        if num_series * series_length != data_matrix.len() {
+            return Err(LagError::InvalidLength);
+        }
 
-    let series_length = layout.len();
-    if num_lags > series_length {
-        return Err(LagError::LagExceedsValueCount);
+        let directions = match directions {
+            Some(directions) if directions.len() != num_series => {
+                return Err(LagError::InvalidLength)
+            }
+            Some(directions) => directions.to_vec(),
+            None => vec![LagDirection::Lag; num_series],
+        };
+
+        let (row_major, min_stride) = match layout {
+            MatrixLayout::RowMajor(_) => (true, series_length),
+            MatrixLayout::ColumnMajor(_) => (false, num_series * num_lags),
+        };
+
+        if row_stride == 0 {
+            row_stride = min_stride;
+        }
+
+        if row_stride < min_stride {
+            return Err(LagError::InvalidStride);
+        }
+
+        Ok(Self {
+            row_major,
+            series_length,
+            num_series,
+            row_stride,
+            lags,
+            directions,
+        })
     }
 
-    let num_series = data_matrix.len() / series_length;
-    if num_series * series_length != data_matrix.len() {
-        return Err(LagError::InvalidLength);
+    /// The number of elements of a unit.
+    #[inline(always)]
+    fn unit_len(&self) -> usize {
+        if self.row_major {
+            self.num_series * self.row_stride
+        } else {
+            self.row_stride
+        }
     }
 
-    if row_stride == 0 {
-        row_stride = num_series * lags.len();
+    /// The number of units of the matrix.
+    #[inline(always)]
+    fn num_units(&self) -> usize {
+        if self.row_major {
+            self.lags.len()
+        } else {
+            self.series_length
+        }
     }
 
-    Ok(match layout {
-        MatrixLayout::RowMajor(_) => {
-            if row_stride < series_length {
-                return Err(LagError::InvalidStride);
-            }
+    /// Fills the specified units, where `lagged` starts at the first unit to fill
+    /// and is pre-filled with the fill value.
+    fn fill<T: Copy>(&self, data_matrix: &[T], lagged: &mut [T], units: Range<usize>) {
+        self.fill_units(data_matrix, lagged, units.clone());
+
+        #[cfg(all(feature = "debug-verify", debug_assertions))]
+        self.verify(data_matrix.len(), units);
+    }
 
-            let mut lagged = vec![fill; num_series * row_stride * num_lags];
-            for (set, lag) in lags.into_iter().enumerate() {
-                let set_offset = set * num_series * row_stride;
+    /// Checks the kernels of [`fill`](Self::fill) against the naive definition
+    /// `lagged[lag][t] == data[t - lag]` for the specified units.
+    ///
+    /// The kernels are generic over the values, so they are run on the indices of the
+    /// values instead, which requires no bounds on the value type.
+    #[cfg(all(feature = "debug-verify", debug_assertions))]
+    fn verify(&self, data_len: usize, units: Range<usize>) {
+        let unit_len = self.unit_len();
+        let mut lagged = vec![verify::GAP; unit_len * units.len()];
+        self.fill_units(&verify::indices(data_len), &mut lagged, units.clone());
 
-                // Each series is shifted by the same lag.
-                for s in 0..num_series {
-                    let data_start = s * series_length;
-                    let data_end = (s + 1) * series_length - lag;
+        if self.row_major {
+            self.check(&lagged, units.clone(), 0..self.series_length, |l, s, t| {
+                (l - units.start) * unit_len + s * self.row_stride + t
+            });
+        } else {
+            self.check(&lagged, 0..self.lags.len(), units.clone(), |l, s, t| {
+                (t - units.start) * unit_len + l * self.num_series + s
+            });
+        }
+    }
 
-                    let lagged_offset = set_offset + s * row_stride + lag;
-                    let lagged_rows = series_length - lag;
-                    let lagged_end = lagged_offset + lagged_rows;
+    /// Checks the kernel of [`fill_steps`](Self::fill_steps) for row-major matrices like
+    /// [`verify`](Self::verify).
+    #[cfg(all(feature = "debug-verify", debug_assertions))]
+    fn verify_steps(&self, data_len: usize, steps: Range<usize>) {
+        let mut lagged = vec![verify::GAP; self.unit_len() * self.num_units()];
+        self.copy_steps(&verify::indices(data_len), &mut lagged, steps.clone());
+        self.check(&lagged, 0..self.lags.len(), steps, |l, s, t| {
+            l * self.unit_len() + s * self.row_stride + t
+        });
+    }
 
-                    copy_range(
-                        data_matrix,
-                        &mut lagged,
-                        data_start..data_end,
-                        lagged_offset..lagged_end,
+    /// Checks the indices of the specified lags and time steps, where `offset` locates the
+    /// value of a lag index, series and time step within `lagged`.
+    #[cfg(all(feature = "debug-verify", debug_assertions))]
+    fn check(
+        &self,
+        lagged: &[usize],
+        lag_indices: Range<usize>,
+        steps: Range<usize>,
+        offset: impl Fn(usize, usize, usize) -> usize,
+    ) {
+        for lag_index in lag_indices {
+            for (series, &direction) in self.directions.iter().enumerate() {
+                let shift = shift_of(self.lags[lag_index], direction);
+                for t in steps.clone() {
+                    let expected =
+                        verify::naive_source(t, shift, self.series_length).map(|source| {
+                            if self.row_major {
+                                series * self.series_length + source
+                            } else {
+                                source * self.num_series + series
+                            }
+                        });
+                    verify::check(
+                        "LagPlan",
+                        lagged[offset(lag_index, series, t)],
+                        expected,
+                        format_args!("lag index {lag_index} of series {series} at time step {t}"),
                     );
                 }
             }
+        }
+    }
 
-            LagMatrix {
-                data: lagged,
-                num_rows: num_series * num_lags,
-                num_cols: series_length,
-                series_length,
-                series_count: num_series,
-                num_lags,
-                row_stride,
-                row_major: true,
+    /// Fills the specified units; see [`fill`](Self::fill).
+    fn fill_units<T: Copy>(&self, data_matrix: &[T], lagged: &mut [T], units: Range<usize>) {
+        let shifts = self.shifts();
+        let shifts = shifts.chunks_exact(self.num_series);
+        if self.row_major {
+            let blocks = lagged.chunks_exact_mut(self.unit_len());
+            for (block, shifts) in blocks.zip(shifts.skip(units.start)) {
+                let steps = 0..self.series_length;
+                for (src, dst) in
+                    row_major_copies(shifts, self.series_length, self.row_stride, steps)
+                {
+                    copy_range(data_matrix, block, src, dst);
+                }
             }
+            return;
         }
-        MatrixLayout::ColumnMajor(_) => {
-            if row_stride < num_series * num_lags {
-                return Err(LagError::InvalidStride);
-            }
-
-            let mut lagged = vec![fill; row_stride * series_length];
-            for (set, lag) in lags.into_iter().enumerate() {
-                let set_offset = set * num_series;
-
-                // Each series is shifted by the same lag.
-                for s in 0..(series_length - lag) {
-                    let data_start = s * num_series;
-                    let data_end = (s + 1) * num_series;
-
-                    let lagged_offset = set_offset + (s + lag) * row_stride;
-                    let lagged_end = lagged_offset + num_series;
 
+        for (row, t) in lagged.chunks_exact_mut(self.row_stride).zip(units) {
+            for (set, shifts) in shifts.clone().enumerate() {
+                // Adjacent series of the same shift, e.g. all of them, are copied at once.
+                let set_offset = set * self.num_series;
+                for (src, dst) in column_major_copies(shifts, self.series_length, t) {
                     copy_range(
                         data_matrix,
-                        &mut lagged,
-                        data_start..data_end,
-                        lagged_offset..lagged_end,
+                        row,
+                        src,
+                        set_offset + dst.start..set_offset + dst.end,
                     );
                 }
             }
+        }
+    }
+
+    /// The shift of every series for every lag, lag by lag.
+    fn shifts(&self) -> Vec<isize> {
+        self.lags
+            .iter()
+            .flat_map(|&lag| self.directions.iter().map(move |&d| shift_of(lag, d)))
+            .collect()
+    }
+
+    /// Fills the time steps `steps` of all units, where `lagged` holds all units and is
+    /// pre-filled with the fill value, e.g. to fill several matrices tile by tile.
+    fn fill_steps<T: Copy>(&self, data_matrix: &[T], lagged: &mut [T], steps: Range<usize>) {
+        let unit_len = self.unit_len();
+        if !self.row_major {
+            let units = &mut lagged[steps.start * unit_len..steps.end * unit_len];
+            self.fill(data_matrix, units, steps);
+            return;
+        }
+
+        self.copy_steps(data_matrix, lagged, steps.clone());
+
+        #[cfg(all(feature = "debug-verify", debug_assertions))]
+        self.verify_steps(data_matrix.len(), steps);
+    }
 
-            LagMatrix {
-                data: lagged,
-                num_cols: num_series * num_lags,
-                num_rows: series_length,
-                series_length,
-                series_count: num_series,
-                num_lags,
-                row_stride,
-                row_major: false,
+    /// Copies the time steps `steps` of all units of a row-major matrix; see
+    /// [`fill_steps`](Self::fill_steps).
+    fn copy_steps<T: Copy>(&self, data_matrix: &[T], lagged: &mut [T], steps: Range<usize>) {
+        let shifts = self.shifts();
+        let blocks = lagged.chunks_exact_mut(self.unit_len());
+        for (block, shifts) in blocks.zip(shifts.chunks_exact(self.num_series)) {
+            let copies =
+                row_major_copies(shifts, self.series_length, self.row_stride, steps.clone());
+            for (src, dst) in copies {
+                copy_range(data_matrix, block, src, dst);
             }
         }
-    })
+    }
+
+    /// Creates the matrix from the filled data.
+    fn into_matrix<T>(self, lagged: Vec<T>) -> LagMatrix<T> {
+        let mut matrix = LagMatrix::new(
+            lagged,
+            self.row_major,
+            self.series_length,
+            self.num_series,
+            self.lags,
+            self.row_stride,
+        );
+        matrix.directions = self.directions;
+        matrix
+    }
+}
+
+/// Collects the lags, failing with [`LagError::InvalidLags`] if a lag is out of range.
+fn collect_lags<L: IntoLag, R: IntoIterator<Item = L>>(lags: R) -> Result<Vec<isize>, LagError> {
+    lags.into_iter()
+        .map(|lag| lag.into_lag().ok_or(LagError::InvalidLags))
+        .collect()
+}
+
+/// The signed shift of a series, where positive values lag and negative values lead.
+#[inline(always)]
+fn shift_of(lag: isize, direction: LagDirection) -> isize {
+    match direction {
+        LagDirection::Lag => lag,
+        LagDirection::Lead => -lag,
+    }
 }
 
 fn copy_range<T: Copy>(src: &[T], dst: &mut [T], src_range: Range<usize>, dst_range: Range<usize>) {
-    if cfg!(feature = "unsafe") {
-        unsafe {
-            let src: &[T] = src.get_unchecked(src_range);
-            let dst: &mut [T] = dst.get_unchecked_mut(dst_range);
-            dst.copy_from_slice(src);
-        }
-    } else {
+    #[cfg(feature = "unsafe")]
+    unsafe {
+        let src: &[T] = src.get_unchecked(src_range);
+        let dst: &mut [T] = dst.get_unchecked_mut(dst_range);
+        dst.copy_from_slice(src);
+    }
+
+    #[cfg(not(feature = "unsafe"))]
+    {
         let src: &[T] = &src[src_range];
         let dst: &mut [T] = &mut dst[dst_range];
         dst.copy_from_slice(src);
@@ -801,7 +2763,10 @@ fn copy_range<T: Copy>(src: &[T], dst: &mut [T], src_range: Range<usize>, dst_ra
 }
 
 /// An error during creation of a lagged data matrix.
+///
+/// New variants may be added in minor releases, so matches need a wildcard arm.
 #[derive(Debug, Copy, Clone, Eq, PartialEq)]
+#[non_exhaustive]
 pub enum LagError {
     /// Invalid or no lags were specified.
     InvalidLags,
@@ -815,10 +2780,37 @@ pub enum LagError {
     InvalidLength,
     /// The data is in an invalid (e.g. non-contiguous) memory layout.
     InvalidMemoryLayout,
+    /// The channel or series index is out of range, or the number of series does not match.
+    InvalidChannel,
+    /// The matrix is singular or not positive definite.
+    SingularMatrix,
+    /// The time series holds no valid values, e.g. only NaN.
+    MissingValues,
+    /// The quantiles lie outside `[0, 1]` or are in the wrong order.
+    InvalidQuantiles,
+    /// The number of series names does not match the number of series, or the names differ.
+    InvalidSeriesNames,
+    /// The static columns are not supported by the operation, or differ.
+    InvalidStaticColumns,
+    /// The axis does not exist.
+    InvalidAxis,
+    /// The matrix has lagged gaps within the series that the operation cannot account for.
+    InteriorGaps,
 }
 
 impl std::error::Error for LagError {}
 
+impl From<LayoutError> for LagError {
+    fn from(error: LayoutError) -> Self {
+        match error {
+            LayoutError::InvalidLags => LagError::InvalidLags,
+            LayoutError::EmptyData => LagError::EmptyData,
+            LayoutError::InvalidStride => LagError::InvalidStride,
+            _ => LagError::InvalidLength,
+        }
+    }
+}
+
 impl Display for LagError {
     fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
         match self {
@@ -842,8 +2834,27 @@ impl Display for LagError {
                 f,
                 "The data is in an invalid (e.g. non-contiguous) memory layout"
             ),
+            LagError::InvalidChannel => write!(f, "The channel or series index is out of range"),
+            LagError::SingularMatrix => {
+                write!(f, "The matrix is singular or not positive definite")
+            }
             LagError::InvalidLags => write!(f, "Invalid or no lags were specified"),
-            LagError::EmptyData => write!(f, "TThe data slice was emptyt"),
+            LagError::EmptyData => write!(f, "The data slice was empty"),
+            LagError::MissingValues => write!(f, "The time series holds no valid values"),
+            LagError::InvalidQuantiles => {
+                write!(
+                    f,
+                    "The quantiles must lie in [0, 1] and be in ascending order"
+                )
+            }
+            LagError::InvalidSeriesNames => write!(f, "The series names do not match the series"),
+            LagError::InvalidStaticColumns => {
+                write!(f, "The static columns are unsupported or differ")
+            }
+            LagError::InvalidAxis => write!(f, "The axis does not exist"),
+            LagError::InteriorGaps => {
+                write!(f, "The matrix has lagged gaps within the series")
+            }
         }
     }
 }
@@ -1119,6 +3130,579 @@ mod tests {
         );
     }
 
+    #[test]
+    #[rustfmt::skip]
+    fn test_lag_2d_directed_columnwise() {
+        let data = [
+            1.0, -1.0,
+            2.0, -2.0,
+            3.0, -3.0,
+        ];
+
+        let lag = f64::INFINITY;
+        let directions = [LagDirection::Lead, LagDirection::Lag];
+
+        let direct = lag_matrix_2d_directed(&data, MatrixLayout::ColumnMajor(3), [0, 2], &directions, lag, 0).unwrap();
+
+        assert_eq!(direct.num_rows(), 3);
+        assert_eq!(direct.num_cols(), 4);
+        assert_eq!(direct.row_stride(), 4);
+        assert_eq!(direct.directions(), &directions);
+        assert!(direct.is_padding(1, 0, 1));
+        assert!(!direct.is_padding(1, 1, 2));
+
+        assert_eq!(
+            direct,
+            &[
+            //   original   second lead / lag
+                1.0, -1.0,  3.0,  lag,
+                2.0, -2.0,  lag,  lag,
+                3.0, -3.0,  lag, -1.0,
+            ]
+        );
+    }
+
+    #[test]
+    fn test_lag_2d_directed_matches_undirected() {
+        let data: Vec<f64> = (0..12).map(f64::from).collect();
+        let directions = [LagDirection::Lag; 3];
+
+        for layout in [MatrixLayout::RowMajor(4), MatrixLayout::ColumnMajor(4)] {
+            let directed =
+                lag_matrix_2d_directed(&data, layout, [2, 0, 1], &directions, -1.0, 0).unwrap();
+            let undirected = lag_matrix_2d(&data, layout, [2, 0, 1], -1.0, 0).unwrap();
+            assert_eq!(directed, undirected);
+        }
+    }
+
+    #[test]
+    fn test_lag_2d_directed_invalid_directions() {
+        let data = [1.0, 2.0, 3.0, 4.0];
+        let result = lag_matrix_2d_directed(
+            &data,
+            MatrixLayout::RowMajor(2),
+            [1],
+            &[LagDirection::Lead],
+            0.0,
+            0,
+        );
+        assert_eq!(result.unwrap_err(), LagError::InvalidLength);
+    }
+
+    #[test]
+    #[rustfmt::skip]
+    fn test_with_static_columns_padded_columnwise() {
+        let data = [1, 10, 2, 20, 3, 30];
+        let lagged = lag_matrix_2d(&data, MatrixLayout::ColumnMajor(3), [0], 0, 3)
+            .unwrap()
+            .with_static_columns(&[7]);
+
+        assert_eq!(lagged.num_cols(), 3);
+        assert_eq!(lagged.row_stride(), 4);
+        assert_eq!(lagged, &[
+            1, 10, 7, 0,
+            2, 20, 7, 0,
+            3, 30, 7, 0,
+        ]);
+    }
+
+    #[test]
+    #[rustfmt::skip]
+    fn test_with_static_columns_rowwise() {
+        let data = [1, 2, 3];
+        let lagged = lag_matrix(&data, [1], 0, 4)
+            .unwrap()
+            .with_static_columns(&[5, 6]);
+
+        assert_eq!(lagged.num_rows(), 3);
+        assert_eq!(lagged.static_count(), 2);
+        assert_eq!(lagged, &[
+            0, 1, 2, 0,
+            5, 5, 5, 5,
+            6, 6, 6, 6,
+        ]);
+    }
+
+    #[test]
+    fn test_with_row_weights_invalid_length() {
+        let lagged = lag_matrix(&[1.0, 2.0, 3.0], [1], 0.0, 0).unwrap();
+        assert_eq!(
+            lagged.with_row_weights(vec![1.0; 2]).unwrap_err(),
+            LagError::InvalidLength
+        );
+    }
+
+    #[test]
+    #[rustfmt::skip]
+    fn test_signed_lags_columnwise() {
+        let data = [
+            1.0, -1.0,
+            2.0, -2.0,
+            3.0, -3.0,
+        ];
+
+        let lagged = lag_matrix_2d(&data, MatrixLayout::ColumnMajor(3), [-2_isize, 1], 0.0, 0).unwrap();
+        assert_eq!(lagged, &[
+            3.0, -3.0, 0.0,  0.0,
+            0.0,  0.0, 1.0, -1.0,
+            0.0,  0.0, 2.0, -2.0,
+        ]);
+    }
+
+    #[test]
+    fn test_signed_lags_with_lead_direction() {
+        // Leading a series by a negative lag lags it.
+        let data = [1, 2, 3, 10, 20, 30];
+        let directions = [LagDirection::Lag, LagDirection::Lead];
+        let lagged =
+            lag_matrix_2d_directed(&data, MatrixLayout::RowMajor(3), [-1], &directions, 0, 0)
+                .unwrap();
+        assert_eq!(lagged, &[2, 3, 0, 0, 10, 20]);
+        assert!(lagged.is_padding(0, 0, 2));
+        assert!(lagged.is_padding(0, 1, 0));
+        assert!(!lagged.is_padding(0, 1, 2));
+    }
+
+    #[test]
+    fn test_lag_2d_rowwise_default_stride() {
+        let data = [1, 2, 3, 10, 20, 30];
+        let lagged = lag_matrix_2d(&data, MatrixLayout::RowMajor(3), [0, 1, 2], 0, 0).unwrap();
+        assert_eq!(lagged.row_stride(), 3);
+        assert_eq!(lagged.len(), 18);
+    }
+
+    #[test]
+    fn test_lag_exceeding_length() {
+        let data = [1.0, 2.0, 3.0];
+        assert_eq!(
+            lag_matrix(&data, [0, 4], 0.0, 0).unwrap_err(),
+            LagError::LagExceedsValueCount
+        );
+        assert_eq!(
+            lag_matrix_2d(&data, MatrixLayout::ColumnMajor(3), [-4], 0.0, 0).unwrap_err(),
+            LagError::LagExceedsValueCount
+        );
+        assert_eq!(
+            lag_matrix(&data, [usize::MAX], 0.0, 0).unwrap_err(),
+            LagError::InvalidLags
+        );
+    }
+
+    #[test]
+    fn test_arbitrary_lag_selections() {
+        let data = [1, 2, 3, 4, 5];
+        let slice: &[usize] = &[4, 0, 2];
+        let expected = lag_matrix(&data, [4, 0, 2], 0, 0).unwrap();
+
+        assert_eq!(data.lag_matrix(slice, 0, 0).unwrap(), expected);
+        assert_eq!(data.lag_matrix(slice.to_vec(), 0, 0).unwrap(), expected);
+        assert_eq!(
+            data.lag_matrix(LagSet::new([4, 0, 2]), 0, 0).unwrap(),
+            expected
+        );
+        assert_eq!(expected.lags(), &[4, 0, 2]);
+
+        let lagged = data
+            .lag_matrix_2d(MatrixLayout::ColumnMajor(5), (0..=4).step_by(2), 0, 0)
+            .unwrap();
+        assert_eq!(lagged.lags(), &[0, 2, 4]);
+    }
+
+    #[test]
+    fn test_lag_matrix_with_step() {
+        let data: Vec<i32> = (0..30).collect();
+        let stepped = lag_matrix_with_step(&data, [0, 1, 2, 3], 5, -1, 0).unwrap();
+        assert_eq!(stepped, lag_matrix(&data, [0, 5, 10, 15], -1, 0).unwrap());
+
+        assert_eq!(
+            lag_matrix_with_step(&data, [1], 0, -1, 0).unwrap_err(),
+            LagError::InvalidLags
+        );
+        assert_eq!(
+            lag_matrix_with_step(&data, [isize::MAX], 2, -1, 0).unwrap_err(),
+            LagError::InvalidLags
+        );
+    }
+
+    #[test]
+    #[rustfmt::skip]
+    fn test_lag_matrix_2d_with_receives_gap_positions() {
+        let data = [
+            1, 10,
+            2, 20,
+            3, 30,
+        ];
+
+        let mut visited = Vec::new();
+        let lagged = lag_matrix_2d_with(&data, MatrixLayout::ColumnMajor(3), [1, -1], |series, lag, t| {
+            visited.push((series, lag, t));
+            -100 * lag as i32 - t as i32
+        }, 5).unwrap();
+
+        assert_eq!(visited, [(0, 1, 0), (1, 1, 0), (0, -1, 2), (1, -1, 2)]);
+        assert_eq!(lagged, &[
+            -100, -100,  2, 20, 0,
+               1,   10,  3, 30, 0,
+               2,   20, 98, 98, 0,
+        ]);
+        assert_eq!(lagged.lags(), &[1, -1]);
+    }
+
+    #[test]
+    #[rustfmt::skip]
+    fn test_lag_matrix_opt_leads_and_padding() {
+        let data = [i64::MIN, 0, i64::MAX];
+        let lagged = lag_matrix_opt(&data, [-1], 4).unwrap();
+        assert_eq!(lagged, &[Some(0), Some(i64::MAX), None, None]);
+
+        assert_eq!(
+            lag_matrix_opt::<i64, usize, _>(&[], [0], 0).unwrap_err(),
+            LagError::EmptyData
+        );
+    }
+
+    #[test]
+    fn test_view_shares_accessors() {
+        let data = [1.0, 2.0, 3.0, 4.0];
+        let lagged = lag_matrix_2d(&data, MatrixLayout::ColumnMajor(4), [0, 1], 0.0, 3).unwrap();
+        let view = lagged.view();
+
+        assert_eq!(view.ptr(), lagged.ptr());
+        assert_eq!(view.lda(), 3);
+        assert_eq!(view.lags(), lagged.lags());
+        assert_eq!(view.valid_length(), 3);
+        #[cfg(feature = "float")]
+        assert_eq!(view.gemm_ready(), lagged.gemm_ready());
+        assert_eq!(view, lagged.as_ref());
+        assert_eq!(view.into_owned(), lagged);
+
+        let buffer: Vec<f64> = lagged.clone().into();
+        let shape = LagMatrixShape::new(MatrixLayout::ColumnMajor(4), 1, vec![0, 1], 3).unwrap();
+        let wrapped = LagMatrixView::from_parts(&buffer[..], shape.clone()).unwrap();
+        assert_eq!(wrapped, lagged.view());
+        assert_eq!(
+            LagMatrixView::from_parts(&buffer[1..], shape).unwrap_err(),
+            LagError::InvalidLength
+        );
+        assert_eq!(
+            LagMatrixShape::new(MatrixLayout::ColumnMajor(4), 1, vec![0, 1], 1).unwrap_err(),
+            LagError::InvalidStride
+        );
+    }
+
+    #[test]
+    fn test_cow_borrows_only_exact_copies() {
+        let data = [1, 2, 3, 4, 5, 6];
+        let layout = MatrixLayout::RowMajor(3);
+
+        let borrowed = lag_matrix_2d_cow(&data, layout, [0], -1, 0).unwrap();
+        assert!(borrowed.is_borrowed());
+        assert_eq!(borrowed.ptr(), data.as_ptr());
+        assert_eq!(
+            LagMatrix::from(borrowed),
+            lag_matrix_2d(&data, layout, [0], -1, 0).unwrap()
+        );
+
+        for (lags, row_stride) in [(vec![0, 1], 0), (vec![0], 4)] {
+            let owned = lag_matrix_2d_cow(&data, layout, lags.clone(), -1, row_stride).unwrap();
+            assert!(!owned.is_borrowed());
+            assert_eq!(
+                LagMatrix::from(owned),
+                lag_matrix_2d(&data, layout, lags, -1, row_stride).unwrap()
+            );
+        }
+
+        assert_eq!(
+            lag_matrix_2d_cow(&data[1..], layout, [0], -1, 0).unwrap_err(),
+            LagError::InvalidLength
+        );
+
+        let owned = lag_matrix(&data, [0], -1, 0).unwrap();
+        let mut matrix = LagMatrixCow::from(owned.view());
+        assert!(matrix.is_borrowed());
+        matrix.to_mut()[0] = 10;
+        assert!(!matrix.is_borrowed());
+        assert_eq!((matrix[0], owned[0]), (10, 1));
+        assert!(!LagMatrixCow::from(owned).is_borrowed());
+    }
+
+    #[test]
+    fn test_index_skips_row_padding() {
+        let data = [1, 10, 2, 20, 3, 30];
+        let lagged = lag_matrix_2d(&data, MatrixLayout::ColumnMajor(3), [0, 1], -1, 5).unwrap();
+
+        assert_eq!(lagged[(2, 0)], 3);
+        assert_eq!(lagged[(2, 3)], 20);
+        assert_eq!(lagged.view()[(1, 2)], 1);
+        assert_eq!(lagged.get(0, 4), None);
+        assert_eq!(lagged.get(3, 0), None);
+        assert_eq!(lagged.get(1, 1), Some(&lagged[5 + 1]));
+        assert_eq!(lagged[5..7], [2, 20]);
+    }
+
+    #[test]
+    fn test_lag_series_in_both_layouts() {
+        let data = [1, 2, 3, 10, 20, 30];
+        let expected = [-1, 10, 20];
+
+        let row_major = lag_matrix_2d(&data, MatrixLayout::RowMajor(3), [0, 1], -1, 5).unwrap();
+        let data = to_column_major(&data, MatrixLayout::RowMajor(3)).unwrap();
+        let column_major =
+            lag_matrix_2d(&data, MatrixLayout::ColumnMajor(3), [0, 1], -1, 5).unwrap();
+
+        for lagged in [&row_major, &column_major] {
+            let values: Vec<i32> = lagged.lag_series(1, 1).unwrap().copied().collect();
+            assert_eq!(values, expected);
+            assert_eq!(lagged.lag_series(1, 1).unwrap().len(), 3);
+            assert!(lagged.lag_series(2, 0).is_none());
+            assert!(lagged.lag_series(0, 2).is_none());
+        }
+
+        assert_eq!(row_major.lag(1), Some(&[-1, 1, 2][..]));
+        assert_eq!(column_major.lag(1), None);
+    }
+
+    #[test]
+    fn test_select_lags_retains_metadata() {
+        let data: Vec<i32> = (0..12).collect();
+        let directions = [LagDirection::Lead, LagDirection::Lag];
+        for (layout, row_stride, expected_stride) in [
+            (MatrixLayout::ColumnMajor(6), 9, 7),
+            (MatrixLayout::RowMajor(6), 8, 8),
+        ] {
+            let lagged =
+                lag_matrix_2d_directed(&data, layout, [0, 1, 2], &directions, -1, row_stride)
+                    .unwrap()
+                    .with_static_columns(&[42])
+                    .with_row_weights(vec![1; 6])
+                    .unwrap()
+                    .trim_incomplete()
+                    .unwrap();
+            let expected =
+                lag_matrix_2d_directed(&data, layout, [2, 0], &directions, -1, expected_stride)
+                    .unwrap()
+                    .with_static_columns(&[42])
+                    .with_row_weights(vec![1; 6])
+                    .unwrap()
+                    .trim_incomplete()
+                    .unwrap();
+
+            let selected = lagged.select_lags([2, 0]).unwrap();
+            assert_eq!(selected, expected);
+            assert_eq!(selected.time_offset(), 2);
+            assert_eq!(selected.valid_length(), selected.series_length());
+        }
+
+        let lagged = lag_matrix(&[1, 2, 3], 0..=1, 0, 0).unwrap();
+        assert_eq!(lagged.select_lags([2]).unwrap_err(), LagError::InvalidLags);
+        assert_eq!(
+            lagged.select_lags::<usize, _>([]).unwrap_err(),
+            LagError::InvalidLags
+        );
+    }
+
+    #[test]
+    fn test_compact_keeps_logical_values() {
+        let data: Vec<i32> = (0..8).collect();
+        for layout in [MatrixLayout::ColumnMajor(4), MatrixLayout::RowMajor(4)] {
+            let padded = lag_matrix_2d(&data, layout, [0, 1], -1, 11)
+                .unwrap()
+                .with_row_checksums();
+            let compact = padded.clone().compact();
+
+            assert_eq!(compact.row_stride(), compact.num_cols());
+            assert_eq!(compact.len(), compact.num_rows() * compact.num_cols());
+            assert!(compact.rows().eq(padded.rows()));
+            assert_eq!(compact.verify_rows(0..compact.num_rows()), Ok(()));
+            assert_eq!(
+                compact,
+                lag_matrix_2d(&data, layout, [0, 1], -1, 0)
+                    .unwrap()
+                    .with_row_checksums()
+            );
+        }
+    }
+
+    #[test]
+    fn test_transpose_switches_orientation() {
+        let data: Vec<i32> = (0..10).collect();
+        let directions = [LagDirection::Lag, LagDirection::Lead];
+        let row_major =
+            lag_matrix_2d_directed(&data, MatrixLayout::RowMajor(5), [0, 2], &directions, -1, 7)
+                .unwrap()
+                .with_static_columns(&[9])
+                .trim_incomplete()
+                .unwrap();
+        let column_major = to_column_major(&data, MatrixLayout::RowMajor(5)).unwrap();
+        let column_major = lag_matrix_2d_directed(
+            &column_major,
+            MatrixLayout::ColumnMajor(5),
+            [0, 2],
+            &directions,
+            -1,
+            0,
+        )
+        .unwrap()
+        .with_static_columns(&[9])
+        .trim_incomplete()
+        .unwrap();
+
+        let transposed = row_major.transpose();
+        assert_eq!(transposed, column_major);
+        assert_eq!(transposed.valid_length(), 1);
+        assert!(transposed.transpose().rows().eq(row_major.rows()));
+        assert!(LagMatrix::<f64>::default().transpose().is_empty());
+    }
+
+    #[test]
+    fn test_map_and_cast_retain_metadata() {
+        let lagged = lag_matrix_2d(
+            &[1.0, 2.0, 3.0],
+            MatrixLayout::ColumnMajor(3),
+            0..=1,
+            -1.0,
+            3,
+        )
+        .unwrap()
+        .with_row_weights(vec![0.5, 1.0, 2.0])
+        .unwrap()
+        .trim_incomplete()
+        .unwrap();
+
+        let cast = lagged.cast::<i16>();
+        assert_eq!((cast.time_offset(), cast.row_stride()), (1, 3));
+        assert_eq!(cast.row_weights(), Some(&[1, 2][..]));
+        assert_eq!(cast, &[2, 1, -1, 3, 2, -1]);
+
+        let mapped = lagged.map(|value| value * 10.0);
+        assert_eq!(mapped.row_weights(), None);
+        assert_eq!(mapped.lags(), lagged.lags());
+        assert_eq!(mapped[(1, 1)], 20.0);
+    }
+
+    #[test]
+    fn test_concat_time_in_both_layouts() {
+        let data = [1, 2, 3, 4, 5, 6];
+        let mut lagger = ChunkedLagger::new([0, 2], -1).unwrap();
+        let first = lagger.push_chunk(&data[..2]).unwrap();
+        let second = lagger.push_chunk(&data[2..]).unwrap();
+
+        let column_major = first.concat_time(&second).unwrap();
+        let expected = lag_matrix_2d(&data, MatrixLayout::ColumnMajor(6), [0, 2], -1, 0).unwrap();
+        assert_eq!(column_major, expected);
+        assert_eq!(column_major.valid_length(), 4);
+
+        let row_major = first.transpose().concat_time(&second.transpose()).unwrap();
+        assert_eq!(row_major, lag_matrix(&data, [0, 2], -1, 0).unwrap());
+
+        let weighted = first
+            .clone()
+            .with_row_weights(vec![1, 2])
+            .unwrap()
+            .concat_time(&second.clone().with_row_weights(vec![3, 4, 5, 6]).unwrap())
+            .unwrap();
+        assert_eq!(weighted.row_weights(), Some(&[1, 2, 3, 4, 5, 6][..]));
+    }
+
+    #[test]
+    fn test_slice_time_in_both_layouts() {
+        let data = [1, 10, 2, 20, 3, 30, 4, 40, 5, 50];
+        let column_major = lag_matrix_2d(&data, MatrixLayout::ColumnMajor(5), [0, 2], -1, 6)
+            .unwrap()
+            .with_row_weights(vec![1, 2, 3, 4, 5])
+            .unwrap();
+        let row_major = column_major.transpose();
+
+        let window = column_major.slice_time(1..4).unwrap();
+        assert_eq!(window.row_stride(), 6);
+        assert_eq!(window.row_weights(), Some(&[2, 3, 4][..]));
+        assert!(window.rows().eq(column_major.rows().skip(1).take(3)));
+        assert_eq!(window.valid_length(), 2);
+
+        let window = row_major.slice_time(1..4).unwrap();
+        assert_eq!((window.num_cols(), window.time_offset()), (3, 1));
+        assert_eq!(
+            window.transpose(),
+            column_major.slice_time(1..4).unwrap().compact()
+        );
+
+        for range in [2..2, 3..6] {
+            assert_eq!(
+                column_major.slice_time(range).unwrap_err(),
+                LagError::InvalidLength
+            );
+        }
+    }
+
+    #[test]
+    fn test_concat_time_rejects_mismatches() {
+        let mut lagger = ChunkedLagger::new([0, 1], 0).unwrap();
+        let first = lagger.push_chunk(&[1, 2]).unwrap();
+        let second = lagger.push_chunk(&[3, 4]).unwrap();
+
+        assert_eq!(second.concat_time(&first), Err(LagError::InvalidLength));
+        assert_eq!(
+            first.concat_time(&second.select_lags([1, 0]).unwrap()),
+            Err(LagError::InvalidLags)
+        );
+        assert_eq!(
+            first.concat_time(&second.transpose()),
+            Err(LagError::InvalidMemoryLayout)
+        );
+        assert_eq!(
+            first.concat_time(&second.clone().with_static_columns(&[7])),
+            Err(LagError::InvalidStaticColumns)
+        );
+        assert_eq!(
+            first.concat_time(&second.clone().with_row_weights(vec![1, 1]).unwrap()),
+            Err(LagError::InvalidLength)
+        );
+    }
+
+    #[test]
+    #[should_panic(expected = "index (0, 3) out of range for a 3x3 matrix")]
+    fn test_index_panics_on_padding() {
+        let lagged = lag_matrix_2d(&[1, 2, 3], MatrixLayout::ColumnMajor(3), 0..=2, -1, 5).unwrap();
+        let _ = lagged[(0, 3)];
+    }
+
+    #[test]
+    #[rustfmt::skip]
+    fn test_trim_incomplete() {
+        let data = [1, 2, 3, 4, 5];
+        let lagged = lag_matrix(&data, [1, -1], -1, 6)
+            .unwrap()
+            .with_row_weights(vec![10, 20, 30, 40, 50])
+            .unwrap();
+        assert_eq!(lagged.valid_length(), 3);
+
+        let trimmed = lagged.trim_incomplete().unwrap();
+        assert_eq!(trimmed.num_cols(), 3);
+        assert_eq!(trimmed.row_stride(), 4);
+        assert_eq!(trimmed.valid_length(), 3);
+        assert_eq!(trimmed.time_offset(), 1);
+        assert_eq!(trimmed.row_weights(), Some(&[20, 30, 40][..]));
+        assert_eq!(trimmed, &[
+            1, 2, 3, -1,
+            3, 4, 5, -1,
+        ]);
+
+        let trimmed = lag_matrix_2d(&data, MatrixLayout::ColumnMajor(5), [1, -1], -1, 0)
+            .unwrap()
+            .trim_incomplete()
+            .unwrap();
+        assert_eq!(trimmed, &[
+            1, 3,
+            2, 4,
+            3, 5,
+        ]);
+
+        assert_eq!(
+            lag_matrix(&data, [3, -2], -1, 0).unwrap().trim_incomplete().unwrap_err(),
+            LagError::LagExceedsValueCount
+        );
+    }
+
     #[test]
     fn test_lag_matrix_2d_long_series_rowwise() {
         let long_data_rowwise: Vec<f64> = (0..20_000).map(|i| i as f64).collect();
@@ -0,0 +1,234 @@
+use crate::{
+    collect_lags, lag_matrix_2d_impl, shift_of, IntoLag, LagError, LagMatrix, MatrixLayout,
+};
+
+/// Create a time-lagged matrix of named columns, e.g. the `(name, values)` pairs of a table,
+/// naming the lagged features after the columns.
+///
+/// The result is column-major, i.e. it has one row per time step and one named column per
+/// lag and input column, such that it maps back onto a table; see
+/// [`column_names`](LagMatrix::column_names). See [`lag_matrix_2d`](crate::lag_matrix_2d)
+/// for the remaining arguments.
+///
+/// ## Returns
+/// The matrix, or
+/// * [`LagError::EmptyData`] if there are no columns or the columns are empty,
+/// * [`LagError::InvalidLength`] if the columns differ in length.
+///
+/// ## Example
+/// ```
+/// use timelag::lag_columns;
+///
+/// let columns = vec![
+///     ("price".to_string(), vec![1.0, 2.0, 3.0]),
+///     ("volume".to_string(), vec![10.0, 20.0, 30.0]),
+/// ];
+/// let lagged = lag_columns(&columns, [1], f64::NAN, 0).unwrap();
+///
+/// assert_eq!(lagged.column_names(), ["price_lag1", "volume_lag1"]);
+/// assert_eq!(lagged[(2, 1)], 20.0);
+/// ```
+pub fn lag_columns<T, N, V, L, R>(
+    columns: &[(N, V)],
+    lags: R,
+    fill: T,
+    row_stride: usize,
+) -> Result<LagMatrix<T>, LagError>
+where
+    T: Copy,
+    N: AsRef<str>,
+    V: AsRef<[T]>,
+    L: IntoLag,
+    R: IntoIterator<Item = L>,
+{
+    let lags = collect_lags(lags)?;
+    let Some((_, first)) = columns.first() else {
+        return Err(LagError::EmptyData);
+    };
+
+    let series_length = first.as_ref().len();
+    if columns
+        .iter()
+        .any(|(_, values)| values.as_ref().len() != series_length)
+    {
+        return Err(LagError::InvalidLength);
+    }
+
+    let mut data = Vec::with_capacity(series_length * columns.len());
+    for t in 0..series_length {
+        data.extend(columns.iter().map(|(_, values)| values.as_ref()[t]));
+    }
+
+    let mut matrix = lag_matrix_2d_impl(
+        &data,
+        MatrixLayout::ColumnMajor(series_length),
+        lags,
+        None,
+        fill,
+        row_stride,
+    )?;
+    matrix.series_names = Some(
+        columns
+            .iter()
+            .map(|(name, _)| name.as_ref().to_string())
+            .collect(),
+    );
+    Ok(matrix)
+}
+
+impl<T, S> LagMatrix<T, S> {
+    /// Attaches a name to every series, from which the [`column_names`](Self::column_names)
+    /// are generated, e.g. for exporting the matrix to data frames or CSV files.
+    ///
+    /// ## Returns
+    /// The named matrix, or [`LagError::InvalidSeriesNames`] if the number of names does not
+    /// match the number of series.
+    ///
+    /// ## Example
+    /// ```
+    /// use timelag::{lag_matrix_2d, MatrixLayout};
+    ///
+    /// let data = [1.0, 10.0, 2.0, 20.0];
+    /// let lagged = lag_matrix_2d(&data, MatrixLayout::ColumnMajor(2), [0, 1], 0.0, 0)
+    ///     .unwrap()
+    ///     .with_series_names(["price", "volume"])
+    ///     .unwrap();
+    ///
+    /// assert_eq!(lagged.series_names().unwrap(), ["price", "volume"]);
+    /// ```
+    pub fn with_series_names<N, I>(mut self, names: I) -> Result<Self, LagError>
+    where
+        N: Into<String>,
+        I: IntoIterator<Item = N>,
+    {
+        let names: Vec<String> = names.into_iter().map(Into::into).collect();
+        if names.len() != self.series_count {
+            return Err(LagError::InvalidSeriesNames);
+        }
+
+        self.series_names = Some(names);
+        Ok(self)
+    }
+
+    /// The names attached via [`with_series_names`](Self::with_series_names), if any.
+    #[inline(always)]
+    pub fn series_names(&self) -> Option<&[String]> {
+        self.series_names.as_deref()
+    }
+
+    /// Generates a label for every feature, e.g. `price_lag1` or `volume_lead2`.
+    ///
+    /// The labels are ordered like the columns of a column-major matrix and the rows of a
+    /// row-major matrix, i.e. by lag and then by series, followed by `static0`, `static1`
+    /// and so on for the static columns. Series without a name are called `series0`,
+    /// `series1` and so on.
+    ///
+    /// ## Example
+    /// ```
+    /// use timelag::{lag_matrix_2d, MatrixLayout};
+    ///
+    /// let data = [1.0, 10.0, 2.0, 20.0, 3.0, 30.0];
+    /// let lagged = lag_matrix_2d(&data, MatrixLayout::ColumnMajor(3), [1, -1], 0.0, 0)
+    ///     .unwrap()
+    ///     .with_series_names(["price", "volume"])
+    ///     .unwrap()
+    ///     .with_static_columns(&[1.0]);
+    ///
+    /// assert_eq!(
+    ///     lagged.column_names(),
+    ///     ["price_lag1", "volume_lag1", "price_lead1", "volume_lead1", "static0"]
+    /// );
+    /// ```
+    pub fn column_names(&self) -> Vec<String> {
+        let mut labels = Vec::with_capacity(self.features_per_step() + self.static_count);
+        for &lag in &self.lags {
+            for (series, &direction) in self.directions.iter().enumerate() {
+                let shift = shift_of(lag, direction);
+                let kind = if shift >= 0 { "lag" } else { "lead" };
+                let amount = shift.unsigned_abs();
+                labels.push(match &self.series_names {
+                    Some(names) => format!("{}_{kind}{amount}", names[series]),
+                    None => format!("series{series}_{kind}{amount}"),
+                });
+            }
+        }
+
+        labels.extend((0..self.static_count).map(|index| format!("static{index}")));
+        labels
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{lag_matrix, lag_matrix_2d_directed, LagDirection, MatrixLayout};
+
+    #[test]
+    fn test_labels_follow_directions_and_survive_transformations() {
+        let data: Vec<f64> = (0..8).map(f64::from).collect();
+        let directions = [LagDirection::Lag, LagDirection::Lead];
+        let lagged = lag_matrix_2d_directed(
+            &data,
+            MatrixLayout::RowMajor(4),
+            [0, 2],
+            &directions,
+            f64::NAN,
+            0,
+        )
+        .unwrap()
+        .with_series_names(["load", "forecast"])
+        .unwrap();
+
+        assert_eq!(
+            lagged.column_names(),
+            ["load_lag0", "forecast_lag0", "load_lag2", "forecast_lead2"]
+        );
+        assert_eq!(
+            lagged.select_lags([2]).unwrap().column_names(),
+            ["load_lag2", "forecast_lead2"]
+        );
+        assert_eq!(lagged.transpose().series_names(), lagged.series_names());
+        assert_eq!(lagged.view().column_names(), lagged.column_names());
+    }
+
+    #[test]
+    fn test_lag_columns_matches_interleaved_data() {
+        let columns = [("a", [1, 2, 3]), ("b", [10, 20, 30])];
+        let lagged = lag_columns(&columns, [0, 2], -1, 5).unwrap();
+
+        let expected = crate::lag_matrix_2d(
+            &[1, 10, 2, 20, 3, 30],
+            MatrixLayout::ColumnMajor(3),
+            [0, 2],
+            -1,
+            5,
+        )
+        .unwrap();
+        assert!(lagged.rows().eq(expected.rows()));
+        assert_eq!(
+            lagged.column_names(),
+            ["a_lag0", "b_lag0", "a_lag2", "b_lag2"]
+        );
+
+        let ragged: [(&str, &[i32]); 2] = [("a", &[1, 2]), ("b", &[1])];
+        assert_eq!(
+            lag_columns(&ragged, [0], 0, 0).unwrap_err(),
+            LagError::InvalidLength
+        );
+        let empty: [(&str, &[i32]); 0] = [];
+        assert_eq!(
+            lag_columns(&empty, [0], 0, 0).unwrap_err(),
+            LagError::EmptyData
+        );
+    }
+
+    #[test]
+    fn test_unnamed_series_and_invalid_names() {
+        let lagged = lag_matrix(&[1, 2, 3], [1, 0], 0, 0).unwrap();
+        assert_eq!(lagged.column_names(), ["series0_lag1", "series0_lag0"]);
+        assert_eq!(
+            lagged.with_series_names(["a", "b"]).unwrap_err(),
+            LagError::InvalidSeriesNames
+        );
+    }
+}
@@ -1,8 +1,29 @@
-use crate::{lag_matrix, lag_matrix_2d, LagError, LagMatrix, MatrixLayout};
+use crate::{
+    collect_lags, exceeds_length, lag_matrix, lag_matrix_2d, source_step, IntoLag, LagError,
+    LagMatrix, LagMatrixShape, MatrixLayout,
+};
 use ndarray::prelude::*;
-use ndarray::{Array1, OwnedRepr};
+use ndarray::{ArrayD, Data, IxDyn};
 
-/// Provides the [`lag_matrix`](LagMatrixFromArray::lag_matrix) function for [`Array1`] and [`Array2`] types.
+#[cfg(all(feature = "debug-verify", debug_assertions))]
+use crate::verify;
+
+/// Provides the [`lag_matrix`](LagMatrixFromArray::lag_matrix) function for one- and
+/// two-dimensional arrays of any storage, e.g. [`Array1`], [`Array2`] or views of them.
+///
+/// Views are lagged without copying them first, e.g. a window of a larger array:
+///
+/// ```
+/// use ndarray::{s, Array1};
+/// use timelag::prelude::*;
+///
+/// let data = Array1::from_iter(0..100);
+/// let window = data.slice(s![10..14]);
+/// let lagged = window.lag_matrix(0..=1, -1, 0).unwrap();
+///
+/// assert_eq!(lagged.shape(), &[2, 4]);
+/// assert_eq!(lagged.row(1).to_vec(), [-1, 10, 11, 12]);
+/// ```
 pub trait LagMatrixFromArray<A>
 where
     A: Copy,
@@ -15,18 +36,21 @@ where
     /// index) elements will be dropped with each lag. Lagged versions are prepended with the placeholder.
     ///
     /// ## Arguments
-    /// * `lags` - The number of lagged versions to create.
+    /// * `lags` - The lags to create; negative lags create leads, see [`IntoLag`](crate::IntoLag).
     /// * `fill` - The value to use to fill in lagged gaps.
     /// * `stride` - The number of elements between lagged versions in the resulting vector.
-    ///            If set to `0` or `data.len()`, no padding is introduced. Values larger than
-    ///            `data.len()` creates padding entries set to the `fill` value.
+    ///   If set to `0` or `data.len()`, no padding is introduced. Values larger than
+    ///   `data.len()` creates padding entries set to the `fill` value.
     ///
     /// ## Returns
-    /// A vector containing lagged copies of the original data, or an error.
+    /// A vector containing lagged copies of the original data, or an error, e.g.
+    /// [`LagError::InvalidMemoryLayout`] if the array is not contiguous; see
+    /// [`lag_matrix_copying`](Self::lag_matrix_copying).
     ///
     /// For `N` data points and `M` lags, the result can be interpreted as an `M×N` matrix with
-    /// lagged versions along the rows. With strides `S >= N`, the resulting matrix is of shape `M×S`
-    /// with an `M×N` submatrix at `0×0` and padding to the right.
+    /// lagged versions along the rows. The resulting array is always of shape `M×N`; with
+    /// strides `S > N`, its rows are `S` elements apart in the underlying buffer, i.e. the
+    /// padding to the right of every row is not part of the array.
     ///
     /// ## Example
     /// ```
@@ -71,43 +95,142 @@ where
     ///     ]
     /// );
     /// ```
-    fn lag_matrix<R: IntoIterator<Item = usize>>(
+    fn lag_matrix<L: IntoLag, R: IntoIterator<Item = L>>(
+        &self,
+        lags: R,
+        fill: A,
+        stride: usize,
+    ) -> Result<Array2<A>, LagError>;
+
+    /// Create a time-lagged matrix of time series values like [`lag_matrix`](Self::lag_matrix),
+    /// copying the values into a contiguous buffer first if the array is not contiguous,
+    /// e.g. a slice with a step.
+    ///
+    /// Contiguous arrays are lagged without copying them, like [`lag_matrix`](Self::lag_matrix).
+    /// Two-dimensional arrays are copied into the standard layout, i.e. with the series along
+    /// the rows.
+    ///
+    /// ## Example
+    /// ```
+    /// use ndarray::{s, Array1};
+    /// use timelag::prelude::*;
+    ///
+    /// let data = Array1::from_iter(0..10);
+    /// let every_other = data.slice(s![..;2]);
+    ///
+    /// assert!(every_other.lag_matrix(0..=1, -1, 0).is_err());
+    /// let lagged = every_other.lag_matrix_copying(0..=1, -1, 0).unwrap();
+    /// assert_eq!(lagged.row(1).to_vec(), [-1, 0, 2, 4, 6]);
+    /// ```
+    fn lag_matrix_copying<L: IntoLag, R: IntoIterator<Item = L>>(
         &self,
         lags: R,
         fill: A,
         stride: usize,
     ) -> Result<Array2<A>, LagError>;
+
+    /// Create a time-lagged matrix of time series values like [`lag_matrix`](Self::lag_matrix),
+    /// together with its shape, e.g. to determine the series and lag of every row or column
+    /// of the array; see [`LagMatrix::into_array2`].
+    ///
+    /// ## Example
+    /// ```
+    /// use ndarray::Array2;
+    /// use timelag::prelude::*;
+    ///
+    /// // Two series along the rows.
+    /// let data = Array2::from_shape_vec((2, 3), vec![1, 2, 3, 10, 20, 30]).unwrap();
+    /// let (lagged, shape) = data.lag_matrix_with_shape([0, 2], 0, 0).unwrap();
+    ///
+    /// // One row per lag and series.
+    /// assert_eq!(lagged.shape(), &[4, 3]);
+    /// assert_eq!(shape.lags(), &[0, 2]);
+    /// assert_eq!(shape.series_count(), 2);
+    /// assert_eq!(lagged.row(3).to_vec(), [0, 0, 10]);
+    /// ```
+    fn lag_matrix_with_shape<L: IntoLag, R: IntoIterator<Item = L>>(
+        &self,
+        lags: R,
+        fill: A,
+        stride: usize,
+    ) -> Result<(Array2<A>, LagMatrixShape), LagError>;
 }
 
-impl<A> LagMatrixFromArray<A> for Array1<A>
+impl<A, S> LagMatrixFromArray<A> for ArrayBase<S, Ix1>
 where
     A: Copy,
+    S: Data<Elem = A>,
 {
-    fn lag_matrix<R: IntoIterator<Item = usize>>(
+    fn lag_matrix<L: IntoLag, R: IntoIterator<Item = L>>(
+        &self,
+        lags: R,
+        fill: A,
+        stride: usize,
+    ) -> Result<Array2<A>, LagError> {
+        let (array, _) = self.lag_matrix_with_shape(lags, fill, stride)?;
+        Ok(array)
+    }
+
+    fn lag_matrix_copying<L: IntoLag, R: IntoIterator<Item = L>>(
         &self,
         lags: R,
         fill: A,
         stride: usize,
     ) -> Result<Array2<A>, LagError> {
+        self.as_standard_layout().lag_matrix(lags, fill, stride)
+    }
+
+    fn lag_matrix_with_shape<L: IntoLag, R: IntoIterator<Item = L>>(
+        &self,
+        lags: R,
+        fill: A,
+        stride: usize,
+    ) -> Result<(Array2<A>, LagMatrixShape), LagError> {
         if let Some(slice) = self.as_slice() {
             let lagged = lag_matrix(slice, lags, fill, stride)?;
-            Ok(make_array(lagged))
+            Ok(lagged.into_array2())
         } else {
             Err(LagError::InvalidMemoryLayout)
         }
     }
 }
 
-impl<A> LagMatrixFromArray<A> for Array2<A>
+impl<A, S> LagMatrixFromArray<A> for ArrayBase<S, Ix2>
 where
     A: Copy,
+    S: Data<Elem = A>,
 {
-    fn lag_matrix<R: IntoIterator<Item = usize>>(
+    fn lag_matrix<L: IntoLag, R: IntoIterator<Item = L>>(
         &self,
         lags: R,
         fill: A,
         stride: usize,
     ) -> Result<Array2<A>, LagError> {
+        let (array, _) = self.lag_matrix_with_shape(lags, fill, stride)?;
+        Ok(array)
+    }
+
+    fn lag_matrix_copying<L: IntoLag, R: IntoIterator<Item = L>>(
+        &self,
+        lags: R,
+        fill: A,
+        stride: usize,
+    ) -> Result<Array2<A>, LagError> {
+        let contiguous = self.as_slice_memory_order().is_some()
+            && (self.is_standard_layout() || self.t().is_standard_layout());
+        if contiguous {
+            self.lag_matrix(lags, fill, stride)
+        } else {
+            self.as_standard_layout().lag_matrix(lags, fill, stride)
+        }
+    }
+
+    fn lag_matrix_with_shape<L: IntoLag, R: IntoIterator<Item = L>>(
+        &self,
+        lags: R,
+        fill: A,
+        stride: usize,
+    ) -> Result<(Array2<A>, LagMatrixShape), LagError> {
         if let Some(slice) = self.as_slice_memory_order() {
             if self.is_standard_layout() {
                 let series_len = self.ncols();
@@ -119,8 +242,8 @@ where
                     stride,
                 )?;
 
-                Ok(make_array_2d_row_major(lagged))
-            } else {
+                Ok(lagged.into_array2())
+            } else if self.t().is_standard_layout() {
                 let series_len = self.nrows();
                 let lagged = lag_matrix_2d(
                     slice,
@@ -130,7 +253,9 @@ where
                     stride,
                 )?;
 
-                Ok(make_array_2d_column_major(lagged))
+                Ok(lagged.into_array2())
+            } else {
+                Err(LagError::InvalidMemoryLayout)
             }
         } else {
             Err(LagError::InvalidMemoryLayout)
@@ -138,155 +263,405 @@ where
     }
 }
 
-/// Converts a `LagMatrix` into a 2D `ArrayBase` with a layout determined by the matrix's stride.
-///
-/// This function takes a `LagMatrix` and returns a 2D array without transposing it.
-/// The output layout matches the stride and shape properties defined in the `LagMatrix`, allowing for
-/// flexible memory layouts while preserving the original structure of the data.
-///
-/// # Parameters
-///
-/// - `matrix`: The input `LagMatrix<A>` containing the data to be transformed into
-///   a 2D array. This matrix has shape and stride properties that determine the output layout.
-///
-/// # Returns
-///
-/// A 2D `ArrayBase<OwnedRepr<A>, Ix2>` reflecting the original structure of `matrix`,
-/// with either a standard row-major layout or a layout determined by custom strides.
-///
-/// # Panics
-///
-/// This function will panic if the data length in `LagMatrix` is incompatible with the expected
-/// shape and stride configuration, specifically:
-/// - If the length of `matrix.data` does not match the calculated shape
-///   `(matrix.series_length, matrix.num_lags)`.
-/// - If custom strides are applied that do not align with the length of `matrix.data`.
-///
-/// # Notes
-///
-/// - If `row_stride` is equal to `series_length`, the function uses a standard row-major layout
-///   for the data.
-/// - When `row_stride` differs from `series_length`, custom strides are applied to preserve
-///   the intended layout of `matrix`, using `(matrix.series_length, matrix.num_lags).strides((matrix.row_stride, 1))`.
-///
-/// This function is ideal when you need a straightforward conversion of `LagMatrix` to
-/// `Array2`, without reordering for row- or column-major access patterns.
-fn make_array<A>(matrix: LagMatrix<A>) -> ArrayBase<OwnedRepr<A>, Ix2> {
-    let array = if matrix.row_stride == matrix.series_length {
-        Array2::<A>::from_shape_vec((matrix.series_length, matrix.num_lags), matrix.data)
-            .expect("the shape is valid")
-    } else {
-        Array2::<A>::from_shape_vec(
-            (matrix.series_length, matrix.num_lags).strides((matrix.row_stride, 1)),
-            matrix.data,
+impl<A> LagMatrix<A> {
+    /// Converts the matrix into an array of its rows and columns without copying, together
+    /// with its shape, e.g. to determine the series and lag of every row or column.
+    ///
+    /// The array has the shape ([`num_rows`](Self::num_rows), [`num_cols`](Self::num_cols))
+    /// and skips the row padding via its strides. The shape retains the lags, the number of
+    /// series and the layout, and locates every value via [`LagMatrixShape::offsets`].
+    ///
+    /// ## Example
+    /// ```
+    /// use timelag::{lag_matrix_2d, MatrixLayout};
+    ///
+    /// let data = [
+    ///     1, 10,
+    ///     2, 20,
+    ///     3, 30,
+    /// ];
+    /// let lagged = lag_matrix_2d(&data, MatrixLayout::ColumnMajor(3), [0, 2], 0, 5).unwrap();
+    /// let (array, shape) = lagged.into_array2();
+    ///
+    /// assert_eq!(array.shape(), &[3, 4]);
+    /// assert_eq!(array.row(2).to_vec(), [3, 30, 1, 10]);
+    ///
+    /// // The second series at lag 2, located via its offset at the first time step.
+    /// let column = shape.offsets().offset_of(1, 1, 0);
+    /// assert_eq!(array.column(column).to_vec(), [0, 0, 10]);
+    /// assert_eq!(shape.lags(), &[0, 2]);
+    /// ```
+    pub fn into_array2(self) -> (Array2<A>, LagMatrixShape) {
+        let dim = (self.num_rows, self.num_cols).strides((self.row_stride, 1));
+        let (data, shape) = self.into_parts();
+        let array = Array2::from_shape_vec(dim, data).expect("the shape is valid");
+        (array, shape)
+    }
+}
+
+impl<A, S> LagMatrix<A, S>
+where
+    A: Copy,
+    S: AsRef<[A]>,
+{
+    /// Copies the lagged values into a 3D array with the axes (series, lag, time), e.g. the
+    /// input tensor of sequence models and attention-based forecasters.
+    ///
+    /// The element at `[series, lag_index, t]` holds the series at time step `t` shifted by
+    /// the lag at `lag_index` of [`lags`](Self::lags), regardless of the orientation and row
+    /// padding of the matrix. Static columns are not included.
+    ///
+    /// ## Example
+    /// ```
+    /// use timelag::{lag_matrix_2d, MatrixLayout};
+    ///
+    /// let data = [
+    ///     1, 2, 3,
+    ///     10, 20, 30,
+    /// ];
+    /// let lagged = lag_matrix_2d(&data, MatrixLayout::RowMajor(3), 0..=1, 0, 4).unwrap();
+    /// let tensor = lagged.to_array3();
+    ///
+    /// assert_eq!(tensor.shape(), &[2, 2, 3]);
+    /// assert_eq!(tensor[[1, 1, 2]], 20);
+    /// assert_eq!(tensor.as_slice().unwrap(), &[1, 2, 3, 0, 1, 2, 10, 20, 30, 0, 10, 20]);
+    /// ```
+    pub fn to_array3(&self) -> Array3<A> {
+        let data = self.data.as_ref();
+        Array3::from_shape_fn(
+            (self.series_count, self.num_lags, self.series_length),
+            |(series, lag_index, t)| data[self.offset_of(lag_index, series, t)],
         )
-        .expect("the shape is valid")
-    };
-    array
+    }
 }
 
-/// Converts a `LagMatrix` into a 2D row-major `ArrayBase` representation.
+/// Lags a batch of independent multivariate time series at once, e.g. thousands of short
+/// windows, validating the lags and allocating the result only once.
 ///
-/// This function takes a `LagMatrix` and returns a 2D array arranged in row-major order,
-/// preserving the layout of the original data for row-based access patterns.
-/// It is particularly useful when row-oriented memory layouts improve performance,
-/// such as in operations that process rows sequentially.
+/// ## Arguments
+/// * `batch` - The series with the axes (batch, series, time), in any memory layout and of
+///   any storage, e.g. an [`Array3`] or a view of one.
+/// * `lags` - The lags to create; negative lags create leads, see [`IntoLag`].
+/// * `fill` - The value to use to fill in lagged gaps.
 ///
-/// # Parameters
+/// ## Returns
+/// The lagged values with the axes (batch, series, lag, time), such that every batch entry
+/// matches [`LagMatrix::to_array3`], or an error:
+/// * [`LagError::InvalidLags`] if no lags were specified or a lag is out of range,
+/// * [`LagError::EmptyData`] if the batch is empty,
+/// * [`LagError::LagExceedsValueCount`] if a lag spans more than the series length.
 ///
-/// - `matrix`: The input `LagMatrix<A>` containing the data to be transformed into
-///   a 2D array. This matrix may have varying layouts depending on its `row_stride`.
+/// ## Example
+/// ```
+/// use ndarray::Array3;
+/// use timelag::lag_batch;
 ///
-/// # Returns
+/// // Two windows of one series each.
+/// let batch = Array3::from_shape_vec((2, 1, 3), vec![1, 2, 3, 10, 20, 30]).unwrap();
+/// let lagged = lag_batch(&batch, 0..=1, 0).unwrap();
 ///
-/// A 2D `ArrayBase<OwnedRepr<A>, Ix2>` where data is stored in row-major order.
+/// assert_eq!(lagged.shape(), &[2, 1, 2, 3]);
+/// assert_eq!(lagged.as_slice().unwrap(), &[1, 2, 3, 0, 1, 2, 10, 20, 30, 0, 10, 20]);
+/// ```
+pub fn lag_batch<A, S, L, R>(
+    batch: &ArrayBase<S, Ix3>,
+    lags: R,
+    fill: A,
+) -> Result<Array4<A>, LagError>
+where
+    A: Copy,
+    S: Data<Elem = A>,
+    L: IntoLag,
+    R: IntoIterator<Item = L>,
+{
+    let lags = collect_lags(lags)?;
+    if lags.is_empty() {
+        return Err(LagError::InvalidLags);
+    }
+    if batch.is_empty() {
+        return Err(LagError::EmptyData);
+    }
+
+    if exceeds_length(&lags, batch.dim().2) {
+        return Err(LagError::LagExceedsValueCount);
+    }
+
+    #[cfg(all(feature = "debug-verify", debug_assertions))]
+    verify_batch(batch.dim(), &lags);
+
+    Ok(lag_entries(batch, &lags, fill))
+}
+
+/// Lags every series of every batch entry; see [`lag_batch`].
+fn lag_entries<A, S>(batch: &ArrayBase<S, Ix3>, lags: &[isize], fill: A) -> Array4<A>
+where
+    A: Copy,
+    S: Data<Elem = A>,
+{
+    let (batch_size, series_count, series_length) = batch.dim();
+    Array4::from_shape_fn(
+        (batch_size, series_count, lags.len(), series_length),
+        |(entry, series, lag_index, t)| {
+            source_step(t, lags[lag_index], series_length)
+                .map_or(fill, |source| batch[[entry, series, source]])
+        },
+    )
+}
+
+/// Checks the kernel of [`lag_batch`] against the naive definition by running it on the
+/// indices of the values of a batch of the specified shape.
+#[cfg(all(feature = "debug-verify", debug_assertions))]
+fn verify_batch(dim: (usize, usize, usize), lags: &[isize]) {
+    let (batch_size, series_count, series_length) = dim;
+    let indices = verify::indices(batch_size * series_count * series_length);
+    let indices = Array3::from_shape_vec(dim, indices).expect("the shape matches the indices");
+    let lagged = lag_entries(&indices, lags, verify::GAP);
+    for ((entry, series, lag_index, t), &actual) in lagged.indexed_iter() {
+        let expected = verify::naive_source(t, lags[lag_index], series_length)
+            .map(|source| (entry * series_count + series) * series_length + source);
+        verify::check(
+            "lag_batch",
+            actual,
+            expected,
+            format_args!(
+                "lag index {lag_index} of series {series} of entry {entry} at time step {t}"
+            ),
+        );
+    }
+}
+
+/// Lags an array of any dimension along one of its axes, e.g. the time axis of a
+/// site × sensor × time cube, without reshaping it into a matrix first.
 ///
-/// # Panics
+/// ## Arguments
+/// * `array` - The array, in any memory layout and of any storage and dimension, e.g. an
+///   [`ArrayD`], an [`Array3`] or a view of one.
+/// * `axis` - The axis to lag along.
+/// * `lags` - The lags to create; negative lags create leads, see [`IntoLag`].
+/// * `fill` - The value to use to fill in lagged gaps.
 ///
-/// This function will panic if the underlying vector in `LagMatrix` does not match
-/// the expected shape or layout, specifically:
-/// - If the length of the data does not match the calculated shape
-///   `(matrix.series_length, matrix.series_count * matrix.num_lags)`.
-/// - If the specified strides are incompatible with the data's length.
+/// ## Returns
+/// The lagged values with a new lag axis inserted before the lagged axis, e.g. the axes
+/// (site, sensor, lag, time) for a cube lagged along its last axis, or an error:
+/// * [`LagError::InvalidAxis`] if the axis does not exist,
+/// * [`LagError::InvalidLags`] if no lags were specified or a lag is out of range,
+/// * [`LagError::EmptyData`] if the array is empty,
+/// * [`LagError::LagExceedsValueCount`] if a lag spans more than the length of the axis.
 ///
-/// # Notes
+/// ## Example
+/// ```
+/// use ndarray::{ArrayD, Axis, IxDyn};
+/// use timelag::lag_along_axis;
 ///
-/// - If the `row_stride` of the `LagMatrix` is equal to `series_length`, the matrix
-///   is reshaped directly into the target layout.
-/// - If `row_stride` differs from `series_length`, custom strides are applied during
-///   reshaping to accurately reflect the row-based structure of the data.
-fn make_array_2d_row_major<A>(matrix: LagMatrix<A>) -> ArrayBase<OwnedRepr<A>, Ix2> {
-    let array = if matrix.row_stride == matrix.series_length {
-        Array2::<A>::from_shape_vec(
-            (matrix.series_length, matrix.series_count * matrix.num_lags),
-            matrix.into_vec(),
-        )
-        .expect("the shape is valid")
-    } else {
-        Array2::<A>::from_shape_vec(
-            (matrix.series_count * matrix.num_lags, matrix.series_length)
-                .strides((matrix.row_stride, 1)),
-            matrix.into_vec(),
-        )
-        .expect("the shape is valid")
-    };
-    array
+/// // Two sites with one sensor each, over three time steps.
+/// let cube = ArrayD::from_shape_vec(IxDyn(&[2, 1, 3]), vec![1, 2, 3, 10, 20, 30]).unwrap();
+/// let lagged = lag_along_axis(&cube, Axis(2), 0..=1, 0).unwrap();
+///
+/// assert_eq!(lagged.shape(), &[2, 1, 2, 3]);
+/// assert_eq!(lagged[[1, 0, 1, 2]], 20);
+/// ```
+pub fn lag_along_axis<A, S, D, L, R>(
+    array: &ArrayBase<S, D>,
+    axis: Axis,
+    lags: R,
+    fill: A,
+) -> Result<ArrayD<A>, LagError>
+where
+    A: Copy,
+    S: Data<Elem = A>,
+    D: Dimension,
+    L: IntoLag,
+    R: IntoIterator<Item = L>,
+{
+    let array = array.view().into_dyn();
+    let Axis(axis) = axis;
+    if axis >= array.ndim() {
+        return Err(LagError::InvalidAxis);
+    }
+    let lags = collect_lags(lags)?;
+    if lags.is_empty() {
+        return Err(LagError::InvalidLags);
+    }
+    if array.is_empty() {
+        return Err(LagError::EmptyData);
+    }
+
+    if exceeds_length(&lags, array.len_of(Axis(axis))) {
+        return Err(LagError::LagExceedsValueCount);
+    }
+
+    #[cfg(all(feature = "debug-verify", debug_assertions))]
+    verify_axis(array.shape(), axis, &lags);
+
+    Ok(lag_axis(&array, axis, &lags, fill))
 }
 
-/// Converts a `LagMatrix` into a 2D column-major `ArrayBase` representation.
-///
-/// This function takes a `LagMatrix` and returns a 2D array in column-major order,
-/// where the rows and columns of the original matrix are effectively transposed
-/// to prioritize column-oriented operations. This is particularly useful for
-/// performance optimizations in applications where column-major memory layouts
-/// are advantageous.
-///
-/// # Parameters
+/// Lags the array along the axis; see [`lag_along_axis`].
+fn lag_axis<A: Copy>(array: &ArrayViewD<'_, A>, axis: usize, lags: &[isize], fill: A) -> ArrayD<A> {
+    let length = array.len_of(Axis(axis));
+    let mut shape = array.shape().to_vec();
+    shape.insert(axis, lags.len());
+    let mut source = vec![0; array.ndim()];
+    ArrayD::from_shape_fn(IxDyn(&shape), |index| {
+        for (dim, position) in source.iter_mut().enumerate() {
+            *position = if dim < axis {
+                index[dim]
+            } else {
+                index[dim + 1]
+            };
+        }
+        match source_step(source[axis], lags[index[axis]], length) {
+            Some(step) => {
+                source[axis] = step;
+                array[source.as_slice()]
+            }
+            None => fill,
+        }
+    })
+}
+
+/// Checks the kernel of [`lag_along_axis`] against the naive definition by running it on
+/// the indices of the values of an array of the specified shape.
+#[cfg(all(feature = "debug-verify", debug_assertions))]
+fn verify_axis(shape: &[usize], axis: usize, lags: &[isize]) {
+    let indices = verify::indices(shape.iter().product());
+    let indices = ArrayD::from_shape_vec(shape, indices).expect("the shape matches the indices");
+    let lagged = lag_axis(&indices.view(), axis, lags, verify::GAP);
+    for (index, &actual) in lagged.indexed_iter() {
+        let index = index.slice();
+        let mut source: Vec<usize> = index
+            .iter()
+            .enumerate()
+            .filter_map(|(dim, &position)| (dim != axis).then_some(position))
+            .collect();
+        let expected =
+            verify::naive_source(source[axis], lags[index[axis]], shape[axis]).map(|step| {
+                source[axis] = step;
+                let linear = |linear, (&position, &len)| linear * len + position;
+                source.iter().zip(shape).fold(0, linear)
+            });
+        verify::check(
+            "lag_along_axis",
+            actual,
+            expected,
+            format_args!("element {index:?}"),
+        );
+    }
+}
+
+/// Writes the time-lagged matrix of a series into an existing array, e.g. a block of a
+/// larger design matrix, without allocating.
 ///
-/// - `matrix`: The input `LagMatrix<A>` containing the data to be transformed into
-///   a 2D array. The layout of the matrix may vary depending on its `row_stride`.
+/// The output holds one row per lag like [`LagMatrixFromArray::lag_matrix`], i.e. has the
+/// shape (lags, time steps); pass a transposed view to write one row per time step instead.
 ///
-/// # Returns
+/// ## Arguments
+/// * `series` - The series to lag, in any memory layout.
+/// * `lags` - The lags to create; negative lags create leads, see [`IntoLag`].
+/// * `fill` - The value to use to fill in lagged gaps.
+/// * `out` - The array to write to.
 ///
-/// A 2D `ArrayBase<OwnedRepr<A>, Ix2>` where the data is arranged in column-major order.
+/// ## Returns
+/// `Ok(())` if the matrix was written, or an error, leaving `out` unchanged:
+/// * [`LagError::InvalidLags`] if no lags were specified or a lag is out of range,
+/// * [`LagError::EmptyData`] if the series is empty,
+/// * [`LagError::LagExceedsValueCount`] if a lag spans more than the series length,
+/// * [`LagError::InvalidLength`] if the shape of `out` does not match.
 ///
-/// # Panics
+/// ## Example
+/// ```
+/// use ndarray::{s, Array1, Array2};
+/// use timelag::lag_matrix_into;
 ///
-/// This function will panic if the underlying vector in `LagMatrix` does not match
-/// the shape constraints. Specifically:
-/// - If the length of the data does not align with the calculated shape of
-///   `(matrix.series_count * matrix.num_lags, matrix.series_length)`.
-/// - If the specified strides do not match the data's length.
+/// let series = Array1::from_vec(vec![1.0, 2.0, 3.0]);
 ///
-/// # Notes
+/// // One row per time step, with the two lags in columns 1 and 2.
+/// let mut design = Array2::<f64>::ones((3, 3));
+/// let block = design.slice_mut(s![.., 1..3]).reversed_axes();
+/// lag_matrix_into(&series, 1..=2, 0.0, block).unwrap();
 ///
-/// - If the `row_stride` of the `LagMatrix` matches `series_length`, a straightforward
-///   conversion is performed, followed by a transpose to achieve the column-major layout.
-/// - If the `row_stride` differs from `series_length`, custom strides are used
-///   during reshaping to correctly interpret the data layout before transposition.
-fn make_array_2d_column_major<A>(matrix: LagMatrix<A>) -> ArrayBase<OwnedRepr<A>, Ix2> {
-    let array = if matrix.row_stride == matrix.series_length {
-        Array2::<A>::from_shape_vec(
-            (matrix.series_count * matrix.num_lags, matrix.series_length),
-            matrix.into_vec(),
-        )
-        .expect("the shape is valid")
-        .reversed_axes()
-    } else {
-        Array2::<A>::from_shape_vec(
-            (matrix.series_count * matrix.num_lags, matrix.series_length)
-                .strides((1, matrix.row_stride)),
-            matrix.into_vec(),
-        )
-        .expect("the shape is valid")
-        .reversed_axes()
-    };
-    array
+/// assert_eq!(design.as_slice().unwrap(), &[
+///     1.0, 0.0, 0.0,
+///     1.0, 1.0, 0.0,
+///     1.0, 2.0, 1.0,
+/// ]);
+/// ```
+pub fn lag_matrix_into<A, S, L, R>(
+    series: &ArrayBase<S, Ix1>,
+    lags: R,
+    fill: A,
+    out: ArrayViewMut2<'_, A>,
+) -> Result<(), LagError>
+where
+    A: Copy,
+    S: Data<Elem = A>,
+    L: IntoLag,
+    R: IntoIterator<Item = L>,
+{
+    let lags = collect_lags(lags)?;
+    if lags.is_empty() {
+        return Err(LagError::InvalidLags);
+    }
+    if series.is_empty() {
+        return Err(LagError::EmptyData);
+    }
+
+    let series_length = series.len();
+    if exceeds_length(&lags, series_length) {
+        return Err(LagError::LagExceedsValueCount);
+    }
+    if out.dim() != (lags.len(), series_length) {
+        return Err(LagError::InvalidLength);
+    }
+
+    write_rows(series, &lags, fill, out);
+
+    #[cfg(all(feature = "debug-verify", debug_assertions))]
+    verify_rows(series_length, &lags);
+
+    Ok(())
+}
+
+/// Writes one lagged version of the series per row of `out`; see [`lag_matrix_into`].
+fn write_rows<A, S>(
+    series: &ArrayBase<S, Ix1>,
+    lags: &[isize],
+    fill: A,
+    mut out: ArrayViewMut2<'_, A>,
+) where
+    A: Copy,
+    S: Data<Elem = A>,
+{
+    let series_length = series.len();
+    for (mut row, &lag) in out.rows_mut().into_iter().zip(lags) {
+        for (t, value) in row.iter_mut().enumerate() {
+            *value = source_step(t, lag, series_length).map_or(fill, |source| series[source]);
+        }
+    }
+}
+
+/// Checks the kernel of [`lag_matrix_into`] against the naive definition by running it on
+/// the indices of the values of a series of the specified length.
+#[cfg(all(feature = "debug-verify", debug_assertions))]
+fn verify_rows(series_length: usize, lags: &[isize]) {
+    let series = Array1::from_vec(verify::indices(series_length));
+    let mut lagged = Array2::from_elem((lags.len(), series_length), verify::GAP);
+    write_rows(&series, lags, verify::GAP, lagged.view_mut());
+    for ((lag_index, t), &actual) in lagged.indexed_iter() {
+        let lag = lags[lag_index];
+        verify::check(
+            "lag_matrix_into",
+            actual,
+            verify::naive_source(t, lag, series_length),
+            format_args!("lag {lag} at time step {t}"),
+        );
+    }
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
+    use ndarray::Slice;
 
     #[test]
     #[rustfmt::skip]
@@ -362,6 +737,167 @@ mod tests {
         );
     }
 
+    #[test]
+    fn test_views_match_owned_arrays() {
+        let data = Array1::from_iter(0..20);
+        let window = data.slice_axis(Axis(0), Slice::from(5..11));
+        assert_eq!(
+            window.lag_matrix([0, 2, -1], -1, 0),
+            window.to_owned().lag_matrix([0, 2, -1], -1, 0)
+        );
+        let lagged = window.lag_matrix([0, 2, -1], -1, 7).unwrap();
+        assert_eq!(lagged.shape(), &[3, 6]);
+        assert_eq!(lagged.row(1).to_vec(), [-1, -1, 5, 6, 7, 8]);
+
+        let matrix = Array2::from_shape_fn((4, 5), |(s, t)| (10 * s + t) as i32);
+        let rows = matrix.slice_axis(Axis(0), Slice::from(1..3));
+        let lagged = rows.lag_matrix(0..=2, -1, 0).unwrap();
+        assert_eq!(lagged.shape(), &[6, 5]);
+        assert_eq!(lagged.row(3).to_vec(), [-1, 20, 21, 22, 23]);
+
+        let owned = Array2::from_shape_vec((5, 4).f(), matrix.as_slice().unwrap().to_vec());
+        assert_eq!(
+            matrix.t().lag_matrix(0..=2, -1, 0),
+            owned.unwrap().lag_matrix(0..=2, -1, 0)
+        );
+        assert_eq!(
+            matrix
+                .slice_axis(Axis(1), Slice::new(0, None, -1))
+                .lag_matrix([1], 0, 0),
+            Err(LagError::InvalidMemoryLayout)
+        );
+    }
+
+    #[test]
+    fn test_copying_gathers_strided_views() {
+        let data = Array1::from_iter(0..12);
+        let strided = data.slice_axis(Axis(0), Slice::new(1, None, 3));
+        assert_eq!(
+            strided.lag_matrix_copying([0, 1], -1, 0),
+            strided.to_owned().lag_matrix([0, 1], -1, 0)
+        );
+
+        let matrix = Array2::from_shape_fn((4, 6), |(s, t)| (10 * s + t) as i32);
+        let reversed = matrix.slice_axis(Axis(1), Slice::new(0, None, -1));
+        let lagged = reversed.lag_matrix_copying([0, 2], -1, 0).unwrap();
+        let expected = Array2::from_shape_fn((4, 6), |(s, t)| (10 * s + 5 - t) as i32);
+        assert_eq!(lagged, expected.lag_matrix([0, 2], -1, 0).unwrap());
+        assert_eq!(lagged.row(5).to_vec(), [-1, -1, 15, 14, 13, 12]);
+
+        let columns = matrix.t();
+        assert_eq!(
+            columns.lag_matrix_copying([1], -1, 0),
+            columns.lag_matrix([1], -1, 0)
+        );
+    }
+
+    #[test]
+    fn test_into_matches_lag_matrix() {
+        let series = Array1::from_iter(0..12);
+        let strided = series.slice_axis(Axis(0), Slice::new(0, None, 2));
+        let mut out = Array2::zeros((3, 6));
+        lag_matrix_into(&strided, [0, 2, -1], -1, out.view_mut()).unwrap();
+        assert_eq!(out, strided.lag_matrix_copying([0, 2, -1], -1, 0).unwrap());
+
+        let mut transposed = Array2::zeros((6, 3));
+        lag_matrix_into(
+            &strided,
+            [0, 2, -1],
+            -1,
+            transposed.view_mut().reversed_axes(),
+        )
+        .unwrap();
+        assert_eq!(transposed.t(), out);
+
+        assert_eq!(
+            lag_matrix_into(&strided, [0, 2], -1, out.view_mut()),
+            Err(LagError::InvalidLength)
+        );
+        assert_eq!(
+            lag_matrix_into(&strided, [7], -1, out.view_mut()),
+            Err(LagError::LagExceedsValueCount)
+        );
+    }
+
+    #[test]
+    fn test_into_array2_matches_get() {
+        let data = [1, 10, 2, 20, 3, 30, 4, 40];
+        for layout in [MatrixLayout::ColumnMajor(4), MatrixLayout::RowMajor(4)] {
+            let lagged = lag_matrix_2d(&data, layout, [0, 2, -1], -1, 9).unwrap();
+            let (array, shape) = lagged.clone().into_array2();
+            assert_eq!(array.dim(), (lagged.num_rows(), lagged.num_cols()));
+            for ((row, col), &value) in array.indexed_iter() {
+                assert_eq!(lagged.get(row, col), Some(&value));
+            }
+            assert_eq!(shape, lagged.into_parts().1);
+        }
+    }
+
+    #[test]
+    fn test_array3_ignores_orientation() {
+        let data = [1, 10, 2, 20, 3, 30, 4, 40];
+        let column_major = lag_matrix_2d(&data, MatrixLayout::ColumnMajor(4), [0, 2, -1], -1, 7);
+        let tensor = column_major.unwrap().to_array3();
+        assert_eq!(tensor.shape(), &[2, 3, 4]);
+        // Indexed rather than sliced, as `s!` allows the unsafe code this crate forbids.
+        let series = tensor.index_axis(Axis(0), 1);
+        assert_eq!(series.index_axis(Axis(1), 2).to_vec(), [30, 10, 40]);
+
+        let transposed = lag_matrix_2d(&data, MatrixLayout::ColumnMajor(4), [0, 2, -1], -1, 0)
+            .unwrap()
+            .transpose();
+        assert_eq!(transposed.to_array3(), tensor);
+    }
+
+    #[test]
+    fn test_batch_matches_per_entry_matrices() {
+        let batch = Array3::from_shape_fn((3, 2, 5), |(b, s, t)| (100 * b + 10 * s + t) as i32);
+        let fortran = batch.t().as_standard_layout().reversed_axes().to_owned();
+        for input in [batch.view(), fortran.view()] {
+            let lagged = lag_batch(&input, [0, 2, -1], -1).unwrap();
+            for entry in 0..3 {
+                let series = batch.index_axis(Axis(0), entry).to_owned();
+                let layout = MatrixLayout::RowMajor(5);
+                let expected = lag_matrix_2d(series.as_slice().unwrap(), layout, [0, 2, -1], -1, 0);
+                assert_eq!(
+                    lagged.index_axis(Axis(0), entry),
+                    expected.unwrap().to_array3()
+                );
+            }
+        }
+
+        let empty = Array3::<i32>::zeros((0, 2, 5));
+        assert_eq!(lag_batch(&empty, [1], 0), Err(LagError::EmptyData));
+        assert_eq!(
+            lag_batch(&batch, [6], 0),
+            Err(LagError::LagExceedsValueCount)
+        );
+    }
+
+    #[test]
+    fn test_lag_along_axis_matches_lag_batch() {
+        let cube = Array3::from_shape_fn((2, 3, 4), |(a, b, c)| (100 * a + 10 * b + c) as i32);
+        let lagged = lag_along_axis(&cube.clone().into_dyn(), Axis(2), [0, 1, -2], -1).unwrap();
+        assert_eq!(lagged, lag_batch(&cube, [0, 1, -2], -1).unwrap().into_dyn());
+
+        // Lagging along the first axis inserts the lag axis in front; views of arrays of
+        // static dimension are lagged as well.
+        let lagged = lag_along_axis(&cube.view(), Axis(0), [1], -1).unwrap();
+        assert_eq!(lagged.shape(), &[1, 2, 3, 4]);
+        assert_eq!(lagged[[0, 0, 2, 1]], -1);
+        assert_eq!(lagged[[0, 1, 2, 1]], 21);
+
+        let array = cube.into_dyn();
+        assert_eq!(
+            lag_along_axis(&array, Axis(3), [1], 0),
+            Err(LagError::InvalidAxis)
+        );
+        assert_eq!(
+            lag_along_axis(&array, Axis(0), [3], 0),
+            Err(LagError::LagExceedsValueCount)
+        );
+    }
+
     #[test]
     #[rustfmt::skip]
     fn test_lag_2d_columnwise() {
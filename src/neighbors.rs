@@ -0,0 +1,275 @@
+use crate::{LagError, LagMatrix};
+use core::cmp::Ordering;
+use num_traits::Float;
+
+/// A distance between two rows of values, e.g. time steps of a [`LagMatrix`] and a query;
+/// see [`knn_lag_query`].
+///
+/// Implement this trait to search with a domain-specific distance; the built-in distances
+/// are provided by [`Metric`].
+///
+/// ## Example
+/// ```
+/// use timelag::{knn_lag_query, lag_matrix, DistanceMetric};
+///
+/// /// The largest absolute difference.
+/// struct Chebyshev;
+///
+/// impl DistanceMetric<f64> for Chebyshev {
+///     fn distance(&self, a: &[f64], b: &[f64]) -> f64 {
+///         a.iter().zip(b).map(|(a, b)| (a - b).abs()).fold(0.0, f64::max)
+///     }
+/// }
+///
+/// let matrix = lag_matrix(&[1.0, 2.0, 4.0], [0, 1], f64::NAN, 0).unwrap();
+/// let neighbors = knn_lag_query(&matrix, &[4.0, 3.0], 1, Chebyshev).unwrap();
+/// assert_eq!(neighbors[0].index, 2);
+/// assert_eq!(neighbors[0].distance, 1.0);
+/// ```
+pub trait DistanceMetric<T: Float> {
+    /// Computes the distance between two rows of equal length.
+    fn distance(&self, a: &[T], b: &[T]) -> T;
+
+    /// Extrapolates a distance computed over a fraction of the cells, `coverage` in `(0, 1]`,
+    /// to the full row, e.g. when padding cells are ignored.
+    ///
+    /// The default scales the distance linearly, as appropriate for sums of differences.
+    fn extrapolate(&self, distance: T, coverage: T) -> T {
+        distance / coverage
+    }
+}
+
+/// The built-in distance metrics used to compare time steps of a [`LagMatrix`].
+#[derive(Debug, Copy, Clone, Eq, PartialEq, Default)]
+pub enum Metric {
+    /// The Euclidean (L2) distance.
+    #[default]
+    Euclidean,
+    /// The Manhattan (L1) distance.
+    Manhattan,
+    /// The cosine distance, i.e. one minus the cosine similarity; `1` if either row is zero.
+    Cosine,
+}
+
+impl<T: Float> DistanceMetric<T> for Metric {
+    fn distance(&self, a: &[T], b: &[T]) -> T {
+        let pairs = a.iter().zip(b);
+        match self {
+            Metric::Euclidean => pairs
+                .fold(T::zero(), |sum, (&a, &b)| sum + (a - b) * (a - b))
+                .sqrt(),
+            Metric::Manhattan => pairs.fold(T::zero(), |sum, (&a, &b)| sum + (a - b).abs()),
+            Metric::Cosine => {
+                let (dot, norm_a, norm_b) = pairs.fold(
+                    (T::zero(), T::zero(), T::zero()),
+                    |(dot, na, nb), (&a, &b)| (dot + a * b, na + a * a, nb + b * b),
+                );
+                let norm = (norm_a * norm_b).sqrt();
+                if norm == T::zero() {
+                    T::one()
+                } else {
+                    T::one() - dot / norm
+                }
+            }
+        }
+    }
+
+    /// Scales the squared Euclidean and the Manhattan distance linearly; the cosine distance
+    /// is independent of the number of cells.
+    fn extrapolate(&self, distance: T, coverage: T) -> T {
+        match self {
+            Metric::Euclidean => distance / coverage.sqrt(),
+            Metric::Manhattan => distance / coverage,
+            Metric::Cosine => distance,
+        }
+    }
+}
+
+/// A time step of a [`LagMatrix`] found by [`knn_lag_query`].
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub struct Neighbor<T> {
+    /// The time step, i.e. the index of the observation at lag zero.
+    pub index: usize,
+    /// The distance of the time step to the query.
+    pub distance: T,
+}
+
+/// Finds the `k` time steps of a lag matrix nearest to a query.
+///
+/// Every time step of the matrix is treated as one row of values, holding the values of all
+/// series for the first lag, followed by the values of all series for the second lag and so on.
+/// Padding cells are ignored when computing distances; to keep time steps with fewer valid
+/// cells comparable, the distance is scaled up by the ratio of total to valid cells, similar to
+/// scikit-learn's `nan_euclidean_distances`. Time steps without any valid cell are skipped.
+///
+/// ## Arguments
+/// * `matrix` - The lag matrix to search.
+/// * `query_row` - The query, with one value per lag and series.
+/// * `k` - The maximum number of neighbors to return.
+/// * `metric` - The distance metric, e.g. a [`Metric`] or a custom [`DistanceMetric`].
+///
+/// ## Returns
+/// The nearest time steps in order of increasing distance, or [`LagError::InvalidLength`]
+/// if the query length does not match the number of values per time step.
+///
+/// ## Example
+/// ```
+/// use timelag::{knn_lag_query, lag_matrix, Metric};
+///
+/// let data = [1.0, 2.0, 3.0, 1.0, 2.0, 3.0];
+/// let matrix = lag_matrix(&data, [0, 1], f64::NAN, 0).unwrap();
+///
+/// // Find the time steps most similar to "2.0 followed by 3.0".
+/// let neighbors = knn_lag_query(&matrix, &[3.0, 2.0], 2, Metric::Euclidean).unwrap();
+/// assert_eq!(neighbors[0].index, 2);
+/// assert_eq!(neighbors[1].index, 5);
+/// assert_eq!(neighbors[0].distance, 0.0);
+/// ```
+pub fn knn_lag_query<T, M>(
+    matrix: &LagMatrix<T>,
+    query_row: &[T],
+    k: usize,
+    metric: M,
+) -> Result<Vec<Neighbor<T>>, LagError>
+where
+    T: Float,
+    M: DistanceMetric<T>,
+{
+    if query_row.len() != matrix.features_per_step() {
+        return Err(LagError::InvalidLength);
+    }
+
+    let mut values = Vec::with_capacity(query_row.len());
+    let mut query = Vec::with_capacity(query_row.len());
+    let mut neighbors: Vec<_> = (0..matrix.series_length())
+        .filter_map(|t| {
+            step_distance(matrix, query_row, t, &metric, &mut values, &mut query)
+                .map(|distance| Neighbor { index: t, distance })
+        })
+        .collect();
+
+    neighbors.sort_by(|a, b| {
+        a.distance
+            .partial_cmp(&b.distance)
+            .unwrap_or(Ordering::Equal)
+            .then(a.index.cmp(&b.index))
+    });
+    neighbors.truncate(k);
+    Ok(neighbors)
+}
+
+/// Computes the distance between time step `t` and the query, ignoring padding cells.
+///
+/// The valid cells of the time step and the query are collected into the buffers.
+fn step_distance<T, M>(
+    matrix: &LagMatrix<T>,
+    query_row: &[T],
+    t: usize,
+    metric: &M,
+    values: &mut Vec<T>,
+    query: &mut Vec<T>,
+) -> Option<T>
+where
+    T: Float,
+    M: DistanceMetric<T>,
+{
+    values.clear();
+    query.clear();
+    for lag_index in 0..matrix.num_lags() {
+        for series in 0..matrix.series_count() {
+            if matrix.is_padding(lag_index, series, t) {
+                continue;
+            }
+
+            values.push(matrix[matrix.offset_of(lag_index, series, t)]);
+            query.push(query_row[lag_index * matrix.series_count() + series]);
+        }
+    }
+
+    if values.is_empty() {
+        return None;
+    }
+
+    let distance = metric.distance(values, query);
+    if values.len() == query_row.len() {
+        return Some(distance);
+    }
+
+    let coverage = T::from(values.len())? / T::from(query_row.len())?;
+    Some(metric.extrapolate(distance, coverage))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{lag_matrix_2d, MatrixLayout};
+
+    #[test]
+    fn test_knn_manhattan_ignores_padding() {
+        let data = [0.0, 10.0, 1.0, 4.0];
+        let matrix = crate::lag_matrix(&data, [0, 2], f64::INFINITY, 0).unwrap();
+
+        let neighbors = knn_lag_query(&matrix, &[1.0, 1.0], 4, Metric::Manhattan).unwrap();
+        let indices: Vec<_> = neighbors.iter().map(|n| n.index).collect();
+
+        // Time steps 0 and 1 are compared using lag zero only, scaled up by two.
+        assert_eq!(indices, vec![2, 0, 3, 1]);
+        assert_eq!(neighbors[0].distance, 1.0);
+        assert_eq!(neighbors[1].distance, 2.0);
+        assert_eq!(neighbors[2].distance, 12.0);
+        assert_eq!(neighbors[3].distance, 18.0);
+    }
+
+    #[test]
+    #[rustfmt::skip]
+    fn test_knn_layouts_agree() {
+        let rows = [
+            1.0, 2.0, 3.0, 4.0,
+            -1.0, -2.0, -3.0, -4.0,
+        ];
+        let columns = [
+            1.0, -1.0,
+            2.0, -2.0,
+            3.0, -3.0,
+            4.0, -4.0,
+        ];
+
+        let lag = f64::INFINITY;
+        let row_major = lag_matrix_2d(&rows, MatrixLayout::RowMajor(4), [0, 1], lag, 6).unwrap();
+        let column_major = lag_matrix_2d(&columns, MatrixLayout::ColumnMajor(4), [0, 1], lag, 0).unwrap();
+
+        let query = [3.0, -3.0, 2.0, -2.0];
+        let a = knn_lag_query(&row_major, &query, 2, Metric::Euclidean).unwrap();
+        let b = knn_lag_query(&column_major, &query, 2, Metric::Euclidean).unwrap();
+
+        assert_eq!(a, b);
+        assert_eq!(a[0].index, 2);
+        assert_eq!(a[0].distance, 0.0);
+    }
+
+    #[test]
+    fn test_knn_invalid_query() {
+        let matrix = crate::lag_matrix(&[1.0, 2.0], [0, 1], 0.0, 0).unwrap();
+        assert_eq!(
+            knn_lag_query(&matrix, &[1.0], 1, Metric::Euclidean).unwrap_err(),
+            LagError::InvalidLength
+        );
+    }
+
+    #[test]
+    fn test_cosine_ignores_magnitude() {
+        let data = [1.0, 2.0, 10.0, 20.0, 0.0];
+        let matrix = crate::lag_matrix(&data, [0, 1], f64::NAN, 0).unwrap();
+
+        let neighbors = knn_lag_query(&matrix, &[4.0, 2.0], 5, Metric::Cosine).unwrap();
+        let indices: Vec<_> = neighbors.iter().map(|n| n.index).collect();
+
+        // Steps 1 and 3 are parallel to the query; step 0 is compared using lag zero only.
+        assert_eq!(indices, vec![0, 1, 3, 2, 4]);
+        assert!(neighbors[1].distance.abs() < 1e-12);
+        assert!((neighbors[4].distance - (1.0 - 1.0 / 5f64.sqrt())).abs() < 1e-12);
+
+        let zero: f64 = Metric::Cosine.distance(&[0.0, 0.0], &[1.0, 2.0]);
+        assert_eq!(zero, 1.0);
+    }
+}
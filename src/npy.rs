@@ -0,0 +1,363 @@
+use crate::LagMatrix;
+use std::io::{Error, ErrorKind, Read, Write};
+
+/// The magic string at the start of every NPY file.
+const MAGIC: &[u8] = b"\x93NUMPY";
+
+/// An element type that can be stored in NumPy's NPY format.
+///
+/// Values are always written in little-endian byte order and can be read in either
+/// byte order, such that files are portable between platforms.
+///
+/// The trait is sealed and implemented for the primitive integer and floating-point types,
+/// for which every bit pattern is a valid value.
+pub trait NpyElement: Copy + sealed::Sealed {
+    /// The type code and size of the NumPy dtype, e.g. `f8` for `f64`.
+    const DTYPE: &'static str;
+
+    /// The size of a value in bytes.
+    const SIZE: usize;
+
+    /// Appends the little-endian bytes of the value.
+    fn extend_le_bytes(self, bytes: &mut Vec<u8>);
+
+    /// Reads a value from exactly [`SIZE`](Self::SIZE) bytes of the specified byte order.
+    fn from_bytes(bytes: &[u8], big_endian: bool) -> Self;
+}
+
+mod sealed {
+    /// Restricts [`NpyElement`](super::NpyElement) to the types of this crate.
+    pub trait Sealed {}
+}
+
+macro_rules! impl_npy_element {
+    ($($type:ty => $dtype:literal),*) => {
+        $(
+            impl sealed::Sealed for $type {}
+
+            impl NpyElement for $type {
+                const DTYPE: &'static str = $dtype;
+                const SIZE: usize = size_of::<$type>();
+
+                #[inline(always)]
+                fn extend_le_bytes(self, bytes: &mut Vec<u8>) {
+                    bytes.extend_from_slice(&self.to_le_bytes());
+                }
+
+                #[inline(always)]
+                fn from_bytes(bytes: &[u8], big_endian: bool) -> Self {
+                    let bytes = bytes.try_into().expect("the slice has the size of the type");
+                    if big_endian {
+                        <$type>::from_be_bytes(bytes)
+                    } else {
+                        <$type>::from_le_bytes(bytes)
+                    }
+                }
+            }
+        )*
+    };
+}
+
+impl_npy_element!(
+    f32 => "f4", f64 => "f8",
+    i8 => "i1", i16 => "i2", i32 => "i4", i64 => "i8",
+    u8 => "u1", u16 => "u2", u32 => "u4", u64 => "u8"
+);
+
+/// A two-dimensional array read from an NPY file; see [`read_npy`].
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct NpyMatrix<T> {
+    num_rows: usize,
+    num_cols: usize,
+    data: Vec<T>,
+}
+
+impl<T> NpyMatrix<T> {
+    /// The number of rows of the array.
+    #[inline(always)]
+    pub const fn num_rows(&self) -> usize {
+        self.num_rows
+    }
+
+    /// The number of columns of the array.
+    #[inline(always)]
+    pub const fn num_cols(&self) -> usize {
+        self.num_cols
+    }
+
+    /// The values of the array in row-major order.
+    #[inline(always)]
+    pub fn data(&self) -> &[T] {
+        &self.data
+    }
+
+    /// Converts the array into its values in row-major order.
+    #[inline(always)]
+    pub fn into_vec(self) -> Vec<T> {
+        self.data
+    }
+}
+
+impl<T, S> LagMatrix<T, S>
+where
+    T: NpyElement,
+    S: AsRef<[T]>,
+{
+    /// Writes the logical region of the matrix in NumPy's NPY format (version 1.0).
+    ///
+    /// The array has the shape `(num_rows, num_cols)` in C order; row padding is omitted.
+    /// Values are written in little-endian byte order on every platform, e.g. `<f8` for `f64`,
+    /// so that the file loads correctly via `numpy.load` on any architecture.
+    ///
+    /// ## Example
+    /// ```
+    /// use timelag::{lag_matrix, read_npy};
+    ///
+    /// let lagged = lag_matrix(&[1.0, 2.0, 3.0], 0..=1, 0.0, 4).unwrap();
+    ///
+    /// let mut file = Vec::new();
+    /// lagged.write_npy(&mut file).unwrap();
+    ///
+    /// let array = read_npy::<f64, _>(file.as_slice()).unwrap();
+    /// assert_eq!(array.num_rows(), 2);
+    /// assert_eq!(array.data(), &[1.0, 2.0, 3.0, 0.0, 1.0, 2.0]);
+    /// ```
+    pub fn write_npy<W: Write>(&self, mut writer: W) -> std::io::Result<()> {
+        let order = if T::SIZE == 1 { '|' } else { '<' };
+        let mut header = format!(
+            "{{'descr': '{order}{}', 'fortran_order': False, 'shape': ({}, {}), }}",
+            T::DTYPE,
+            self.num_rows,
+            self.num_cols
+        );
+
+        // The data starts at a multiple of 64 bytes, and the header ends with a newline.
+        let unpadded = MAGIC.len() + 4 + header.len() + 1;
+        header.extend(core::iter::repeat_n(
+            ' ',
+            unpadded.next_multiple_of(64) - unpadded,
+        ));
+        header.push('\n');
+
+        let header_len = u16::try_from(header.len())
+            .map_err(|_| Error::new(ErrorKind::InvalidInput, "The NPY header is too long"))?;
+
+        let mut bytes = Vec::with_capacity(MAGIC.len() + 4 + header.len());
+        bytes.extend_from_slice(MAGIC);
+        bytes.extend_from_slice(&[1, 0]);
+        bytes.extend_from_slice(&header_len.to_le_bytes());
+        bytes.extend_from_slice(header.as_bytes());
+        writer.write_all(&bytes)?;
+
+        let mut row_bytes = Vec::with_capacity(self.num_cols * T::SIZE);
+        for row in self
+            .data
+            .as_ref()
+            .chunks(self.row_stride)
+            .take(self.num_rows)
+        {
+            row_bytes.clear();
+            for &value in &row[..self.num_cols] {
+                value.extend_le_bytes(&mut row_bytes);
+            }
+            writer.write_all(&row_bytes)?;
+        }
+        Ok(())
+    }
+}
+
+/// Reads a two-dimensional array from NumPy's NPY format.
+///
+/// Both byte orders and both C and Fortran order are supported; the result is always
+/// row-major. The dtype of the file has to match the requested element type.
+///
+/// ## Returns
+/// The array, or an error of kind [`ErrorKind::InvalidData`] if the file is malformed,
+/// not two-dimensional, or of a different dtype.
+pub fn read_npy<T, R>(mut reader: R) -> std::io::Result<NpyMatrix<T>>
+where
+    T: NpyElement,
+    R: Read,
+{
+    let mut preamble = [0; 8];
+    reader.read_exact(&mut preamble)?;
+    if &preamble[..MAGIC.len()] != MAGIC {
+        return Err(invalid("Not an NPY file"));
+    }
+
+    let header_len = match preamble[MAGIC.len()] {
+        1 => {
+            let mut len = [0; 2];
+            reader.read_exact(&mut len)?;
+            usize::from(u16::from_le_bytes(len))
+        }
+        2 | 3 => {
+            let mut len = [0; 4];
+            reader.read_exact(&mut len)?;
+            usize::try_from(u32::from_le_bytes(len)).map_err(|_| invalid("Header too long"))?
+        }
+        _ => return Err(invalid("Unsupported NPY version")),
+    };
+
+    let header = read_bounded(&mut reader, header_len)?;
+    let header = String::from_utf8(header).map_err(|_| invalid("The header is not UTF-8"))?;
+
+    let descr = header_value(&header, "descr").ok_or_else(|| invalid("Missing dtype"))?;
+    let descr = descr.trim_matches(|c| c == '\'' || c == '"');
+    let mut chars = descr.chars();
+    let big_endian = match chars.next() {
+        Some('<' | '|') => false,
+        Some('>') => true,
+        Some('=') => cfg!(target_endian = "big"),
+        _ => return Err(invalid("Unsupported byte order")),
+    };
+    if chars.as_str() != T::DTYPE {
+        return Err(invalid("The dtype does not match the element type"));
+    }
+
+    let fortran_order = match header_value(&header, "fortran_order") {
+        Some("True") => true,
+        Some("False") => false,
+        _ => return Err(invalid("Missing memory order")),
+    };
+
+    let shape = header_value(&header, "shape").ok_or_else(|| invalid("Missing shape"))?;
+    let shape: Vec<usize> = shape
+        .trim_matches(|c| c == '(' || c == ')')
+        .split(',')
+        .map(str::trim)
+        .filter(|value| !value.is_empty())
+        .map(str::parse)
+        .collect::<Result<_, _>>()
+        .map_err(|_| invalid("Invalid shape"))?;
+    let &[num_rows, num_cols] = shape.as_slice() else {
+        return Err(invalid("The array is not two-dimensional"));
+    };
+
+    let len = num_rows
+        .checked_mul(num_cols)
+        .and_then(|len| len.checked_mul(T::SIZE))
+        .ok_or_else(|| invalid("The array is too large"))?;
+    let bytes = read_bounded(&mut reader, len)?;
+
+    let values: Vec<T> = bytes
+        .chunks_exact(T::SIZE)
+        .map(|bytes| T::from_bytes(bytes, big_endian))
+        .collect();
+
+    let data = if fortran_order {
+        (0..num_rows * num_cols)
+            .map(|index| values[(index % num_cols) * num_rows + index / num_cols])
+            .collect()
+    } else {
+        values
+    };
+
+    Ok(NpyMatrix {
+        num_rows,
+        num_cols,
+        data,
+    })
+}
+
+/// Extracts the raw value of a key from the dictionary literal of an NPY header.
+fn header_value<'a>(header: &'a str, key: &str) -> Option<&'a str> {
+    let start = header
+        .find(&format!("'{key}'"))
+        .or_else(|| header.find(&format!("\"{key}\"")))?;
+    let rest = header[start + key.len() + 2..]
+        .trim_start()
+        .strip_prefix(':')?;
+    let rest = rest.trim_start();
+
+    let end = if rest.starts_with('(') {
+        rest.find(')')? + 1
+    } else {
+        rest.find(',').or_else(|| rest.find('}'))?
+    };
+    Some(rest[..end].trim())
+}
+
+/// Reads exactly `len` bytes without trusting `len` for the allocation, such that a
+/// malformed header cannot exhaust the memory.
+fn read_bounded<R: Read>(reader: &mut R, len: usize) -> std::io::Result<Vec<u8>> {
+    let mut bytes = Vec::new();
+    reader.take(len as u64).read_to_end(&mut bytes)?;
+    if bytes.len() != len {
+        return Err(Error::new(
+            ErrorKind::UnexpectedEof,
+            "The file ends before the announced data",
+        ));
+    }
+    Ok(bytes)
+}
+
+fn invalid(message: &str) -> Error {
+    Error::new(ErrorKind::InvalidData, message.to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{lag_matrix, lag_matrix_2d, MatrixLayout};
+
+    const GOLDEN_F64: &[u8] = include_bytes!("../testdata/lag_matrix_f64.npy");
+    const GOLDEN_I32_BE: &[u8] = include_bytes!("../testdata/lag_matrix_i32_be.npy");
+    const GOLDEN_F32_FORTRAN: &[u8] = include_bytes!("../testdata/lag_matrix_f32_fortran.npy");
+
+    #[test]
+    fn test_write_matches_golden_file() {
+        let lagged = lag_matrix(&[1.0, 2.0, 3.0], 0..=1, 0.0, 5).unwrap();
+        let mut file = Vec::new();
+        lagged.write_npy(&mut file).unwrap();
+        assert_eq!(file, GOLDEN_F64);
+        assert_eq!((file.len() - 6 * 8) % 64, 0);
+    }
+
+    #[test]
+    fn test_read_golden_files() {
+        let expected = [1, 2, 3, 0, 1, 2];
+
+        let array = read_npy::<f64, _>(GOLDEN_F64).unwrap();
+        assert_eq!((array.num_rows(), array.num_cols()), (2, 3));
+        assert_eq!(array.data(), expected.map(f64::from));
+
+        // As produced on a big-endian machine or via dtype('>i4').
+        let array = read_npy::<i32, _>(GOLDEN_I32_BE).unwrap();
+        assert_eq!(array.into_vec(), expected);
+
+        let array = read_npy::<f32, _>(GOLDEN_F32_FORTRAN).unwrap();
+        assert_eq!(array.into_vec(), expected.map(|value| value as f32));
+
+        let error = read_npy::<f32, _>(GOLDEN_F64).unwrap_err();
+        assert_eq!(error.kind(), ErrorKind::InvalidData);
+    }
+
+    #[test]
+    fn test_round_trip_column_major() {
+        let data: Vec<u16> = (0..12).collect();
+        let lagged = lag_matrix_2d(&data, MatrixLayout::ColumnMajor(6), [0, 2], 99, 7).unwrap();
+
+        let mut file = Vec::new();
+        lagged.write_npy(&mut file).unwrap();
+        let array = read_npy::<u16, _>(file.as_slice()).unwrap();
+
+        assert_eq!((array.num_rows(), array.num_cols()), (6, 4));
+        let expected: Vec<u16> = lagged.chunks(7).flat_map(|row| row[..4].to_vec()).collect();
+        assert_eq!(array.into_vec(), expected);
+    }
+
+    #[test]
+    fn test_rejects_truncated_data_of_huge_shape() {
+        let mut file = GOLDEN_F64[..GOLDEN_F64.len() - 6 * 8].to_vec();
+        let header = std::str::from_utf8(&file[10..]).unwrap();
+        let patched = header.replace("(2, 3)", "(1099511627776, 3)");
+        file.truncate(10);
+        file.extend_from_slice(patched.as_bytes());
+        file[8..10].copy_from_slice(&(patched.len() as u16).to_le_bytes());
+        file.extend_from_slice(&[0; 16]);
+
+        let error = read_npy::<f64, _>(file.as_slice()).unwrap_err();
+        assert_eq!(error.kind(), ErrorKind::UnexpectedEof);
+    }
+}
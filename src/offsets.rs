@@ -0,0 +1,88 @@
+use crate::{LagMatrix, LagOffsets};
+
+impl<T, S> LagMatrix<T, S> {
+    /// The offsets of the values in the buffer of this matrix; see [`LagOffsets`].
+    #[inline(always)]
+    pub const fn offsets(&self) -> LagOffsets {
+        LagOffsets::from_shape(
+            self.matrix_layout(),
+            self.series_count,
+            self.num_lags,
+            self.static_count,
+            self.row_stride,
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{
+        lag_matrix_2d, lag_matrix_2d_directed, to_column_major, LagDirection, MatrixLayout,
+    };
+
+    #[test]
+    fn test_offsets_match_naive_definition() {
+        let data: Vec<i32> = (0..12).collect();
+        let columns = to_column_major(&data, MatrixLayout::RowMajor(4)).unwrap();
+        let lags = [2, 0, -1];
+
+        for (values, layout, stride) in [
+            (&data, MatrixLayout::RowMajor(4), 0),
+            (&data, MatrixLayout::RowMajor(4), 7),
+            (&columns, MatrixLayout::ColumnMajor(4), 0),
+            (&columns, MatrixLayout::ColumnMajor(4), 11),
+        ] {
+            let lagged = lag_matrix_2d(values, layout, lags, -1, stride).unwrap();
+            let offsets = lagged.offsets();
+            assert_eq!(offsets.len(), lagged.len());
+
+            for series in 0..3 {
+                for (lag_index, &lag) in lags.iter().enumerate() {
+                    for t in 0..4 {
+                        let source = t as isize - lag;
+                        let expected = if (0..4).contains(&source) {
+                            data[series * 4 + source as usize]
+                        } else {
+                            -1
+                        };
+                        let offset = offsets.get(series, lag_index, t).unwrap();
+                        assert_eq!(lagged[offset], expected, "{layout:?}, stride {stride}");
+                    }
+                }
+            }
+
+            assert_eq!(offsets.get(3, 0, 0), None);
+            assert_eq!(offsets.get(0, 3, 0), None);
+            assert_eq!(offsets.get(0, 0, 4), None);
+        }
+    }
+
+    #[test]
+    fn test_offsets_agree_with_matrix_indexing() {
+        let data: Vec<i32> = (0..10).collect();
+        let directions = [LagDirection::Lag, LagDirection::Lead];
+        for layout in [MatrixLayout::RowMajor(5), MatrixLayout::ColumnMajor(5)] {
+            let lagged = lag_matrix_2d_directed(&data, layout, [0, 1], &directions, -1, 9)
+                .unwrap()
+                .with_static_columns(&[7, 8]);
+            let offsets = lagged.offsets();
+
+            for lag_index in 0..2 {
+                for series in 0..2 {
+                    for t in 0..5 {
+                        assert_eq!(
+                            offsets.offset_of(series, lag_index, t),
+                            lagged.offset_of(lag_index, series, t)
+                        );
+                    }
+                }
+            }
+            for t in 0..5 {
+                assert_eq!(lagged[offsets.static_offset(0, t).unwrap()], 7);
+                assert_eq!(lagged[offsets.static_offset(1, t).unwrap()], 8);
+            }
+            assert_eq!(offsets.static_offset(2, 0), None);
+            assert_eq!(offsets.len(), lagged.len());
+        }
+    }
+}
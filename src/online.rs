@@ -0,0 +1,473 @@
+use crate::LagError;
+use std::collections::VecDeque;
+use std::ops::{Deref, Sub};
+
+/// A single row of lagged values produced by an [`OnlineLagger`].
+///
+/// The values are ordered like the lags the lagger was created with.
+#[derive(Debug, Clone, PartialEq)]
+pub struct LagRow<T> {
+    index: usize,
+    values: Vec<T>,
+}
+
+impl<T> LagRow<T> {
+    /// The zero-based time index of the observation that completed this row.
+    #[inline(always)]
+    pub const fn index(&self) -> usize {
+        self.index
+    }
+
+    /// The lagged values, in order of the configured lags.
+    #[inline(always)]
+    pub fn values(&self) -> &[T] {
+        &self.values
+    }
+
+    /// Converts this [`LagRow`] into a vector of lagged values.
+    #[inline(always)]
+    pub fn into_vec(self) -> Vec<T> {
+        self.values
+    }
+}
+
+impl<T> Deref for LagRow<T> {
+    type Target = [T];
+
+    #[inline(always)]
+    fn deref(&self) -> &Self::Target {
+        &self.values
+    }
+}
+
+impl<T> From<LagRow<T>> for Vec<T> {
+    #[inline(always)]
+    fn from(value: LagRow<T>) -> Self {
+        value.values
+    }
+}
+
+/// Incrementally creates lagged rows from a stream of observations.
+///
+/// Observations are pushed one at a time. As soon as enough history is available
+/// to populate every requested lag, each subsequent push yields a complete [`LagRow`];
+/// rows that would require padding are never emitted.
+///
+/// ## Example
+/// ```
+/// use timelag::OnlineLagger;
+///
+/// let mut lagger = OnlineLagger::new([0, 2]).unwrap();
+///
+/// assert!(lagger.push(1.0).is_none());
+/// assert!(lagger.push(2.0).is_none());
+///
+/// let row = lagger.push(3.0).unwrap();
+/// assert_eq!(row.index(), 2);
+/// assert_eq!(row.values(), &[3.0, 1.0]);
+/// ```
+#[derive(Debug, Clone)]
+pub struct OnlineLagger<T> {
+    lags: Vec<usize>,
+    max_lag: usize,
+    history: VecDeque<T>,
+    count: usize,
+    repeat_filter: Option<RepeatFilter<T>>,
+}
+
+/// The tolerance and comparison used to detect repeated observations.
+#[derive(Debug, Clone, Copy)]
+struct RepeatFilter<T> {
+    tolerance: T,
+    is_repeat: fn(T, T, T) -> bool,
+}
+
+fn within_tolerance<T>(previous: T, value: T, tolerance: T) -> bool
+where
+    T: PartialOrd + Sub<Output = T>,
+{
+    let difference = if value > previous {
+        value - previous
+    } else {
+        previous - value
+    };
+    difference <= tolerance
+}
+
+impl<T> OnlineLagger<T>
+where
+    T: Copy,
+{
+    /// Creates a new lagger producing rows for the specified lags.
+    ///
+    /// ## Arguments
+    /// * `lags` - The lags to produce, in the order they should appear in each row.
+    ///
+    /// ## Returns
+    /// The lagger, or [`LagError::InvalidLags`] if no lags were specified.
+    pub fn new<R: IntoIterator<Item = usize>>(lags: R) -> Result<Self, LagError> {
+        let lags = Vec::from_iter(lags);
+        let max_lag = match lags.iter().max() {
+            Some(&max_lag) => max_lag,
+            None => return Err(LagError::InvalidLags),
+        };
+
+        Ok(Self {
+            lags,
+            max_lag,
+            history: VecDeque::with_capacity(max_lag + 1),
+            count: 0,
+            repeat_filter: None,
+        })
+    }
+
+    /// Skips observations that repeat the previously recorded observation.
+    ///
+    /// An observation is considered a repeat if it differs from the previous one by at most
+    /// `tolerance`. Repeated observations are neither recorded in the history nor emit a row,
+    /// which suits change-only feeds in which identical consecutive values carry no information.
+    /// Skipped observations still advance the time index reported by [`LagRow::index`].
+    ///
+    /// ## Example
+    /// ```
+    /// use timelag::OnlineLagger;
+    ///
+    /// let mut lagger = OnlineLagger::new([0, 1]).unwrap().skip_repeated(0.01);
+    ///
+    /// assert!(lagger.push(1.0).is_none());
+    /// assert!(lagger.push(1.005).is_none());
+    ///
+    /// let row = lagger.push(2.0).unwrap();
+    /// assert_eq!(row.index(), 2);
+    /// assert_eq!(row.values(), &[2.0, 1.0]);
+    /// ```
+    pub fn skip_repeated(mut self, tolerance: T) -> Self
+    where
+        T: PartialOrd + Sub<Output = T>,
+    {
+        self.repeat_filter = Some(RepeatFilter {
+            tolerance,
+            is_repeat: within_tolerance::<T>,
+        });
+        self
+    }
+
+    /// Determines whether repeated observations are skipped; see [`OnlineLagger::skip_repeated`].
+    #[inline(always)]
+    pub const fn skips_repeated(&self) -> bool {
+        self.repeat_filter.is_some()
+    }
+
+    /// The lags produced by this lagger.
+    #[inline(always)]
+    pub fn lags(&self) -> &[usize] {
+        &self.lags
+    }
+
+    /// The largest lag produced by this lagger; this is the number of
+    /// observations consumed before the first row is emitted.
+    #[inline(always)]
+    pub const fn max_lag(&self) -> usize {
+        self.max_lag
+    }
+
+    /// The number of observations pushed so far.
+    #[inline(always)]
+    pub const fn count(&self) -> usize {
+        self.count
+    }
+
+    /// The number of recorded observations still required before the first row is emitted.
+    pub fn warmup_remaining(&self) -> usize {
+        self.max_lag.saturating_sub(self.history.len())
+    }
+
+    /// Pushes a new observation.
+    ///
+    /// ## Returns
+    /// The completed row ending at this observation, or `None` if not
+    /// enough history has been observed yet or the observation was skipped
+    /// as a repeat.
+    pub fn push(&mut self, value: T) -> Option<LagRow<T>> {
+        let index = self.count;
+        self.count += 1;
+
+        if let (Some(filter), Some(&previous)) = (&self.repeat_filter, self.history.back()) {
+            if (filter.is_repeat)(previous, value, filter.tolerance) {
+                return None;
+            }
+        }
+
+        if self.history.len() > self.max_lag {
+            self.history.pop_front();
+        }
+        self.history.push_back(value);
+
+        if self.history.len() <= self.max_lag {
+            return None;
+        }
+
+        let newest = self.history.len() - 1;
+        let values = self
+            .lags
+            .iter()
+            .map(|&lag| self.history[newest - lag])
+            .collect();
+
+        Some(LagRow { index, values })
+    }
+
+    /// Clears the history, as if no observation was pushed yet.
+    pub fn reset(&mut self) {
+        self.history.clear();
+        self.count = 0;
+    }
+}
+
+/// Incrementally creates lagged rows from multiple channels sampled at different rates.
+///
+/// Every channel can be updated independently and retains its most recent value
+/// (sample-and-hold). Whenever the trigger channel is updated, the current values of
+/// all channels are recorded as one time step; once enough time steps are recorded,
+/// each trigger update emits a [`LagRow`].
+///
+/// Each row contains the values of all channels for the first lag, followed by the
+/// values of all channels for the second lag and so on. Trigger updates that occur
+/// before every channel received a value are ignored.
+///
+/// ## Example
+/// ```
+/// use timelag::MultiChannelLagger;
+///
+/// // Channel 0 is sampled quickly and triggers rows; channel 1 is slow.
+/// let mut lagger = MultiChannelLagger::new(2, 0, [0, 1]).unwrap();
+///
+/// lagger.update(1, 10.0);
+/// assert!(lagger.update(0, 1.0).is_none());
+///
+/// let row = lagger.update(0, 2.0).unwrap();
+/// assert_eq!(row.values(), &[2.0, 10.0, 1.0, 10.0]);
+///
+/// lagger.update(1, 20.0);
+/// let row = lagger.update(0, 3.0).unwrap();
+/// assert_eq!(row.values(), &[3.0, 20.0, 2.0, 10.0]);
+/// ```
+#[derive(Debug, Clone)]
+pub struct MultiChannelLagger<T> {
+    lags: Vec<usize>,
+    max_lag: usize,
+    trigger: usize,
+    latest: Vec<Option<T>>,
+    history: VecDeque<T>,
+    count: usize,
+}
+
+impl<T> MultiChannelLagger<T>
+where
+    T: Copy,
+{
+    /// Creates a new lagger for the specified number of channels.
+    ///
+    /// ## Arguments
+    /// * `channels` - The number of channels.
+    /// * `trigger` - The index of the channel whose updates emit rows.
+    /// * `lags` - The lags to produce, in the order they should appear in each row.
+    ///
+    /// ## Returns
+    /// The lagger, or an error if the channels or lags are invalid.
+    pub fn new<R: IntoIterator<Item = usize>>(
+        channels: usize,
+        trigger: usize,
+        lags: R,
+    ) -> Result<Self, LagError> {
+        if trigger >= channels {
+            return Err(LagError::InvalidChannel);
+        }
+
+        let lags = Vec::from_iter(lags);
+        let max_lag = match lags.iter().max() {
+            Some(&max_lag) => max_lag,
+            None => return Err(LagError::InvalidLags),
+        };
+
+        Ok(Self {
+            lags,
+            max_lag,
+            trigger,
+            latest: vec![None; channels],
+            history: VecDeque::with_capacity((max_lag + 1) * channels),
+            count: 0,
+        })
+    }
+
+    /// The lags produced by this lagger.
+    #[inline(always)]
+    pub fn lags(&self) -> &[usize] {
+        &self.lags
+    }
+
+    /// The number of channels.
+    #[inline(always)]
+    pub fn channels(&self) -> usize {
+        self.latest.len()
+    }
+
+    /// The index of the channel whose updates emit rows.
+    #[inline(always)]
+    pub const fn trigger(&self) -> usize {
+        self.trigger
+    }
+
+    /// The number of trigger updates observed so far.
+    #[inline(always)]
+    pub const fn count(&self) -> usize {
+        self.count
+    }
+
+    /// The most recent value of the specified channel, if any.
+    #[inline(always)]
+    pub fn latest(&self, channel: usize) -> Option<T> {
+        self.latest.get(channel).copied().flatten()
+    }
+
+    /// Updates the value of a channel.
+    ///
+    /// ## Returns
+    /// If `channel` is the trigger channel, the completed row ending at this time step;
+    /// `None` if the channel is not the trigger channel or not enough history is available.
+    ///
+    /// ## Panics
+    /// Panics if `channel` is out of range.
+    pub fn update(&mut self, channel: usize, value: T) -> Option<LagRow<T>> {
+        self.latest[channel] = Some(value);
+        if channel != self.trigger {
+            return None;
+        }
+
+        let index = self.count;
+        self.count += 1;
+
+        let channels = self.latest.len();
+        if self.latest.iter().any(Option::is_none) {
+            return None;
+        }
+
+        if self.history.len() > self.max_lag * channels {
+            self.history.drain(..channels);
+        }
+        self.history.extend(self.latest.iter().flatten());
+
+        let steps = self.history.len() / channels;
+        if steps <= self.max_lag {
+            return None;
+        }
+
+        let newest = steps - 1;
+        let mut values = Vec::with_capacity(self.lags.len() * channels);
+        for &lag in &self.lags {
+            let offset = (newest - lag) * channels;
+            values.extend(self.history.range(offset..offset + channels));
+        }
+
+        Some(LagRow { index, values })
+    }
+
+    /// Clears the history and all held channel values.
+    pub fn reset(&mut self) {
+        self.latest.fill(None);
+        self.history.clear();
+        self.count = 0;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_online_lag() {
+        let mut lagger = OnlineLagger::new(0..=2).unwrap();
+        let rows: Vec<_> = [1.0, 2.0, 3.0, 4.0, 5.0]
+            .into_iter()
+            .filter_map(|value| lagger.push(value))
+            .collect();
+
+        assert_eq!(rows.len(), 3);
+        assert_eq!(rows[0].index(), 2);
+        assert_eq!(rows[0].values(), &[3.0, 2.0, 1.0]);
+        assert_eq!(rows[1].values(), &[4.0, 3.0, 2.0]);
+        assert_eq!(rows[2].values(), &[5.0, 4.0, 3.0]);
+        assert_eq!(lagger.count(), 5);
+    }
+
+    #[test]
+    fn test_online_lag_unordered() {
+        let mut lagger = OnlineLagger::new([1, 3, 0]).unwrap();
+        let rows: Vec<_> = (1..=5).filter_map(|value| lagger.push(value)).collect();
+
+        assert_eq!(rows.len(), 2);
+        assert_eq!(rows[0].values(), &[3, 1, 4]);
+        assert_eq!(rows[1].values(), &[4, 2, 5]);
+    }
+
+    #[test]
+    fn test_online_lag_reset() {
+        let mut lagger = OnlineLagger::new([1]).unwrap();
+        assert!(lagger.push(1).is_none());
+        lagger.reset();
+        assert!(lagger.push(2).is_none());
+        assert_eq!(lagger.push(3).unwrap().values(), &[2]);
+    }
+
+    #[test]
+    fn test_online_lag_skip_repeated() {
+        let mut lagger = OnlineLagger::new([0, 1]).unwrap().skip_repeated(0);
+        let rows: Vec<_> = [1, 1, 2, 2, 2, 3, 1]
+            .into_iter()
+            .filter_map(|value| lagger.push(value))
+            .collect();
+
+        assert_eq!(rows.len(), 3);
+        assert_eq!((rows[0].index(), rows[0].values()), (2, &[2, 1][..]));
+        assert_eq!((rows[1].index(), rows[1].values()), (5, &[3, 2][..]));
+        assert_eq!((rows[2].index(), rows[2].values()), (6, &[1, 3][..]));
+    }
+
+    #[test]
+    fn test_multi_channel_lag() {
+        let mut lagger = MultiChannelLagger::new(3, 1, [1, 0]).unwrap();
+
+        // The trigger is ignored until all channels have a value.
+        assert!(lagger.update(1, 1).is_none());
+        lagger.update(0, 100);
+        lagger.update(2, 1000);
+
+        assert!(lagger.update(1, 2).is_none());
+        lagger.update(0, 200);
+        lagger.update(0, 300);
+
+        let row = lagger.update(1, 3).unwrap();
+        assert_eq!(row.index(), 2);
+        assert_eq!(row.values(), &[100, 2, 1000, 300, 3, 1000]);
+
+        let row = lagger.update(1, 4).unwrap();
+        assert_eq!(row.values(), &[300, 3, 1000, 300, 4, 1000]);
+        assert_eq!(lagger.latest(0), Some(300));
+    }
+
+    #[test]
+    fn test_multi_channel_lag_invalid_trigger() {
+        assert_eq!(
+            MultiChannelLagger::<f64>::new(2, 2, [0]).unwrap_err(),
+            LagError::InvalidChannel
+        );
+    }
+
+    #[test]
+    fn test_online_lag_no_lags() {
+        assert_eq!(
+            OnlineLagger::<f64>::new([]).unwrap_err(),
+            LagError::InvalidLags
+        );
+    }
+}
@@ -0,0 +1,232 @@
+use crate::{delay_embedding, LagError};
+
+/// The ordinal patterns of a time series; see [`ordinal_patterns`].
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct OrdinalPatterns {
+    order: usize,
+    delay: usize,
+    offset: usize,
+    patterns: Vec<usize>,
+}
+
+impl OrdinalPatterns {
+    /// The order `m`, i.e. the number of values per pattern.
+    #[inline(always)]
+    pub const fn order(&self) -> usize {
+        self.order
+    }
+
+    /// The delay `τ` between subsequent values of each pattern.
+    #[inline(always)]
+    pub const fn delay(&self) -> usize {
+        self.delay
+    }
+
+    /// The time step of the first pattern, i.e. the first time step with a complete delay vector.
+    #[inline(always)]
+    pub const fn offset(&self) -> usize {
+        self.offset
+    }
+
+    /// The pattern indices in `0..m!`; the pattern at index `i` describes time step `offset() + i`.
+    #[inline(always)]
+    pub fn patterns(&self) -> &[usize] {
+        &self.patterns
+    }
+
+    /// The number of patterns.
+    #[inline(always)]
+    pub fn len(&self) -> usize {
+        self.patterns.len()
+    }
+
+    /// Determines whether there are no patterns, i.e. the series was too short.
+    #[inline(always)]
+    pub fn is_empty(&self) -> bool {
+        self.patterns.is_empty()
+    }
+
+    /// The number of distinct possible patterns, `m!`.
+    pub fn num_possible(&self) -> usize {
+        factorial(self.order).unwrap_or(usize::MAX)
+    }
+
+    /// The Shannon entropy, in nats, of the relative pattern frequencies.
+    ///
+    /// Returns zero if there are no patterns.
+    pub fn permutation_entropy(&self) -> f64 {
+        let mut patterns = self.patterns.clone();
+        patterns.sort_unstable();
+
+        let total = patterns.len() as f64;
+        patterns
+            .chunk_by(|a, b| a == b)
+            .map(|run| {
+                let p = run.len() as f64 / total;
+                -p * p.ln()
+            })
+            .sum()
+    }
+
+    /// The permutation entropy normalized by its maximum `ln(m!)`, i.e. a value in `[0, 1]`.
+    ///
+    /// Returns zero if the order is one, as only a single pattern exists.
+    pub fn normalized_permutation_entropy(&self) -> f64 {
+        let max = (2..=self.order).map(|k| (k as f64).ln()).sum::<f64>();
+        if max > 0.0 {
+            self.permutation_entropy() / max
+        } else {
+            0.0
+        }
+    }
+}
+
+/// Maps every delay vector of a time series to the index of its ordinal pattern.
+///
+/// Each complete delay vector `[x(t - (m - 1)·τ), …, x(t - τ), x(t)]` of the
+/// [`delay_embedding`] is replaced by the permutation that sorts its values in
+/// ascending order, encoded as its lexicographic rank in `0..m!`. Ties are resolved
+/// by time, with earlier values ranking lower. Time steps whose delay vector would reach
+/// before the start of the series are skipped.
+///
+/// ## Arguments
+/// * `data` - The time series data.
+/// * `order` - The order `m` of the patterns, i.e. the embedding dimension.
+/// * `delay` - The delay `τ` between subsequent values of each delay vector.
+///
+/// ## Returns
+/// The ordinal patterns, or an error if the order is zero, `m!` does not fit into a `usize`,
+/// or the embedding spans more than the available data.
+///
+/// ## Example
+/// ```
+/// use timelag::ordinal_patterns;
+///
+/// let data = [1.0, 3.0, 2.0, 4.0, 5.0];
+/// let patterns = ordinal_patterns(&data, 3, 1).unwrap();
+///
+/// // [1, 3, 2] sorts as (0, 2, 1), [3, 2, 4] as (1, 0, 2) and [2, 4, 5] as (0, 1, 2).
+/// assert_eq!(patterns.offset(), 2);
+/// assert_eq!(patterns.patterns(), &[1, 2, 0]);
+/// assert!(patterns.normalized_permutation_entropy() > 0.5);
+/// ```
+pub fn ordinal_patterns<T>(
+    data: &[T],
+    order: usize,
+    delay: usize,
+) -> Result<OrdinalPatterns, LagError>
+where
+    T: Copy + PartialOrd,
+{
+    if order == 0 || factorial(order).is_none() {
+        return Err(LagError::InvalidLags);
+    }
+
+    let Some(&fill) = data.first() else {
+        return Err(LagError::EmptyData);
+    };
+
+    let embedding = delay_embedding(data, order, delay, fill)?;
+    let offset = (order - 1) * delay;
+
+    let mut vector = Vec::with_capacity(order);
+    let mut permutation = Vec::with_capacity(order);
+    let patterns = embedding
+        .chunks_exact(order)
+        .skip(offset)
+        .map(|row| {
+            // The embedding holds the most recent value first.
+            vector.clear();
+            vector.extend(row.iter().rev().copied());
+
+            permutation.clear();
+            permutation.extend(0..order);
+            permutation.sort_by(|&a, &b| {
+                vector[a]
+                    .partial_cmp(&vector[b])
+                    .unwrap_or(core::cmp::Ordering::Equal)
+            });
+
+            lehmer_rank(&permutation)
+        })
+        .collect();
+
+    Ok(OrdinalPatterns {
+        order,
+        delay,
+        offset,
+        patterns,
+    })
+}
+
+/// The lexicographic rank of a permutation of `0..n`.
+fn lehmer_rank(permutation: &[usize]) -> usize {
+    let n = permutation.len();
+    let mut rank = 0;
+    for (i, &value) in permutation.iter().enumerate() {
+        let smaller = permutation[i + 1..].iter().filter(|&&v| v < value).count();
+        rank = rank * (n - i) + smaller;
+    }
+    rank
+}
+
+fn factorial(n: usize) -> Option<usize> {
+    (2..=n).try_fold(1usize, |acc, k| acc.checked_mul(k))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_lehmer_rank_is_lexicographic() {
+        let permutations = [
+            [0, 1, 2],
+            [0, 2, 1],
+            [1, 0, 2],
+            [1, 2, 0],
+            [2, 0, 1],
+            [2, 1, 0],
+        ];
+        for (rank, permutation) in permutations.iter().enumerate() {
+            assert_eq!(lehmer_rank(permutation), rank);
+        }
+    }
+
+    #[test]
+    fn test_monotonic_series_has_zero_entropy() {
+        let data: Vec<i32> = (0..20).collect();
+        let patterns = ordinal_patterns(&data, 4, 2).unwrap();
+        assert_eq!(patterns.offset(), 6);
+        assert_eq!(patterns.len(), 14);
+        assert!(patterns.patterns().iter().all(|&p| p == 0));
+        assert_eq!(patterns.permutation_entropy(), 0.0);
+        assert_eq!(patterns.num_possible(), 24);
+    }
+
+    #[test]
+    fn test_alternating_series() {
+        let data = [0, 1, 0, 1, 0, 1];
+        let patterns = ordinal_patterns(&data, 2, 1).unwrap();
+        assert_eq!(patterns.patterns(), &[0, 1, 0, 1, 0]);
+
+        let entropy = patterns.normalized_permutation_entropy();
+        assert!((entropy - 0.971).abs() < 1e-3);
+    }
+
+    #[test]
+    fn test_invalid_order() {
+        assert_eq!(
+            ordinal_patterns(&[1, 2, 3], 0, 1).unwrap_err(),
+            LagError::InvalidLags
+        );
+        assert_eq!(
+            ordinal_patterns(&[1, 2, 3], 64, 0).unwrap_err(),
+            LagError::InvalidLags
+        );
+        assert_eq!(
+            ordinal_patterns::<i32>(&[], 2, 1).unwrap_err(),
+            LagError::EmptyData
+        );
+    }
+}
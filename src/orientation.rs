@@ -0,0 +1,145 @@
+use crate::{lag_matrix_2d, to_column_major, IntoLag, LagError, LagMatrix, MatrixLayout};
+use std::borrow::Cow;
+
+/// The orientation of a lag matrix, independent of the layout of the data matrix;
+/// see [`lag_matrix_2d_oriented`].
+#[derive(Debug, Copy, Clone, Eq, PartialEq, Hash, Default)]
+pub enum OutputLayout {
+    /// One row per lag and series, with the time steps along the columns,
+    /// i.e. a row-major matrix.
+    #[default]
+    LagsAlongRows,
+    /// One row per time step, with the lags and series along the columns,
+    /// i.e. a column-major matrix.
+    LagsAlongColumns,
+}
+
+impl OutputLayout {
+    /// The layout of a data matrix with series of the specified length that results in this
+    /// orientation when lagged.
+    #[inline(always)]
+    pub const fn input_layout(&self, series_length: usize) -> MatrixLayout {
+        match self {
+            OutputLayout::LagsAlongRows => MatrixLayout::RowMajor(series_length),
+            OutputLayout::LagsAlongColumns => MatrixLayout::ColumnMajor(series_length),
+        }
+    }
+
+    /// Converts the data matrix into the layout matching this orientation, borrowing it if it
+    /// already matches.
+    pub(crate) fn convert<'a, T: Copy>(
+        &self,
+        data_matrix: &'a [T],
+        layout: MatrixLayout,
+    ) -> Result<(Cow<'a, [T]>, MatrixLayout), LagError> {
+        let target = self.input_layout(layout.len());
+        let data = match (layout, target) {
+            (MatrixLayout::RowMajor(_), MatrixLayout::ColumnMajor(_)) => {
+                Cow::Owned(to_column_major(data_matrix, layout)?)
+            }
+            (MatrixLayout::ColumnMajor(_), MatrixLayout::RowMajor(_)) => {
+                Cow::Owned(to_row_major(data_matrix, layout)?)
+            }
+            _ => Cow::Borrowed(data_matrix),
+        };
+        Ok((data, target))
+    }
+}
+
+/// Converts a column-major (interleaved) data matrix into the row-major layout.
+fn to_row_major<T: Copy>(data: &[T], layout: MatrixLayout) -> Result<Vec<T>, LagError> {
+    let series_length = layout.len();
+    if series_length == 0 || !data.len().is_multiple_of(series_length) {
+        return Err(LagError::InvalidLength);
+    }
+
+    let num_series = data.len() / series_length;
+    Ok((0..data.len())
+        .map(|i| data[(i % series_length) * num_series + i / series_length])
+        .collect())
+}
+
+/// Create a time-lagged matrix of multiple time series in the specified orientation,
+/// regardless of the layout of the data matrix.
+///
+/// [`lag_matrix_2d`] derives the orientation of the result from the layout of the data
+/// matrix. This function converts the data matrix first if the layouts differ, which is
+/// cheaper than transposing the lagged matrix. See [`lag_matrix_2d`] for the arguments.
+///
+/// ## Example
+/// ```
+/// use timelag::{lag_matrix_2d_oriented, MatrixLayout, OutputLayout};
+///
+/// let data = [
+///     1, 2, 3,
+///     10, 20, 30,
+/// ];
+/// let layout = MatrixLayout::RowMajor(3);
+/// let lagged =
+///     lag_matrix_2d_oriented(&data, layout, [0, 1], 0, 0, OutputLayout::LagsAlongColumns).unwrap();
+///
+/// assert!(lagged.is_column_major());
+/// assert_eq!(
+///     lagged,
+///     &[
+///         1, 10, 0, 0,
+///         2, 20, 1, 10,
+///         3, 30, 2, 20,
+///     ]
+/// );
+/// ```
+pub fn lag_matrix_2d_oriented<T: Copy, L: IntoLag, R: IntoIterator<Item = L>>(
+    data_matrix: &[T],
+    layout: MatrixLayout,
+    lags: R,
+    fill: T,
+    row_stride: usize,
+    output: OutputLayout,
+) -> Result<LagMatrix<T>, LagError> {
+    let (data, layout) = output.convert(data_matrix, layout)?;
+    lag_matrix_2d(&data, layout, lags, fill, row_stride)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    #[rustfmt::skip]
+    fn test_orientations_are_transposes() {
+        let data = [
+            1, 10,
+            2, 20,
+            3, 30,
+            4, 40,
+        ];
+        let layout = MatrixLayout::ColumnMajor(4);
+        let rows = lag_matrix_2d_oriented(&data, layout, [0, 2], -1, 5, OutputLayout::LagsAlongRows)
+            .unwrap();
+        assert!(rows.is_row_major());
+        assert_eq!(rows, &[
+            1, 2, 3, 4, -1,
+            10, 20, 30, 40, -1,
+            -1, -1, 1, 2, -1,
+            -1, -1, 10, 20, -1,
+        ]);
+
+        let columns = lag_matrix_2d(&data, layout, [0, 2], -1, 0).unwrap();
+        assert_eq!(rows.transpose(), columns);
+    }
+
+    #[test]
+    fn test_invalid_length() {
+        assert_eq!(
+            lag_matrix_2d_oriented(
+                &[1, 2, 3],
+                MatrixLayout::ColumnMajor(2),
+                [0],
+                0,
+                0,
+                OutputLayout::LagsAlongRows
+            ),
+            Err(LagError::InvalidLength)
+        );
+    }
+}
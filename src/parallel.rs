@@ -0,0 +1,148 @@
+use crate::{LagDirection, LagError, LagMatrix, LagPlan, MatrixLayout};
+use std::num::NonZeroUsize;
+
+/// Limits the parallelism used to create lag matrices; see [`LagMatrixBuilder::parallel`](crate::LagMatrixBuilder::parallel).
+///
+/// The matrix is split into chunks of at least `min_chunk_elems` elements which are filled
+/// by at most `threads` threads, including the calling thread. Small matrices are therefore
+/// created on the calling thread alone.
+///
+/// ## Example
+/// ```
+/// use timelag::{LagMatrixBuilder, ParallelConfig};
+///
+/// let data: Vec<f64> = (0..10_000).map(f64::from).collect();
+///
+/// // Use at most four threads on a shared machine.
+/// let config = ParallelConfig {
+///     threads: 4,
+///     min_chunk_elems: 4096,
+/// };
+///
+/// let lagged = LagMatrixBuilder::new(f64::NAN)
+///     .parallel(config)
+///     .build(&data, 0..=24)
+///     .unwrap();
+/// assert_eq!(lagged.num_rows(), 25);
+/// ```
+#[derive(Debug, Copy, Clone, Eq, PartialEq)]
+pub struct ParallelConfig {
+    /// The maximum number of threads to use; `0` uses the available parallelism.
+    pub threads: usize,
+    /// The minimum number of matrix elements per thread.
+    pub min_chunk_elems: usize,
+}
+
+impl Default for ParallelConfig {
+    fn default() -> Self {
+        Self {
+            threads: 0,
+            min_chunk_elems: 1 << 16,
+        }
+    }
+}
+
+impl ParallelConfig {
+    /// The number of threads to use, resolving `0` to the available parallelism.
+    pub fn effective_threads(&self) -> usize {
+        match self.threads {
+            0 => std::thread::available_parallelism().map_or(1, NonZeroUsize::get),
+            threads => threads,
+        }
+    }
+
+    /// The number of units of `unit_len` elements to process per thread.
+    pub(crate) fn units_per_chunk(&self, unit_len: usize, num_units: usize) -> usize {
+        let even = num_units.div_ceil(self.effective_threads());
+        let min = self.min_chunk_elems.div_ceil(unit_len.max(1));
+        even.max(min).max(1)
+    }
+}
+
+/// Creates a lag matrix like `lag_matrix_2d_impl`, filling it on multiple threads.
+pub(crate) fn lag_matrix_2d_parallel<T>(
+    data_matrix: &[T],
+    layout: MatrixLayout,
+    lags: Vec<isize>,
+    directions: Option<&[LagDirection]>,
+    fill: T,
+    row_stride: usize,
+    config: &ParallelConfig,
+) -> Result<LagMatrix<T>, LagError>
+where
+    T: Copy + Send + Sync,
+{
+    let plan = LagPlan::new(data_matrix, layout, lags, directions, row_stride)?;
+    let (unit_len, num_units) = (plan.unit_len(), plan.num_units());
+    let mut lagged = vec![fill; unit_len * num_units];
+
+    let per_chunk = config.units_per_chunk(unit_len, num_units);
+    if per_chunk >= num_units {
+        plan.fill(data_matrix, &mut lagged, 0..num_units);
+        return Ok(plan.into_matrix(lagged));
+    }
+
+    std::thread::scope(|scope| {
+        let mut chunks = lagged.chunks_mut(per_chunk * unit_len).enumerate();
+        let (_, first) = chunks.next().expect("the matrix is not empty");
+        let plan = &plan;
+
+        for (index, chunk) in chunks {
+            let start = index * per_chunk;
+            let units = start..start + chunk.len() / unit_len;
+            scope.spawn(move || plan.fill(data_matrix, chunk, units));
+        }
+
+        let units = 0..first.len() / unit_len;
+        plan.fill(data_matrix, first, units);
+    });
+
+    Ok(plan.into_matrix(lagged))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::lag_matrix_2d_directed;
+
+    /// Forces a split into many small chunks.
+    const CONFIG: ParallelConfig = ParallelConfig {
+        threads: 3,
+        min_chunk_elems: 1,
+    };
+
+    #[test]
+    fn test_parallel_matches_sequential() {
+        let data: Vec<i32> = (0..300).collect();
+        let directions = [LagDirection::Lag, LagDirection::Lead, LagDirection::Lag];
+
+        for layout in [MatrixLayout::RowMajor(100), MatrixLayout::ColumnMajor(100)] {
+            for directions in [Some(&directions[..]), None] {
+                let lags = vec![0, 1, 7, -3];
+                let parallel =
+                    lag_matrix_2d_parallel(&data, layout, lags.clone(), directions, -1, 0, &CONFIG)
+                        .unwrap();
+                let sequential = match directions {
+                    Some(directions) => {
+                        lag_matrix_2d_directed(&data, layout, lags, directions, -1, 0)
+                    }
+                    None => crate::lag_matrix_2d(&data, layout, lags, -1, 0),
+                }
+                .unwrap();
+                assert_eq!(parallel, sequential);
+            }
+        }
+    }
+
+    #[test]
+    fn test_units_per_chunk() {
+        let config = ParallelConfig {
+            threads: 4,
+            min_chunk_elems: 100,
+        };
+        assert_eq!(config.units_per_chunk(10, 1000), 250);
+        assert_eq!(config.units_per_chunk(10, 20), 10);
+        assert_eq!(config.units_per_chunk(1000, 2), 1);
+        assert!(ParallelConfig::default().effective_threads() >= 1);
+    }
+}
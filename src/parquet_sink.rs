@@ -0,0 +1,472 @@
+use crate::pipeline::column_name;
+use crate::{ChunkSink, LagMatrix, ParallelConfig};
+use bytes::Bytes;
+use parquet::basic::{Repetition, Type as PhysicalType};
+use parquet::column::writer::{ColumnCloseResult, ColumnWriterImpl};
+use parquet::data_type::{DataType, DoubleType, FloatType, Int32Type, Int64Type};
+use parquet::errors::ParquetError;
+use parquet::file::properties::{WriterProperties, WriterPropertiesPtr};
+use parquet::file::writer::{SerializedFileWriter, SerializedPageWriter, TrackedWrite};
+use parquet::schema::types::{ColumnDescPtr, Type};
+use std::io::Write;
+use std::sync::Arc;
+
+/// A value that can be written to a Parquet column by a [`ParquetSink`].
+///
+/// The trait is sealed and implemented for `f32`, `f64`, `i32` and `i64`, which map to the
+/// physical types of the same width.
+pub trait ParquetElement: Copy + Send + Sync + sealed::Sealed {
+    /// The Parquet data type of the column.
+    type DataType: DataType<T = Self>;
+
+    /// The physical type of the column.
+    const PHYSICAL_TYPE: PhysicalType;
+}
+
+mod sealed {
+    /// Restricts [`ParquetElement`](super::ParquetElement) to the types of this crate.
+    pub trait Sealed {}
+}
+
+macro_rules! impl_parquet_element {
+    ($($type:ty => $data_type:ty, $physical:ident),*) => {
+        $(
+            impl sealed::Sealed for $type {}
+
+            impl ParquetElement for $type {
+                type DataType = $data_type;
+                const PHYSICAL_TYPE: PhysicalType = PhysicalType::$physical;
+            }
+        )*
+    };
+}
+
+impl_parquet_element!(
+    f32 => FloatType, FLOAT,
+    f64 => DoubleType, DOUBLE,
+    i32 => Int32Type, INT32,
+    i64 => Int64Type, INT64
+);
+
+/// Writes lagged chunks as a Parquet file, one row group per chunk and one required column
+/// per lag and series, named like the columns of [`CsvSink`](crate::CsvSink).
+///
+/// The column chunks of a row group are encoded concurrently, at most one thread per
+/// [`ParallelConfig::min_chunk_elems`] values, and are then appended to the file in order.
+/// The schema is taken from the first chunk; all further chunks must have the same lags and
+/// series. If the first chunk carries [`row_weights`](LagMatrix::row_weights), they are
+/// written as a trailing `weight` column and all further chunks must carry weights as well.
+/// Both row-major and column-major chunks are supported.
+///
+/// Nothing is written if no chunk is, since the schema is unknown.
+///
+/// ## Example
+/// ```
+/// use timelag::{lag_csv_column, CsvColumnReader, ParquetSink};
+///
+/// let csv = "value\n1\n2\n3\n4\n";
+/// let reader = CsvColumnReader::by_name(csv.as_bytes(), "value").unwrap();
+///
+/// let mut sink = ParquetSink::new(Vec::new());
+/// let steps = lag_csv_column(reader, [0, 1], f64::NAN, 2, &mut sink).unwrap();
+/// assert_eq!(steps, 4);
+///
+/// let file = sink.into_inner().unwrap();
+/// assert!(file.starts_with(b"PAR1") && file.ends_with(b"PAR1"));
+/// ```
+pub struct ParquetSink<W: Write + Send> {
+    state: State<W>,
+    properties: WriterPropertiesPtr,
+    config: ParallelConfig,
+}
+
+/// The progress of a [`ParquetSink`].
+enum State<W: Write + Send> {
+    /// No chunk was written yet.
+    Pending(W),
+    /// The schema was written, followed by the row groups of all chunks so far.
+    Writing {
+        file: Box<SerializedFileWriter<W>>,
+        lags: Vec<isize>,
+        series_count: usize,
+        weighted: bool,
+    },
+    /// The footer was written.
+    Finished(W),
+    /// Writing failed, leaving the file incomplete.
+    Failed,
+}
+
+impl<W> ParquetSink<W>
+where
+    W: Write + Send,
+{
+    /// Creates a sink writing to the specified writer with the default writer properties
+    /// and parallelism.
+    pub fn new(writer: W) -> Self {
+        Self {
+            state: State::Pending(writer),
+            properties: Arc::new(WriterProperties::default()),
+            config: ParallelConfig::default(),
+        }
+    }
+
+    /// Sets the properties of the file, e.g. its compression and page sizes.
+    pub fn properties(mut self, properties: WriterProperties) -> Self {
+        self.properties = Arc::new(properties);
+        self
+    }
+
+    /// Limits the threads encoding the column chunks of a row group.
+    pub fn parallel(mut self, config: ParallelConfig) -> Self {
+        self.config = config;
+        self
+    }
+
+    /// Returns the underlying writer, writing the footer unless [`finish`](Self::finish)
+    /// did already.
+    ///
+    /// ## Returns
+    /// The writer, or an error if the footer cannot be written or an earlier write failed.
+    pub fn into_inner(self) -> Result<W, ParquetError> {
+        match self.state {
+            State::Pending(writer) | State::Finished(writer) => Ok(writer),
+            State::Writing { file, .. } => file.into_inner(),
+            State::Failed => Err(failed()),
+        }
+    }
+
+    /// Writes the footer of the file after the last chunk, like [`ChunkSink::finish`], but
+    /// without naming the type of the values.
+    pub fn finish(&mut self) -> Result<(), ParquetError> {
+        self.state = match std::mem::replace(&mut self.state, State::Failed) {
+            State::Writing { file, .. } => State::Finished(file.into_inner()?),
+            state => state,
+        };
+        Ok(())
+    }
+
+    /// Writes the schema of the columns of the first chunk.
+    fn start<T: ParquetElement>(
+        &self,
+        writer: W,
+        chunk: &LagMatrix<T>,
+    ) -> Result<SerializedFileWriter<W>, ParquetError> {
+        let weight = chunk.row_weights().map(|_| "weight".to_string());
+        let fields = (0..chunk.features_per_step())
+            .map(|feature| column_name(chunk, feature))
+            .chain(weight)
+            .map(|name| {
+                Type::primitive_type_builder(&name, T::PHYSICAL_TYPE)
+                    .with_repetition(Repetition::REQUIRED)
+                    .build()
+                    .map(Arc::new)
+            })
+            .collect::<Result<_, _>>()?;
+        let schema = Type::group_type_builder("schema")
+            .with_fields(fields)
+            .build()?;
+        SerializedFileWriter::new(writer, Arc::new(schema), self.properties.clone())
+    }
+
+    /// Writes the chunk as the next row group.
+    fn write_row_group<T: ParquetElement>(
+        &mut self,
+        chunk: &LagMatrix<T>,
+    ) -> Result<(), ParquetError> {
+        match self.state {
+            State::Finished(_) => {
+                return Err(ParquetError::General(
+                    "the Parquet file is already finished".into(),
+                ))
+            }
+            State::Failed => return Err(failed()),
+            State::Pending(_) | State::Writing { .. } => {}
+        }
+
+        let (mut file, lags, series_count, weighted) =
+            match std::mem::replace(&mut self.state, State::Failed) {
+                State::Pending(writer) => {
+                    let file = Box::new(self.start(writer, chunk)?);
+                    let weighted = chunk.row_weights().is_some();
+                    (file, chunk.lags().to_vec(), chunk.series_count(), weighted)
+                }
+                State::Writing {
+                    file,
+                    lags,
+                    series_count,
+                    weighted,
+                } => (file, lags, series_count, weighted),
+                State::Finished(_) | State::Failed => unreachable!("the state was checked"),
+            };
+
+        if chunk.lags() != lags
+            || chunk.series_count() != series_count
+            || chunk.row_weights().is_some() != weighted
+        {
+            self.state = State::Writing {
+                file,
+                lags,
+                series_count,
+                weighted,
+            };
+            return Err(ParquetError::General(
+                "the chunk does not match the columns of the first chunk".into(),
+            ));
+        }
+
+        let columns: Vec<ColumnDescPtr> = file.schema_descr().columns().to_vec();
+        let encoded = encode_columns(chunk, &columns, &self.properties, &self.config)?;
+        let mut row_group = file.next_row_group()?;
+        for (bytes, close) in encoded {
+            row_group.append_column(&bytes, close)?;
+        }
+        row_group.close()?;
+
+        self.state = State::Writing {
+            file,
+            lags,
+            series_count,
+            weighted,
+        };
+        Ok(())
+    }
+}
+
+impl<T, W> ChunkSink<T> for ParquetSink<W>
+where
+    T: ParquetElement,
+    W: Write + Send,
+{
+    type Error = ParquetError;
+
+    /// Writes the chunk as the next row group; chunks of other lags or series than the first
+    /// chunk, or that differ from it in carrying row weights, are rejected with
+    /// [`ParquetError::General`].
+    fn write_chunk(&mut self, chunk: &LagMatrix<T>) -> Result<(), Self::Error> {
+        self.write_row_group(chunk)
+    }
+
+    fn finish(&mut self) -> Result<(), Self::Error> {
+        ParquetSink::finish(self)
+    }
+}
+
+impl<W> core::fmt::Debug for ParquetSink<W>
+where
+    W: Write + Send,
+{
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        let state = match self.state {
+            State::Pending(_) => "Pending",
+            State::Writing { .. } => "Writing",
+            State::Finished(_) => "Finished",
+            State::Failed => "Failed",
+        };
+        f.debug_struct("ParquetSink")
+            .field("state", &state)
+            .field("config", &self.config)
+            .finish_non_exhaustive()
+    }
+}
+
+/// The error of writing to a sink after an earlier write failed.
+fn failed() -> ParquetError {
+    ParquetError::General("the Parquet file is incomplete after a failed write".into())
+}
+
+/// Encodes the columns of a chunk on up to [`ParallelConfig::effective_threads`] threads,
+/// in the order of the columns.
+fn encode_columns<T: ParquetElement>(
+    chunk: &LagMatrix<T>,
+    columns: &[ColumnDescPtr],
+    properties: &WriterPropertiesPtr,
+    config: &ParallelConfig,
+) -> Result<Vec<(Bytes, ColumnCloseResult)>, ParquetError> {
+    let encode = |features: core::ops::Range<usize>| {
+        features
+            .map(|feature| encode_column(chunk, feature, columns[feature].clone(), properties))
+            .collect::<Vec<_>>()
+    };
+
+    let num_columns = columns.len();
+    let per_chunk = config.units_per_chunk(chunk.series_length(), num_columns);
+    if per_chunk >= num_columns {
+        return encode(0..num_columns).into_iter().collect();
+    }
+
+    std::thread::scope(|scope| {
+        let encode = &encode;
+        let workers: Vec<_> = (per_chunk..num_columns)
+            .step_by(per_chunk)
+            .map(|start| scope.spawn(move || encode(start..num_columns.min(start + per_chunk))))
+            .collect();
+
+        let mut encoded = encode(0..per_chunk);
+        for worker in workers {
+            encoded.extend(worker.join().expect("encoding a column does not panic"));
+        }
+        encoded.into_iter().collect()
+    })
+}
+
+/// Encodes the values of feature `feature` of a chunk as a column chunk, or the row weights
+/// if `feature` is the column after the last feature.
+fn encode_column<T: ParquetElement>(
+    chunk: &LagMatrix<T>,
+    feature: usize,
+    column: ColumnDescPtr,
+    properties: &WriterPropertiesPtr,
+) -> Result<(Bytes, ColumnCloseResult), ParquetError> {
+    let values: Vec<T> = match chunk.row_weights() {
+        Some(weights) if feature == chunk.features_per_step() => weights.to_vec(),
+        _ => {
+            let (lag_index, series) = (
+                feature / chunk.series_count(),
+                feature % chunk.series_count(),
+            );
+            (0..chunk.series_length())
+                .map(|t| chunk[chunk.offset_of(lag_index, series, t)])
+                .collect()
+        }
+    };
+
+    let mut buffer = TrackedWrite::new(Vec::new());
+    let mut writer = ColumnWriterImpl::<T::DataType>::new(
+        column,
+        properties.clone(),
+        Box::new(SerializedPageWriter::new(&mut buffer)),
+    );
+    writer.write_batch(&values, None, None)?;
+    let close = writer.close()?;
+    Ok((Bytes::from(buffer.into_inner()?), close))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{lag_matrix, lag_matrix_2d, MatrixLayout};
+    use parquet::file::reader::{FileReader, SerializedFileReader};
+    use parquet::record::Field;
+
+    fn read_rows(file: Vec<u8>) -> (Vec<String>, usize, Vec<Vec<f64>>) {
+        let reader = SerializedFileReader::new(Bytes::from(file)).unwrap();
+        let metadata = reader.metadata();
+        let names = metadata
+            .file_metadata()
+            .schema_descr()
+            .columns()
+            .iter()
+            .map(|column| column.name().to_string())
+            .collect();
+        let row_groups = metadata.num_row_groups();
+        let rows = reader
+            .get_row_iter(None)
+            .unwrap()
+            .map(|row| {
+                row.unwrap()
+                    .get_column_iter()
+                    .map(|(_, field)| match field {
+                        Field::Double(value) => *value,
+                        field => panic!("unexpected field {field:?}"),
+                    })
+                    .collect()
+            })
+            .collect();
+        (names, row_groups, rows)
+    }
+
+    #[test]
+    fn test_writes_one_row_group_per_chunk() {
+        let data = [1.0, 2.0, 3.0, 10.0, 20.0, 30.0];
+        let first = lag_matrix_2d(&data, MatrixLayout::RowMajor(3), [0, 1], 0.0, 0).unwrap();
+        let second = lag_matrix_2d(&data, MatrixLayout::RowMajor(3), [0, 1], 0.0, 4).unwrap();
+
+        let mut file = Vec::new();
+        let mut sink = ParquetSink::new(&mut file);
+        sink.write_chunk(&first).unwrap();
+        sink.write_chunk(&second.transpose()).unwrap();
+        sink.finish().unwrap();
+        drop(sink);
+
+        let (names, row_groups, rows) = read_rows(file);
+        assert_eq!(names, ["lag_0_0", "lag_0_1", "lag_1_0", "lag_1_1"]);
+        assert_eq!(row_groups, 2);
+        assert_eq!(rows.len(), 6);
+        for (t, row) in rows.iter().enumerate() {
+            let t = t % 3;
+            let lagged = |series: usize| {
+                if t == 0 {
+                    0.0
+                } else {
+                    data[series * 3 + t - 1]
+                }
+            };
+            assert_eq!(row, &[data[t], data[3 + t], lagged(0), lagged(1)], "at {t}");
+        }
+    }
+
+    #[test]
+    fn test_concurrent_encoding_matches_single_thread() {
+        let data: Vec<f64> = (0..500).map(f64::from).collect();
+        let chunk = lag_matrix(&data, 0..=7, f64::NAN, 0).unwrap();
+        let write = |threads| {
+            let config = ParallelConfig {
+                threads,
+                min_chunk_elems: 1,
+            };
+            let mut sink = ParquetSink::new(Vec::new()).parallel(config);
+            sink.write_chunk(&chunk).unwrap();
+            sink.into_inner().unwrap()
+        };
+
+        let single = write(1);
+        assert_eq!(write(3), single);
+        assert_eq!(write(8), single);
+        assert_eq!(read_rows(single).2.len(), 500);
+    }
+
+    #[test]
+    fn test_writes_row_weights() {
+        let data = [1.0, 2.0, 3.0];
+        let weights = crate::recency_weights(3, 1.0);
+        let chunk = lag_matrix(&data, 0..=1, 0.0, 0)
+            .unwrap()
+            .with_row_weights(weights.clone())
+            .unwrap();
+
+        let mut sink = ParquetSink::new(Vec::new());
+        sink.write_chunk(&chunk).unwrap();
+        sink.write_chunk(&chunk.transpose()).unwrap();
+        let unweighted = lag_matrix(&data, 0..=1, 0.0, 0).unwrap();
+        assert!(sink.write_chunk(&unweighted).is_err());
+
+        let (names, _, rows) = read_rows(sink.into_inner().unwrap());
+        assert_eq!(names, ["lag_0", "lag_1", "weight"]);
+        assert_eq!(rows.len(), 6);
+        for (t, row) in rows.iter().enumerate() {
+            let t = t % 3;
+            assert_eq!(row[2], weights[t], "at {t}");
+        }
+    }
+
+    #[test]
+    fn test_rejects_mismatching_chunks() {
+        let data = [1, 2, 3, 4];
+        let sink = ParquetSink::new(Vec::new());
+        assert!(sink.into_inner().unwrap().is_empty());
+
+        let mut sink = ParquetSink::new(Vec::new());
+        sink.write_chunk(&lag_matrix(&data, 0..=1, 0i32, 0).unwrap())
+            .unwrap();
+        let other = lag_matrix(&data, 1..=2, 0, 0).unwrap();
+        assert!(matches!(
+            sink.write_chunk(&other),
+            Err(ParquetError::General(_))
+        ));
+
+        sink.finish().unwrap();
+        assert!(sink.write_chunk(&other).is_err());
+        assert!(sink.into_inner().unwrap().ends_with(b"PAR1"));
+    }
+}
@@ -0,0 +1,325 @@
+use crate::{collect_lags, exceeds_length, shifted_ranges, IntoLag, LagError, MatrixLayout};
+use core::ops::Deref;
+
+/// A lag matrix of multiple time series with a separate selection of lags per series;
+/// see [`lag_matrix_2d_per_series`].
+///
+/// The features are ordered by series, then by lag: all lags of the first series, followed
+/// by all lags of the second series and so on. Row-major matrices hold one row per feature,
+/// column-major matrices one row per time step.
+#[derive(Debug, Clone, PartialEq)]
+pub struct PerSeriesLagMatrix<T> {
+    data: Vec<T>,
+    row_major: bool,
+    series_length: usize,
+    row_stride: usize,
+    lags: Vec<Vec<isize>>,
+}
+
+impl<T> PerSeriesLagMatrix<T> {
+    /// The number of rows.
+    #[inline(always)]
+    pub fn num_rows(&self) -> usize {
+        if self.row_major {
+            self.num_features()
+        } else {
+            self.series_length
+        }
+    }
+
+    /// The number of columns, excluding row padding.
+    #[inline(always)]
+    pub fn num_cols(&self) -> usize {
+        if self.row_major {
+            self.series_length
+        } else {
+            self.num_features()
+        }
+    }
+
+    /// The number of elements from one row to the next.
+    #[inline(always)]
+    pub const fn row_stride(&self) -> usize {
+        self.row_stride
+    }
+
+    /// Determines whether the matrix holds one row per feature.
+    #[inline(always)]
+    pub const fn is_row_major(&self) -> bool {
+        self.row_major
+    }
+
+    /// The length of every series.
+    #[inline(always)]
+    pub const fn series_length(&self) -> usize {
+        self.series_length
+    }
+
+    /// The number of series.
+    #[inline(always)]
+    pub fn series_count(&self) -> usize {
+        self.lags.len()
+    }
+
+    /// The total number of lags over all series.
+    #[inline(always)]
+    pub fn num_features(&self) -> usize {
+        self.lags.iter().map(Vec::len).sum()
+    }
+
+    /// The lags of the specified series, or `None` if the series does not exist.
+    #[inline(always)]
+    pub fn lags(&self, series: usize) -> Option<&[isize]> {
+        self.lags.get(series).map(Vec::as_slice)
+    }
+
+    /// The index of the feature holding the specified lag of the specified series, i.e. its
+    /// row in row-major and its column in column-major matrices.
+    pub fn feature_index(&self, series: usize, lag: isize) -> Option<usize> {
+        let position = self.lags.get(series)?.iter().position(|&l| l == lag)?;
+        Some(self.lags[..series].iter().map(Vec::len).sum::<usize>() + position)
+    }
+
+    /// The names of the features, e.g. `series1_lag7`, in feature order.
+    pub fn column_names(&self) -> Vec<String> {
+        self.lags
+            .iter()
+            .enumerate()
+            .flat_map(|(series, lags)| {
+                lags.iter().map(move |&lag| match lag {
+                    lag if lag < 0 => format!("series{series}_lead{}", lag.unsigned_abs()),
+                    lag => format!("series{series}_lag{lag}"),
+                })
+            })
+            .collect()
+    }
+
+    /// Gets the element at the specified row and column, or `None` if out of range.
+    pub fn get(&self, row: usize, col: usize) -> Option<&T> {
+        if row >= self.num_rows() || col >= self.num_cols() {
+            return None;
+        }
+        self.data.get(row * self.row_stride + col)
+    }
+
+    /// Returns the underlying vector, including any row padding.
+    #[inline(always)]
+    pub fn into_vec(self) -> Vec<T> {
+        self.data
+    }
+}
+
+impl<T> Deref for PerSeriesLagMatrix<T> {
+    type Target = [T];
+
+    #[inline(always)]
+    fn deref(&self) -> &Self::Target {
+        &self.data
+    }
+}
+
+impl<T, const N: usize> PartialEq<&[T; N]> for PerSeriesLagMatrix<T>
+where
+    T: PartialEq,
+{
+    fn eq(&self, other: &&[T; N]) -> bool {
+        self.data.eq(other)
+    }
+}
+
+/// Create a time-lagged matrix of multiple time series, lagging every series by its own
+/// selection of lags, e.g. short-term lags for one series and seasonal lags for another,
+/// instead of the union of all lags.
+///
+/// ## Arguments
+/// * `data_matrix` - The matrix of multiple time series data to create lagged versions of.
+/// * `layout` - The layout of the data matrix, including the series length; the output
+///   matrix uses the same orientation.
+/// * `lags` - The lags to create for every series; negative lags create leads, see [`IntoLag`].
+/// * `fill` - The value to use to fill in lagged gaps.
+/// * `row_stride` - The number of elements along a row of the output matrix, or `0` for
+///   rows without padding.
+///
+/// ## Returns
+/// The matrix, or an error:
+/// * [`LagError::InvalidChannel`] if the number of lag selections differs from the number of series,
+/// * [`LagError::InvalidLags`] if the lags of a series are empty or out of range,
+/// * [`LagError::LagExceedsValueCount`] if a lag spans more than the series length.
+///
+/// ## Example
+/// ```
+/// use timelag::{lag_matrix_2d_per_series, MatrixLayout};
+///
+/// let data = [
+///     1, 2, 3, 4,
+///     10, 20, 30, 40,
+/// ];
+/// let lags: [&[usize]; 2] = [&[1, 2], &[3]];
+/// let lagged = lag_matrix_2d_per_series(&data, MatrixLayout::RowMajor(4), &lags, 0, 0).unwrap();
+///
+/// assert_eq!(lagged.column_names(), ["series0_lag1", "series0_lag2", "series1_lag3"]);
+/// assert_eq!(lagged.num_rows(), 3);
+/// assert_eq!(
+///     lagged,
+///     &[
+///         0, 1, 2, 3,
+///         0, 0, 1, 2,
+///         0, 0, 0, 10,
+///     ]
+/// );
+/// ```
+pub fn lag_matrix_2d_per_series<T, L, R>(
+    data_matrix: &[T],
+    layout: MatrixLayout,
+    lags: &[R],
+    fill: T,
+    row_stride: usize,
+) -> Result<PerSeriesLagMatrix<T>, LagError>
+where
+    T: Copy,
+    L: IntoLag,
+    R: AsRef<[L]>,
+{
+    if data_matrix.is_empty() {
+        return Err(LagError::EmptyData);
+    }
+
+    let series_length = layout.len();
+    if series_length == 0 || !data_matrix.len().is_multiple_of(series_length) {
+        return Err(LagError::InvalidLength);
+    }
+    let series_count = data_matrix.len() / series_length;
+    if lags.len() != series_count {
+        return Err(LagError::InvalidChannel);
+    }
+
+    let lags = lags
+        .iter()
+        .map(|lags| collect_lags(lags.as_ref().iter().copied()))
+        .collect::<Result<Vec<_>, _>>()?;
+    if lags.iter().any(Vec::is_empty) {
+        return Err(LagError::InvalidLags);
+    }
+    if lags.iter().any(|lags| exceeds_length(lags, series_length)) {
+        return Err(LagError::LagExceedsValueCount);
+    }
+
+    let row_major = matches!(layout, MatrixLayout::RowMajor(_));
+    let num_features: usize = lags.iter().map(Vec::len).sum();
+    let width = if row_major {
+        series_length
+    } else {
+        num_features
+    };
+    let row_stride = match row_stride {
+        0 => width,
+        stride if stride < width => return Err(LagError::InvalidStride),
+        stride => stride,
+    };
+    let num_rows = if row_major {
+        num_features
+    } else {
+        series_length
+    };
+
+    let mut data = vec![fill; num_rows * row_stride];
+    let features = lags
+        .iter()
+        .enumerate()
+        .flat_map(|(series, lags)| lags.iter().map(move |&lag| (series, lag)));
+    // The values are read from the data matrix as they are, in either layout.
+    for (feature, (series, lag)) in features.enumerate() {
+        let (sources, targets) = shifted_ranges(lag, series_length);
+        if row_major {
+            let series = &data_matrix[series * series_length..(series + 1) * series_length];
+            let row = &mut data[feature * row_stride..feature * row_stride + series_length];
+            row[targets].copy_from_slice(&series[sources]);
+        } else {
+            for (source, t) in sources.zip(targets) {
+                data[t * row_stride + feature] = data_matrix[source * series_count + series];
+            }
+        }
+    }
+
+    Ok(PerSeriesLagMatrix {
+        data,
+        row_major,
+        series_length,
+        row_stride,
+        lags,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{lag_matrix_2d, to_column_major};
+
+    #[test]
+    #[rustfmt::skip]
+    fn test_column_major_matches_selected_columns() {
+        let data = [
+            1, 10,
+            2, 20,
+            3, 30,
+            4, 40,
+        ];
+        let lags: [Vec<isize>; 2] = [vec![0, -1], vec![2]];
+        let lagged = lag_matrix_2d_per_series(&data, MatrixLayout::ColumnMajor(4), &lags, -1, 4).unwrap();
+        assert_eq!(lagged, &[
+            1, 2, -1, -1,
+            2, 3, -1, -1,
+            3, 4, 10, -1,
+            4, -1, 20, -1,
+        ]);
+
+        // Every feature equals the matching column of the full lag matrix.
+        let full = lag_matrix_2d(&data, MatrixLayout::ColumnMajor(4), [0, -1, 2], -1, 0).unwrap();
+        let feature = lagged.feature_index(1, 2).unwrap();
+        assert_eq!(feature, 2);
+        for t in 0..4 {
+            assert_eq!(lagged.get(t, feature), full.get(t, 5));
+        }
+        assert_eq!(lagged.column_names()[1], "series0_lead1");
+    }
+
+    #[test]
+    fn test_layouts_agree() {
+        let rows = [1, 2, 3, 4, 5, 10, 20, 30, 40, 50];
+        let columns = to_column_major(&rows, MatrixLayout::RowMajor(5)).unwrap();
+        let lags: [Vec<isize>; 2] = [vec![1, -2], vec![0, 3, -1]];
+
+        let row_major =
+            lag_matrix_2d_per_series(&rows, MatrixLayout::RowMajor(5), &lags, 0, 7).unwrap();
+        let column_major =
+            lag_matrix_2d_per_series(&columns, MatrixLayout::ColumnMajor(5), &lags, 0, 0).unwrap();
+        for feature in 0..row_major.num_features() {
+            for t in 0..5 {
+                assert_eq!(row_major.get(feature, t), column_major.get(t, feature));
+            }
+        }
+    }
+
+    #[test]
+    fn test_invalid_lag_selections() {
+        let data = [1, 2, 3, 4];
+        let layout = MatrixLayout::RowMajor(2);
+        let one: [&[usize]; 1] = [&[1]];
+        assert_eq!(
+            lag_matrix_2d_per_series(&data, layout, &one, 0, 0),
+            Err(LagError::InvalidChannel)
+        );
+
+        let empty: [&[usize]; 2] = [&[1], &[]];
+        assert_eq!(
+            lag_matrix_2d_per_series(&data, layout, &empty, 0, 0),
+            Err(LagError::InvalidLags)
+        );
+
+        let long: [&[usize]; 2] = [&[1], &[3]];
+        assert_eq!(
+            lag_matrix_2d_per_series(&data, layout, &long, 0, 0),
+            Err(LagError::LagExceedsValueCount)
+        );
+    }
+}
@@ -0,0 +1,554 @@
+use crate::{ChunkedLagger, IntoLag, LagError, LagMatrix};
+use core::fmt::{Display, Formatter};
+use std::io::{BufRead, Write};
+use std::str::FromStr;
+use std::sync::mpsc;
+
+/// A destination for the lagged chunks produced by [`lag_csv_column`].
+///
+/// Implementations typically append every chunk to a file, e.g. as one row group
+/// of a columnar format, or forward it to a model.
+pub trait ChunkSink<T> {
+    /// The error returned when a chunk cannot be written.
+    type Error;
+
+    /// Writes the next lagged chunk.
+    fn write_chunk(&mut self, chunk: &LagMatrix<T>) -> Result<(), Self::Error>;
+
+    /// Completes the output after the last chunk was written.
+    fn finish(&mut self) -> Result<(), Self::Error> {
+        Ok(())
+    }
+}
+
+/// Writes lagged chunks as CSV rows, one row per time step, preceded by a header.
+///
+/// The columns are named `lag_<k>` for a single series, and `lag_<k>_<series>` otherwise.
+/// If the first chunk carries [`row_weights`](LagMatrix::row_weights), they are written as
+/// a trailing `weight` column and all further chunks must carry weights as well.
+#[derive(Debug)]
+pub struct CsvSink<W> {
+    writer: W,
+    /// Whether the header includes a weight column, once it was written.
+    weighted: Option<bool>,
+}
+
+impl<W> CsvSink<W>
+where
+    W: Write,
+{
+    /// Creates a sink writing to the specified writer.
+    pub const fn new(writer: W) -> Self {
+        Self {
+            writer,
+            weighted: None,
+        }
+    }
+
+    /// Returns the underlying writer.
+    pub fn into_inner(self) -> W {
+        self.writer
+    }
+
+    fn write_header(&mut self, chunk: &LagMatrix<impl Copy>) -> std::io::Result<()> {
+        for column in 0..chunk.num_cols() {
+            if column > 0 {
+                write!(self.writer, ",")?;
+            }
+            write!(self.writer, "{}", column_name(chunk, column))?;
+        }
+        if chunk.row_weights().is_some() {
+            write!(self.writer, ",weight")?;
+        }
+        writeln!(self.writer)
+    }
+}
+
+/// The name of the column holding feature `feature` of a chunk, i.e. `lag_<k>` for a single
+/// series and `lag_<k>_<series>` otherwise.
+pub(crate) fn column_name<T, S>(chunk: &LagMatrix<T, S>, feature: usize) -> String {
+    let series_count = chunk.series_count();
+    let lag = chunk.lags()[feature / series_count];
+    if series_count == 1 {
+        format!("lag_{lag}")
+    } else {
+        format!("lag_{lag}_{}", feature % series_count)
+    }
+}
+
+impl<T, W> ChunkSink<T> for CsvSink<W>
+where
+    T: Copy + Display,
+    W: Write,
+{
+    type Error = std::io::Error;
+
+    /// Writes the rows of a column-major chunk; row-major chunks and chunks that differ from
+    /// the first chunk in carrying row weights are rejected with
+    /// [`std::io::ErrorKind::InvalidInput`].
+    fn write_chunk(&mut self, chunk: &LagMatrix<T>) -> Result<(), Self::Error> {
+        if chunk.is_row_major() {
+            return Err(std::io::Error::new(
+                std::io::ErrorKind::InvalidInput,
+                "CSV output requires column-major chunks",
+            ));
+        }
+
+        let weights = chunk.row_weights();
+        match self.weighted {
+            None => {
+                self.write_header(chunk)?;
+                self.weighted = Some(weights.is_some());
+            }
+            Some(weighted) if weighted != weights.is_some() => {
+                return Err(std::io::Error::new(
+                    std::io::ErrorKind::InvalidInput,
+                    "the chunk does not match the columns of the first chunk",
+                ));
+            }
+            Some(_) => {}
+        }
+
+        let rows = chunk.chunks(chunk.row_stride()).take(chunk.num_rows());
+        for (t, row) in rows.enumerate() {
+            for (column, value) in row[..chunk.num_cols()].iter().enumerate() {
+                if column > 0 {
+                    write!(self.writer, ",")?;
+                }
+                write!(self.writer, "{value}")?;
+            }
+            if let Some(weights) = weights {
+                write!(self.writer, ",{}", weights[t])?;
+            }
+            writeln!(self.writer)?;
+        }
+        Ok(())
+    }
+
+    fn finish(&mut self) -> Result<(), Self::Error> {
+        self.writer.flush()
+    }
+}
+
+/// Reads the values of a single column from delimited text, e.g. a CSV file.
+///
+/// Fields are split at the delimiter and trimmed; quoted fields are not supported.
+/// Empty lines are skipped.
+#[derive(Debug)]
+pub struct CsvColumnReader<R> {
+    reader: R,
+    column: usize,
+    delimiter: char,
+    line: usize,
+    buffer: String,
+}
+
+impl<R> CsvColumnReader<R>
+where
+    R: BufRead,
+{
+    /// Creates a reader for the column at the specified zero-based index.
+    ///
+    /// ## Arguments
+    /// * `reader` - The source of the delimited text.
+    /// * `column` - The zero-based index of the column to read.
+    /// * `skip_header` - Whether the first line is a header and should be skipped.
+    pub fn by_index(reader: R, column: usize, skip_header: bool) -> Result<Self, CsvError> {
+        let mut reader = Self {
+            reader,
+            column,
+            delimiter: ',',
+            line: 0,
+            buffer: String::new(),
+        };
+
+        if skip_header {
+            reader.next_line()?;
+        }
+        Ok(reader)
+    }
+
+    /// Creates a reader for the column with the specified name in the header line.
+    pub fn by_name(reader: R, name: &str) -> Result<Self, CsvError> {
+        Self::by_name_delimited(reader, name, ',')
+    }
+
+    /// Creates a reader for the column with the specified name in the header line,
+    /// using a custom field delimiter.
+    pub fn by_name_delimited(reader: R, name: &str, delimiter: char) -> Result<Self, CsvError> {
+        let mut reader = Self {
+            reader,
+            column: 0,
+            delimiter,
+            line: 0,
+            buffer: String::new(),
+        };
+
+        let position = match reader.next_line()? {
+            true => reader
+                .record()
+                .split(delimiter)
+                .position(|field| field.trim() == name),
+            false => None,
+        };
+        reader.column = position.ok_or_else(|| CsvError::UnknownColumn(name.to_string()))?;
+        Ok(reader)
+    }
+
+    /// Sets the field delimiter; defaults to a comma.
+    pub fn with_delimiter(mut self, delimiter: char) -> Self {
+        self.delimiter = delimiter;
+        self
+    }
+
+    /// The number of lines read so far.
+    #[inline(always)]
+    pub const fn line(&self) -> usize {
+        self.line
+    }
+
+    /// Reads up to `max_values` values of the column.
+    ///
+    /// ## Returns
+    /// The values read, which is empty once the input is exhausted.
+    pub fn read_chunk<T>(&mut self, max_values: usize) -> Result<Vec<T>, CsvError>
+    where
+        T: FromStr,
+    {
+        let mut values = Vec::with_capacity(max_values);
+        self.read_chunk_into(&mut values, max_values)?;
+        Ok(values)
+    }
+
+    /// Reads up to `max_values` values of the column into the buffer, replacing its contents.
+    pub fn read_chunk_into<T>(
+        &mut self,
+        values: &mut Vec<T>,
+        max_values: usize,
+    ) -> Result<(), CsvError>
+    where
+        T: FromStr,
+    {
+        values.clear();
+        while values.len() < max_values {
+            if !self.next_line()? {
+                break;
+            }
+
+            let Some(field) = self.record().split(self.delimiter).nth(self.column) else {
+                return Err(CsvError::MissingColumn { line: self.line });
+            };
+
+            let field = field.trim();
+            let value = field.parse().map_err(|_| CsvError::Parse {
+                line: self.line,
+                value: field.to_string(),
+            })?;
+            values.push(value);
+        }
+        Ok(())
+    }
+
+    /// Reads the next non-empty line, returning `false` at the end of the input.
+    fn next_line(&mut self) -> Result<bool, CsvError> {
+        loop {
+            self.buffer.clear();
+            if self.reader.read_line(&mut self.buffer)? == 0 {
+                return Ok(false);
+            }
+
+            self.line += 1;
+            if !self.buffer.trim().is_empty() {
+                return Ok(true);
+            }
+        }
+    }
+
+    /// The line read last, without the line break.
+    fn record(&self) -> &str {
+        self.buffer.trim_end_matches(['\r', '\n'])
+    }
+}
+
+/// Lags a column of delimited text chunk by chunk and writes the chunks to a sink.
+///
+/// Reading and lagging run on a worker thread while the calling thread writes the previous
+/// chunk, so that parsing and output overlap. The lags are carried over chunk boundaries by
+/// a [`ChunkedLagger`], making the output identical to lagging the whole column at once
+/// while only a few chunks are held in memory.
+///
+/// To produce Parquet files with one row group per chunk, use the `ParquetSink` of the
+/// `parquet` feature; other formats can implement [`ChunkSink`].
+///
+/// ## Arguments
+/// * `reader` - The column to read.
+/// * `lags` - The lags to produce; leads are not supported.
+/// * `fill` - The value to use to fill in lagged gaps at the start of the series.
+/// * `chunk_len` - The number of values per chunk.
+/// * `sink` - The destination of the lagged chunks.
+///
+/// ## Returns
+/// The total number of time steps written, or the first error encountered.
+///
+/// ## Example
+/// ```
+/// use timelag::{lag_csv_column, CsvColumnReader, CsvSink};
+///
+/// let csv = "time,value\n0,1\n1,2\n2,3\n";
+/// let reader = CsvColumnReader::by_name(csv.as_bytes(), "value").unwrap();
+///
+/// let mut sink = CsvSink::new(Vec::new());
+/// let rows = lag_csv_column(reader, [0, 1], f64::NAN, 2, &mut sink).unwrap();
+///
+/// assert_eq!(rows, 3);
+/// assert_eq!(
+///     String::from_utf8(sink.into_inner()).unwrap(),
+///     "lag_0,lag_1\n1,NaN\n2,1\n3,2\n"
+/// );
+/// ```
+pub fn lag_csv_column<R, T, S, L, I>(
+    mut reader: CsvColumnReader<R>,
+    lags: I,
+    fill: T,
+    chunk_len: usize,
+    sink: &mut S,
+) -> Result<usize, PipelineError<S::Error>>
+where
+    R: BufRead + Send,
+    T: FromStr + Copy + Send,
+    S: ChunkSink<T>,
+    L: IntoLag,
+    I: IntoIterator<Item = L>,
+{
+    if chunk_len == 0 {
+        return Err(PipelineError::Lag(LagError::InvalidLength));
+    }
+
+    let mut lagger = ChunkedLagger::new(lags, fill)?;
+
+    // Two chunks in flight suffice to keep both threads busy.
+    let (sender, receiver) = mpsc::sync_channel::<Result<LagMatrix<T>, PipelineError<()>>>(2);
+    std::thread::scope(|scope| {
+        scope.spawn(move || {
+            let mut values = Vec::with_capacity(chunk_len);
+            loop {
+                let chunk = match reader.read_chunk_into(&mut values, chunk_len) {
+                    Ok(()) if values.is_empty() => break,
+                    Ok(()) => lagger.push_chunk(&values).map_err(PipelineError::Lag),
+                    Err(error) => Err(PipelineError::Csv(error)),
+                };
+
+                let failed = chunk.is_err();
+                if sender.send(chunk).is_err() || failed {
+                    break;
+                }
+            }
+        });
+
+        let mut rows = 0;
+        for chunk in receiver {
+            let chunk = chunk.map_err(PipelineError::widen)?;
+            sink.write_chunk(&chunk).map_err(PipelineError::Sink)?;
+            rows += chunk.num_rows();
+        }
+
+        sink.finish().map_err(PipelineError::Sink)?;
+        Ok(rows)
+    })
+}
+
+/// An error reading delimited text; see [`CsvColumnReader`].
+#[derive(Debug)]
+pub enum CsvError {
+    /// Reading from the source failed.
+    Io(std::io::Error),
+    /// The header does not contain the requested column.
+    UnknownColumn(String),
+    /// A line has fewer fields than required to read the column.
+    MissingColumn {
+        /// The one-based line number.
+        line: usize,
+    },
+    /// A field could not be parsed as a value.
+    Parse {
+        /// The one-based line number.
+        line: usize,
+        /// The field that failed to parse.
+        value: String,
+    },
+}
+
+impl From<std::io::Error> for CsvError {
+    fn from(value: std::io::Error) -> Self {
+        CsvError::Io(value)
+    }
+}
+
+impl std::error::Error for CsvError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            CsvError::Io(error) => Some(error),
+            _ => None,
+        }
+    }
+}
+
+impl Display for CsvError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            CsvError::Io(error) => write!(f, "Failed to read the input: {error}"),
+            CsvError::UnknownColumn(name) => write!(f, "The header has no column named {name:?}"),
+            CsvError::MissingColumn { line } => {
+                write!(f, "Line {line} does not contain the requested column")
+            }
+            CsvError::Parse { line, value } => {
+                write!(f, "The value {value:?} in line {line} could not be parsed")
+            }
+        }
+    }
+}
+
+/// An error of the [`lag_csv_column`] pipeline.
+#[derive(Debug)]
+pub enum PipelineError<E> {
+    /// The input could not be read.
+    Csv(CsvError),
+    /// The input could not be lagged.
+    Lag(LagError),
+    /// The sink failed to write a chunk.
+    Sink(E),
+}
+
+impl PipelineError<()> {
+    /// Converts an error raised before the sink was involved.
+    fn widen<E>(self) -> PipelineError<E> {
+        match self {
+            PipelineError::Csv(error) => PipelineError::Csv(error),
+            PipelineError::Lag(error) => PipelineError::Lag(error),
+            PipelineError::Sink(()) => unreachable!("the worker never raises sink errors"),
+        }
+    }
+}
+
+impl<E> From<CsvError> for PipelineError<E> {
+    fn from(value: CsvError) -> Self {
+        PipelineError::Csv(value)
+    }
+}
+
+impl<E> From<LagError> for PipelineError<E> {
+    fn from(value: LagError) -> Self {
+        PipelineError::Lag(value)
+    }
+}
+
+impl<E> std::error::Error for PipelineError<E>
+where
+    E: std::error::Error + 'static,
+{
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            PipelineError::Csv(error) => Some(error),
+            PipelineError::Lag(error) => Some(error),
+            PipelineError::Sink(error) => Some(error),
+        }
+    }
+}
+
+impl<E> Display for PipelineError<E>
+where
+    E: Display,
+{
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            PipelineError::Csv(error) => write!(f, "{error}"),
+            PipelineError::Lag(error) => write!(f, "{error}"),
+            PipelineError::Sink(error) => write!(f, "Failed to write a chunk: {error}"),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{lag_matrix_2d, MatrixLayout};
+
+    /// Collects all chunks in memory.
+    #[derive(Default)]
+    struct Collect {
+        values: Vec<i32>,
+        chunks: usize,
+    }
+
+    impl ChunkSink<i32> for Collect {
+        type Error = &'static str;
+
+        fn write_chunk(&mut self, chunk: &LagMatrix<i32>) -> Result<(), Self::Error> {
+            if self.chunks == 100 {
+                return Err("full");
+            }
+            self.values.extend_from_slice(chunk);
+            self.chunks += 1;
+            Ok(())
+        }
+    }
+
+    #[test]
+    fn test_pipeline_matches_lag_matrix() {
+        let data: Vec<i32> = (0..1000).map(|t| (t * 37) % 101).collect();
+        let csv: String = data.iter().map(|value| format!("{value};x\n")).collect();
+
+        let reader = CsvColumnReader::by_index(csv.as_bytes(), 0, false)
+            .unwrap()
+            .with_delimiter(';');
+        let mut sink = Collect::default();
+        let rows = lag_csv_column(reader, [0, 1, 24], -1, 64, &mut sink).unwrap();
+
+        let expected =
+            lag_matrix_2d(&data, MatrixLayout::ColumnMajor(1000), [0, 1, 24], -1, 0).unwrap();
+        assert_eq!(rows, 1000);
+        assert_eq!(sink.chunks, 16);
+        assert_eq!(expected, sink.values.as_slice());
+    }
+
+    #[test]
+    fn test_pipeline_errors() {
+        let csv = "value\n1\n2\nthree\n";
+        let reader = CsvColumnReader::by_name(csv.as_bytes(), "value").unwrap();
+        let error = lag_csv_column(reader, [1], 0, 1, &mut Collect::default()).unwrap_err();
+        assert!(matches!(
+            error,
+            PipelineError::Csv(CsvError::Parse { line: 4, .. })
+        ));
+
+        let data = "1\n".repeat(500);
+        let reader = CsvColumnReader::by_index(data.as_bytes(), 0, false).unwrap();
+        let error = lag_csv_column(reader, [1], 0, 1, &mut Collect::default()).unwrap_err();
+        assert!(matches!(error, PipelineError::Sink("full")));
+
+        assert!(matches!(
+            CsvColumnReader::by_name("a,b\n".as_bytes(), "c").unwrap_err(),
+            CsvError::UnknownColumn(_)
+        ));
+    }
+
+    #[test]
+    fn test_csv_sink_writes_row_weights() {
+        let chunk = lag_matrix_2d(&[1, 2, 3], MatrixLayout::ColumnMajor(3), [0, 1], 0, 0)
+            .unwrap()
+            .with_row_weights(vec![4, 2, 1])
+            .unwrap();
+
+        let mut sink = CsvSink::new(Vec::new());
+        sink.write_chunk(&chunk).unwrap();
+        let unweighted =
+            lag_matrix_2d(&[4, 5], MatrixLayout::ColumnMajor(2), [0, 1], 0, 0).unwrap();
+        let error = sink.write_chunk(&unweighted).unwrap_err();
+        assert_eq!(error.kind(), std::io::ErrorKind::InvalidInput);
+
+        assert_eq!(
+            String::from_utf8(sink.into_inner()).unwrap(),
+            "lag_0,lag_1,weight\n1,0,4\n2,1,2\n3,2,1\n"
+        );
+    }
+}
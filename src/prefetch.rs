@@ -0,0 +1,238 @@
+use crate::{collect_lags, shifted_ranges, IntoLag, LagError, LagMatrix, LagPlan, MatrixLayout};
+use core::ops::Range;
+use std::sync::mpsc;
+
+#[cfg(all(feature = "debug-verify", debug_assertions))]
+use crate::verify;
+
+/// The number of bytes between two touched elements when prefetching, i.e. a typical page size.
+const PAGE_SIZE: usize = 4096;
+
+/// Create a time-lagged matrix of a time series in tiles of time steps, reading ahead into
+/// the next tile on a helper thread while the current tile is processed.
+///
+/// This is meant for huge inputs backed by a memory-mapped file. Instead of passing over the
+/// whole series once per lag, every tile is lagged by all lags before moving on, such that the
+/// series is read sequentially. While a tile is copied, a single helper thread prefetches the
+/// next tile, so the page faults of the next tile overlap with the copying of the current one:
+/// on Unix with the `unsafe` feature, it advises the kernel to read the pages ahead via
+/// `madvise(MADV_WILLNEED)`, and otherwise, or if the advice fails, it touches one element
+/// per page. Tiles should therefore
+/// span many pages, e.g. a few megabytes. The result equals that of
+/// [`lag_matrix`](crate::lag_matrix).
+///
+/// ## Arguments
+/// * `data` - The time series, e.g. a memory-mapped file.
+/// * `lags` - The lags to create; negative lags create leads, see [`IntoLag`].
+/// * `fill` - The value to use to fill in lagged gaps.
+/// * `row_stride` - The number of elements along a row of the matrix, see
+///   [`lag_matrix`](crate::lag_matrix).
+/// * `tile_len` - The number of time steps per tile.
+///
+/// ## Returns
+/// The matrix, or an error, e.g. [`LagError::InvalidLength`] if the tile length is zero.
+///
+/// ## Example
+/// ```
+/// use timelag::lag_matrix_prefetched;
+///
+/// let data: Vec<f64> = (0..10_000).map(f64::from).collect();
+/// let lagged = lag_matrix_prefetched(&data, 0..=3, f64::NAN, 0, 1024).unwrap();
+///
+/// assert_eq!(lagged.num_rows(), 4);
+/// assert_eq!(lagged.get(3, 5000), Some(&4997.0));
+/// ```
+pub fn lag_matrix_prefetched<T, L, R>(
+    data: &[T],
+    lags: R,
+    fill: T,
+    row_stride: usize,
+    tile_len: usize,
+) -> Result<LagMatrix<T>, LagError>
+where
+    T: Copy + Send + Sync,
+    L: IntoLag,
+    R: IntoIterator<Item = L>,
+{
+    if tile_len == 0 {
+        return Err(LagError::InvalidLength);
+    }
+
+    let plan = LagPlan::new(
+        data,
+        MatrixLayout::RowMajor(data.len()),
+        collect_lags(lags)?,
+        None,
+        row_stride,
+    )?;
+    let lagged = vec![fill; plan.unit_len() * plan.num_units()];
+    let mut matrix = plan.into_matrix(lagged);
+
+    let series_length = matrix.series_length;
+    std::thread::scope(|scope| {
+        // A single helper thread prefetches the tiles it is sent, one after the other.
+        let (tiles, requests) = mpsc::channel::<&[T]>();
+        scope.spawn(move || {
+            for tile in requests {
+                prefetch(tile);
+            }
+        });
+
+        let mut start = 0;
+        while start < series_length {
+            let end = (start + tile_len).min(series_length);
+            let next = &data[end..(end + tile_len).min(series_length)];
+            if !next.is_empty() {
+                // The helper only exits once the sender is dropped, so sending cannot fail.
+                let _ = tiles.send(next);
+            }
+
+            copy_tile(&mut matrix, data, start..end);
+            start = end;
+        }
+    });
+
+    #[cfg(all(feature = "debug-verify", debug_assertions))]
+    verify_tiles(&matrix, tile_len);
+
+    Ok(matrix)
+}
+
+/// Copies the time steps `steps` of all lags of the series into the matrix.
+fn copy_tile<T: Copy>(matrix: &mut LagMatrix<T>, data: &[T], steps: Range<usize>) {
+    for lag_index in 0..matrix.num_lags {
+        let shift = matrix.lags[lag_index];
+        let (sources, targets) = shifted_ranges(shift, matrix.series_length);
+        let from = targets.start.max(steps.start);
+        let to = targets.end.min(steps.end);
+        if from >= to {
+            continue;
+        }
+
+        let source = sources.start + (from - targets.start);
+        let offset = matrix.offset_of(lag_index, 0, from);
+        matrix.data[offset..offset + (to - from)]
+            .copy_from_slice(&data[source..source + (to - from)]);
+    }
+}
+
+/// Checks the kernel of [`lag_matrix_prefetched`] against the naive definition by running
+/// it on the indices of the values of the series, tile by tile.
+#[cfg(all(feature = "debug-verify", debug_assertions))]
+fn verify_tiles<T>(matrix: &LagMatrix<T>, tile_len: usize) {
+    let series_length = matrix.series_length;
+    let mut indices = matrix.map_metadata(vec![verify::GAP; matrix.data.len()]);
+    let data = verify::indices(series_length);
+    for start in (0..series_length).step_by(tile_len) {
+        copy_tile(
+            &mut indices,
+            &data,
+            start..(start + tile_len).min(series_length),
+        );
+    }
+
+    for (lag_index, &lag) in matrix.lags.iter().enumerate() {
+        for t in 0..series_length {
+            verify::check(
+                "lag_matrix_prefetched",
+                indices.data[indices.offset_of(lag_index, 0, t)],
+                verify::naive_source(t, lag, series_length),
+                format_args!("lag {lag} at time step {t}"),
+            );
+        }
+    }
+}
+
+/// Faults in the pages of the values ahead of their use.
+fn prefetch<T: Copy>(values: &[T]) {
+    if !advise_will_need(values) {
+        touch_pages(values);
+    }
+}
+
+/// Advises the kernel that the pages of the values will be needed soon, such that it reads
+/// them ahead asynchronously.
+///
+/// ## Returns
+/// `true` if the advice was accepted.
+#[cfg(all(unix, feature = "unsafe"))]
+fn advise_will_need<T>(values: &[T]) -> bool {
+    if values.is_empty() {
+        return true;
+    }
+
+    // SAFETY: `sysconf` has no preconditions.
+    let page_size = match unsafe { libc::sysconf(libc::_SC_PAGESIZE) } {
+        size if size > 0 => size as usize,
+        _ => return false,
+    };
+
+    // The advice applies to whole pages, so the range is widened to page boundaries.
+    let address = values.as_ptr() as usize;
+    let start = address - address % page_size;
+    let len = address + size_of_val(values) - start;
+
+    // SAFETY: `MADV_WILLNEED` only schedules reads of the pages, which hold the borrowed
+    // values; it neither modifies nor unmaps any memory.
+    unsafe { libc::madvise(start as *mut libc::c_void, len, libc::MADV_WILLNEED) == 0 }
+}
+
+/// Advises the kernel that the pages of the values will be needed soon, which requires the
+/// `unsafe` feature on Unix and is not supported elsewhere.
+#[cfg(not(all(unix, feature = "unsafe")))]
+fn advise_will_need<T>(_values: &[T]) -> bool {
+    false
+}
+
+/// Reads one element per page of the values to fault them in.
+fn touch_pages<T: Copy>(values: &[T]) {
+    let step = (PAGE_SIZE / size_of::<T>().max(1)).max(1);
+    for value in values.iter().step_by(step) {
+        std::hint::black_box(*value);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::lag_matrix;
+
+    #[test]
+    fn test_tiles_match_lag_matrix() {
+        let data: Vec<i32> = (0..1000).collect();
+        let expected = lag_matrix(&data, [0, 1, 17, -5, 300], -1, 1024).unwrap();
+        for tile_len in [1, 7, 256, 1000, 5000] {
+            let lagged =
+                lag_matrix_prefetched(&data, [0, 1, 17, -5, 300], -1, 1024, tile_len).unwrap();
+            assert_eq!(lagged, expected);
+        }
+    }
+
+    #[test]
+    fn test_prefetch_unaligned_ranges() {
+        let data: Vec<u8> = (0..=255).cycle().take(3 * PAGE_SIZE + 17).collect();
+        for values in [
+            &data[..],
+            &data[1..],
+            &data[PAGE_SIZE + 3..PAGE_SIZE + 4],
+            &data[..0],
+        ] {
+            #[cfg(all(unix, feature = "unsafe"))]
+            assert!(advise_will_need(values));
+            prefetch(values);
+        }
+        touch_pages(&data);
+    }
+
+    #[test]
+    fn test_invalid_tile_length() {
+        assert_eq!(
+            lag_matrix_prefetched(&[1, 2, 3], [1], 0, 0, 0),
+            Err(LagError::InvalidLength)
+        );
+        assert_eq!(
+            lag_matrix_prefetched(&[1, 2, 3], [4], 0, 0, 2),
+            Err(LagError::LagExceedsValueCount)
+        );
+    }
+}
@@ -0,0 +1,263 @@
+use crate::LagError;
+use num_traits::Float;
+
+/// The matrix profile of a time series; see [`matrix_profile`].
+#[derive(Debug, Clone, PartialEq)]
+pub struct MatrixProfile<T> {
+    window: usize,
+    distances: Vec<T>,
+    indices: Vec<usize>,
+}
+
+impl<T> MatrixProfile<T>
+where
+    T: Float,
+{
+    /// The length of the compared windows.
+    #[inline(always)]
+    pub const fn window(&self) -> usize {
+        self.window
+    }
+
+    /// The z-normalized Euclidean distance of each window to its nearest neighbor.
+    ///
+    /// The window at index `i` spans the values `i..i + window`, which is the delay
+    /// vector at time step `i + window - 1` of a delay embedding with unit delay.
+    #[inline(always)]
+    pub fn distances(&self) -> &[T] {
+        &self.distances
+    }
+
+    /// The start index of the nearest neighbor of each window.
+    #[inline(always)]
+    pub fn indices(&self) -> &[usize] {
+        &self.indices
+    }
+
+    /// The number of windows, i.e. the length of the profile.
+    #[inline(always)]
+    pub fn len(&self) -> usize {
+        self.distances.len()
+    }
+
+    /// Determines whether the profile is empty.
+    #[inline(always)]
+    pub fn is_empty(&self) -> bool {
+        self.distances.is_empty()
+    }
+
+    /// The best matching pair of windows (the top motif) as `(index, neighbor, distance)`.
+    pub fn motif(&self) -> Option<(usize, usize, T)> {
+        self.extremum(|candidate, best| candidate < best)
+    }
+
+    /// The window farthest from its nearest neighbor (the top discord) as `(index, neighbor, distance)`.
+    pub fn discord(&self) -> Option<(usize, usize, T)> {
+        self.extremum(|candidate, best| candidate > best)
+    }
+
+    fn extremum(&self, better: impl Fn(T, T) -> bool) -> Option<(usize, usize, T)> {
+        let mut result: Option<(usize, usize, T)> = None;
+        for (i, (&distance, &neighbor)) in self.distances.iter().zip(&self.indices).enumerate() {
+            if !distance.is_finite() {
+                continue;
+            }
+            match result {
+                Some((_, _, best)) if !better(distance, best) => {}
+                _ => result = Some((i, neighbor, distance)),
+            }
+        }
+        result
+    }
+}
+
+/// Computes the matrix profile of a time series.
+///
+/// For every window of `window` consecutive values, the matrix profile holds the
+/// z-normalized Euclidean distance to the most similar other window, as well as that
+/// window's index. Trivial matches within an exclusion zone of a quarter window length
+/// around each window are ignored. Low profile values indicate repeated patterns (motifs),
+/// high values indicate anomalies (discords).
+///
+/// The profile is computed in `O(n²)` time and `O(n)` memory using the STOMP
+/// algorithm's sliding dot product updates.
+///
+/// ## Arguments
+/// * `data` - The time series data.
+/// * `window` - The length of the compared windows; must be at least two.
+///
+/// ## Returns
+/// The matrix profile, or an error if the window is invalid for the data.
+/// Windows without any admissible neighbor have an infinite distance.
+///
+/// ## Example
+/// ```
+/// use timelag::matrix_profile;
+///
+/// // The pattern 0, 1, 3, 2 repeats at index 8.
+/// let data = [0.0, 1.0, 3.0, 2.0, 9.0, 1.0, 14.0, 15.0, 0.0, 1.0, 3.0, 2.0, 7.0];
+/// let profile = matrix_profile(&data, 4).unwrap();
+///
+/// assert_eq!(profile.len(), 10);
+/// let (a, b, distance) = profile.motif().unwrap();
+/// assert_eq!((a, b), (0, 8));
+/// assert!(distance < 1e-6);
+/// ```
+pub fn matrix_profile<T>(data: &[T], window: usize) -> Result<MatrixProfile<T>, LagError>
+where
+    T: Float,
+{
+    if data.is_empty() {
+        return Err(LagError::EmptyData);
+    }
+    if window < 2 {
+        return Err(LagError::InvalidLags);
+    }
+    if window > data.len() {
+        return Err(LagError::LagExceedsValueCount);
+    }
+
+    let n = data.len() - window + 1;
+    let m = T::from(window).ok_or(LagError::InvalidLength)?;
+    let exclusion = window.div_ceil(4);
+
+    let (means, stds) = window_statistics(data, window, m);
+
+    // Dot products of the first window with all windows; reused for the first column.
+    let first_row: Vec<T> = (0..n).map(|j| dot(data, 0, j, window)).collect();
+    let mut qt = first_row.clone();
+
+    let mut distances = vec![T::infinity(); n];
+    let mut indices = vec![0; n];
+
+    for i in 0..n {
+        if i > 0 {
+            for j in (1..n).rev() {
+                qt[j] = qt[j - 1] - data[i - 1] * data[j - 1]
+                    + data[i + window - 1] * data[j + window - 1];
+            }
+            qt[0] = first_row[i];
+        }
+
+        for j in 0..n {
+            if i.abs_diff(j) <= exclusion {
+                continue;
+            }
+
+            let distance = z_normalized_distance(qt[j], m, means[i], stds[i], means[j], stds[j]);
+            if distance < distances[i] {
+                distances[i] = distance;
+                indices[i] = j;
+            }
+        }
+    }
+
+    Ok(MatrixProfile {
+        window,
+        distances,
+        indices,
+    })
+}
+
+fn dot<T: Float>(data: &[T], a: usize, b: usize, window: usize) -> T {
+    data[a..a + window]
+        .iter()
+        .zip(&data[b..b + window])
+        .fold(T::zero(), |sum, (&x, &y)| sum + x * y)
+}
+
+/// Computes the mean and standard deviation of every window.
+fn window_statistics<T: Float>(data: &[T], window: usize, m: T) -> (Vec<T>, Vec<T>) {
+    data.windows(window)
+        .map(|values| {
+            let mean = values.iter().fold(T::zero(), |sum, &x| sum + x) / m;
+            let variance = values
+                .iter()
+                .fold(T::zero(), |sum, &x| sum + (x - mean) * (x - mean))
+                / m;
+            (mean, variance.sqrt())
+        })
+        .unzip()
+}
+
+fn z_normalized_distance<T: Float>(qt: T, m: T, mean_a: T, std_a: T, mean_b: T, std_b: T) -> T {
+    let two = T::one() + T::one();
+    match (std_a.is_zero(), std_b.is_zero()) {
+        (true, true) => T::zero(),
+        (true, false) | (false, true) => m.sqrt(),
+        (false, false) => {
+            let correlation = (qt - m * mean_a * mean_b) / (m * std_a * std_b);
+            (two * m * (T::one() - correlation)).max(T::zero()).sqrt()
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Computes the matrix profile by comparing all windows directly.
+    fn naive_profile(data: &[f64], window: usize) -> Vec<f64> {
+        let normalize = |values: &[f64]| {
+            let mean = values.iter().sum::<f64>() / values.len() as f64;
+            let std = (values.iter().map(|x| (x - mean).powi(2)).sum::<f64>()
+                / values.len() as f64)
+                .sqrt();
+            values.iter().map(|x| (x - mean) / std).collect::<Vec<_>>()
+        };
+
+        let windows: Vec<_> = data.windows(window).map(normalize).collect();
+        (0..windows.len())
+            .map(|i| {
+                (0..windows.len())
+                    .filter(|j| i.abs_diff(*j) > window.div_ceil(4))
+                    .map(|j| {
+                        windows[i]
+                            .iter()
+                            .zip(&windows[j])
+                            .map(|(a, b)| (a - b).powi(2))
+                            .sum::<f64>()
+                            .sqrt()
+                    })
+                    .fold(f64::INFINITY, f64::min)
+            })
+            .collect()
+    }
+
+    #[test]
+    fn test_matrix_profile_matches_naive() {
+        let data: Vec<f64> = (0..50)
+            .map(|i| (i as f64 * 0.7).sin() + ((i * 7919) % 13) as f64 * 0.05)
+            .collect();
+
+        let profile = matrix_profile(&data, 6).unwrap();
+        let expected = naive_profile(&data, 6);
+
+        assert_eq!(profile.len(), expected.len());
+        for (actual, expected) in profile.distances().iter().zip(&expected) {
+            assert!((actual - expected).abs() < 1e-6, "{actual} != {expected}");
+        }
+    }
+
+    #[test]
+    fn test_matrix_profile_discord() {
+        let mut data: Vec<f64> = (0..40).map(|i| (i as f64 * 0.5).sin()).collect();
+        data[20] = 5.0;
+
+        let profile = matrix_profile(&data, 4).unwrap();
+        let (index, _, _) = profile.discord().unwrap();
+        assert!((17..=20).contains(&index));
+    }
+
+    #[test]
+    fn test_matrix_profile_invalid_window() {
+        assert_eq!(
+            matrix_profile(&[1.0, 2.0], 3).unwrap_err(),
+            LagError::LagExceedsValueCount
+        );
+        assert_eq!(
+            matrix_profile(&[1.0, 2.0], 1).unwrap_err(),
+            LagError::InvalidLags
+        );
+    }
+}
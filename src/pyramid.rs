@@ -0,0 +1,172 @@
+use crate::LagError;
+use num_traits::Float;
+
+/// The multiscale means of the trailing windows of a series; see [`summary_pyramid`].
+#[derive(Debug, Clone, PartialEq)]
+pub struct SummaryPyramid<T> {
+    window: usize,
+    levels: usize,
+    values: Vec<T>,
+}
+
+impl<T> SummaryPyramid<T> {
+    /// The number of values per window.
+    #[inline(always)]
+    pub const fn window(&self) -> usize {
+        self.window
+    }
+
+    /// The number of levels, where level `l` splits the window into `2^l` segments.
+    #[inline(always)]
+    pub const fn levels(&self) -> usize {
+        self.levels
+    }
+
+    /// The number of features per time step, i.e. `2^levels - 1`.
+    #[inline(always)]
+    pub const fn features_per_step(&self) -> usize {
+        (1 << self.levels) - 1
+    }
+
+    /// The number of time steps, i.e. the series length.
+    #[inline(always)]
+    pub fn series_length(&self) -> usize {
+        self.values.len() / self.features_per_step()
+    }
+
+    /// The features of all time steps, stored with one row per time step.
+    #[inline(always)]
+    pub fn values(&self) -> &[T] {
+        &self.values
+    }
+
+    /// The features of the window ending at time step `t`, or `None` if `t` is out of range.
+    pub fn get(&self, t: usize) -> Option<&[T]> {
+        let features = self.features_per_step();
+        self.values.get(t * features..(t + 1) * features)
+    }
+}
+
+/// Summarizes the trailing window of every time step by the means of its halves, quarters
+/// and so on, as a cheap approximation of multiscale (e.g. Haar wavelet) features.
+///
+/// The window of time step `t` holds the values `t + 1 - window..=t`, i.e. the lags
+/// `0..window`. Level `l` splits it into `2^l` segments of equal length, and the features of
+/// a time step are the segment means of level `0` (the window mean), level `1` and so on,
+/// every level in time order. The time steps whose windows reach before the start of the
+/// series are filled in, like the lagged gaps of [`lag_matrix`](crate::lag_matrix). Means
+/// are computed from prefix sums in constant time per segment.
+///
+/// ## Arguments
+/// * `data` - The time series.
+/// * `window` - The number of values per window; must be divisible by `2^(levels - 1)`.
+/// * `levels` - The number of levels.
+/// * `fill` - The value to use for the incomplete windows.
+///
+/// ## Returns
+/// The features, or an error:
+/// * [`LagError::InvalidLength`] if there are no levels or the window cannot be split evenly,
+/// * [`LagError::LagExceedsValueCount`] if the window exceeds the series length.
+///
+/// ## Example
+/// ```
+/// use timelag::summary_pyramid;
+///
+/// let data = [1.0, 3.0, 5.0, 7.0, 9.0];
+/// let pyramid = summary_pyramid(&data, 4, 2, f64::NAN).unwrap();
+///
+/// assert_eq!(pyramid.features_per_step(), 3);
+/// assert!(pyramid.get(2).unwrap()[0].is_nan());
+///
+/// // The window mean, followed by the means of the older and the newer half.
+/// assert_eq!(pyramid.get(3).unwrap(), &[4.0, 2.0, 6.0]);
+/// assert_eq!(pyramid.get(4).unwrap(), &[6.0, 4.0, 8.0]);
+/// ```
+pub fn summary_pyramid<T: Float>(
+    data: &[T],
+    window: usize,
+    levels: usize,
+    fill: T,
+) -> Result<SummaryPyramid<T>, LagError> {
+    let finest = levels
+        .checked_sub(1)
+        .and_then(|level| 1usize.checked_shl(level as u32))
+        .ok_or(LagError::InvalidLength)?;
+    if window == 0 || !window.is_multiple_of(finest) {
+        return Err(LagError::InvalidLength);
+    }
+    if window > data.len() {
+        return Err(LagError::LagExceedsValueCount);
+    }
+
+    let mut prefix = Vec::with_capacity(data.len() + 1);
+    prefix.push(T::zero());
+    for &value in data {
+        prefix.push(prefix[prefix.len() - 1] + value);
+    }
+
+    let features = (1 << levels) - 1;
+    let mut values = Vec::with_capacity(data.len() * features);
+    values.resize((window - 1) * features, fill);
+    for start in 0..=data.len() - window {
+        for level in 0..levels {
+            let segments = 1 << level;
+            let length = window / segments;
+            let count = T::from(length).ok_or(LagError::InvalidLength)?;
+            for segment in 0..segments {
+                let from = start + segment * length;
+                values.push((prefix[from + length] - prefix[from]) / count);
+            }
+        }
+    }
+
+    Ok(SummaryPyramid {
+        window,
+        levels,
+        values,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_segments_match_direct_means() {
+        let data: Vec<f64> = (0..20).map(|t| f64::from(t * t % 7)).collect();
+        let pyramid = summary_pyramid(&data, 8, 3, 0.0).unwrap();
+        assert_eq!(pyramid.series_length(), 20);
+        assert_eq!(pyramid.get(6), Some(&[0.0; 7][..]));
+
+        let mean = |values: &[f64]| values.iter().sum::<f64>() / values.len() as f64;
+        for t in 7..20 {
+            let window = &data[t - 7..=t];
+            let expected: Vec<f64> = [1, 2, 4]
+                .iter()
+                .flat_map(|&segments| window.chunks(8 / segments).map(mean))
+                .collect();
+            let actual = pyramid.get(t).unwrap();
+            assert!(actual
+                .iter()
+                .zip(&expected)
+                .all(|(a, b)| (a - b).abs() < 1e-12));
+        }
+    }
+
+    #[test]
+    fn test_invalid_windows() {
+        let data = [1.0; 8];
+        assert_eq!(
+            summary_pyramid(&data, 4, 0, 0.0),
+            Err(LagError::InvalidLength)
+        );
+        assert_eq!(
+            summary_pyramid(&data, 6, 3, 0.0),
+            Err(LagError::InvalidLength)
+        );
+        assert_eq!(
+            summary_pyramid(&data, 16, 1, 0.0),
+            Err(LagError::LagExceedsValueCount)
+        );
+    }
+}
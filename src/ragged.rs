@@ -0,0 +1,169 @@
+use crate::{collect_lags, lag_matrix, lag_matrix_2d, IntoLag, LagError, LagMatrix, MatrixLayout};
+
+/// How series of different lengths are aligned in time; see [`lag_matrix_ragged`].
+#[derive(Debug, Copy, Clone, Eq, PartialEq, Hash, Default)]
+pub enum RaggedAlignment {
+    /// The series share the last time step, e.g. sensors that started recording at different
+    /// times; shorter series are padded at the start.
+    #[default]
+    End,
+    /// The series share the first time step; shorter series are padded at the end.
+    Start,
+}
+
+/// Creates one lag matrix per series for series of possibly different lengths.
+///
+/// ## Arguments
+/// * `series` - The independent time series.
+/// * `lags` - The lags to create for every series; negative lags create leads, see [`IntoLag`].
+/// * `fill` - The value to use to fill in lagged gaps.
+/// * `row_stride` - The number of elements along a row of every matrix, see [`lag_matrix`].
+///
+/// ## Returns
+/// The matrices in the order of the series, or an error, e.g. [`LagError::EmptyData`] if
+/// there are no series or one of them is empty.
+///
+/// ## Example
+/// ```
+/// use timelag::lag_matrices;
+///
+/// let series: [&[i32]; 2] = [&[1, 2, 3], &[4, 5]];
+/// let lagged = lag_matrices(&series, 0..=1, 0, 0).unwrap();
+///
+/// assert_eq!(lagged[0], &[1, 2, 3, 0, 1, 2]);
+/// assert_eq!(lagged[1], &[4, 5, 0, 4]);
+/// ```
+pub fn lag_matrices<T, S, L, R>(
+    series: &[S],
+    lags: R,
+    fill: T,
+    row_stride: usize,
+) -> Result<Vec<LagMatrix<T>>, LagError>
+where
+    T: Copy,
+    S: AsRef<[T]>,
+    L: IntoLag,
+    R: IntoIterator<Item = L>,
+{
+    if series.is_empty() {
+        return Err(LagError::EmptyData);
+    }
+
+    let lags = collect_lags(lags)?;
+    series
+        .iter()
+        .map(|series| lag_matrix(series.as_ref(), lags.iter().copied(), fill, row_stride))
+        .collect()
+}
+
+/// Creates a combined lag matrix of series of possibly different lengths, padding the
+/// shorter series with the fill value according to the alignment.
+///
+/// The padded series are lagged like the rows of a row-major data matrix, see
+/// [`lag_matrix_2d`], such that the result holds one row per lag and series.
+///
+/// ## Arguments
+/// * `series` - The independent time series.
+/// * `alignment` - Whether the series share their last or their first time step.
+/// * `lags` - The lags to create for every series; negative lags create leads, see [`IntoLag`].
+/// * `fill` - The value to pad the shorter series with and to fill in lagged gaps.
+/// * `row_stride` - The number of elements along a row of the matrix, see [`lag_matrix_2d`].
+///
+/// ## Returns
+/// The matrix, or an error, e.g. [`LagError::EmptyData`] if there are no series or all of
+/// them are empty.
+///
+/// ## Example
+/// ```
+/// use timelag::{lag_matrix_ragged, RaggedAlignment};
+///
+/// let series: [&[i32]; 2] = [&[1, 2, 3], &[4, 5]];
+/// let lagged = lag_matrix_ragged(&series, RaggedAlignment::End, [0, 1], 0, 0).unwrap();
+///
+/// assert_eq!(lagged.series_count(), 2);
+/// assert_eq!(
+///     lagged,
+///     &[
+///         1, 2, 3,
+///         0, 4, 5,
+///         0, 1, 2,
+///         0, 0, 4,
+///     ]
+/// );
+/// ```
+pub fn lag_matrix_ragged<T, S, L, R>(
+    series: &[S],
+    alignment: RaggedAlignment,
+    lags: R,
+    fill: T,
+    row_stride: usize,
+) -> Result<LagMatrix<T>, LagError>
+where
+    T: Copy,
+    S: AsRef<[T]>,
+    L: IntoLag,
+    R: IntoIterator<Item = L>,
+{
+    let length = series
+        .iter()
+        .map(|series| series.as_ref().len())
+        .max()
+        .unwrap_or(0);
+    if length == 0 {
+        return Err(LagError::EmptyData);
+    }
+
+    let mut padded = Vec::with_capacity(series.len() * length);
+    for series in series {
+        let values = series.as_ref();
+        let padding = core::iter::repeat_n(fill, length - values.len());
+        match alignment {
+            RaggedAlignment::End => {
+                padded.extend(padding);
+                padded.extend_from_slice(values);
+            }
+            RaggedAlignment::Start => {
+                padded.extend_from_slice(values);
+                padded.extend(padding);
+            }
+        }
+    }
+
+    lag_matrix_2d(
+        &padded,
+        MatrixLayout::RowMajor(length),
+        lags,
+        fill,
+        row_stride,
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    #[rustfmt::skip]
+    fn test_start_alignment_pads_at_the_end() {
+        let series = [vec![1, 2], vec![3, 4, 5]];
+        let lagged = lag_matrix_ragged(&series, RaggedAlignment::Start, [1], -1, 4).unwrap();
+        assert_eq!(lagged, &[
+            -1, 1, 2, -1,
+            -1, 3, 4, -1,
+        ]);
+    }
+
+    #[test]
+    fn test_errors() {
+        let none: [&[i32]; 0] = [];
+        assert_eq!(lag_matrices(&none, [0], 0, 0), Err(LagError::EmptyData));
+        assert_eq!(
+            lag_matrix_ragged(&none, RaggedAlignment::End, [0], 0, 0),
+            Err(LagError::EmptyData)
+        );
+
+        let series: [&[i32]; 2] = [&[1, 2, 3], &[]];
+        assert_eq!(lag_matrices(&series, [0], 0, 0), Err(LagError::EmptyData));
+        assert!(lag_matrix_ragged(&series, RaggedAlignment::End, [0], 0, 0).is_ok());
+    }
+}
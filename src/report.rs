@@ -0,0 +1,195 @@
+use crate::LagMatrix;
+use core::fmt::{Display, Formatter};
+
+/// The time steps to drop when counting usable observations; see [`LagMatrix::effective_rows`].
+#[derive(Debug, Copy, Clone, Eq, PartialEq, Hash, Default)]
+pub enum TrimMode {
+    /// Keep all time steps, including those holding fill values.
+    #[default]
+    None,
+    /// Drop the incomplete time steps at the start, i.e. those padded by lags.
+    Forward,
+    /// Drop the incomplete time steps at the end, i.e. those padded by leads.
+    Backward,
+    /// Drop all incomplete time steps, like [`LagMatrix::trim_incomplete`].
+    Both,
+}
+
+impl<T, S> LagMatrix<T, S> {
+    /// The number of time steps that remain usable as observations when trimming the
+    /// incomplete time steps according to `mode`.
+    ///
+    /// ## Example
+    /// ```
+    /// use timelag::{lag_matrix, TrimMode};
+    ///
+    /// let lagged = lag_matrix(&[1.0, 2.0, 3.0, 4.0, 5.0], [-1, 0, 2], 0.0, 0).unwrap();
+    ///
+    /// assert_eq!(lagged.effective_rows(TrimMode::None), 5);
+    /// assert_eq!(lagged.effective_rows(TrimMode::Forward), 3);
+    /// assert_eq!(lagged.effective_rows(TrimMode::Backward), 4);
+    /// assert_eq!(lagged.effective_rows(TrimMode::Both), 2);
+    /// ```
+    pub fn effective_rows(&self, mode: TrimMode) -> usize {
+        let (leading, trailing) = self.incomplete_steps();
+        match mode {
+            TrimMode::None => self.series_length,
+            TrimMode::Forward => self.series_length - leading,
+            TrimMode::Backward => self.series_length - trailing,
+            TrimMode::Both => self.valid_length(),
+        }
+    }
+
+    /// Summarizes the sample size of the matrix and warns about configurations that
+    /// are prone to overfitting.
+    ///
+    /// ## Arguments
+    /// * `max_ratio` - The largest acceptable ratio of features to complete observations;
+    ///   `0.1`, i.e. ten observations per feature, is a common rule of thumb.
+    ///
+    /// ## Example
+    /// ```
+    /// use timelag::{lag_matrix, LagWarning};
+    ///
+    /// let data: Vec<f64> = (0..30).map(f64::from).collect();
+    /// let lagged = lag_matrix(&data, 0..=5, f64::NAN, 0).unwrap();
+    ///
+    /// let report = lagged.report(0.1);
+    /// assert_eq!(report.effective_rows(), 25);
+    /// assert_eq!(report.num_features(), 6);
+    /// assert!(matches!(report.warnings(), [LagWarning::TooManyFeatures { .. }]));
+    /// ```
+    pub fn report(&self, max_ratio: f64) -> LagReport {
+        let num_features = self.features_per_step() + self.static_count;
+        let effective_rows = self.effective_rows(TrimMode::Both);
+
+        let mut warnings = Vec::new();
+        if effective_rows == 0 {
+            warnings.push(LagWarning::NoCompleteRows);
+        } else {
+            let ratio = num_features as f64 / effective_rows as f64;
+            if ratio > max_ratio {
+                warnings.push(LagWarning::TooManyFeatures { ratio, max_ratio });
+            }
+        }
+
+        LagReport {
+            observations: self.series_length,
+            effective_rows,
+            num_features,
+            warnings,
+        }
+    }
+}
+
+/// A summary of the sample size of a lag matrix; see [`LagMatrix::report`].
+#[derive(Debug, Clone, PartialEq)]
+pub struct LagReport {
+    observations: usize,
+    effective_rows: usize,
+    num_features: usize,
+    warnings: Vec<LagWarning>,
+}
+
+impl LagReport {
+    /// The number of time steps in the matrix, including incomplete ones.
+    #[inline(always)]
+    pub const fn observations(&self) -> usize {
+        self.observations
+    }
+
+    /// The number of time steps without fill values.
+    #[inline(always)]
+    pub const fn effective_rows(&self) -> usize {
+        self.effective_rows
+    }
+
+    /// The number of features per time step, i.e. lagged values and static columns.
+    #[inline(always)]
+    pub const fn num_features(&self) -> usize {
+        self.num_features
+    }
+
+    /// The ratio of features to complete observations, or `None` if there are none.
+    pub fn ratio(&self) -> Option<f64> {
+        (self.effective_rows > 0).then(|| self.num_features as f64 / self.effective_rows as f64)
+    }
+
+    /// The problems found in the matrix configuration.
+    #[inline(always)]
+    pub fn warnings(&self) -> &[LagWarning] {
+        &self.warnings
+    }
+
+    /// Determines whether any problems were found.
+    #[inline(always)]
+    pub fn has_warnings(&self) -> bool {
+        !self.warnings.is_empty()
+    }
+}
+
+/// A problem found in a lag matrix configuration; see [`LagReport::warnings`].
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub enum LagWarning {
+    /// Every time step holds at least one fill value.
+    NoCompleteRows,
+    /// There are too few complete observations per feature.
+    TooManyFeatures {
+        /// The ratio of features to complete observations.
+        ratio: f64,
+        /// The largest acceptable ratio.
+        max_ratio: f64,
+    },
+}
+
+impl Display for LagWarning {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            LagWarning::NoCompleteRows => write!(f, "Every time step holds a fill value"),
+            LagWarning::TooManyFeatures { ratio, max_ratio } => write!(
+                f,
+                "The ratio of features to complete observations is {ratio:.3}, exceeding {max_ratio}"
+            ),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{lag_matrix_2d, MatrixLayout};
+
+    #[test]
+    fn test_report_counts_static_columns() {
+        let data: Vec<f64> = (0..40).map(f64::from).collect();
+        let lagged = lag_matrix_2d(&data, MatrixLayout::ColumnMajor(20), 0..=1, 0.0, 0)
+            .unwrap()
+            .with_static_columns(&[1.0]);
+
+        let report = lagged.report(0.5);
+        assert_eq!(report.observations(), 20);
+        assert_eq!(report.effective_rows(), 19);
+        assert_eq!(report.num_features(), 5);
+        assert!(!report.has_warnings());
+
+        let report = lagged.report(0.2);
+        assert_eq!(
+            report.warnings(),
+            [LagWarning::TooManyFeatures {
+                ratio: 5.0 / 19.0,
+                max_ratio: 0.2
+            }]
+        );
+    }
+
+    #[test]
+    fn test_report_without_complete_rows() {
+        let lagged = lag_matrix_2d(&[1, 2, 3], MatrixLayout::RowMajor(3), [-2, 2], 0, 0).unwrap();
+        let report = lagged.report(1.0);
+
+        assert_eq!(lagged.effective_rows(TrimMode::Forward), 1);
+        assert_eq!(report.effective_rows(), 0);
+        assert_eq!(report.ratio(), None);
+        assert_eq!(report.warnings(), [LagWarning::NoCompleteRows]);
+    }
+}
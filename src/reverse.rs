@@ -0,0 +1,90 @@
+use crate::{lag_matrix, IntoLag, LagError, LagMatrix};
+
+/// The number of values per chunk when collecting newest-first records.
+const CHUNK_LEN: usize = 1 << 14;
+
+/// Create a time-lagged matrix of a time series delivered newest first, e.g. the records of
+/// an exchange or vendor API.
+///
+/// The records are collected in chunks, which avoids copying the values already read
+/// whenever the buffer grows, and are then reversed chunk by chunk into chronological order.
+/// The lags therefore refer to the past like in [`lag_matrix`], and the last time step of
+/// the matrix is the first record.
+///
+/// ## Arguments
+/// * `records` - The values of the series, starting with the most recent one.
+/// * `lags` - The lags to create; negative lags create leads, see [`IntoLag`].
+/// * `fill` - The value to use to fill in lagged gaps.
+/// * `row_stride` - The number of elements along a row of the matrix, see [`lag_matrix`].
+///
+/// ## Returns
+/// The matrix, or an error, e.g. [`LagError::EmptyData`] if there are no records.
+///
+/// ## Example
+/// ```
+/// use timelag::{lag_matrix, lag_matrix_newest_first};
+///
+/// // Prices as returned by the API: today, yesterday, the day before.
+/// let prices = vec![103, 102, 101];
+/// let lagged = lag_matrix_newest_first(prices, 0..=1, 0, 0).unwrap();
+///
+/// assert_eq!(lagged, lag_matrix(&[101, 102, 103], 0..=1, 0, 0).unwrap());
+/// ```
+pub fn lag_matrix_newest_first<T, I, L, R>(
+    records: I,
+    lags: R,
+    fill: T,
+    row_stride: usize,
+) -> Result<LagMatrix<T>, LagError>
+where
+    T: Copy,
+    I: IntoIterator<Item = T>,
+    L: IntoLag,
+    R: IntoIterator<Item = L>,
+{
+    let series = chronological(records);
+    lag_matrix(&series, lags, fill, row_stride)
+}
+
+/// Collects newest-first records in chunks and returns them in chronological order.
+fn chronological<T, I>(records: I) -> Vec<T>
+where
+    I: IntoIterator<Item = T>,
+{
+    let mut records = records.into_iter();
+    let mut chunks = Vec::new();
+    loop {
+        let chunk: Vec<T> = records.by_ref().take(CHUNK_LEN).collect();
+        if chunk.is_empty() {
+            break;
+        }
+        chunks.push(chunk);
+    }
+
+    let mut series = Vec::with_capacity(chunks.iter().map(Vec::len).sum());
+    for chunk in chunks.into_iter().rev() {
+        series.extend(chunk.into_iter().rev());
+    }
+    series
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_reverses_across_chunks() {
+        let len = 2 * CHUNK_LEN + 3;
+        let series = chronological((0..len).rev());
+        assert_eq!(series.len(), len);
+        assert!(series.iter().enumerate().all(|(t, &value)| t == value));
+    }
+
+    #[test]
+    fn test_no_records() {
+        assert_eq!(
+            lag_matrix_newest_first(core::iter::empty::<f64>(), [1], 0.0, 0),
+            Err(LagError::EmptyData)
+        );
+    }
+}
@@ -0,0 +1,379 @@
+use crate::{InverseTransform, LagError, LagMatrix, SeriesTransform};
+use core::cmp::Ordering;
+use core::ops::RangeInclusive;
+use num_traits::Float;
+
+/// The cells from which scaling parameters are estimated; see [`LagMatrix::scale_minmax`].
+#[derive(Debug, Copy, Clone, Eq, PartialEq, Hash, Default)]
+pub enum ScaleScope {
+    /// Every lag of every series is scaled by its own parameters.
+    #[default]
+    PerLag,
+    /// All lagged values share the same parameters.
+    Global,
+}
+
+/// The parameters of an affine scaling `(x - center) / scale` applied to every lag of
+/// every series of a lag matrix.
+///
+/// The parameters are tied to the lags and series count of the matrix they were estimated
+/// on, such that they can be applied to matrices of the same configuration only, e.g. to
+/// scale test data like the training data, or to map predictions back to the data scale.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Scaling<T> {
+    lags: Vec<isize>,
+    series_count: usize,
+    centers: Vec<T>,
+    scales: Vec<T>,
+}
+
+impl<T: Float> LagMatrix<T> {
+    /// Scales the lagged values linearly into `range`, based on their minimum and maximum.
+    ///
+    /// Only values that are neither fill values nor padding are scaled and contribute to the
+    /// range estimate; static columns are left untouched. Constant lags and degenerate ranges
+    /// are only shifted, such that the scaling remains invertible.
+    ///
+    /// ## Arguments
+    /// * `range` - The target range of the values, e.g. `0.0..=1.0`.
+    /// * `scope` - Whether to scale every lag of every series separately or all of them alike.
+    ///
+    /// ## Returns
+    /// The scaling parameters, e.g. to apply them to further matrices or to invert them.
+    ///
+    /// ## Example
+    /// ```
+    /// use timelag::{lag_matrix, ScaleScope};
+    ///
+    /// let mut lagged = lag_matrix(&[2.0, 4.0, 6.0], 0..=1, f64::INFINITY, 0).unwrap();
+    /// let scaling = lagged.scale_minmax(0.0..=1.0, ScaleScope::PerLag);
+    ///
+    /// assert_eq!(lagged, &[0.0, 0.5, 1.0, f64::INFINITY, 0.0, 1.0]);
+    ///
+    /// // Map a prediction of the lag-zero value back to the data scale.
+    /// assert_eq!(scaling.inverse_value(0, 0, 0.25), Some(3.0));
+    /// ```
+    pub fn scale_minmax(&mut self, range: RangeInclusive<T>, scope: ScaleScope) -> Scaling<T> {
+        let (start, end) = range.into_inner();
+        let mut features: Vec<(T, T)> = (0..self.features_per_step())
+            .map(|feature| {
+                self.valid_values(feature)
+                    .fold((T::infinity(), T::neg_infinity()), |(min, max), value| {
+                        (min.min(value), max.max(value))
+                    })
+            })
+            .collect();
+
+        if scope == ScaleScope::Global {
+            let global = features.iter().fold(
+                (T::infinity(), T::neg_infinity()),
+                |(min, max), &(lo, hi)| (min.min(lo), max.max(hi)),
+            );
+            features.fill(global);
+        }
+
+        let (centers, scales) = features
+            .into_iter()
+            .map(|(min, max)| {
+                if min > max {
+                    return (T::zero(), T::one());
+                }
+                let scale = (max - min) / (end - start);
+                if scale.is_finite() && scale > T::zero() {
+                    (min - start * scale, scale)
+                } else {
+                    (min - start, T::one())
+                }
+            })
+            .unzip();
+
+        let scaling = Scaling {
+            lags: self.lags.clone(),
+            series_count: self.series_count,
+            centers,
+            scales,
+        };
+        scaling.apply(self, |value, center, scale| (value - center) / scale);
+        scaling
+    }
+
+    /// Centers the lagged values on their median and scales them by their interquartile
+    /// range, which is robust against outliers, e.g. in sensor or financial data.
+    ///
+    /// Only values that are neither fill values nor padding are scaled and contribute to the
+    /// estimates; static columns are left untouched. Quantiles are interpolated linearly
+    /// between the sorted values. Lags without spread are only centered.
+    ///
+    /// ## Arguments
+    /// * `scope` - Whether to scale every lag of every series separately or all of them alike.
+    ///
+    /// ## Returns
+    /// The scaling parameters, e.g. to apply them to further matrices or to invert them.
+    ///
+    /// ## Example
+    /// ```
+    /// use timelag::{lag_matrix, ScaleScope};
+    ///
+    /// let mut lagged = lag_matrix(&[1.0, 2.0, 3.0, 4.0, 100.0], [0], 0.0, 0).unwrap();
+    /// let scaling = lagged.scale_robust(ScaleScope::PerLag);
+    ///
+    /// // The outlier does not affect the scale of the remaining values.
+    /// assert_eq!(scaling.params(0, 0), Some((3.0, 2.0)));
+    /// assert_eq!(lagged, &[-1.0, -0.5, 0.0, 0.5, 48.5]);
+    /// ```
+    pub fn scale_robust(&mut self, scope: ScaleScope) -> Scaling<T> {
+        let mut features: Vec<Vec<T>> = (0..self.features_per_step())
+            .map(|feature| self.valid_values(feature).collect())
+            .collect();
+
+        if scope == ScaleScope::Global {
+            let global = features.concat();
+            features.fill(global);
+        }
+
+        let (centers, scales) = features
+            .into_iter()
+            .map(|mut values| {
+                values.sort_by(|a, b| a.partial_cmp(b).unwrap_or(Ordering::Equal));
+                let Some(median) = quantile(&values, 0.5) else {
+                    return (T::zero(), T::one());
+                };
+                let iqr = quantile(&values, 0.75).unwrap_or(median)
+                    - quantile(&values, 0.25).unwrap_or(median);
+                if iqr.is_finite() && iqr > T::zero() {
+                    (median, iqr)
+                } else {
+                    (median, T::one())
+                }
+            })
+            .unzip();
+
+        let scaling = Scaling {
+            lags: self.lags.clone(),
+            series_count: self.series_count,
+            centers,
+            scales,
+        };
+        scaling.apply(self, |value, center, scale| (value - center) / scale);
+        scaling
+    }
+
+    /// The values of the feature, i.e. the lag index and series, that are neither fill
+    /// values nor padding, in time order.
+    fn valid_values(&self, feature: usize) -> impl Iterator<Item = T> + '_ {
+        let (lag_index, series) = (feature / self.series_count, feature % self.series_count);
+        (0..self.series_length)
+            .filter(move |&t| !self.is_padding(lag_index, series, t))
+            .map(move |t| self.data[self.offset_of(lag_index, series, t)])
+    }
+}
+
+impl<T: Float> Scaling<T> {
+    /// The lags the parameters apply to.
+    #[inline(always)]
+    pub fn lags(&self) -> &[isize] {
+        &self.lags
+    }
+
+    /// The number of series the parameters apply to.
+    #[inline(always)]
+    pub const fn series_count(&self) -> usize {
+        self.series_count
+    }
+
+    /// The center and scale of the specified lag of the specified series, or `None` if
+    /// either is not part of the scaling.
+    pub fn params(&self, lag: isize, series: usize) -> Option<(T, T)> {
+        let feature = self.feature_of(lag, series)?;
+        Some((self.centers[feature], self.scales[feature]))
+    }
+
+    /// Scales a matrix of the same configuration, e.g. test data like the training data.
+    ///
+    /// ## Returns
+    /// `Ok(())`, or [`LagError::InvalidLags`] or [`LagError::InvalidChannel`] if the
+    /// lags or the number of series of the matrix differ.
+    pub fn transform(&self, matrix: &mut LagMatrix<T>) -> Result<(), LagError> {
+        self.check(matrix)?;
+        self.apply(matrix, |value, center, scale| (value - center) / scale);
+        Ok(())
+    }
+
+    /// Maps a scaled matrix back to the data scale; see [`transform`](Self::transform).
+    pub fn inverse_transform(&self, matrix: &mut LagMatrix<T>) -> Result<(), LagError> {
+        self.check(matrix)?;
+        self.apply(matrix, |value, center, scale| value * scale + center);
+        Ok(())
+    }
+
+    /// Maps a scaled value of the specified lag of the specified series back to the data
+    /// scale, e.g. a prediction of the lag-zero value.
+    ///
+    /// ## Returns
+    /// The value, or `None` if the lag or the series is not part of the scaling.
+    pub fn inverse_value(&self, lag: isize, series: usize, value: T) -> Option<T> {
+        let (center, scale) = self.params(lag, series)?;
+        Some(value * scale + center)
+    }
+
+    /// The index of the parameters of the specified lag and series.
+    fn feature_of(&self, lag: isize, series: usize) -> Option<usize> {
+        if series >= self.series_count {
+            return None;
+        }
+        let lag_index = self.lags.iter().position(|&l| l == lag)?;
+        Some(lag_index * self.series_count + series)
+    }
+
+    /// Ensures the matrix has the configuration the parameters were estimated on.
+    fn check(&self, matrix: &LagMatrix<T>) -> Result<(), LagError> {
+        if matrix.lags != self.lags {
+            return Err(LagError::InvalidLags);
+        }
+        if matrix.series_count != self.series_count {
+            return Err(LagError::InvalidChannel);
+        }
+        Ok(())
+    }
+
+    /// Replaces every value that is neither a fill value nor padding by `f(value, center, scale)`.
+    fn apply<F>(&self, matrix: &mut LagMatrix<T>, f: F)
+    where
+        F: Fn(T, T, T) -> T,
+    {
+        matrix.row_checksums = None;
+        for lag_index in 0..matrix.num_lags {
+            for series in 0..matrix.series_count {
+                let feature = lag_index * matrix.series_count + series;
+                let (center, scale) = (self.centers[feature], self.scales[feature]);
+                for t in 0..matrix.series_length {
+                    if !matrix.is_padding(lag_index, series, t) {
+                        let offset = matrix.offset_of(lag_index, series, t);
+                        matrix.data[offset] = f(matrix.data[offset], center, scale);
+                    }
+                }
+            }
+        }
+    }
+}
+
+/// The linearly interpolated quantile `q` of sorted values, or `None` if there are none.
+pub(crate) fn quantile<T: Float>(sorted: &[T], q: f64) -> Option<T> {
+    let last = sorted.len().checked_sub(1)?;
+    let position = q * last as f64;
+    let (index, fraction) = (position.floor() as usize, position.fract());
+    let lower = sorted[index];
+    let upper = sorted[(index + 1).min(last)];
+    Some(lower + (upper - lower) * T::from(fraction)?)
+}
+
+impl<T: Float> SeriesTransform<LagMatrix<T>> for Scaling<T> {
+    type Output = LagMatrix<T>;
+
+    /// Scales a copy of the matrix; see [`transform`](Self::transform).
+    fn apply(&self, input: &LagMatrix<T>) -> Result<Self::Output, LagError> {
+        let mut matrix = input.clone();
+        self.transform(&mut matrix)?;
+        Ok(matrix)
+    }
+}
+
+impl<T: Float> InverseTransform<LagMatrix<T>> for Scaling<T> {
+    type Output = LagMatrix<T>;
+
+    /// Maps a copy of the scaled matrix back; see [`inverse_transform`](Self::inverse_transform).
+    fn inverse_apply(&self, input: &LagMatrix<T>) -> Result<Self::Output, LagError> {
+        let mut matrix = input.clone();
+        self.inverse_transform(&mut matrix)?;
+        Ok(matrix)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{lag_matrix_2d, MatrixLayout};
+
+    #[test]
+    fn test_minmax_round_trip() {
+        let data = [1.0, 100.0, 3.0, 300.0, 2.0, 200.0, 5.0, 500.0];
+        for scope in [ScaleScope::PerLag, ScaleScope::Global] {
+            let original = lag_matrix_2d(
+                &data,
+                MatrixLayout::ColumnMajor(4),
+                [0, 1],
+                f64::INFINITY,
+                6,
+            )
+            .unwrap()
+            .with_static_columns(&[7.0]);
+            let mut lagged = original.clone();
+            let scaling = lagged.scale_minmax(-1.0..=1.0, scope);
+
+            assert!(lagged
+                .iter_complete()
+                .all(|(_, mut values)| values.all(|&x| x == 7.0 || (-1.0..=1.0).contains(&x))));
+            assert_eq!(lagged[(0, 2)], f64::INFINITY);
+            assert_eq!(lagged[(0, 4)], 7.0);
+
+            scaling.inverse_transform(&mut lagged).unwrap();
+            for (a, b) in lagged.iter().zip(original.iter()) {
+                assert!(a == b || (a - b).abs() < 1e-12);
+            }
+        }
+    }
+
+    #[test]
+    fn test_robust_scaling() {
+        let data = [4.0, 1.0, 3.0, 2.0, -50.0, 5.0];
+        let mut lagged =
+            lag_matrix_2d(&data, MatrixLayout::RowMajor(6), [0, 2], f64::NAN, 0).unwrap();
+        let scaling = lagged.scale_robust(ScaleScope::PerLag);
+
+        // Lag zero: sorted [-50, 1, 2, 3, 4, 5] with quartiles 1.25 and 3.75.
+        assert_eq!(scaling.params(0, 0), Some((2.5, 2.5)));
+        // Lag two: sorted [1, 2, 3, 4] with quartiles 1.75 and 3.25.
+        assert_eq!(scaling.params(2, 0), Some((2.5, 1.5)));
+        assert!(lagged[6].is_nan() && lagged[7].is_nan());
+
+        let global = lag_matrix_2d(&data, MatrixLayout::RowMajor(6), [0, 2], f64::NAN, 0)
+            .unwrap()
+            .scale_robust(ScaleScope::Global);
+        assert_eq!(global.params(0, 0), global.params(2, 0));
+        assert_eq!(quantile::<f64>(&[], 0.5), None);
+        assert_eq!(quantile(&[7.0], 0.25), Some(7.0));
+    }
+
+    #[test]
+    fn test_scope_and_configuration() {
+        let data = [0.0, 10.0, 5.0];
+        let mut per_lag = lag_matrix_2d(&data, MatrixLayout::ColumnMajor(3), [0], 0.0, 0).unwrap();
+        let mut lagged = lag_matrix_2d(
+            &[0.0, 10.0, 0.0, 20.0],
+            MatrixLayout::ColumnMajor(2),
+            [0],
+            0.0,
+            0,
+        )
+        .unwrap();
+        let global = lagged.scale_minmax(0.0..=1.0, ScaleScope::Global);
+        assert_eq!(lagged, &[0.0, 0.5, 0.0, 1.0]);
+        assert_eq!(global.params(0, 1), Some((0.0, 20.0)));
+        assert_eq!(global.params(1, 0), None);
+
+        assert_eq!(
+            global.transform(&mut per_lag),
+            Err(LagError::InvalidChannel)
+        );
+        let scaling = per_lag.scale_minmax(0.0..=1.0, ScaleScope::PerLag);
+        assert_eq!(per_lag, &[0.0, 1.0, 0.5]);
+
+        // Constant values are shifted only.
+        let mut constant =
+            lag_matrix_2d(&[4.0, 4.0], MatrixLayout::ColumnMajor(2), [0], 0.0, 0).unwrap();
+        let shifted = constant.scale_minmax(1.0..=2.0, ScaleScope::PerLag);
+        assert_eq!(constant, &[1.0, 1.0]);
+        assert_eq!(shifted.inverse_value(0, 0, 1.5), Some(4.5));
+        assert_eq!(scaling.inverse_value(0, 0, 0.5), Some(5.0));
+    }
+}
@@ -0,0 +1,275 @@
+use crate::builder::PlannedMatrix;
+use crate::{
+    collect_lags, IntoLag, LagError, LagMatrix, LagMatrixBuilder, MatrixLayout, OutputLayout,
+};
+use std::borrow::Cow;
+use std::num::NonZeroUsize;
+use std::sync::Barrier;
+
+/// A lag configuration to create a matrix with, i.e. the lags and the builder options;
+/// see [`build_all`].
+///
+/// ## Example
+/// ```
+/// use timelag::{LagMatrixBuilder, LagSchema, MatrixLayout};
+///
+/// let schema = LagSchema::new(LagMatrixBuilder::new(0).trim_incomplete(true), 0..=1).unwrap();
+/// let lagged = schema.build(&[1, 2, 3], MatrixLayout::RowMajor(3)).unwrap();
+///
+/// assert_eq!(schema.lags(), &[0, 1]);
+/// assert_eq!(lagged, &[2, 3, 1, 2]);
+/// ```
+#[derive(Debug, Clone, PartialEq)]
+pub struct LagSchema<T> {
+    builder: LagMatrixBuilder<T>,
+    lags: Vec<isize>,
+}
+
+impl<T> LagSchema<T>
+where
+    T: Copy,
+{
+    /// Creates a schema of the specified builder options and lags.
+    ///
+    /// ## Returns
+    /// The schema, or [`LagError::InvalidLags`] if a lag is out of range.
+    pub fn new<L, R>(builder: LagMatrixBuilder<T>, lags: R) -> Result<Self, LagError>
+    where
+        L: IntoLag,
+        R: IntoIterator<Item = L>,
+    {
+        Ok(Self {
+            builder,
+            lags: collect_lags(lags)?,
+        })
+    }
+
+    /// The builder options.
+    #[inline(always)]
+    pub const fn builder(&self) -> &LagMatrixBuilder<T> {
+        &self.builder
+    }
+
+    /// The lags, where negative values denote leads.
+    #[inline(always)]
+    pub fn lags(&self) -> &[isize] {
+        &self.lags
+    }
+
+    /// Creates the matrix of multiple time series; see [`LagMatrixBuilder::build_2d`].
+    pub fn build(&self, data_matrix: &[T], layout: MatrixLayout) -> Result<LagMatrix<T>, LagError> {
+        self.builder
+            .build_2d(data_matrix, layout, self.lags.iter().copied())
+    }
+}
+
+/// The number of input bytes per tile of the shared scan of [`build_all`].
+const SCAN_TILE_BYTES: usize = 1 << 16;
+
+/// Creates one matrix per schema from the same input, e.g. the same series lagged for
+/// several models, sharing a single scan of the input between all matrices.
+///
+/// The input is scanned once in tiles of time steps, and every tile is lagged into all
+/// matrices while it is in the cache, instead of passing over the whole input once per
+/// schema. The matrices are distributed over up to the available parallelism of threads,
+/// which all borrow the input and advance through the tiles in lockstep; the
+/// [`parallel`](LagMatrixBuilder::parallel) option of the builders is therefore not used.
+/// Inputs are converted once per [`output_layout`](LagMatrixBuilder::output_layout). Every
+/// matrix is validated independently, such that an invalid schema does not affect the others.
+///
+/// ## Arguments
+/// * `specs` - The lag configurations.
+/// * `data_matrix` - The matrix of multiple time series data to create lagged versions of.
+/// * `layout` - The layout of the data matrix, specifying the length of each series.
+///
+/// ## Returns
+/// The matrices or errors, in the order of the schemas; every matrix equals the one
+/// created by [`LagSchema::build`].
+///
+/// ## Example
+/// ```
+/// use timelag::{build_all, lag_matrix, LagError, LagMatrixBuilder, LagSchema, MatrixLayout};
+///
+/// let data = [1.0, 2.0, 3.0, 4.0];
+/// let builder = LagMatrixBuilder::new(f64::INFINITY);
+/// let specs = [
+///     LagSchema::new(builder.clone(), 0..=1).unwrap(),
+///     LagSchema::new(builder.clone(), [5]).unwrap(),
+///     LagSchema::new(builder.row_stride(6), [1, 3]).unwrap(),
+/// ];
+///
+/// let matrices = build_all(&specs, &data, MatrixLayout::RowMajor(4));
+/// assert_eq!(matrices[0], lag_matrix(&data, 0..=1, f64::INFINITY, 0));
+/// assert_eq!(matrices[1], Err(LagError::LagExceedsValueCount));
+/// assert_eq!(matrices[2], lag_matrix(&data, [1, 3], f64::INFINITY, 6));
+/// ```
+pub fn build_all<T>(
+    specs: &[LagSchema<T>],
+    data_matrix: &[T],
+    layout: MatrixLayout,
+) -> Vec<Result<LagMatrix<T>, LagError>>
+where
+    T: Copy + Send + Sync,
+{
+    // The input converted for every output layout, indexed by `layout_index`.
+    let mut inputs: [Option<Converted<'_, T>>; 3] = [None, None, None];
+    for spec in specs {
+        inputs[layout_index(spec.builder.output())]
+            .get_or_insert_with(|| spec.builder.convert(data_matrix, layout));
+    }
+
+    let mut results: Vec<Option<Result<LagMatrix<T>, LagError>>> = Vec::with_capacity(specs.len());
+    let mut pending = Vec::new();
+    for (index, spec) in specs.iter().enumerate() {
+        let converted = inputs[layout_index(spec.builder.output())]
+            .as_ref()
+            .expect("every output layout is converted");
+        let planned = converted
+            .as_ref()
+            .map_err(|&error| error)
+            .and_then(|(input, layout)| {
+                let lags = spec.lags.iter().copied();
+                let planned = spec.builder.plan(input, *layout, lags)?;
+                Ok(Pending {
+                    index,
+                    input: input.as_ref(),
+                    planned,
+                })
+            });
+        match planned {
+            Ok(planned) => {
+                results.push(None);
+                pending.push(planned);
+            }
+            Err(error) => results.push(Some(Err(error))),
+        }
+    }
+
+    let series_length = layout.len();
+    let num_series = data_matrix.len() / series_length.max(1);
+    let tile_len = (SCAN_TILE_BYTES / (size_of::<T>() * num_series).max(1)).max(1);
+    let threads = std::thread::available_parallelism().map_or(1, NonZeroUsize::get);
+    let per_thread = pending.len().div_ceil(threads).max(1);
+
+    if per_thread >= pending.len() {
+        scan(&mut pending, series_length, tile_len, None);
+    } else {
+        let groups = pending.len().div_ceil(per_thread);
+        let barrier = Barrier::new(groups);
+        std::thread::scope(|scope| {
+            for group in pending.chunks_mut(per_thread) {
+                let barrier = &barrier;
+                scope.spawn(move || scan(group, series_length, tile_len, Some(barrier)));
+            }
+        });
+    }
+
+    for Pending { index, planned, .. } in pending {
+        results[index] = Some(specs[index].builder.finish(planned));
+    }
+    results
+        .into_iter()
+        .map(|result| result.expect("every matrix is planned or failed"))
+        .collect()
+}
+
+/// The input converted for an output layout; see [`LagMatrixBuilder::convert`].
+type Converted<'a, T> = Result<(Cow<'a, [T]>, MatrixLayout), LagError>;
+
+/// A matrix of [`build_all`] that is being filled from its input.
+struct Pending<'a, T> {
+    index: usize,
+    input: &'a [T],
+    planned: PlannedMatrix<T>,
+}
+
+/// Fills the matrices tile by tile, waiting for the other threads after every tile.
+fn scan<T: Copy>(
+    pending: &mut [Pending<'_, T>],
+    series_length: usize,
+    tile_len: usize,
+    barrier: Option<&Barrier>,
+) {
+    for start in (0..series_length).step_by(tile_len) {
+        let steps = start..(start + tile_len).min(series_length);
+        for matrix in pending.iter_mut() {
+            let planned = &mut matrix.planned;
+            planned
+                .plan
+                .fill_steps(matrix.input, &mut planned.lagged, steps.clone());
+        }
+        if let Some(barrier) = barrier {
+            barrier.wait();
+        }
+    }
+}
+
+/// The index of the converted input of an output layout.
+fn layout_index(output: Option<OutputLayout>) -> usize {
+    match output {
+        None => 0,
+        Some(OutputLayout::LagsAlongRows) => 1,
+        Some(OutputLayout::LagsAlongColumns) => 2,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::lag_matrix_2d;
+
+    #[test]
+    fn test_many_specs_keep_their_order() {
+        let data: Vec<i32> = (0..40).collect();
+        let layout = MatrixLayout::ColumnMajor(20);
+        let specs: Vec<_> = (0..25)
+            .map(|lag| LagSchema::new(LagMatrixBuilder::new(-1), [0, lag]).unwrap())
+            .collect();
+
+        let matrices = build_all(&specs, &data, layout);
+        assert_eq!(matrices.len(), 25);
+        for (lag, matrix) in matrices.into_iter().enumerate() {
+            let expected = lag_matrix_2d(&data, layout, [0, lag], -1, 0);
+            if lag <= 20 {
+                assert_eq!(matrix, expected);
+            } else {
+                assert_eq!(matrix, Err(LagError::LagExceedsValueCount));
+            }
+        }
+    }
+
+    #[test]
+    fn test_shared_scan_matches_build() {
+        // Long enough to span several tiles.
+        let data: Vec<i32> = (0..20_000).collect();
+        let builder = LagMatrixBuilder::new(-1);
+        let specs = [
+            LagSchema::new(builder.clone(), [0, 1, 9000]).unwrap(),
+            LagSchema::new(builder.clone().row_stride(10_003), [-3, 7]).unwrap(),
+            LagSchema::new(builder.clone().unit_stride(true), [2, -8193]).unwrap(),
+            LagSchema::new(builder.clone().trim_incomplete(true), [5, -2]).unwrap(),
+            LagSchema::new(builder.clone().lag_fills(vec![7, 8]), [0, 3]).unwrap(),
+            LagSchema::new(builder.exclude_zero_lag(true), [0]).unwrap(),
+        ];
+
+        for layout in [
+            MatrixLayout::RowMajor(10_000),
+            MatrixLayout::ColumnMajor(10_000),
+        ] {
+            let matrices = build_all(&specs, &data, layout);
+            for (spec, matrix) in specs.iter().zip(matrices) {
+                assert_eq!(matrix, spec.build(&data, layout));
+            }
+        }
+    }
+
+    #[test]
+    fn test_empty_specs() {
+        let specs: [LagSchema<i32>; 0] = [];
+        assert!(build_all(&specs, &[1, 2, 3], MatrixLayout::RowMajor(3)).is_empty());
+        assert_eq!(
+            LagSchema::new(LagMatrixBuilder::new(0), [usize::MAX]),
+            Err(LagError::InvalidLags)
+        );
+    }
+}
@@ -0,0 +1,147 @@
+use crate::{
+    collect_lags, lag_matrix_2d_impl, shift_of, IntoLag, LagError, LagMatrix, MatrixLayout,
+};
+
+/// Create a time-lagged matrix of time series values, restarting the lags at every changepoint.
+///
+/// See [`lag_matrix_2d_segmented`] and [`lag_matrix`](crate::lag_matrix) for details.
+pub fn lag_matrix_segmented<T: Copy, L: IntoLag, R: IntoIterator<Item = L>>(
+    data: &[T],
+    lags: R,
+    fill: T,
+    stride: usize,
+    changepoints: &[usize],
+) -> Result<LagMatrix<T>, LagError> {
+    lag_matrix_2d_segmented(
+        data,
+        MatrixLayout::RowMajor(data.len()),
+        lags,
+        fill,
+        stride,
+        changepoints,
+    )
+}
+
+/// Create a time-lagged matrix of multiple time series values, restarting the lags at every
+/// changepoint, e.g. a regime change or a meter replacement.
+///
+/// Every changepoint is the first time step of a new segment. Lagged values that would cross
+/// a segment boundary are set to the fill value, as if every segment was lagged on its own.
+/// Changepoints outside the series are ignored. See [`lag_matrix_2d`](crate::lag_matrix_2d)
+/// for the remaining arguments.
+///
+/// The gaps at the changepoints are tracked as gaps within the series, such that
+/// [`valid_length`](LagMatrix::valid_length), [`iter_complete`](LagMatrix::iter_complete) and
+/// the scaling and GEMM helpers account for them; see
+/// [`has_interior_gaps`](LagMatrix::has_interior_gaps).
+///
+/// ## Example
+/// ```
+/// # use timelag::{lag_matrix_2d_segmented, MatrixLayout};
+/// let data = [1, 2, 3, 10, 20];
+///
+/// // A new regime starts at time step three.
+/// let lagged = lag_matrix_2d_segmented(&data, MatrixLayout::RowMajor(5), [1], 0, 0, &[3]).unwrap();
+///
+/// assert_eq!(lagged, &[0, 1, 2, 0, 10]);
+/// ```
+pub fn lag_matrix_2d_segmented<T: Copy, L: IntoLag, R: IntoIterator<Item = L>>(
+    data_matrix: &[T],
+    layout: MatrixLayout,
+    lags: R,
+    fill: T,
+    row_stride: usize,
+    changepoints: &[usize],
+) -> Result<LagMatrix<T>, LagError> {
+    let mut matrix = lag_matrix_2d_impl(
+        data_matrix,
+        layout,
+        collect_lags(lags)?,
+        None,
+        fill,
+        row_stride,
+    )?;
+
+    let series_length = matrix.series_length;
+    for lag_index in 0..matrix.num_lags {
+        for series in 0..matrix.series_count {
+            let shift = shift_of(matrix.lags[lag_index], matrix.directions[series]);
+            let distance = shift.unsigned_abs();
+
+            for &changepoint in changepoints {
+                if changepoint == 0 || changepoint >= series_length {
+                    continue;
+                }
+
+                // Lags reach back before the changepoint, leads reach beyond it.
+                let steps = if shift >= 0 {
+                    changepoint..(changepoint + distance).min(series_length)
+                } else {
+                    changepoint.saturating_sub(distance)..changepoint
+                };
+                for t in steps {
+                    let offset = matrix.offset_of(lag_index, series, t);
+                    matrix.data[offset] = fill;
+                    matrix.mark_gap(lag_index, series, t);
+                }
+            }
+        }
+    }
+
+    Ok(matrix)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::lag_matrix_2d;
+
+    #[test]
+    #[rustfmt::skip]
+    fn test_segments_are_lagged_independently() {
+        let data = [
+            1, 10,
+            2, 20,
+            3, 30,
+            4, 40,
+            5, 50,
+        ];
+        let lagged =
+            lag_matrix_2d_segmented(&data, MatrixLayout::ColumnMajor(5), [0, 2, -1], -1, 0, &[2, 9])
+                .unwrap();
+
+        assert_eq!(
+            lagged,
+            &[
+                1, 10, -1, -1,  2, 20,
+                2, 20, -1, -1, -1, -1,
+                3, 30, -1, -1,  4, 40,
+                4, 40, -1, -1,  5, 50,
+                5, 50,  3, 30, -1, -1,
+            ]
+        );
+    }
+
+    #[test]
+    fn test_changepoint_gaps_are_incomplete() {
+        let data = [1.0, 2.0, 3.0, 10.0, 20.0];
+        let lagged = lag_matrix_segmented(&data, 0..=1, f64::NAN, 0, &[3]).unwrap();
+
+        assert!(lagged.has_interior_gaps());
+        assert_eq!(lagged.valid_length(), 3);
+        let steps: Vec<usize> = lagged.iter_complete().map(|(t, _)| t).collect();
+        assert_eq!(steps, [1, 2, 4]);
+        assert_eq!(lagged.iter_complete().len(), 3);
+        assert_eq!(lagged.iter_complete().next_back().map(|(t, _)| t), Some(4));
+    }
+
+    #[test]
+    fn test_without_changepoints() {
+        let data = [1.0, 2.0, 3.0, 4.0];
+        let expected = lag_matrix_2d(&data, MatrixLayout::RowMajor(4), 0..=2, 0.0, 6).unwrap();
+        for changepoints in [&[][..], &[0, 4]] {
+            let lagged = lag_matrix_segmented(&data, 0..=2, 0.0, 6, changepoints).unwrap();
+            assert_eq!(lagged, expected);
+        }
+    }
+}
@@ -0,0 +1,227 @@
+use crate::{LagDirection, LagError, LagMatrix, LagOffsets, MatrixLayout};
+
+/// The shape and lag metadata of a [`LagMatrix`] without its values, e.g. to hand the buffer
+/// to a deserializer, an FFI layer or a GPU and to reconstitute the matrix later;
+/// see [`LagMatrix::into_parts`] and [`LagMatrix::from_parts`].
+///
+/// ## Example
+/// ```
+/// use timelag::{lag_matrix, LagMatrix, LagMatrixShape, MatrixLayout};
+///
+/// let shape = LagMatrixShape::new(MatrixLayout::RowMajor(3), 1, vec![0, 1], 0).unwrap();
+/// assert_eq!(shape.len(), 6);
+///
+/// let matrix = LagMatrix::from_parts(vec![1, 2, 3, 0, 1, 2], shape).unwrap();
+/// assert_eq!(matrix, lag_matrix(&[1, 2, 3], 0..=1, 0, 0).unwrap());
+/// ```
+#[derive(Debug, Clone, Eq, PartialEq)]
+pub struct LagMatrixShape {
+    pub(crate) layout: MatrixLayout,
+    pub(crate) series_count: usize,
+    pub(crate) lags: Vec<isize>,
+    pub(crate) directions: Vec<LagDirection>,
+    pub(crate) series_names: Option<Vec<String>>,
+    pub(crate) row_stride: usize,
+    pub(crate) static_count: usize,
+    pub(crate) time_offset: usize,
+    pub(crate) source_length: usize,
+    pub(crate) gaps: Option<Vec<bool>>,
+}
+
+impl LagMatrixShape {
+    /// Describes a matrix laid out like the matrices created by [`lag_matrix_2d`](crate::lag_matrix_2d),
+    /// with every series lagged in the [`Lag`](LagDirection::Lag) direction.
+    ///
+    /// ## Arguments
+    /// * `layout` - The layout of the matrix, specifying the length of each series.
+    /// * `series_count` - The number of lagged series.
+    /// * `lags` - The lags represented in the matrix.
+    /// * `row_stride` - The number of elements between subsequent rows; `0` for no padding.
+    ///
+    /// ## Returns
+    /// The shape, or an error if the lags, the series or the stride are invalid;
+    /// see [`LagOffsets::new`].
+    pub fn new(
+        layout: MatrixLayout,
+        series_count: usize,
+        lags: Vec<isize>,
+        row_stride: usize,
+    ) -> Result<Self, LagError> {
+        let offsets = LagOffsets::new(layout, series_count, lags.len(), row_stride)?;
+        Ok(Self {
+            layout,
+            series_count,
+            lags,
+            directions: vec![LagDirection::Lag; series_count],
+            series_names: None,
+            row_stride: offsets.row_stride(),
+            static_count: 0,
+            time_offset: 0,
+            source_length: layout.len(),
+            gaps: None,
+        })
+    }
+
+    /// Sets the shift direction of each series.
+    ///
+    /// ## Returns
+    /// The shape, or [`LagError::InvalidChannel`] if the number of directions does not match
+    /// the number of series.
+    pub fn with_directions(mut self, directions: Vec<LagDirection>) -> Result<Self, LagError> {
+        if directions.len() != self.series_count {
+            return Err(LagError::InvalidChannel);
+        }
+
+        self.directions = directions;
+        Ok(self)
+    }
+
+    /// The layout of the matrix, specifying the length of each series.
+    #[inline(always)]
+    pub const fn layout(&self) -> MatrixLayout {
+        self.layout
+    }
+
+    /// The number of lagged series.
+    #[inline(always)]
+    pub const fn series_count(&self) -> usize {
+        self.series_count
+    }
+
+    /// The lags represented in the matrix.
+    #[inline(always)]
+    pub fn lags(&self) -> &[isize] {
+        &self.lags
+    }
+
+    /// The shift direction of each series.
+    #[inline(always)]
+    pub fn directions(&self) -> &[LagDirection] {
+        &self.directions
+    }
+
+    /// The number of elements between subsequent rows.
+    #[inline(always)]
+    pub const fn row_stride(&self) -> usize {
+        self.row_stride
+    }
+
+    /// The number of static columns.
+    #[inline(always)]
+    pub const fn static_count(&self) -> usize {
+        self.static_count
+    }
+
+    /// The time step of the source series that corresponds to the first time step of the matrix.
+    #[inline(always)]
+    pub const fn time_offset(&self) -> usize {
+        self.time_offset
+    }
+
+    /// The number of elements the buffer has to hold, including the row padding.
+    pub fn len(&self) -> usize {
+        self.offsets().len()
+    }
+
+    /// Determines whether the buffer is empty.
+    #[inline(always)]
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// The offsets of the values in the buffer; see [`LagOffsets`].
+    pub fn offsets(&self) -> LagOffsets {
+        LagOffsets::from_shape(
+            self.layout,
+            self.series_count,
+            self.lags.len(),
+            self.static_count,
+            self.row_stride,
+        )
+    }
+}
+
+impl<T> LagMatrix<T> {
+    /// Splits the matrix into its buffer and its shape, e.g. to hand the buffer to external
+    /// code and to reconstitute the matrix later via [`from_parts`](Self::from_parts).
+    ///
+    /// Row weights and checksums are discarded.
+    ///
+    /// ## Example
+    /// ```
+    /// use timelag::{lag_matrix, LagMatrix};
+    ///
+    /// let lagged = lag_matrix(&[1, 2, 3], 0..=1, 0, 4).unwrap();
+    /// let (buffer, shape) = lagged.clone().into_parts();
+    ///
+    /// assert_eq!(buffer.len(), shape.len());
+    /// assert_eq!(LagMatrix::from_parts(buffer, shape).unwrap(), lagged);
+    /// ```
+    pub fn into_parts(self) -> (Vec<T>, LagMatrixShape) {
+        let shape = LagMatrixShape {
+            layout: self.matrix_layout(),
+            series_count: self.series_count,
+            lags: self.lags,
+            directions: self.directions,
+            series_names: self.series_names,
+            row_stride: self.row_stride,
+            static_count: self.static_count,
+            time_offset: self.time_offset,
+            source_length: self.source_length,
+            gaps: self.gaps,
+        };
+        (self.data, shape)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{lag_matrix_2d, lag_matrix_2d_directed, LagMatrixView};
+
+    #[test]
+    fn test_round_trip_retains_metadata() {
+        let data: Vec<f64> = (0..8).map(f64::from).collect();
+        let directions = [LagDirection::Lag, LagDirection::Lead];
+        for layout in [MatrixLayout::RowMajor(4), MatrixLayout::ColumnMajor(4)] {
+            let lagged = lag_matrix_2d_directed(&data, layout, [0, 1], &directions, f64::NAN, 9)
+                .unwrap()
+                .with_static_columns(&[-1.0])
+                .trim_incomplete()
+                .unwrap();
+
+            let (buffer, shape) = lagged.clone().into_parts();
+            assert_eq!(shape.static_count(), 1);
+            assert_eq!(shape.time_offset(), 1);
+
+            let view = LagMatrixView::from_parts(&buffer[..], shape.clone()).unwrap();
+            assert_eq!(view.valid_length(), lagged.valid_length());
+            assert_eq!(view.directions(), &directions);
+
+            let restored = LagMatrix::from_parts(buffer, shape).unwrap();
+            assert_eq!(restored.num_cols(), lagged.num_cols());
+            assert_eq!(restored.num_rows(), lagged.num_rows());
+            assert!(restored.rows().eq(lagged.rows()));
+        }
+    }
+
+    #[test]
+    fn test_from_parts_validates_buffer() {
+        let lagged = lag_matrix_2d(&[1, 2, 3], MatrixLayout::ColumnMajor(3), [0, 1], 0, 3).unwrap();
+        let (mut buffer, shape) = lagged.into_parts();
+
+        buffer.pop();
+        assert_eq!(
+            LagMatrix::from_parts(buffer, shape.clone()).unwrap_err(),
+            LagError::InvalidLength
+        );
+        assert_eq!(
+            shape.with_directions(vec![]).unwrap_err(),
+            LagError::InvalidChannel
+        );
+        assert_eq!(
+            LagMatrixShape::new(MatrixLayout::ColumnMajor(3), 1, vec![0, 1], 1).unwrap_err(),
+            LagError::InvalidStride
+        );
+    }
+}
@@ -0,0 +1,593 @@
+use crate::{LagDirection, LagMatrix, LagMatrixShape, MatrixLayout, NpyElement};
+use std::ffi::CString;
+use std::fmt::{Debug, Formatter};
+use std::fs::File;
+use std::io::{Error, ErrorKind};
+use std::marker::PhantomData;
+use std::ops::Deref;
+use std::os::fd::{FromRawFd, OwnedFd};
+use std::os::unix::fs::FileExt;
+use std::ptr::NonNull;
+
+/// The magic string at the start of every shared matrix.
+const MAGIC: &[u8; 8] = b"TLAGSHM\0";
+
+/// The version of the shared matrix format.
+const VERSION: u32 = 2;
+
+/// The alignment of the values within the object, which suits every [`NpyElement`].
+const DATA_ALIGN: usize = 8;
+
+const FLAG_ROW_MAJOR: u8 = 1;
+const FLAG_ROW_WEIGHTS: u8 = 2;
+const FLAG_SERIES_NAMES: u8 = 4;
+const FLAG_ROW_CHECKSUMS: u8 = 8;
+const FLAG_GAPS: u8 = 16;
+
+/// The values of a matrix mapped from a POSIX shared memory object; see
+/// [`LagMatrix::from_shm`].
+///
+/// The values are borrowed from a read-only mapping of the object rather than copied, and the
+/// mapping is released once the values are dropped. Viewing them is sound only as long as
+/// no process modifies the object, which [`LagMatrix::from_shm`] requires of its callers.
+pub struct SharedValues<T> {
+    mapping: NonNull<libc::c_void>,
+    mapping_len: usize,
+    offset: usize,
+    len: usize,
+    values: PhantomData<T>,
+}
+
+// SAFETY: The mapping is read-only and owned by the values, like a boxed slice.
+unsafe impl<T: Sync> Send for SharedValues<T> {}
+
+// SAFETY: The mapping is only ever read.
+unsafe impl<T: Sync> Sync for SharedValues<T> {}
+
+impl<T> Deref for SharedValues<T> {
+    type Target = [T];
+
+    #[inline(always)]
+    fn deref(&self) -> &[T] {
+        // SAFETY: `from_shm` checked that the `len` values at `offset` lie within the mapping
+        // and are aligned, and `NpyElement` types are valid for every bit pattern. The caller
+        // of `from_shm` guarantees that the object is not modified while it is mapped.
+        unsafe {
+            let values = self.mapping.as_ptr().cast::<u8>().add(self.offset);
+            std::slice::from_raw_parts(values.cast::<T>(), self.len)
+        }
+    }
+}
+
+impl<T> AsRef<[T]> for SharedValues<T> {
+    #[inline(always)]
+    fn as_ref(&self) -> &[T] {
+        self
+    }
+}
+
+impl<T: Debug> Debug for SharedValues<T> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        f.debug_list().entries(self.iter()).finish()
+    }
+}
+
+impl<T: PartialEq> PartialEq for SharedValues<T> {
+    fn eq(&self, other: &Self) -> bool {
+        **self == **other
+    }
+}
+
+impl<T> Drop for SharedValues<T> {
+    fn drop(&mut self) {
+        // SAFETY: The mapping was created by `from_shm` with this length and is no longer
+        // borrowed, as the values are dropped.
+        unsafe {
+            libc::munmap(self.mapping.as_ptr(), self.mapping_len);
+        }
+    }
+}
+
+impl<T, S> LagMatrix<T, S>
+where
+    T: NpyElement,
+    S: AsRef<[T]>,
+{
+    /// Places the matrix and its metadata in the POSIX shared memory object `name`,
+    /// such that other processes can map it via [`from_shm`](LagMatrix::from_shm).
+    ///
+    /// The metadata includes the series names, row weights, row checksums and lagged gaps.
+    /// An existing object of the same name is unlinked and replaced by a new one, so
+    /// processes that mapped the old object keep observing its values. The object is marked
+    /// as a matrix only once it was written completely, such that readers never observe a
+    /// partially written matrix. All values are stored in little-endian byte order; use
+    /// [`remove_shm`](Self::remove_shm) to delete the object once it is no longer needed.
+    ///
+    /// ## Arguments
+    /// * `name` - The name of the object, e.g. `/features`; a single leading slash is optional.
+    ///
+    /// ## Example
+    /// ```no_run
+    /// use timelag::{lag_matrix, LagMatrix};
+    ///
+    /// let lagged = lag_matrix(&[1.0, 2.0, 3.0], 0..=1, f64::NAN, 0).unwrap();
+    /// lagged.to_shm("/features").unwrap();
+    ///
+    /// // In another process; no process writes to the object while it is mapped.
+    /// let shared = unsafe { LagMatrix::<f64>::from_shm("/features") }.unwrap();
+    /// assert_eq!(shared.lags(), &[0, 1]);
+    /// ```
+    pub fn to_shm(&self, name: &str) -> std::io::Result<()> {
+        let name = shm_name(name)?;
+        let bytes = self.shm_bytes();
+
+        // SAFETY: The name is a valid C string; unlinking a missing object merely fails.
+        unsafe {
+            libc::shm_unlink(name.as_ptr());
+        }
+        let flags = libc::O_CREAT | libc::O_EXCL | libc::O_RDWR | libc::O_CLOEXEC;
+        let file = open(&name, flags)?;
+
+        // The magic string is written last, marking the object as complete.
+        file.write_all_at(&bytes[MAGIC.len()..], MAGIC.len() as u64)?;
+        file.write_all_at(MAGIC, 0)
+    }
+
+    /// Serializes the matrix, placing the values at an offset aligned to [`DATA_ALIGN`].
+    fn shm_bytes(&self) -> Vec<u8> {
+        let data = self.data.as_ref();
+        let mut bytes = Vec::with_capacity(256 + data.len() * T::SIZE);
+
+        bytes.extend_from_slice(MAGIC);
+        bytes.extend_from_slice(&VERSION.to_le_bytes());
+        bytes.push(T::DTYPE.len() as u8);
+        bytes.extend_from_slice(T::DTYPE.as_bytes());
+
+        let mut flags = 0;
+        for (flag, present) in [
+            (FLAG_ROW_MAJOR, self.row_major),
+            (FLAG_ROW_WEIGHTS, self.row_weights.is_some()),
+            (FLAG_SERIES_NAMES, self.series_names.is_some()),
+            (FLAG_ROW_CHECKSUMS, self.row_checksums.is_some()),
+            (FLAG_GAPS, self.gaps.is_some()),
+        ] {
+            if present {
+                flags |= flag;
+            }
+        }
+        bytes.push(flags);
+
+        for value in [
+            self.series_length,
+            self.series_count,
+            self.num_rows,
+            self.num_cols,
+            self.row_stride,
+            self.num_lags,
+            self.static_count,
+            self.time_offset,
+            self.source_length,
+            data.len(),
+        ] {
+            bytes.extend_from_slice(&(value as u64).to_le_bytes());
+        }
+
+        for &lag in &self.lags {
+            bytes.extend_from_slice(&(lag as i64).to_le_bytes());
+        }
+        bytes.extend(self.directions.iter().map(|direction| match direction {
+            LagDirection::Lag => 0,
+            LagDirection::Lead => 1,
+        }));
+
+        for name in self.series_names.iter().flatten() {
+            bytes.extend_from_slice(&(name.len() as u64).to_le_bytes());
+            bytes.extend_from_slice(name.as_bytes());
+        }
+        for &checksum in self.row_checksums.iter().flatten() {
+            bytes.extend_from_slice(&checksum.to_le_bytes());
+        }
+        bytes.extend(self.gaps.iter().flatten().map(|&gap| u8::from(gap)));
+
+        bytes.resize(bytes.len().next_multiple_of(DATA_ALIGN), 0);
+        for &value in data.iter().chain(self.row_weights.iter().flatten()) {
+            value.extend_le_bytes(&mut bytes);
+        }
+        bytes
+    }
+
+    /// Deletes the POSIX shared memory object `name`.
+    ///
+    /// Processes that mapped the object keep observing its values until they drop them.
+    pub fn remove_shm(name: &str) -> std::io::Result<()> {
+        let name = shm_name(name)?;
+        // SAFETY: The name is a valid C string.
+        if unsafe { libc::shm_unlink(name.as_ptr()) } != 0 {
+            return Err(Error::last_os_error());
+        }
+        Ok(())
+    }
+}
+
+impl<T> LagMatrix<T>
+where
+    T: NpyElement,
+{
+    /// Maps a matrix placed in the POSIX shared memory object `name` via
+    /// [`to_shm`](LagMatrix::to_shm).
+    ///
+    /// The values are borrowed from a read-only mapping of the object without copying them,
+    /// see [`SharedValues`], while the metadata is copied and validated like the shape of
+    /// [`from_parts`](LagMatrix::from_parts).
+    ///
+    /// ## Safety
+    /// The object must not be modified while the matrix or its values are alive. While
+    /// [`to_shm`](LagMatrix::to_shm) replaces objects rather than overwriting them, any
+    /// process that may open the object can write to it; the caller has to ensure that none
+    /// does, e.g. by restricting the object to cooperating processes of the same user.
+    ///
+    /// ## Returns
+    /// The matrix, or an error:
+    /// * of kind [`ErrorKind::InvalidData`] if the object is not a completely written matrix
+    ///   of the element type, or if its metadata is inconsistent,
+    /// * of kind [`ErrorKind::Unsupported`] on big-endian targets, which cannot view the
+    ///   little-endian values in place.
+    pub unsafe fn from_shm(name: &str) -> std::io::Result<LagMatrix<T, SharedValues<T>>> {
+        if cfg!(target_endian = "big") {
+            return Err(Error::new(
+                ErrorKind::Unsupported,
+                "Shared matrices can only be mapped on little-endian targets",
+            ));
+        }
+
+        let file = open(&shm_name(name)?, libc::O_RDONLY | libc::O_CLOEXEC)?;
+        let mapping_len = usize::try_from(file.metadata()?.len())
+            .map_err(|_| invalid("The shared matrix is too large"))?;
+        if mapping_len < MAGIC.len() {
+            return Err(invalid("Not a shared lag matrix"));
+        }
+
+        // SAFETY: A fresh read-only mapping of the whole object, which `SharedValues` owns.
+        let mapping = unsafe {
+            libc::mmap(
+                std::ptr::null_mut(),
+                mapping_len,
+                libc::PROT_READ,
+                libc::MAP_SHARED,
+                std::os::fd::AsRawFd::as_raw_fd(&file),
+                0,
+            )
+        };
+        if mapping == libc::MAP_FAILED {
+            return Err(Error::last_os_error());
+        }
+        let mut values = SharedValues {
+            mapping: NonNull::new(mapping).expect("mmap does not return null on success"),
+            mapping_len,
+            offset: 0,
+            len: 0,
+            values: PhantomData,
+        };
+
+        // SAFETY: The mapping spans `mapping_len` readable bytes and lives as long as `values`.
+        let bytes = unsafe { std::slice::from_raw_parts(mapping.cast::<u8>(), mapping_len) };
+        let mut reader = ByteReader { bytes };
+
+        if reader.take(MAGIC.len())? != MAGIC {
+            return Err(invalid("Not a shared lag matrix"));
+        }
+        if reader.u32()? != VERSION {
+            return Err(invalid("Unsupported shared matrix version"));
+        }
+
+        let dtype_len = usize::from(reader.take(1)?[0]);
+        if reader.take(dtype_len)? != T::DTYPE.as_bytes() {
+            return Err(invalid("The dtype does not match the element type"));
+        }
+
+        let flags = reader.take(1)?[0];
+        let series_length = reader.usize()?;
+        let series_count = reader.usize()?;
+        let num_rows = reader.usize()?;
+        let num_cols = reader.usize()?;
+        let row_stride = reader.usize()?;
+        let num_lags = reader.usize()?;
+        let static_count = reader.usize()?;
+        let time_offset = reader.usize()?;
+        let source_length = reader.usize()?;
+        let data_len = reader.usize()?;
+
+        let lags = (0..num_lags)
+            .map(|_| {
+                reader
+                    .i64()
+                    .and_then(|lag| isize::try_from(lag).map_err(|_| invalid("Lag out of range")))
+            })
+            .collect::<Result<Vec<_>, _>>()?;
+        let directions = reader
+            .take(series_count)?
+            .iter()
+            .map(|&direction| match direction {
+                0 => Ok(LagDirection::Lag),
+                1 => Ok(LagDirection::Lead),
+                _ => Err(invalid("Invalid lag direction")),
+            })
+            .collect::<Result<Vec<_>, _>>()?;
+
+        let series_names = if flags & FLAG_SERIES_NAMES != 0 {
+            let names = (0..series_count).map(|_| {
+                let len = reader.usize()?;
+                String::from_utf8(reader.take(len)?.to_vec())
+                    .map_err(|_| invalid("Invalid series name"))
+            });
+            Some(names.collect::<Result<Vec<_>, _>>()?)
+        } else {
+            None
+        };
+        let row_checksums = if flags & FLAG_ROW_CHECKSUMS != 0 {
+            Some(
+                (0..num_rows)
+                    .map(|_| reader.u64())
+                    .collect::<Result<_, _>>()?,
+            )
+        } else {
+            None
+        };
+        let gaps = if flags & FLAG_GAPS != 0 {
+            let len = num_lags
+                .checked_mul(series_count)
+                .and_then(|features| features.checked_mul(series_length))
+                .ok_or_else(|| invalid("Inconsistent matrix shape"))?;
+            Some(reader.take(len)?.iter().map(|&gap| gap != 0).collect())
+        } else {
+            None
+        };
+
+        let position = mapping_len - reader.bytes.len();
+        let offset = position.next_multiple_of(DATA_ALIGN);
+        reader.take(offset - position)?;
+        let data_bytes = data_len
+            .checked_mul(T::SIZE)
+            .ok_or_else(|| invalid("The shared matrix is too large"))?;
+        reader.take(data_bytes)?;
+        (values.offset, values.len) = (offset, data_len);
+
+        let row_weights = if flags & FLAG_ROW_WEIGHTS != 0 {
+            Some(reader.values(series_length)?)
+        } else {
+            None
+        };
+
+        let layout = if flags & FLAG_ROW_MAJOR != 0 {
+            MatrixLayout::RowMajor(series_length)
+        } else {
+            MatrixLayout::ColumnMajor(series_length)
+        };
+        // Rule out overflows in the length of the buffer before the shape is validated.
+        let features = num_lags.checked_mul(series_count);
+        let rows = features.and_then(|features| features.checked_add(static_count));
+        if rows.and_then(|rows| rows.checked_mul(row_stride)).is_none()
+            || series_length.checked_mul(row_stride).is_none()
+        {
+            return Err(invalid("Inconsistent matrix shape"));
+        }
+
+        let inconsistent = |_| invalid("Inconsistent matrix shape");
+        let mut shape = LagMatrixShape::new(layout, series_count, lags, row_stride)
+            .and_then(|shape| shape.with_directions(directions))
+            .map_err(inconsistent)?;
+        shape.series_names = series_names;
+        shape.static_count = static_count;
+        shape.time_offset = time_offset;
+        shape.source_length = source_length;
+        shape.gaps = gaps;
+
+        let mut matrix = LagMatrix::from_parts(values, shape).map_err(inconsistent)?;
+        let (_, lead) = matrix.max_shifts();
+        let window_end = time_offset
+            .checked_add(series_length)
+            .and_then(|end| end.checked_add(lead));
+        if matrix.row_stride != row_stride
+            || matrix.num_rows != num_rows
+            || matrix.num_cols != num_cols
+            || num_cols > row_stride
+            || window_end.is_none()
+        {
+            return Err(invalid("Inconsistent matrix shape"));
+        }
+
+        matrix.row_weights = row_weights;
+        matrix.row_checksums = row_checksums;
+        Ok(matrix)
+    }
+}
+
+/// The name of the shared memory object `name`, with a single leading slash.
+fn shm_name(name: &str) -> std::io::Result<CString> {
+    let name = name.strip_prefix('/').unwrap_or(name);
+    if name.is_empty() || name.contains(['/', '\0']) || name == "." || name == ".." {
+        return Err(Error::new(
+            ErrorKind::InvalidInput,
+            "Invalid shared memory object name",
+        ));
+    }
+    Ok(CString::new(format!("/{name}")).expect("the name holds no NUL bytes"))
+}
+
+/// Opens the shared memory object `name` with the specified flags.
+fn open(name: &CString, flags: libc::c_int) -> std::io::Result<File> {
+    // SAFETY: The name is a valid C string.
+    let fd = unsafe { libc::shm_open(name.as_ptr(), flags, 0o600) };
+    if fd < 0 {
+        return Err(Error::last_os_error());
+    }
+    // SAFETY: The descriptor was just opened and is owned by nothing else.
+    Ok(File::from(unsafe { OwnedFd::from_raw_fd(fd) }))
+}
+
+fn invalid(message: &str) -> Error {
+    Error::new(ErrorKind::InvalidData, message.to_string())
+}
+
+/// Reads little-endian values from a byte buffer.
+struct ByteReader<'a> {
+    bytes: &'a [u8],
+}
+
+impl<'a> ByteReader<'a> {
+    fn take(&mut self, len: usize) -> std::io::Result<&'a [u8]> {
+        if len > self.bytes.len() {
+            return Err(invalid("The shared matrix is truncated"));
+        }
+        let (head, tail) = self.bytes.split_at(len);
+        self.bytes = tail;
+        Ok(head)
+    }
+
+    fn u32(&mut self) -> std::io::Result<u32> {
+        let bytes = self.take(4)?.try_into().expect("four bytes were taken");
+        Ok(u32::from_le_bytes(bytes))
+    }
+
+    fn u64(&mut self) -> std::io::Result<u64> {
+        let bytes = self.take(8)?.try_into().expect("eight bytes were taken");
+        Ok(u64::from_le_bytes(bytes))
+    }
+
+    fn i64(&mut self) -> std::io::Result<i64> {
+        let bytes = self.take(8)?.try_into().expect("eight bytes were taken");
+        Ok(i64::from_le_bytes(bytes))
+    }
+
+    fn usize(&mut self) -> std::io::Result<usize> {
+        usize::try_from(self.u64()?).map_err(|_| invalid("Size out of range"))
+    }
+
+    fn values<T: NpyElement>(&mut self, count: usize) -> std::io::Result<Vec<T>> {
+        let len = count
+            .checked_mul(T::SIZE)
+            .ok_or_else(|| invalid("The shared matrix is too large"))?;
+        Ok(self
+            .take(len)?
+            .chunks_exact(T::SIZE)
+            .map(|bytes| T::from_bytes(bytes, false))
+            .collect())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{lag_matrix_2d_directed, lag_matrix_2d_segmented, recency_weights, MatrixLayout};
+
+    #[test]
+    fn test_round_trip() {
+        let name = format!("/timelag-test-{}", std::process::id());
+        let data: Vec<f64> = (0..12).map(f64::from).collect();
+        let directions = [LagDirection::Lag, LagDirection::Lead];
+        let lagged = lag_matrix_2d_directed(
+            &data,
+            MatrixLayout::ColumnMajor(6),
+            [0, 2],
+            &directions,
+            f64::INFINITY,
+            7,
+        )
+        .unwrap()
+        .with_series_names(["x", "ÿ"])
+        .unwrap()
+        .with_static_columns(&[5.0])
+        .with_row_weights(recency_weights(6, 2.0))
+        .unwrap()
+        .trim_incomplete()
+        .unwrap()
+        .with_row_checksums();
+
+        lagged.to_shm(&name).unwrap();
+        let shared = unsafe { LagMatrix::<f64>::from_shm(&name) }.unwrap();
+        assert_eq!(shared.view(), lagged.view());
+        assert_eq!(shared.series_names(), lagged.series_names());
+        assert_eq!(shared.verify_rows(0..shared.num_rows()), Ok(()));
+
+        // The values are viewed in place, at an aligned offset of the mapping.
+        assert!(shared.as_ptr().is_aligned());
+        assert_eq!(shared.data.len, lagged.len());
+
+        assert_eq!(
+            unsafe { LagMatrix::<f32>::from_shm(&name) }
+                .unwrap_err()
+                .kind(),
+            ErrorKind::InvalidData
+        );
+
+        // Replacing the object leaves existing mappings untouched.
+        let gapped =
+            lag_matrix_2d_segmented(&data, MatrixLayout::RowMajor(6), [0, 1], 0.0, 0, &[3])
+                .unwrap();
+        gapped.to_shm(&name).unwrap();
+        let replaced = unsafe { LagMatrix::<f64>::from_shm(&name) }.unwrap();
+        assert!(replaced.has_interior_gaps());
+        assert_eq!(replaced.view(), gapped.view());
+        assert_eq!(shared.view(), lagged.view());
+
+        LagMatrix::<f64>::remove_shm(&name).unwrap();
+        assert_eq!(
+            unsafe { LagMatrix::<f64>::from_shm(&name) }
+                .unwrap_err()
+                .kind(),
+            ErrorKind::NotFound
+        );
+    }
+
+    #[test]
+    fn test_rejects_inconsistent_headers() {
+        let name = format!("/timelag-header-{}", std::process::id());
+        let data: Vec<f64> = (0..12).map(f64::from).collect();
+        let lagged = lag_matrix_2d_directed(
+            &data,
+            MatrixLayout::RowMajor(6),
+            [0, 2],
+            &[LagDirection::Lag, LagDirection::Lead],
+            0.0,
+            8,
+        )
+        .unwrap()
+        .with_row_weights(recency_weights(6, 2.0))
+        .unwrap();
+
+        // The sizes follow the magic string, the version, the dtype and the flags.
+        let sizes = MAGIC.len() + 4 + 1 + f64::DTYPE.len() + 1;
+        let grown = [0, 1, 2, 3, 4, 5, 6].map(|field| (field, false));
+        let overflown = [0, 1, 4, 6, 7].map(|field| (field, true));
+        for (field, overflow) in grown.into_iter().chain(overflown) {
+            lagged.to_shm(&name).unwrap();
+            let file = open(&shm_name(&name).unwrap(), libc::O_RDWR).unwrap();
+            let position = (sizes + field * 8) as u64;
+            let mut header = [0; 8];
+            file.read_exact_at(&mut header, position).unwrap();
+            let value = if overflow {
+                u64::MAX
+            } else {
+                u64::from_le_bytes(header) + 1
+            };
+            file.write_all_at(&value.to_le_bytes(), position).unwrap();
+
+            let error = unsafe { LagMatrix::<f64>::from_shm(&name) }.unwrap_err();
+            assert_eq!(
+                error.kind(),
+                ErrorKind::InvalidData,
+                "field {field}: {error}"
+            );
+        }
+        LagMatrix::<f64>::remove_shm(&name).unwrap();
+    }
+
+    #[test]
+    fn test_invalid_names() {
+        for name in ["", "/", "a/b", "/..", "a\0b"] {
+            assert_eq!(shm_name(name).unwrap_err().kind(), ErrorKind::InvalidInput);
+        }
+        assert_eq!(shm_name("/features").unwrap().as_bytes(), b"/features");
+        assert_eq!(shm_name("features").unwrap().as_bytes(), b"/features");
+    }
+}
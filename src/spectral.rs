@@ -0,0 +1,310 @@
+use crate::{LagError, LagSet};
+use core::cmp::Ordering;
+use num_traits::Float;
+use rustfft::num_complex::Complex;
+use rustfft::{FftNum, FftPlanner};
+
+/// The periodogram of a time series; see [`periodogram`].
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct Periodogram<T> {
+    len: usize,
+    power: Vec<T>,
+}
+
+impl<T> Periodogram<T>
+where
+    T: Float,
+{
+    /// The length of the analyzed series.
+    #[inline(always)]
+    pub const fn series_length(&self) -> usize {
+        self.len
+    }
+
+    /// The spectral power at the frequencies `k / series_length` for `k` in `0..=series_length / 2`.
+    #[inline(always)]
+    pub fn power(&self) -> &[T] {
+        &self.power
+    }
+
+    /// The frequency of bin `k`, in cycles per time step.
+    pub fn frequency(&self, k: usize) -> T {
+        T::from(k).unwrap_or_else(T::nan) / T::from(self.len).unwrap_or_else(T::nan)
+    }
+
+    /// The period of bin `k`, in time steps; infinite for the zero frequency.
+    pub fn period(&self, k: usize) -> T {
+        T::from(self.len).unwrap_or_else(T::nan) / T::from(k).unwrap_or_else(T::nan)
+    }
+
+    /// The bins of the `top_k` strongest spectral peaks, strongest first.
+    ///
+    /// A peak is a bin whose power is at least that of its neighbors; the zero frequency is
+    /// never reported.
+    pub fn peaks(&self, top_k: usize) -> Vec<usize> {
+        let power = &self.power;
+        let mut peaks: Vec<usize> = (1..power.len())
+            .filter(|&k| k == 1 || power[k] >= power[k - 1])
+            .filter(|&k| k + 1 == power.len() || power[k] >= power[k + 1])
+            .filter(|&k| power[k] > T::zero())
+            .collect();
+
+        peaks.sort_by(|&a, &b| {
+            power[b]
+                .partial_cmp(&power[a])
+                .unwrap_or(Ordering::Equal)
+                .then(a.cmp(&b))
+        });
+        peaks.truncate(top_k);
+        peaks
+    }
+
+    /// The periods of the `top_k` strongest spectral peaks, strongest first.
+    pub fn dominant_periods(&self, top_k: usize) -> Vec<T> {
+        self.peaks(top_k)
+            .into_iter()
+            .map(|k| self.period(k))
+            .collect()
+    }
+
+    /// Suggests seasonal lags from the `top_k` strongest spectral peaks.
+    ///
+    /// Every dominant period is rounded to the nearest time step; duplicates and periods
+    /// not shorter than the series are skipped. The lags are ordered by peak strength.
+    pub fn suggest_seasonal_lags(&self, top_k: usize) -> LagSet {
+        let mut lags = Vec::with_capacity(top_k);
+        for period in self.dominant_periods(top_k) {
+            let Some(lag) = period.round().to_usize() else {
+                continue;
+            };
+            if lag >= 1 && lag < self.len && !lags.contains(&lag) {
+                lags.push(lag);
+            }
+        }
+        LagSet::from(lags)
+    }
+}
+
+/// Computes the periodogram of a time series to identify seasonal periods.
+///
+/// The mean is removed before the power `|X(k)|² / n` of the discrete Fourier transform is
+/// computed for the non-negative frequencies. The strongest peaks indicate seasonalities that
+/// are candidates for lag features, see [`Periodogram::suggest_seasonal_lags`].
+///
+/// ## Arguments
+/// * `data` - The time series to analyze.
+///
+/// ## Returns
+/// The periodogram, or [`LagError::EmptyData`] if `data` is empty.
+///
+/// ## Example
+/// ```
+/// use timelag::periodogram;
+///
+/// // A weekly and a monthly seasonality.
+/// let data: Vec<f64> = (0..210)
+///     .map(|t| {
+///         let t = t as f64;
+///         (t * std::f64::consts::TAU / 7.0).sin() + 0.5 * (t * std::f64::consts::TAU / 30.0).cos()
+///     })
+///     .collect();
+///
+/// let spectrum = periodogram(&data).unwrap();
+/// let lags = spectrum.suggest_seasonal_lags(2);
+/// assert_eq!(lags.as_slice(), &[7, 30]);
+/// ```
+pub fn periodogram<T>(data: &[T]) -> Result<Periodogram<T>, LagError>
+where
+    T: Float + FftNum,
+{
+    if data.is_empty() {
+        return Err(LagError::EmptyData);
+    }
+
+    let n = data.len();
+    let scale = T::from(n).ok_or(LagError::InvalidLength)?;
+    let mean = data.iter().fold(T::zero(), |sum, &x| sum + x) / scale;
+
+    let mut spectrum: Vec<_> = data
+        .iter()
+        .map(|&x| Complex::new(x - mean, T::zero()))
+        .collect();
+    FftPlanner::new().plan_fft_forward(n).process(&mut spectrum);
+
+    let power = spectrum[..=n / 2]
+        .iter()
+        .map(|value| value.norm_sqr() / scale)
+        .collect();
+
+    Ok(Periodogram { len: n, power })
+}
+
+/// The spectral magnitudes of the trailing windows of a series; see [`lag_spectrum`].
+#[derive(Debug, Clone, PartialEq)]
+pub struct LagSpectrum<T> {
+    window: usize,
+    bins: usize,
+    magnitudes: Vec<T>,
+}
+
+impl<T> LagSpectrum<T> {
+    /// The number of values per window.
+    #[inline(always)]
+    pub const fn window(&self) -> usize {
+        self.window
+    }
+
+    /// The number of frequency bins per time step.
+    #[inline(always)]
+    pub const fn bins(&self) -> usize {
+        self.bins
+    }
+
+    /// The number of time steps, i.e. the series length.
+    #[inline(always)]
+    pub fn series_length(&self) -> usize {
+        self.magnitudes.len() / self.bins
+    }
+
+    /// The magnitudes of all time steps, stored with one row of bins per time step.
+    #[inline(always)]
+    pub fn magnitudes(&self) -> &[T] {
+        &self.magnitudes
+    }
+
+    /// The magnitudes of the window ending at time step `t`, or `None` if `t` is out of range.
+    pub fn get(&self, t: usize) -> Option<&[T]> {
+        self.magnitudes.get(t * self.bins..(t + 1) * self.bins)
+    }
+}
+
+/// Computes the spectral magnitudes of the trailing window of every time step, e.g. as
+/// frequency-domain features next to the lag matrix in vibration or audio classification.
+///
+/// The window of time step `t` holds the values `t + 1 - window..=t`, i.e. the lags
+/// `0..window`, in time order. Its magnitudes `|X(k)|` of the discrete Fourier transform are
+/// computed for the bins `k` in `0..bins`, where bin `k` corresponds to the frequency
+/// `k / window`. The time steps whose windows reach before the start of the series are
+/// filled in, like the lagged gaps of [`lag_matrix`](crate::lag_matrix).
+///
+/// ## Arguments
+/// * `data` - The time series.
+/// * `window` - The number of values per window.
+/// * `bins` - The number of frequency bins to keep, at most `window / 2 + 1`.
+/// * `fill` - The value to use for the incomplete windows.
+///
+/// ## Returns
+/// The magnitudes, or an error:
+/// * [`LagError::InvalidLength`] if the window is empty or the bins are out of range,
+/// * [`LagError::LagExceedsValueCount`] if the window exceeds the series length.
+///
+/// ## Example
+/// ```
+/// use timelag::lag_spectrum;
+///
+/// let data = [1.0, -1.0, 1.0, -1.0, 1.0];
+/// let spectrum = lag_spectrum(&data, 4, 3, f64::NAN).unwrap();
+///
+/// assert_eq!(spectrum.series_length(), 5);
+/// assert!(spectrum.get(2).unwrap()[0].is_nan());
+///
+/// // The alternation is concentrated in the highest bin.
+/// let magnitudes = spectrum.get(3).unwrap();
+/// assert!(magnitudes[0].abs() < 1e-12);
+/// assert!((magnitudes[2] - 4.0).abs() < 1e-12);
+/// ```
+pub fn lag_spectrum<T>(
+    data: &[T],
+    window: usize,
+    bins: usize,
+    fill: T,
+) -> Result<LagSpectrum<T>, LagError>
+where
+    T: Float + FftNum,
+{
+    if window == 0 || bins == 0 || bins > window / 2 + 1 {
+        return Err(LagError::InvalidLength);
+    }
+    if window > data.len() {
+        return Err(LagError::LagExceedsValueCount);
+    }
+
+    let fft = FftPlanner::new().plan_fft_forward(window);
+    let mut buffer = vec![Complex::new(T::zero(), T::zero()); window];
+    let mut magnitudes = Vec::with_capacity(data.len() * bins);
+    magnitudes.resize((window - 1) * bins, fill);
+    for values in data.windows(window) {
+        for (target, &value) in buffer.iter_mut().zip(values) {
+            *target = Complex::new(value, T::zero());
+        }
+        fft.process(&mut buffer);
+        magnitudes.extend(buffer[..bins].iter().map(|value| value.norm()));
+    }
+
+    Ok(LagSpectrum {
+        window,
+        bins,
+        magnitudes,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn seasonal(len: usize, period: f64) -> Vec<f64> {
+        (0..len)
+            .map(|t| 3.0 + (t as f64 * core::f64::consts::TAU / period).sin())
+            .collect()
+    }
+
+    #[test]
+    fn test_single_seasonality() {
+        let spectrum = periodogram(&seasonal(120, 12.0)).unwrap();
+        assert_eq!(spectrum.power().len(), 61);
+        assert_eq!(spectrum.peaks(1), vec![10]);
+        assert!((spectrum.dominant_periods(1)[0] - 12.0).abs() < 1e-12);
+        assert_eq!(spectrum.suggest_seasonal_lags(1).as_slice(), &[12]);
+
+        // The mean is removed.
+        assert!(spectrum.power()[0] < 1e-12);
+    }
+
+    #[test]
+    fn test_constant_series_has_no_peaks() {
+        let spectrum = periodogram(&[2.0_f32; 16]).unwrap();
+        assert!(spectrum.peaks(3).is_empty());
+        assert!(spectrum.suggest_seasonal_lags(3).is_empty());
+    }
+
+    #[test]
+    fn test_empty() {
+        assert_eq!(periodogram::<f64>(&[]).unwrap_err(), LagError::EmptyData);
+    }
+
+    #[test]
+    fn test_lag_spectrum_matches_windowed_dft() {
+        let data = seasonal(32, 8.0);
+        let spectrum = lag_spectrum(&data, 8, 5, -1.0).unwrap();
+        assert_eq!(spectrum.magnitudes().len(), 32 * 5);
+        assert_eq!(spectrum.get(6), Some(&[-1.0; 5][..]));
+        assert_eq!(spectrum.get(32), None);
+
+        // The mean and the seasonality of period 8 dominate every complete window.
+        for t in 7..32 {
+            let magnitudes = spectrum.get(t).unwrap();
+            assert!((magnitudes[0] - 24.0).abs() < 1e-9);
+            assert!((magnitudes[1] - 4.0).abs() < 1e-9);
+            assert!(magnitudes[2..].iter().all(|m| m.abs() < 1e-9));
+        }
+
+        assert_eq!(
+            lag_spectrum(&data, 8, 6, 0.0).unwrap_err(),
+            LagError::InvalidLength
+        );
+        assert_eq!(
+            lag_spectrum(&data[..4], 8, 1, 0.0).unwrap_err(),
+            LagError::LagExceedsValueCount
+        );
+    }
+}
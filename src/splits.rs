@@ -0,0 +1,186 @@
+use crate::{LagError, LagMatrix};
+use core::ops::Range;
+
+/// A train/test split of time step indices; see [`TimeSeriesSplit`].
+#[derive(Debug, Clone, Eq, PartialEq, Hash)]
+pub struct Split {
+    /// The time steps to train on.
+    pub train: Range<usize>,
+    /// The time steps to test on, following the training steps after the gap.
+    pub test: Range<usize>,
+}
+
+/// Creates leak-free cross-validation splits of a time series, where every test window
+/// lies strictly after its training window.
+///
+/// * An _expanding window_ trains on all time steps before the forecast origin.
+/// * A _rolling window_ trains on a fixed number of time steps before the origin.
+///
+/// The origin starts after the minimum training size and advances by the test size, such
+/// that the test windows do not overlap. A gap skips time steps between the training and
+/// test window, similar to the `gap` of scikit-learn's `TimeSeriesSplit`.
+///
+/// ## Example
+/// ```
+/// use timelag::TimeSeriesSplit;
+///
+/// let splits = TimeSeriesSplit::expanding(3, 2).with_gap(1).split(10).unwrap();
+///
+/// assert_eq!(splits.len(), 3);
+/// assert_eq!((splits[0].train.clone(), splits[0].test.clone()), (0..3, 4..6));
+/// assert_eq!((splits[1].train.clone(), splits[1].test.clone()), (0..5, 6..8));
+/// assert_eq!((splits[2].train.clone(), splits[2].test.clone()), (0..7, 8..10));
+/// ```
+#[derive(Debug, Copy, Clone, Eq, PartialEq, Hash)]
+pub struct TimeSeriesSplit {
+    min_train: usize,
+    max_train: Option<usize>,
+    test_size: usize,
+    gap: usize,
+}
+
+impl TimeSeriesSplit {
+    /// Creates expanding-window splits, training on at least `min_train` time steps and
+    /// testing on `test_size` time steps.
+    #[inline(always)]
+    pub const fn expanding(min_train: usize, test_size: usize) -> Self {
+        Self {
+            min_train,
+            max_train: None,
+            test_size,
+            gap: 0,
+        }
+    }
+
+    /// Creates rolling-window splits, training on exactly `train_size` time steps and
+    /// testing on `test_size` time steps.
+    #[inline(always)]
+    pub const fn rolling(train_size: usize, test_size: usize) -> Self {
+        Self {
+            min_train: train_size,
+            max_train: Some(train_size),
+            test_size,
+            gap: 0,
+        }
+    }
+
+    /// Skips `gap` time steps between every training and test window.
+    #[inline(always)]
+    pub const fn with_gap(mut self, gap: usize) -> Self {
+        self.gap = gap;
+        self
+    }
+
+    /// The number of time steps skipped between the training and test windows.
+    #[inline(always)]
+    pub const fn gap(&self) -> usize {
+        self.gap
+    }
+
+    /// Splits the time steps `0..length` of a series.
+    ///
+    /// ## Returns
+    /// The splits in order of their origin, or [`LagError::InvalidLength`] if the sizes are
+    /// zero or no split fits into the series.
+    pub fn split(&self, length: usize) -> Result<Vec<Split>, LagError> {
+        self.split_range(0..length)
+    }
+
+    /// Splits the complete time steps of a lag matrix, i.e. the rows without lagged gaps;
+    /// see [`trim_incomplete`](LagMatrix::trim_incomplete).
+    ///
+    /// The indices refer to the time steps of the matrix, not of the original series.
+    ///
+    /// ## Returns
+    /// The splits in order of their origin, or [`LagError::InvalidLength`] if the sizes are
+    /// zero or no split fits into the complete time steps.
+    ///
+    /// ## Example
+    /// ```
+    /// use timelag::{lag_matrix, TimeSeriesSplit};
+    ///
+    /// let data = [1.0, 2.0, 3.0, 4.0, 5.0, 6.0];
+    /// let lagged = lag_matrix(&data, 0..=2, f64::NAN, 0).unwrap();
+    ///
+    /// // The first two time steps are incomplete.
+    /// let splits = TimeSeriesSplit::rolling(2, 1).split_matrix(&lagged).unwrap();
+    /// assert_eq!(splits[0].train, 2..4);
+    /// assert_eq!(splits[0].test, 4..5);
+    /// assert_eq!(splits[1].train, 3..5);
+    /// assert_eq!(splits[1].test, 5..6);
+    /// ```
+    pub fn split_matrix<T, S>(&self, matrix: &LagMatrix<T, S>) -> Result<Vec<Split>, LagError>
+    where
+        S: AsRef<[T]>,
+    {
+        let (leading, trailing) = matrix.incomplete_steps();
+        let end = matrix.series_length().saturating_sub(trailing);
+        self.split_range(leading..end.max(leading))
+    }
+
+    /// Splits the time steps of the range.
+    fn split_range(&self, steps: Range<usize>) -> Result<Vec<Split>, LagError> {
+        if self.min_train == 0 || self.test_size == 0 {
+            return Err(LagError::InvalidLength);
+        }
+
+        let mut splits = Vec::new();
+        let mut origin = steps.start + self.min_train;
+        while origin + self.gap + self.test_size <= steps.end {
+            let train_start = match self.max_train {
+                Some(size) => origin - size,
+                None => steps.start,
+            };
+            let test_start = origin + self.gap;
+            splits.push(Split {
+                train: train_start..origin,
+                test: test_start..test_start + self.test_size,
+            });
+            origin += self.test_size;
+        }
+
+        if splits.is_empty() {
+            return Err(LagError::InvalidLength);
+        }
+        Ok(splits)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_rolling_window_keeps_training_size() {
+        let splits = TimeSeriesSplit::rolling(3, 2)
+            .with_gap(2)
+            .split(12)
+            .unwrap();
+        let windows: Vec<_> = splits.into_iter().map(|s| (s.train, s.test)).collect();
+        assert_eq!(windows, vec![(0..3, 5..7), (2..5, 7..9), (4..7, 9..11)]);
+    }
+
+    #[test]
+    fn test_invalid_sizes() {
+        assert_eq!(
+            TimeSeriesSplit::expanding(0, 1).split(10),
+            Err(LagError::InvalidLength)
+        );
+        assert_eq!(
+            TimeSeriesSplit::expanding(5, 0).split(10),
+            Err(LagError::InvalidLength)
+        );
+        assert_eq!(
+            TimeSeriesSplit::expanding(5, 3).with_gap(3).split(10),
+            Err(LagError::InvalidLength)
+        );
+        assert_eq!(
+            TimeSeriesSplit::expanding(5, 2)
+                .with_gap(3)
+                .split(10)
+                .unwrap()
+                .len(),
+            1
+        );
+    }
+}
@@ -0,0 +1,437 @@
+use crate::{LagError, LagRow, OnlineLagger};
+use core::pin::Pin;
+use core::task::{ready, Context, Poll};
+use futures_core::stream::FusedStream;
+use futures_core::Stream;
+
+/// Creates a [`Stream`] of completed lag rows from a stream of observations.
+///
+/// Every item of the source stream is fed into an [`OnlineLagger`]; rows are
+/// emitted as soon as all requested lags are available. The stream ends when
+/// the source stream ends, and the source stream is not polled afterwards.
+///
+/// Streams that are not [`Unpin`] can be adapted by pinning them first,
+/// e.g. using `Box::pin`.
+///
+/// ## Arguments
+/// * `stream` - The source stream of observations.
+/// * `lags` - The lags to produce, in the order they should appear in each row.
+///
+/// ## Returns
+/// The stream of lag rows, or an error if the lags are invalid.
+///
+/// ## Example
+/// ```
+/// use futures::{executor::block_on, stream, StreamExt};
+/// use timelag::lag_stream;
+///
+/// let source = stream::iter([1.0, 2.0, 3.0, 4.0]);
+/// let rows = block_on(lag_stream(source, 0..=2).unwrap().collect::<Vec<_>>());
+///
+/// assert_eq!(rows.len(), 2);
+/// assert_eq!(rows[0].values(), &[3.0, 2.0, 1.0]);
+/// assert_eq!(rows[1].values(), &[4.0, 3.0, 2.0]);
+/// ```
+pub fn lag_stream<S, T, R>(stream: S, lags: R) -> Result<LagStream<S, T>, LagError>
+where
+    S: Stream<Item = T> + Unpin,
+    T: Copy,
+    R: IntoIterator<Item = usize>,
+{
+    Ok(LagStream {
+        inner: stream,
+        lagger: OnlineLagger::new(lags)?,
+        done: false,
+    })
+}
+
+/// A [`Stream`] of [`LagRow`] values; see [`lag_stream`].
+#[derive(Debug)]
+pub struct LagStream<S, T> {
+    inner: S,
+    lagger: OnlineLagger<T>,
+    done: bool,
+}
+
+impl<S, T> LagStream<S, T> {
+    /// The lagger used to produce the rows.
+    #[inline(always)]
+    pub fn lagger(&self) -> &OnlineLagger<T> {
+        &self.lagger
+    }
+
+    /// Consumes this stream, returning the source stream.
+    #[inline(always)]
+    pub fn into_inner(self) -> S {
+        self.inner
+    }
+
+    /// Groups the emitted rows into batches of at most `batch_size` rows.
+    ///
+    /// A batch is emitted once it is full or when the source stream ends. Use
+    /// [`BatchedLagStream::flush_when_idle`] to also emit partial batches whenever
+    /// the source stream has no further observation ready, or
+    /// [`BatchedLagStream::flush_on`] to emit them on the ticks of a timer.
+    ///
+    /// ## Panics
+    /// Panics if `batch_size` is zero.
+    ///
+    /// ## Example
+    /// ```
+    /// use futures::{executor::block_on, stream, StreamExt};
+    /// use timelag::lag_stream;
+    ///
+    /// let source = stream::iter(1..=6);
+    /// let batches = lag_stream(source, [0, 1]).unwrap().batched(2);
+    /// let batches = block_on(batches.collect::<Vec<_>>());
+    ///
+    /// assert_eq!(batches.len(), 3);
+    /// assert_eq!(batches[0].len(), 2);
+    /// assert_eq!(batches[2].len(), 1);
+    /// assert_eq!(batches[2][0].values(), &[6, 5]);
+    /// ```
+    pub fn batched(self, batch_size: usize) -> BatchedLagStream<S, T> {
+        assert!(batch_size > 0, "the batch size must be greater than zero");
+        BatchedLagStream {
+            inner: self,
+            batch: Vec::with_capacity(batch_size),
+            batch_size,
+            flush_when_idle: false,
+            ticks: None,
+        }
+    }
+}
+
+// The observations are never pinned, so only the source stream matters.
+impl<S: Unpin, T> Unpin for LagStream<S, T> {}
+
+impl<S, T> Stream for LagStream<S, T>
+where
+    S: Stream<Item = T> + Unpin,
+    T: Copy,
+{
+    type Item = LagRow<T>;
+
+    fn poll_next(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<Self::Item>> {
+        let this = self.get_mut();
+        while !this.done {
+            match ready!(Pin::new(&mut this.inner).poll_next(cx)) {
+                Some(value) => {
+                    if let Some(row) = this.lagger.push(value) {
+                        return Poll::Ready(Some(row));
+                    }
+                }
+                None => this.done = true,
+            }
+        }
+        Poll::Ready(None)
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        if self.done {
+            return (0, Some(0));
+        }
+        let (lower, upper) = self.inner.size_hint();
+        let pending = self.lagger.warmup_remaining();
+        let upper = upper.map(|upper| upper.saturating_sub(pending));
+        if self.lagger.skips_repeated() {
+            (0, upper)
+        } else {
+            (lower.saturating_sub(pending), upper)
+        }
+    }
+}
+
+impl<S, T> FusedStream for LagStream<S, T>
+where
+    S: Stream<Item = T> + Unpin,
+    T: Copy,
+{
+    #[inline(always)]
+    fn is_terminated(&self) -> bool {
+        self.done
+    }
+}
+
+/// A [`Stream`] of batches of [`LagRow`] values; see [`LagStream::batched`].
+///
+/// The ticks of `F` flush partial batches, see [`flush_on`](Self::flush_on); by default,
+/// there are none.
+#[derive(Debug)]
+pub struct BatchedLagStream<S, T, F = NoTicks> {
+    inner: LagStream<S, T>,
+    batch: Vec<LagRow<T>>,
+    batch_size: usize,
+    flush_when_idle: bool,
+    ticks: Option<F>,
+}
+
+/// The ticks of a [`BatchedLagStream`] without a flush timer, i.e. a stream that ends
+/// without yielding.
+#[derive(Debug, Copy, Clone, Default)]
+pub struct NoTicks;
+
+impl Stream for NoTicks {
+    type Item = ();
+
+    #[inline(always)]
+    fn poll_next(self: Pin<&mut Self>, _cx: &mut Context<'_>) -> Poll<Option<Self::Item>> {
+        Poll::Ready(None)
+    }
+}
+
+impl<S, T> BatchedLagStream<S, T> {
+    /// Emits partial batches whenever the specified stream yields, e.g. the interval timer
+    /// of the async runtime, bounding the latency of batches of slow sources.
+    ///
+    /// Timers are not part of `futures-core`, so any runtime's interval stream can be
+    /// used, e.g. `tokio_stream::wrappers::IntervalStream`. Ticks without pending rows are
+    /// skipped, and batches are still emitted once they are full.
+    ///
+    /// ## Example
+    /// ```
+    /// use futures::channel::mpsc;
+    /// use futures::{executor::block_on, FutureExt, StreamExt};
+    /// use timelag::lag_stream;
+    ///
+    /// let (values, source) = mpsc::unbounded();
+    /// let (ticks, timer) = mpsc::unbounded::<()>();
+    /// let mut batches = lag_stream(source, [0, 1]).unwrap().batched(10).flush_on(timer);
+    ///
+    /// for value in 1..=3 {
+    ///     values.unbounded_send(value).unwrap();
+    /// }
+    /// assert!(batches.next().now_or_never().is_none());
+    ///
+    /// // The timer fires, flushing the two pending rows.
+    /// ticks.unbounded_send(()).unwrap();
+    /// let batch = block_on(batches.next()).unwrap();
+    /// assert_eq!(batch.len(), 2);
+    /// ```
+    pub fn flush_on<F>(self, ticks: F) -> BatchedLagStream<S, T, F>
+    where
+        F: Stream + Unpin,
+    {
+        BatchedLagStream {
+            inner: self.inner,
+            batch: self.batch,
+            batch_size: self.batch_size,
+            flush_when_idle: self.flush_when_idle,
+            ticks: Some(ticks),
+        }
+    }
+}
+
+impl<S, T, F> BatchedLagStream<S, T, F> {
+    /// The maximum number of rows per batch.
+    #[inline(always)]
+    pub const fn batch_size(&self) -> usize {
+        self.batch_size
+    }
+
+    /// Determines whether partial batches are emitted as soon as the source
+    /// stream has no observation ready, rather than waiting for the batch to fill up.
+    ///
+    /// This keeps latency low for bursty sources while still batching rows
+    /// whenever observations arrive faster than they are consumed.
+    pub fn flush_when_idle(mut self, flush: bool) -> Self {
+        self.flush_when_idle = flush;
+        self
+    }
+
+    /// Consumes this stream, returning the unbatched stream.
+    ///
+    /// Rows of a pending partial batch are dropped.
+    #[inline(always)]
+    pub fn into_inner(self) -> LagStream<S, T> {
+        self.inner
+    }
+
+    fn take_batch(&mut self) -> Vec<LagRow<T>> {
+        core::mem::replace(&mut self.batch, Vec::with_capacity(self.batch_size))
+    }
+}
+
+impl<S, T, F> BatchedLagStream<S, T, F>
+where
+    F: Stream + Unpin,
+{
+    /// Consumes the pending ticks, returning whether at least one occurred.
+    fn poll_ticks(&mut self, cx: &mut Context<'_>) -> bool {
+        let mut ticked = false;
+        while let Some(ticks) = &mut self.ticks {
+            match Pin::new(ticks).poll_next(cx) {
+                Poll::Ready(Some(_)) => ticked = true,
+                Poll::Ready(None) => self.ticks = None,
+                Poll::Pending => break,
+            }
+        }
+        ticked
+    }
+}
+
+impl<S: Unpin, T, F: Unpin> Unpin for BatchedLagStream<S, T, F> {}
+
+impl<S, T, F> Stream for BatchedLagStream<S, T, F>
+where
+    S: Stream<Item = T> + Unpin,
+    T: Copy,
+    F: Stream + Unpin,
+{
+    type Item = Vec<LagRow<T>>;
+
+    fn poll_next(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<Self::Item>> {
+        let this = self.get_mut();
+        loop {
+            match Pin::new(&mut this.inner).poll_next(cx) {
+                Poll::Ready(Some(row)) => {
+                    this.batch.push(row);
+                    if this.batch.len() >= this.batch_size {
+                        return Poll::Ready(Some(this.take_batch()));
+                    }
+                }
+                Poll::Ready(None) => {
+                    return if this.batch.is_empty() {
+                        Poll::Ready(None)
+                    } else {
+                        Poll::Ready(Some(this.take_batch()))
+                    };
+                }
+                Poll::Pending => {
+                    let ticked = this.poll_ticks(cx);
+                    let flush = this.flush_when_idle || ticked;
+                    return if flush && !this.batch.is_empty() {
+                        Poll::Ready(Some(this.take_batch()))
+                    } else {
+                        Poll::Pending
+                    };
+                }
+            }
+        }
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        let (lower, upper) = self.inner.size_hint();
+        let batches = |rows: usize| (rows + self.batch.len()).div_ceil(self.batch_size);
+        (batches(lower), upper.map(batches))
+    }
+}
+
+impl<S, T, F> FusedStream for BatchedLagStream<S, T, F>
+where
+    S: Stream<Item = T> + Unpin,
+    T: Copy,
+    F: Stream + Unpin,
+{
+    #[inline(always)]
+    fn is_terminated(&self) -> bool {
+        self.inner.is_terminated() && self.batch.is_empty()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use futures::executor::block_on;
+    use futures::{stream, StreamExt};
+
+    #[test]
+    fn test_lag_stream() {
+        let source = stream::iter(1..=5);
+        let rows = lag_stream(source, [1, 0]).unwrap();
+        assert_eq!(rows.size_hint(), (4, Some(4)));
+
+        let rows: Vec<_> = block_on(rows.map(LagRow::into_vec).collect());
+        assert_eq!(rows, vec![vec![1, 2], vec![2, 3], vec![3, 4], vec![4, 5]]);
+    }
+
+    #[test]
+    fn test_lag_stream_too_short() {
+        let source = stream::iter([1.0, 2.0]);
+        let rows: Vec<_> = block_on(lag_stream(source, 0..=2).unwrap().collect());
+        assert!(rows.is_empty());
+    }
+
+    #[test]
+    fn test_batched_lag_stream() {
+        let source = stream::iter(1..=8);
+        let batches = lag_stream(source, [0, 2]).unwrap().batched(4);
+        assert_eq!(batches.size_hint(), (2, Some(2)));
+
+        let batches: Vec<_> = block_on(batches.collect());
+        assert_eq!(batches.len(), 2);
+        assert_eq!(batches[0].len(), 4);
+        assert_eq!(batches[1].len(), 2);
+        assert_eq!(batches[0][0].values(), &[3, 1]);
+        assert_eq!(batches[1][1].values(), &[8, 6]);
+    }
+
+    #[test]
+    fn test_batched_lag_stream_flush_when_idle() {
+        let (tx, rx) = futures::channel::mpsc::unbounded();
+        let mut batches = lag_stream(rx, [0, 1])
+            .unwrap()
+            .batched(10)
+            .flush_when_idle(true);
+
+        for value in 1..=3 {
+            tx.unbounded_send(value).unwrap();
+        }
+
+        // Only two rows are available, so the partial batch is flushed.
+        let batch = block_on(batches.next()).unwrap();
+        assert_eq!(batch.len(), 2);
+        assert_eq!(batch[1].values(), &[3, 2]);
+
+        tx.unbounded_send(4).unwrap();
+        drop(tx);
+
+        let batch = block_on(batches.next()).unwrap();
+        assert_eq!(batch.len(), 1);
+        assert!(block_on(batches.next()).is_none());
+    }
+
+    #[test]
+    fn test_batched_lag_stream_does_not_poll_exhausted_source() {
+        // Unfold panics when polled after it ended.
+        let source = stream::unfold(1, |value| async move {
+            (value <= 6).then_some((value, value + 1))
+        });
+        let batches = lag_stream(Box::pin(source), [0, 1]).unwrap().batched(4);
+        let batches: Vec<_> = block_on(batches.collect());
+
+        assert_eq!(batches.len(), 2);
+        assert_eq!(batches[1].len(), 1);
+        assert_eq!(batches[1][0].values(), &[6, 5]);
+    }
+
+    #[test]
+    fn test_batched_lag_stream_flush_on_ticks() {
+        use futures::FutureExt;
+
+        let (tx, rx) = futures::channel::mpsc::unbounded();
+        let (ticks, timer) = futures::channel::mpsc::unbounded();
+        let mut batches = lag_stream(rx, [0, 1]).unwrap().batched(3).flush_on(timer);
+
+        // Ticks without pending rows are skipped.
+        ticks.unbounded_send(()).unwrap();
+        tx.unbounded_send(1).unwrap();
+        assert!(batches.next().now_or_never().is_none());
+
+        tx.unbounded_send(2).unwrap();
+        assert!(batches.next().now_or_never().is_none());
+        ticks.unbounded_send(()).unwrap();
+        let batch = block_on(batches.next()).unwrap();
+        assert_eq!(batch.len(), 1);
+        assert_eq!(batch[0].values(), &[2, 1]);
+
+        drop(ticks);
+        for value in 3..=5 {
+            tx.unbounded_send(value).unwrap();
+        }
+        drop(tx);
+        let batches: Vec<_> = block_on(batches.collect());
+        assert_eq!(batches.len(), 1);
+        assert_eq!(batches[0].len(), 3);
+    }
+}
@@ -0,0 +1,242 @@
+use crate::{source_step, LagError, LagPlan, MatrixLayout};
+use core::ops::Range;
+
+#[cfg(all(feature = "debug-verify", debug_assertions))]
+use crate::verify;
+
+/// Writes the time steps selected by `steps` of a time-lagged matrix of multiple time series into a
+/// preallocated buffer with arbitrary row and column strides; see
+/// [`LagMatrixBuilder::build_2d_into`](crate::LagMatrixBuilder::build_2d_into).
+///
+/// The element at `row` and `col` is written to `row * row_stride + col * col_stride`, and
+/// gaps of the lag at index `i` are filled with `fill(i)`. A row stride of `0` places the
+/// rows right after each other.
+///
+/// ## Returns
+/// The number of rows and columns, or an error:
+/// * [`LagError::InvalidStride`] if the column stride is zero, the rows and columns would
+///   overlap, or the offsets overflow,
+/// * [`LagError::InvalidLength`] if the buffer is too short,
+/// * any error of [`lag_matrix_2d`](crate::lag_matrix_2d).
+#[allow(clippy::too_many_arguments)]
+pub(crate) fn lag_matrix_2d_strided<T, F>(
+    buffer: &mut [T],
+    data_matrix: &[T],
+    layout: MatrixLayout,
+    lags: Vec<isize>,
+    fill: F,
+    steps: impl Fn(usize) -> Range<usize>,
+    row_stride: usize,
+    col_stride: usize,
+) -> Result<(usize, usize), LagError>
+where
+    T: Copy,
+    F: Fn(usize) -> T,
+{
+    let plan = LagPlan::new(data_matrix, layout, lags, None, 0)?;
+    let (series_length, num_series) = (plan.series_length, plan.num_series);
+    let steps = steps(series_length);
+    if steps.is_empty() {
+        return Err(LagError::LagExceedsValueCount);
+    }
+
+    let num_features = num_series * plan.lags.len();
+    let (num_rows, num_cols) = if plan.row_major {
+        (num_features, steps.len())
+    } else {
+        (steps.len(), num_features)
+    };
+
+    let row_span = col_stride.checked_mul(num_cols);
+    let row_stride = match (row_stride, row_span) {
+        (0, Some(row_span)) => row_span,
+        (0, None) => return Err(LagError::InvalidStride),
+        (row_stride, _) => row_stride,
+    };
+    let disjoint_rows = col_stride > 0 && row_span.is_some_and(|span| row_stride >= span);
+    let disjoint_cols = row_stride
+        .checked_mul(num_rows)
+        .is_some_and(|span| col_stride >= span);
+    if !disjoint_rows && !disjoint_cols {
+        return Err(LagError::InvalidStride);
+    }
+
+    let last = (num_rows - 1)
+        .checked_mul(row_stride)
+        .zip((num_cols - 1).checked_mul(col_stride))
+        .and_then(|(rows, cols)| rows.checked_add(cols))
+        .ok_or(LagError::InvalidStride)?;
+    if buffer.len() <= last {
+        return Err(LagError::InvalidLength);
+    }
+
+    write_strided(
+        buffer,
+        data_matrix,
+        &plan,
+        fill,
+        &steps,
+        row_stride,
+        col_stride,
+    );
+
+    #[cfg(all(feature = "debug-verify", debug_assertions))]
+    verify_strided(
+        last + 1,
+        data_matrix.len(),
+        &plan,
+        &steps,
+        row_stride,
+        col_stride,
+    );
+
+    Ok((num_rows, num_cols))
+}
+
+/// Writes the time steps `steps` of the matrix into the buffer with the specified strides,
+/// which are valid for the buffer; see [`lag_matrix_2d_strided`].
+fn write_strided<T: Copy>(
+    buffer: &mut [T],
+    data_matrix: &[T],
+    plan: &LagPlan,
+    fill: impl Fn(usize) -> T,
+    steps: &Range<usize>,
+    row_stride: usize,
+    col_stride: usize,
+) {
+    let (series_length, num_series) = (plan.series_length, plan.num_series);
+    for (lag_index, &lag) in plan.lags.iter().enumerate() {
+        let fill = fill(lag_index);
+        for series in 0..num_series {
+            let feature = lag_index * num_series + series;
+            for (step, t) in steps.clone().enumerate() {
+                let value = source_step(t, lag, series_length).map_or(fill, |source| {
+                    if plan.row_major {
+                        data_matrix[series * series_length + source]
+                    } else {
+                        data_matrix[source * num_series + series]
+                    }
+                });
+                let (row, col) = if plan.row_major {
+                    (feature, step)
+                } else {
+                    (step, feature)
+                };
+                buffer[row * row_stride + col * col_stride] = value;
+            }
+        }
+    }
+}
+
+/// Checks the kernel of [`lag_matrix_2d_strided`] against the naive definition by running
+/// it on the indices of the values into a buffer of `buffer_len` elements.
+#[cfg(all(feature = "debug-verify", debug_assertions))]
+fn verify_strided(
+    buffer_len: usize,
+    data_len: usize,
+    plan: &LagPlan,
+    steps: &Range<usize>,
+    row_stride: usize,
+    col_stride: usize,
+) {
+    let mut buffer = vec![verify::GAP; buffer_len];
+    let indices = verify::indices(data_len);
+    let gap = |_| verify::GAP;
+    write_strided(
+        &mut buffer,
+        &indices,
+        plan,
+        gap,
+        steps,
+        row_stride,
+        col_stride,
+    );
+
+    let (series_length, num_series) = (plan.series_length, plan.num_series);
+    for (lag_index, &lag) in plan.lags.iter().enumerate() {
+        for series in 0..num_series {
+            let feature = lag_index * num_series + series;
+            for (step, t) in steps.clone().enumerate() {
+                let source = verify::naive_source(t, lag, series_length);
+                let (row, col, expected) = if plan.row_major {
+                    let expected = source.map(|source| series * series_length + source);
+                    (feature, step, expected)
+                } else {
+                    let expected = source.map(|source| source * num_series + series);
+                    (step, feature, expected)
+                };
+                verify::check(
+                    "lag_matrix_2d_strided",
+                    buffer[row * row_stride + col * col_stride],
+                    expected,
+                    format_args!("row {row} and column {col}"),
+                );
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::lag_matrix_2d;
+
+    fn strided(
+        buffer: &mut [i32],
+        data: &[i32],
+        layout: MatrixLayout,
+        row_stride: usize,
+        col_stride: usize,
+    ) -> Result<(usize, usize), LagError> {
+        let lags = vec![0, 2, -1];
+        let steps = |len| 0..len;
+        lag_matrix_2d_strided(
+            buffer,
+            data,
+            layout,
+            lags,
+            |_| -1,
+            steps,
+            row_stride,
+            col_stride,
+        )
+    }
+
+    #[test]
+    fn test_unit_column_stride_matches_lag_matrix() {
+        let data = [1, 10, 2, 20, 3, 30, 4, 40];
+        for layout in [MatrixLayout::RowMajor(4), MatrixLayout::ColumnMajor(4)] {
+            let expected = lag_matrix_2d(&data, layout, [0, 2, -1], -1, 9).unwrap();
+            let mut buffer = vec![-1; expected.len()];
+            let shape = strided(&mut buffer, &data, layout, 9, 1).unwrap();
+            assert_eq!(shape, (expected.num_rows(), expected.num_cols()));
+            assert_eq!(&buffer[..], &expected[..]);
+
+            // Swapping the strides stores the transpose.
+            let transposed = expected.transpose();
+            let rows = transposed.num_cols();
+            let mut buffer = vec![-1; transposed.len()];
+            strided(&mut buffer, &data, layout, 1, rows).unwrap();
+            assert_eq!(&buffer[..], &transposed[..]);
+        }
+    }
+
+    #[test]
+    fn test_overflowing_strides() {
+        let data = [1, 2, 3];
+        let layout = MatrixLayout::RowMajor(3);
+        let mut buffer = [0; 16];
+        assert_eq!(
+            strided(&mut buffer, &data, layout, 0, usize::MAX / 2),
+            Err(LagError::InvalidStride)
+        );
+        assert_eq!(
+            strided(&mut buffer, &data, layout, usize::MAX / 2, 1),
+            Err(LagError::InvalidStride)
+        );
+        assert_eq!(
+            strided(&mut buffer, &data, layout, 1, usize::MAX / 2),
+            Err(LagError::InvalidStride)
+        );
+    }
+}
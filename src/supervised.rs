@@ -0,0 +1,502 @@
+use crate::rng::SplitMix64;
+use crate::{lag_matrix_2d, IntoLag, LagError, LagMatrix, MatrixLayout};
+
+/// A feature matrix of lagged values and the aligned targets; see [`supervised_dataset`],
+/// [`supervised_dataset_multi`] and [`supervised_dataset_with_gap`].
+#[derive(Debug, Clone, PartialEq)]
+pub struct SupervisedDataset<T> {
+    features: LagMatrix<T>,
+    targets: Vec<T>,
+    horizons: Vec<usize>,
+    gap: usize,
+}
+
+impl<T> SupervisedDataset<T> {
+    /// The lagged features, with one time step per row.
+    ///
+    /// The [`time_offset`](LagMatrix::time_offset) is the index of the first retained
+    /// time step in the original series.
+    #[inline(always)]
+    pub fn features(&self) -> &LagMatrix<T> {
+        &self.features
+    }
+
+    /// The targets, with one row per row of the [`features`](Self::features) and one column
+    /// per [horizon](Self::horizons), stored row by row.
+    #[inline(always)]
+    pub fn targets(&self) -> &[T] {
+        &self.targets
+    }
+
+    /// The targets of the horizon at the specified index, one per row of the features.
+    pub fn target_column(&self, index: usize) -> Option<Vec<T>>
+    where
+        T: Copy,
+    {
+        if index >= self.horizons.len() {
+            return None;
+        }
+        Some(
+            self.targets
+                .iter()
+                .skip(index)
+                .step_by(self.horizons.len())
+                .copied()
+                .collect(),
+        )
+    }
+
+    /// The number of time steps each target column lies ahead of the features.
+    #[inline(always)]
+    pub fn horizons(&self) -> &[usize] {
+        &self.horizons
+    }
+
+    /// The number of steps skipped between the features and the targets; see
+    /// [`supervised_dataset_with_gap`].
+    #[inline(always)]
+    pub const fn gap(&self) -> usize {
+        self.gap
+    }
+
+    /// The number of samples, i.e. the number of rows.
+    #[inline(always)]
+    pub fn len(&self) -> usize {
+        self.features.series_length()
+    }
+
+    /// Determines whether the dataset contains no samples.
+    #[inline(always)]
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// Splits the dataset into the features and targets.
+    pub fn into_parts(self) -> (LagMatrix<T>, Vec<T>) {
+        (self.features, self.targets)
+    }
+
+    /// Iterates the samples in mini-batches of up to `batch_size` rows in temporal order,
+    /// e.g. to feed a training loop. The last batch may be smaller.
+    ///
+    /// ## Returns
+    /// The batches, or [`LagError::InvalidLength`] if the batch size is zero.
+    ///
+    /// ## Example
+    /// ```
+    /// use timelag::supervised_dataset;
+    ///
+    /// let dataset = supervised_dataset(&[1, 2, 3, 4, 5, 6], 0..=1, 1, 0).unwrap();
+    /// let batches: Vec<_> = dataset.batches(3).unwrap().collect();
+    ///
+    /// assert_eq!(batches.len(), 2);
+    /// assert_eq!(batches[0].features(), &[2, 1, 3, 2, 4, 3]);
+    /// assert_eq!(batches[0].targets(), &[3, 4, 5]);
+    /// assert_eq!(batches[1].indices(), &[3]);
+    /// ```
+    pub fn batches(&self, batch_size: usize) -> Result<Batches<'_, T>, LagError> {
+        Batches::new(self, batch_size, (0..self.len()).collect())
+    }
+
+    /// Iterates the samples in mini-batches of up to `batch_size` rows in a random order
+    /// that is reproducible for the same seed.
+    ///
+    /// ## Returns
+    /// The batches, or [`LagError::InvalidLength`] if the batch size is zero.
+    ///
+    /// ## Example
+    /// ```
+    /// use timelag::supervised_dataset;
+    ///
+    /// let dataset = supervised_dataset(&[1, 2, 3, 4, 5, 6], 0..=1, 1, 0).unwrap();
+    /// let mut indices: Vec<usize> = dataset
+    ///     .shuffled_batches(2, 42)
+    ///     .unwrap()
+    ///     .flat_map(|batch| batch.indices().to_vec())
+    ///     .collect();
+    ///
+    /// indices.sort();
+    /// assert_eq!(indices, vec![0, 1, 2, 3]);
+    /// ```
+    pub fn shuffled_batches(
+        &self,
+        batch_size: usize,
+        seed: u64,
+    ) -> Result<Batches<'_, T>, LagError> {
+        let mut order: Vec<usize> = (0..self.len()).collect();
+        let mut rng = SplitMix64::new(seed);
+        for i in (1..order.len()).rev() {
+            order.swap(i, rng.below(i + 1));
+        }
+        Batches::new(self, batch_size, order)
+    }
+}
+
+/// A mini-batch of samples of a [`SupervisedDataset`]; see [`SupervisedDataset::batches`].
+#[derive(Debug, Clone, PartialEq)]
+pub struct Batch<T> {
+    features: Vec<T>,
+    targets: Vec<T>,
+    indices: Vec<usize>,
+}
+
+impl<T> Batch<T> {
+    /// The features of the samples, stored row by row.
+    #[inline(always)]
+    pub fn features(&self) -> &[T] {
+        &self.features
+    }
+
+    /// The targets of the samples, stored row by row with one column per horizon.
+    #[inline(always)]
+    pub fn targets(&self) -> &[T] {
+        &self.targets
+    }
+
+    /// The rows of the samples in the dataset.
+    #[inline(always)]
+    pub fn indices(&self) -> &[usize] {
+        &self.indices
+    }
+
+    /// The number of samples.
+    #[inline(always)]
+    pub fn len(&self) -> usize {
+        self.indices.len()
+    }
+
+    /// Determines whether the batch contains no samples.
+    #[inline(always)]
+    pub fn is_empty(&self) -> bool {
+        self.indices.is_empty()
+    }
+
+    /// Splits the batch into the features and targets.
+    pub fn into_parts(self) -> (Vec<T>, Vec<T>) {
+        (self.features, self.targets)
+    }
+}
+
+/// An iterator over the mini-batches of a [`SupervisedDataset`].
+#[derive(Debug, Clone)]
+pub struct Batches<'a, T> {
+    dataset: &'a SupervisedDataset<T>,
+    batch_size: usize,
+    order: Vec<usize>,
+    position: usize,
+}
+
+impl<'a, T> Batches<'a, T> {
+    /// Creates the iterator over the rows in the specified order.
+    fn new(
+        dataset: &'a SupervisedDataset<T>,
+        batch_size: usize,
+        order: Vec<usize>,
+    ) -> Result<Self, LagError> {
+        if batch_size == 0 {
+            return Err(LagError::InvalidLength);
+        }
+        Ok(Self {
+            dataset,
+            batch_size,
+            order,
+            position: 0,
+        })
+    }
+}
+
+impl<T: Copy> Iterator for Batches<'_, T> {
+    type Item = Batch<T>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.position >= self.order.len() {
+            return None;
+        }
+
+        let end = (self.position + self.batch_size).min(self.order.len());
+        let indices = self.order[self.position..end].to_vec();
+        self.position = end;
+
+        let features = &self.dataset.features;
+        let (stride, width) = (features.row_stride(), features.num_cols());
+        let horizons = self.dataset.horizons.len();
+        let mut batch = Batch {
+            features: Vec::with_capacity(indices.len() * width),
+            targets: Vec::with_capacity(indices.len() * horizons),
+            indices,
+        };
+        for &row in &batch.indices {
+            batch
+                .features
+                .extend_from_slice(&features[row * stride..row * stride + width]);
+            batch
+                .targets
+                .extend_from_slice(&self.dataset.targets[row * horizons..(row + 1) * horizons]);
+        }
+        Some(batch)
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        let batches = (self.order.len() - self.position).div_ceil(self.batch_size);
+        (batches, Some(batches))
+    }
+}
+
+impl<T: Copy> ExactSizeIterator for Batches<'_, T> {}
+
+/// Creates a supervised learning dataset from a series, regressing the value `horizon`
+/// steps ahead on the lagged values.
+///
+/// Row `i` of the features describes the time step `t = features().time_offset() + i`,
+/// and target `i` is the value at `t + horizon`. Time steps whose features contain lagged
+/// gaps, or whose target lies beyond the end of the series, are dropped.
+///
+/// ## Arguments
+/// * `data` - The time series.
+/// * `feature_lags` - The lags to use as features; lag `0` is the current value.
+/// * `horizon` - The number of steps the target lies ahead; e.g. `1` for one-step-ahead forecasting.
+/// * `fill` - The value to use to fill in lagged gaps while building the features.
+///
+/// ## Returns
+/// The dataset, or an error, e.g. [`LagError::LagExceedsValueCount`] if no time step is
+/// complete.
+///
+/// ## Example
+/// ```
+/// use timelag::supervised_dataset;
+///
+/// let data = [1, 2, 3, 4, 5];
+/// let dataset = supervised_dataset(&data, 0..=1, 2, 0).unwrap();
+///
+/// // The features of t = 1 and t = 2, predicting the values at t = 3 and t = 4.
+/// assert_eq!(dataset.features().time_offset(), 1);
+/// assert_eq!(dataset.features(), &[2, 1, 3, 2]);
+/// assert_eq!(dataset.targets(), &[4, 5]);
+/// ```
+pub fn supervised_dataset<T, L, R>(
+    data: &[T],
+    feature_lags: R,
+    horizon: usize,
+    fill: T,
+) -> Result<SupervisedDataset<T>, LagError>
+where
+    T: Copy,
+    L: IntoLag,
+    R: IntoIterator<Item = L>,
+{
+    supervised_dataset_multi(data, feature_lags, [horizon], fill)
+}
+
+/// Creates a supervised learning dataset with one target column per horizon, e.g. for
+/// multi-output forecasting models.
+///
+/// Row `i` of the features describes the time step `t = features().time_offset() + i`,
+/// and target column `j` holds the values at `t + horizons[j]`. Time steps whose features
+/// contain lagged gaps, or whose targets lie beyond the end of the series, are dropped;
+/// see [`supervised_dataset`].
+///
+/// ## Arguments
+/// * `data` - The time series.
+/// * `feature_lags` - The lags to use as features; lag `0` is the current value.
+/// * `horizons` - The number of steps each target lies ahead, e.g. `[1, 7, 30]`.
+/// * `fill` - The value to use to fill in lagged gaps while building the features.
+///
+/// ## Returns
+/// The dataset, or an error, e.g. [`LagError::InvalidLags`] if no horizon was specified.
+///
+/// ## Example
+/// ```
+/// use timelag::supervised_dataset_multi;
+///
+/// let data = [1, 2, 3, 4, 5];
+/// let dataset = supervised_dataset_multi(&data, [0], [1, 3], 0).unwrap();
+///
+/// assert_eq!(dataset.features(), &[1, 2]);
+/// assert_eq!(dataset.targets(), &[2, 4, 3, 5]);
+/// assert_eq!(dataset.target_column(1), Some(vec![4, 5]));
+/// ```
+pub fn supervised_dataset_multi<T, L, R, H>(
+    data: &[T],
+    feature_lags: R,
+    horizons: H,
+    fill: T,
+) -> Result<SupervisedDataset<T>, LagError>
+where
+    T: Copy,
+    L: IntoLag,
+    R: IntoIterator<Item = L>,
+    H: IntoIterator<Item = usize>,
+{
+    supervised_dataset_with_gap(data, feature_lags, horizons, 0, fill)
+}
+
+/// Creates a supervised learning dataset whose targets lie `gap` steps beyond the horizons,
+/// e.g. to account for publication delays or to prevent leakage as with the `gap` of
+/// scikit-learn's `TimeSeriesSplit`.
+///
+/// Row `i` of the features describes the time step `t = features().time_offset() + i`,
+/// and target column `j` holds the values at `t + gap + horizons[j]`; e.g. the horizons
+/// `1..=h` forecast `t + gap + 1` to `t + gap + h` from the data up to `t`.
+///
+/// ## Arguments
+/// * `data` - The time series.
+/// * `feature_lags` - The lags to use as features; lag `0` is the current value.
+/// * `horizons` - The number of steps each target lies ahead of the gap.
+/// * `gap` - The number of steps skipped between the features and the targets.
+/// * `fill` - The value to use to fill in lagged gaps while building the features.
+///
+/// ## Returns
+/// The dataset, or an error, e.g. [`LagError::InvalidLags`] if no horizon was specified.
+///
+/// ## Example
+/// ```
+/// use timelag::supervised_dataset_with_gap;
+///
+/// let data = [1, 2, 3, 4, 5, 6];
+/// let dataset = supervised_dataset_with_gap(&data, [0], [1], 2, 0).unwrap();
+///
+/// assert_eq!(dataset.gap(), 2);
+/// assert_eq!(dataset.features(), &[1, 2, 3]);
+/// assert_eq!(dataset.targets(), &[4, 5, 6]);
+/// ```
+pub fn supervised_dataset_with_gap<T, L, R, H>(
+    data: &[T],
+    feature_lags: R,
+    horizons: H,
+    gap: usize,
+    fill: T,
+) -> Result<SupervisedDataset<T>, LagError>
+where
+    T: Copy,
+    L: IntoLag,
+    R: IntoIterator<Item = L>,
+    H: IntoIterator<Item = usize>,
+{
+    let horizons: Vec<usize> = horizons.into_iter().collect();
+    let Some(&max_horizon) = horizons.iter().max() else {
+        return Err(LagError::InvalidLags);
+    };
+
+    let layout = MatrixLayout::ColumnMajor(data.len());
+    let features = lag_matrix_2d(data, layout, feature_lags, fill, 0)?.trim_incomplete()?;
+
+    let start = features.time_offset();
+    let end = (start + features.series_length()).min(data.len().saturating_sub(gap + max_horizon));
+    if end <= start {
+        return Err(LagError::LagExceedsValueCount);
+    }
+
+    let features = features.slice_time(0..end - start)?;
+    let targets = (start..end)
+        .flat_map(|t| horizons.iter().map(move |horizon| data[t + gap + horizon]))
+        .collect();
+    Ok(SupervisedDataset {
+        features,
+        targets,
+        horizons,
+        gap,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    #[rustfmt::skip]
+    fn test_leads_and_horizon_trim_both_ends() {
+        let data = [1, 2, 3, 4, 5, 6];
+        let dataset = supervised_dataset(&data, [-1, 1], 1, 0).unwrap();
+
+        // Lag 1 requires t >= 1; the lead requires t <= 4, the target t <= 4.
+        assert_eq!(dataset.len(), 4);
+        assert_eq!(dataset.horizons(), &[1]);
+        assert_eq!(dataset.features(), &[
+            3, 1,
+            4, 2,
+            5, 3,
+            6, 4,
+        ]);
+        assert_eq!(dataset.targets(), &[3, 4, 5, 6]);
+    }
+
+    #[test]
+    fn test_horizon_exceeding_the_series() {
+        assert_eq!(
+            supervised_dataset(&[1, 2, 3], [1], 2, 0).unwrap_err(),
+            LagError::LagExceedsValueCount
+        );
+
+        let (features, targets) = supervised_dataset(&[1, 2, 3], [0], 2, 0)
+            .unwrap()
+            .into_parts();
+        assert_eq!(features, &[1]);
+        assert_eq!(targets, vec![3]);
+    }
+
+    #[test]
+    #[rustfmt::skip]
+    fn test_multiple_horizons() {
+        let data = [1, 2, 3, 4, 5, 6, 7];
+        let dataset = supervised_dataset_multi(&data, [1], [3, 1], 0).unwrap();
+
+        // Lag 1 requires t >= 1; the longest horizon requires t <= 3.
+        assert_eq!(dataset.features().time_offset(), 1);
+        assert_eq!(dataset.features(), &[1, 2, 3]);
+        assert_eq!(dataset.targets(), &[
+            5, 3,
+            6, 4,
+            7, 5,
+        ]);
+        assert_eq!(dataset.target_column(0), Some(vec![5, 6, 7]));
+        assert_eq!(dataset.target_column(2), None);
+
+        assert_eq!(
+            supervised_dataset_multi(&data, [1], [], 0).unwrap_err(),
+            LagError::InvalidLags
+        );
+    }
+
+    #[test]
+    fn test_gap_shifts_every_horizon() {
+        let data = [1, 2, 3, 4, 5, 6, 7, 8];
+        let gapped = supervised_dataset_with_gap(&data, [0, 1], [1, 2], 3, 0).unwrap();
+        let shifted = supervised_dataset_multi(&data, [0, 1], [4, 5], 0).unwrap();
+
+        assert_eq!(gapped.horizons(), &[1, 2]);
+        assert_eq!(gapped.features(), shifted.features());
+        assert_eq!(gapped.targets(), shifted.targets());
+        assert_eq!(gapped.targets(), &[6, 7, 7, 8]);
+
+        assert_eq!(
+            supervised_dataset_with_gap(&data, [0], [1], 7, 0).unwrap_err(),
+            LagError::LagExceedsValueCount
+        );
+    }
+
+    #[test]
+    fn test_shuffled_batches() {
+        let data: Vec<i32> = (0..20).collect();
+        let dataset = supervised_dataset_multi(&data, [0, 1], [1, 2], 0).unwrap();
+
+        let batches: Vec<_> = dataset.shuffled_batches(4, 7).unwrap().collect();
+        assert_eq!(batches.len(), 5);
+        assert_eq!(batches[4].len(), 1);
+        assert_eq!(
+            batches,
+            dataset.shuffled_batches(4, 7).unwrap().collect::<Vec<_>>()
+        );
+        assert_ne!(batches, dataset.batches(4).unwrap().collect::<Vec<_>>());
+
+        // Every sample keeps its features and targets together.
+        for batch in &batches {
+            for (i, &row) in batch.indices().iter().enumerate() {
+                let t = row as i32 + 1;
+                assert_eq!(&batch.features()[2 * i..2 * i + 2], &[t, t - 1]);
+                assert_eq!(&batch.targets()[2 * i..2 * i + 2], &[t + 1, t + 2]);
+            }
+        }
+
+        assert_eq!(dataset.batches(0).unwrap_err(), LagError::InvalidLength);
+    }
+}
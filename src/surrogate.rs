@@ -0,0 +1,240 @@
+use crate::rng::SplitMix64;
+use crate::{lag_matrix, IntoLag, LagError, LagMatrix};
+use core::cmp::Ordering;
+use num_traits::Float;
+use rustfft::num_complex::Complex;
+use rustfft::{FftNum, FftPlanner};
+
+/// The method used to generate surrogate series; see [`surrogate`].
+#[derive(Debug, Copy, Clone, Eq, PartialEq, Hash, Default)]
+pub enum SurrogateMethod {
+    /// Randomizes the Fourier phases while retaining the amplitude spectrum,
+    /// preserving the linear autocorrelation of the series.
+    #[default]
+    PhaseRandomized,
+    /// The amplitude adjusted Fourier transform; additionally preserves the
+    /// distribution of values by rank-ordering a phase-randomized Gaussian surrogate.
+    Aaft,
+}
+
+/// Generates a surrogate of a time series for significance testing.
+///
+/// Surrogates share the linear properties of the original series but destroy any nonlinear
+/// structure; comparing a nonlinear dependence measure against its distribution over many
+/// surrogates tests the null hypothesis of a linear Gaussian process.
+///
+/// ## Arguments
+/// * `data` - The time series to generate a surrogate of.
+/// * `method` - The surrogate generation method.
+/// * `seed` - The seed of the pseudo-random number generator.
+///
+/// ## Returns
+/// The surrogate series of the same length as `data`.
+///
+/// ## Example
+/// ```
+/// use timelag::{surrogate, SurrogateMethod};
+///
+/// let data: Vec<f64> = (0..64).map(|t| (t as f64 * 0.3).sin() + t as f64 * 0.01).collect();
+/// let shuffled = surrogate(&data, SurrogateMethod::Aaft, 42);
+///
+/// // AAFT surrogates are a reordering of the original values.
+/// let mut sorted = shuffled.clone();
+/// let mut expected = data.clone();
+/// sorted.sort_by(f64::total_cmp);
+/// expected.sort_by(f64::total_cmp);
+/// assert_eq!(sorted, expected);
+/// ```
+pub fn surrogate<T>(data: &[T], method: SurrogateMethod, seed: u64) -> Vec<T>
+where
+    T: Float + FftNum,
+{
+    let mut rng = SplitMix64::new(seed);
+    let mut planner = FftPlanner::new();
+    generate(data, method, &mut rng, &mut planner)
+}
+
+/// Generates `count` surrogates of a time series and lags each of them like [`lag_matrix`].
+///
+/// All resulting matrices share the schema of `lag_matrix(data, lags, fill, stride)`, such
+/// that a statistic computed on the lagged original can be compared directly against its
+/// distribution over the surrogates.
+///
+/// ## Arguments
+/// * `data` - The time series to generate surrogates of.
+/// * `lags` - The lags to create.
+/// * `fill` - The value to use to fill in lagged gaps.
+/// * `stride` - The number of elements between lagged versions in the resulting vector.
+/// * `method` - The surrogate generation method.
+/// * `count` - The number of surrogates to generate.
+/// * `seed` - The seed of the pseudo-random number generator.
+///
+/// ## Returns
+/// The lagged surrogates, or an error.
+///
+/// ## Example
+/// ```
+/// use timelag::{lag_matrix_surrogates, SurrogateMethod};
+///
+/// let data: Vec<f64> = (0..32).map(|t| (t as f64 * 0.5).cos()).collect();
+/// let surrogates = lag_matrix_surrogates(&data, 0..=2, f64::NAN, 0, SurrogateMethod::PhaseRandomized, 8, 1).unwrap();
+///
+/// assert_eq!(surrogates.len(), 8);
+/// assert!(surrogates.iter().all(|lagged| lagged.num_rows() == 3 && lagged.num_cols() == 32));
+/// ```
+pub fn lag_matrix_surrogates<T, L, R>(
+    data: &[T],
+    lags: R,
+    fill: T,
+    stride: usize,
+    method: SurrogateMethod,
+    count: usize,
+    seed: u64,
+) -> Result<Vec<LagMatrix<T>>, LagError>
+where
+    T: Float + FftNum,
+    L: IntoLag,
+    R: IntoIterator<Item = L>,
+{
+    let lags = Vec::from_iter(lags);
+    let mut rng = SplitMix64::new(seed);
+    let mut planner = FftPlanner::new();
+
+    (0..count)
+        .map(|_| {
+            let surrogate = generate(data, method, &mut rng, &mut planner);
+            lag_matrix(&surrogate, lags.iter().copied(), fill, stride)
+        })
+        .collect()
+}
+
+fn generate<T>(
+    data: &[T],
+    method: SurrogateMethod,
+    rng: &mut SplitMix64,
+    planner: &mut FftPlanner<T>,
+) -> Vec<T>
+where
+    T: Float + FftNum,
+{
+    match method {
+        SurrogateMethod::PhaseRandomized => phase_randomize(data, rng, planner),
+        SurrogateMethod::Aaft => {
+            // Gaussian noise with the rank structure of the data ...
+            let order = argsort(data);
+            let mut gaussian: Vec<T> = (0..data.len())
+                .map(|_| T::from(rng.next_gaussian()).unwrap_or_else(T::zero))
+                .collect();
+            gaussian.sort_by(compare);
+
+            let mut ranked = vec![T::zero(); data.len()];
+            for (&index, &value) in order.iter().zip(&gaussian) {
+                ranked[index] = value;
+            }
+
+            // ... is phase randomized and its ranks are mapped back onto the data values.
+            let randomized = phase_randomize(&ranked, rng, planner);
+            let mut sorted = data.to_vec();
+            sorted.sort_by(compare);
+
+            let mut result = vec![T::zero(); data.len()];
+            for (index, value) in argsort(&randomized).into_iter().zip(sorted) {
+                result[index] = value;
+            }
+            result
+        }
+    }
+}
+
+fn phase_randomize<T>(data: &[T], rng: &mut SplitMix64, planner: &mut FftPlanner<T>) -> Vec<T>
+where
+    T: Float + FftNum,
+{
+    let n = data.len();
+    if n < 3 {
+        return data.to_vec();
+    }
+
+    let mut spectrum: Vec<_> = data.iter().map(|&x| Complex::new(x, T::zero())).collect();
+    planner.plan_fft_forward(n).process(&mut spectrum);
+
+    // Rotate every positive frequency by a random phase, mirroring it onto the negative
+    // frequency so that the inverse remains real. The DC and Nyquist terms are retained.
+    for k in 1..n.div_ceil(2) {
+        let phase = T::from(core::f64::consts::TAU * rng.next_f64()).unwrap_or_else(T::zero);
+        let rotated = spectrum[k] * Complex::from_polar(T::one(), phase);
+        spectrum[k] = rotated;
+        spectrum[n - k] = rotated.conj();
+    }
+
+    planner.plan_fft_inverse(n).process(&mut spectrum);
+    let scale = T::from(n).unwrap_or_else(T::one);
+    spectrum.into_iter().map(|value| value.re / scale).collect()
+}
+
+fn argsort<T: Float>(data: &[T]) -> Vec<usize> {
+    let mut order: Vec<usize> = (0..data.len()).collect();
+    order.sort_by(|&a, &b| compare(&data[a], &data[b]));
+    order
+}
+
+fn compare<T: Float>(a: &T, b: &T) -> Ordering {
+    a.partial_cmp(b).unwrap_or(Ordering::Equal)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn amplitudes(data: &[f64]) -> Vec<f64> {
+        let mut spectrum: Vec<_> = data.iter().map(|&x| Complex::new(x, 0.0)).collect();
+        FftPlanner::new()
+            .plan_fft_forward(data.len())
+            .process(&mut spectrum);
+        spectrum.iter().map(|value| value.norm()).collect()
+    }
+
+    fn test_series(len: usize) -> Vec<f64> {
+        (0..len)
+            .map(|t| (t as f64 * 0.7).sin() + ((t * 13) % 7) as f64 * 0.2)
+            .collect()
+    }
+
+    #[test]
+    fn test_phase_randomized_preserves_amplitudes() {
+        for len in [31, 32] {
+            let data = test_series(len);
+            let shuffled = surrogate(&data, SurrogateMethod::PhaseRandomized, 3);
+            assert_ne!(shuffled, data);
+
+            for (expected, actual) in amplitudes(&data).iter().zip(amplitudes(&shuffled)) {
+                assert!((expected - actual).abs() < 1e-9);
+            }
+        }
+    }
+
+    #[test]
+    fn test_reproducible() {
+        let data = test_series(20);
+        let a = surrogate(&data, SurrogateMethod::Aaft, 5);
+        let b = surrogate(&data, SurrogateMethod::Aaft, 5);
+        let c = surrogate(&data, SurrogateMethod::Aaft, 6);
+        assert_eq!(a, b);
+        assert_ne!(a, c);
+    }
+
+    #[test]
+    fn test_lagged_surrogates_share_schema() {
+        let data = test_series(16);
+        let reference = lag_matrix(&data, [0, 2], -1.0, 18).unwrap();
+        let surrogates =
+            lag_matrix_surrogates(&data, [0, 2], -1.0, 18, SurrogateMethod::Aaft, 3, 0).unwrap();
+
+        for lagged in surrogates {
+            assert_eq!(lagged.num_rows(), reference.num_rows());
+            assert_eq!(lagged.row_stride(), reference.row_stride());
+            assert_eq!(lagged.num_lags(), reference.num_lags());
+            assert_eq!(lagged[18..20], [-1.0, -1.0]);
+        }
+    }
+}
@@ -0,0 +1,183 @@
+//! Seeded generators of synthetic time series, e.g. for examples, benchmarks and tests.
+//!
+//! All generators add Gaussian noise from a small built-in pseudo-random number generator,
+//! such that the same seed yields the same series on every platform.
+//!
+//! ## Example
+//! ```
+//! use timelag::lag_matrix;
+//! use timelag::synthetic::ar_process;
+//!
+//! let data: Vec<f64> = ar_process(&[0.6, -0.2], 1.0, 200, 42);
+//! let lagged = lag_matrix(&data, 0..=2, f64::NAN, 0).unwrap();
+//! assert_eq!(lagged.series_length(), 200);
+//! ```
+
+use crate::rng::SplitMix64;
+use num_traits::Float;
+
+/// The number of initial steps of an autoregressive process that are discarded,
+/// such that the series does not depend on the zero start values.
+const BURN_IN: usize = 100;
+
+/// Generates a series of the autoregressive process `x[t] = Σ φ[i] x[t - 1 - i] + ε[t]`.
+///
+/// The process starts at zero and runs for a burn-in period before the first returned value.
+///
+/// ## Arguments
+/// * `coefficients` - The autoregressive coefficients `φ`, starting at the first lag.
+/// * `noise_std` - The standard deviation of the Gaussian innovations `ε`.
+/// * `len` - The length of the series.
+/// * `seed` - The seed of the pseudo-random number generator.
+///
+/// ## Example
+/// ```
+/// use timelag::synthetic::ar_process;
+///
+/// let data: Vec<f64> = ar_process(&[0.9], 0.0, 4, 7);
+/// assert_eq!(data, [0.0; 4]);
+/// ```
+pub fn ar_process<T: Float>(coefficients: &[T], noise_std: T, len: usize, seed: u64) -> Vec<T> {
+    let mut rng = SplitMix64::new(seed);
+    let burn_in = if coefficients.is_empty() { 0 } else { BURN_IN };
+    let mut series: Vec<T> = Vec::with_capacity(burn_in + len);
+
+    for t in 0..burn_in + len {
+        let value = coefficients
+            .iter()
+            .zip(series[..t].iter().rev())
+            .fold(noise(&mut rng, noise_std), |value, (&phi, &past)| {
+                value + phi * past
+            });
+        series.push(value);
+    }
+
+    series.split_off(burn_in)
+}
+
+/// Generates a series repeating a seasonal pattern, with additive Gaussian noise.
+///
+/// ## Arguments
+/// * `pattern` - The values of a single season.
+/// * `noise_std` - The standard deviation of the noise.
+/// * `len` - The length of the series.
+/// * `seed` - The seed of the pseudo-random number generator.
+///
+/// ## Example
+/// ```
+/// use timelag::synthetic::seasonal;
+///
+/// let data = seasonal(&[1.0, 2.0, 3.0], 0.0, 7, 7);
+/// assert_eq!(data, [1.0, 2.0, 3.0, 1.0, 2.0, 3.0, 1.0]);
+/// ```
+pub fn seasonal<T: Float>(pattern: &[T], noise_std: T, len: usize, seed: u64) -> Vec<T> {
+    if pattern.is_empty() {
+        return vec![T::zero(); len];
+    }
+
+    let mut rng = SplitMix64::new(seed);
+    pattern
+        .iter()
+        .cycle()
+        .take(len)
+        .map(|&value| value + noise(&mut rng, noise_std))
+        .collect()
+}
+
+/// Generates a random walk starting at zero, i.e. the cumulative sum of Gaussian steps.
+///
+/// ## Arguments
+/// * `step_std` - The standard deviation of each step.
+/// * `len` - The length of the series.
+/// * `seed` - The seed of the pseudo-random number generator.
+///
+/// ## Example
+/// ```
+/// use timelag::synthetic::random_walk;
+///
+/// let walk: Vec<f64> = random_walk(1.0, 100, 42);
+/// assert_eq!(walk[0], 0.0);
+/// assert_eq!(walk, random_walk(1.0, 100, 42));
+/// ```
+pub fn random_walk<T: Float>(step_std: T, len: usize, seed: u64) -> Vec<T> {
+    let mut rng = SplitMix64::new(seed);
+    let mut position = T::zero();
+    (0..len)
+        .map(|t| {
+            if t > 0 {
+                position = position + noise(&mut rng, step_std);
+            }
+            position
+        })
+        .collect()
+}
+
+/// Generates the sinusoid `amplitude · sin(2π t / period)` with additive Gaussian noise.
+///
+/// ## Arguments
+/// * `period` - The number of time steps per oscillation.
+/// * `amplitude` - The amplitude of the sinusoid.
+/// * `noise_std` - The standard deviation of the noise.
+/// * `len` - The length of the series.
+/// * `seed` - The seed of the pseudo-random number generator.
+///
+/// ## Example
+/// ```
+/// use timelag::synthetic::noisy_sinusoid;
+///
+/// let data: Vec<f64> = noisy_sinusoid(4.0, 2.0, 0.0, 4, 7);
+/// assert!((data[1] - 2.0).abs() < 1e-12);
+/// assert!((data[3] + 2.0).abs() < 1e-12);
+/// ```
+pub fn noisy_sinusoid<T: Float>(
+    period: T,
+    amplitude: T,
+    noise_std: T,
+    len: usize,
+    seed: u64,
+) -> Vec<T> {
+    let mut rng = SplitMix64::new(seed);
+    let tau = T::from(core::f64::consts::TAU).expect("τ is representable");
+    (0..len)
+        .map(|t| {
+            let t = T::from(t).unwrap_or_else(T::infinity);
+            amplitude * (tau * t / period).sin() + noise(&mut rng, noise_std)
+        })
+        .collect()
+}
+
+/// Draws Gaussian noise of the specified standard deviation.
+fn noise<T: Float>(rng: &mut SplitMix64, std: T) -> T {
+    let value = rng.next_gaussian();
+    std * T::from(value).expect("the value is finite")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_ar_process_recovers_coefficient() {
+        let data: Vec<f64> = ar_process(&[0.7], 1.0, 5000, 3);
+        let mean = data.iter().sum::<f64>() / data.len() as f64;
+        let (covariance, variance) = data.windows(2).fold((0.0, 0.0), |(c, v), pair| {
+            let (x, y) = (pair[0] - mean, pair[1] - mean);
+            (c + x * y, v + x * x)
+        });
+
+        // The lag-one autocorrelation of an AR(1) process equals its coefficient.
+        assert!((covariance / variance - 0.7).abs() < 0.05);
+        assert_ne!(data, ar_process(&[0.7], 1.0, 5000, 4));
+    }
+
+    #[test]
+    fn test_noise_level() {
+        let data: Vec<f64> = seasonal(&[5.0], 2.0, 10_000, 11);
+        let mean = data.iter().sum::<f64>() / data.len() as f64;
+        let variance = data.iter().map(|x| (x - mean).powi(2)).sum::<f64>() / data.len() as f64;
+
+        assert!((mean - 5.0).abs() < 0.1);
+        assert!((variance.sqrt() - 2.0).abs() < 0.1);
+        assert_eq!(seasonal::<f32>(&[], 1.0, 3, 0), [0.0; 3]);
+    }
+}
@@ -0,0 +1,261 @@
+use crate::LagError;
+use num_traits::Float;
+
+/// Solves the symmetric Toeplitz system `T·x = b` using the Levinson recursion.
+///
+/// The matrix is given by its first column `r`, i.e. `T[i][j] = r[|i - j|]`, and must be
+/// positive definite, which holds for the autocovariance matrices of stationary processes.
+/// The solver runs in `O(n²)` time and `O(n)` memory, without materializing the matrix.
+///
+/// ## Arguments
+/// * `r` - The first column of the Toeplitz matrix.
+/// * `b` - The right-hand side; must be of the same length as `r`.
+///
+/// ## Returns
+/// The solution `x`, or an error. [`LagError::SingularMatrix`] is returned if the matrix
+/// is not positive definite.
+///
+/// ## Example
+/// ```
+/// use timelag::solve_toeplitz;
+///
+/// // [[2, 1], [1, 2]] · [1, 1] = [3, 3]
+/// let x = solve_toeplitz(&[2.0_f64, 1.0], &[3.0, 3.0]).unwrap();
+/// assert!((x[0] - 1.0).abs() < 1e-12 && (x[1] - 1.0).abs() < 1e-12);
+/// ```
+pub fn solve_toeplitz<T>(r: &[T], b: &[T]) -> Result<Vec<T>, LagError>
+where
+    T: Float,
+{
+    if r.is_empty() {
+        return Err(LagError::EmptyData);
+    }
+
+    if b.len() != r.len() {
+        return Err(LagError::InvalidLength);
+    }
+
+    let r0 = r[0];
+    if !is_positive(r0) {
+        return Err(LagError::SingularMatrix);
+    }
+
+    // Normalize to a unit diagonal; see Golub & Van Loan, Algorithm 4.7.2.
+    let n = r.len();
+    let r: Vec<T> = r.iter().map(|&value| value / r0).collect();
+    let b: Vec<T> = b.iter().map(|&value| value / r0).collect();
+
+    let mut x = Vec::with_capacity(n);
+    x.push(b[0]);
+    if n == 1 {
+        return Ok(x);
+    }
+
+    let mut y = Vec::with_capacity(n);
+    y.push(-r[1]);
+    let mut alpha = -r[1];
+    let mut beta = T::one();
+
+    for k in 1..n {
+        beta = (T::one() - alpha * alpha) * beta;
+        if !is_positive(beta) {
+            return Err(LagError::SingularMatrix);
+        }
+
+        let dot = (0..k).fold(T::zero(), |sum, i| sum + r[i + 1] * x[k - 1 - i]);
+        let mu = (b[k] - dot) / beta;
+        for i in 0..k {
+            x[i] = x[i] + mu * y[k - 1 - i];
+        }
+        x.push(mu);
+
+        if k + 1 < n {
+            let dot = (0..k).fold(T::zero(), |sum, i| sum + r[i + 1] * y[k - 1 - i]);
+            alpha = (-r[k + 1] - dot) / beta;
+
+            let reversed: Vec<T> = y.iter().rev().copied().collect();
+            for (value, reversed) in y.iter_mut().zip(reversed) {
+                *value = *value + alpha * reversed;
+            }
+            y.push(alpha);
+        }
+    }
+
+    Ok(x)
+}
+
+/// The result of the Levinson–Durbin recursion; see [`levinson_durbin`].
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct LevinsonDurbin<T> {
+    coefficients: Vec<T>,
+    reflection_coefficients: Vec<T>,
+    error_variance: T,
+}
+
+impl<T> LevinsonDurbin<T>
+where
+    T: Copy,
+{
+    /// The autoregressive coefficients `φ₁, …, φₚ` such that `x(t) ≈ Σ φₖ·x(t - k)`.
+    #[inline(always)]
+    pub fn coefficients(&self) -> &[T] {
+        &self.coefficients
+    }
+
+    /// The reflection coefficients, i.e. the partial autocorrelations for the lags `1..=p`.
+    #[inline(always)]
+    pub fn reflection_coefficients(&self) -> &[T] {
+        &self.reflection_coefficients
+    }
+
+    /// The variance of the one-step prediction error.
+    #[inline(always)]
+    pub const fn error_variance(&self) -> T {
+        self.error_variance
+    }
+}
+
+/// Fits an autoregressive model of the specified order to an autocovariance sequence
+/// by solving the Yule–Walker equations with the Levinson–Durbin recursion.
+///
+/// ## Arguments
+/// * `autocovariance` - The autocovariances (or autocorrelations) for the lags `0..=order`.
+///   Further values are ignored.
+/// * `order` - The order `p` of the autoregressive model.
+///
+/// ## Returns
+/// The AR coefficients, reflection coefficients and prediction error variance, or an error.
+/// [`LagError::SingularMatrix`] is returned if the autocovariances are not positive definite.
+///
+/// ## Example
+/// ```
+/// use timelag::levinson_durbin;
+///
+/// // The autocorrelation of an AR(1) process with φ = 0.5.
+/// let acf = [1.0_f64, 0.5, 0.25, 0.125];
+///
+/// let fit = levinson_durbin(&acf, 3).unwrap();
+/// assert!((fit.coefficients()[0] - 0.5).abs() < 1e-12);
+/// assert!(fit.coefficients()[1].abs() < 1e-12);
+/// assert!((fit.error_variance() - 0.75).abs() < 1e-12);
+/// ```
+pub fn levinson_durbin<T>(autocovariance: &[T], order: usize) -> Result<LevinsonDurbin<T>, LagError>
+where
+    T: Float,
+{
+    if autocovariance.is_empty() {
+        return Err(LagError::EmptyData);
+    }
+
+    if autocovariance.len() <= order {
+        return Err(LagError::LagExceedsValueCount);
+    }
+
+    let r = autocovariance;
+    let mut error = r[0];
+    if !is_positive(error) {
+        return Err(LagError::SingularMatrix);
+    }
+
+    let mut coefficients: Vec<T> = Vec::with_capacity(order);
+    let mut reflection_coefficients = Vec::with_capacity(order);
+
+    for k in 1..=order {
+        let acc = (1..k).fold(r[k], |acc, j| acc - coefficients[j - 1] * r[k - j]);
+        let kappa = acc / error;
+
+        let previous = coefficients.clone();
+        for j in 1..k {
+            coefficients[j - 1] = previous[j - 1] - kappa * previous[k - j - 1];
+        }
+        coefficients.push(kappa);
+        reflection_coefficients.push(kappa);
+
+        error = error * (T::one() - kappa * kappa);
+        if !is_positive(error) {
+            return Err(LagError::SingularMatrix);
+        }
+    }
+
+    Ok(LevinsonDurbin {
+        coefficients,
+        reflection_coefficients,
+        error_variance: error,
+    })
+}
+
+/// Determines whether a value is positive, treating NaN as not positive.
+#[inline(always)]
+fn is_positive<T: Float>(value: T) -> bool {
+    value > T::zero()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn solve_dense(r: &[f64], b: &[f64]) -> Vec<f64> {
+        let n = r.len();
+        let mut a: Vec<Vec<f64>> = (0..n)
+            .map(|i| {
+                let mut row: Vec<f64> = (0..n).map(|j| r[i.abs_diff(j)]).collect();
+                row.push(b[i]);
+                row
+            })
+            .collect();
+
+        for col in 0..n {
+            let pivot = a[col].clone();
+            for row in a.iter_mut().skip(col + 1) {
+                let factor = row[col] / pivot[col];
+                for (value, pivot) in row[col..].iter_mut().zip(&pivot[col..]) {
+                    *value -= factor * pivot;
+                }
+            }
+        }
+
+        let mut x = vec![0.0; n];
+        for row in (0..n).rev() {
+            let sum: f64 = (row + 1..n).map(|k| a[row][k] * x[k]).sum();
+            x[row] = (a[row][n] - sum) / a[row][row];
+        }
+        x
+    }
+
+    #[test]
+    fn test_solve_matches_dense() {
+        let r = [4.0, 1.5, -0.5, 0.25, 0.1];
+        let b = [1.0, -2.0, 3.0, 0.5, 2.0];
+        let x = solve_toeplitz(&r, &b).unwrap();
+        for (actual, expected) in x.iter().zip(solve_dense(&r, &b)) {
+            assert!((actual - expected).abs() < 1e-12);
+        }
+    }
+
+    #[test]
+    fn test_levinson_durbin_matches_yule_walker() {
+        let acf = [2.0, 1.2, 0.4, -0.1, -0.3];
+        let fit = levinson_durbin(&acf, 4).unwrap();
+        let direct = solve_toeplitz(&acf[..4], &acf[1..=4]).unwrap();
+        for (actual, expected) in fit.coefficients().iter().zip(direct) {
+            assert!((actual - expected).abs() < 1e-12);
+        }
+        assert!((fit.reflection_coefficients()[0] - 0.6).abs() < 1e-12);
+    }
+
+    #[test]
+    fn test_singular() {
+        assert_eq!(
+            solve_toeplitz(&[1.0, 1.0], &[1.0, 2.0]).unwrap_err(),
+            LagError::SingularMatrix
+        );
+        assert_eq!(
+            levinson_durbin(&[0.0, 1.0], 1).unwrap_err(),
+            LagError::SingularMatrix
+        );
+        assert_eq!(
+            levinson_durbin(&[1.0, 0.5], 2).unwrap_err(),
+            LagError::LagExceedsValueCount
+        );
+    }
+}
@@ -0,0 +1,175 @@
+use crate::{lag_matrix_2d, to_column_major, IntoLag, LagError, LagMatrix, MatrixLayout};
+
+/// A lag matrix of the valid series of a batch, together with the outcome of every series;
+/// see [`lag_matrix_2d_tolerant`].
+#[derive(Debug, Clone, PartialEq)]
+pub struct PartialLagMatrix<T> {
+    matrix: LagMatrix<T>,
+    statuses: Vec<Result<usize, LagError>>,
+}
+
+impl<T> PartialLagMatrix<T> {
+    /// The lag matrix of the valid series.
+    #[inline(always)]
+    pub const fn matrix(&self) -> &LagMatrix<T> {
+        &self.matrix
+    }
+
+    /// Returns the lag matrix of the valid series.
+    #[inline(always)]
+    pub fn into_matrix(self) -> LagMatrix<T> {
+        self.matrix
+    }
+
+    /// The outcome of every input series, in input order: the index of the series within
+    /// the matrix, or the reason it was skipped.
+    #[inline(always)]
+    pub fn statuses(&self) -> &[Result<usize, LagError>] {
+        &self.statuses
+    }
+
+    /// The outcome of the specified input series, or `None` if it does not exist.
+    #[inline(always)]
+    pub fn status(&self, series: usize) -> Option<Result<usize, LagError>> {
+        self.statuses.get(series).copied()
+    }
+
+    /// Iterates the skipped input series together with the reason they were skipped.
+    pub fn errors(&self) -> impl Iterator<Item = (usize, LagError)> + '_ {
+        self.statuses
+            .iter()
+            .enumerate()
+            .filter_map(|(series, status)| status.err().map(|error| (series, error)))
+    }
+
+    /// Determines whether every input series is part of the matrix.
+    #[inline(always)]
+    pub fn is_complete(&self) -> bool {
+        self.statuses.iter().all(Result::is_ok)
+    }
+}
+
+/// Create a time-lagged matrix of a batch of time series, skipping malformed series
+/// rather than failing as a whole, e.g. in long-running ETL jobs.
+///
+/// A series is skipped with [`LagError::InvalidLength`] if its length differs from the one
+/// specified by the layout, and with [`LagError::MissingValues`] if none of its values equals
+/// itself, e.g. if it holds only NaN. The remaining series are lagged like the series of a
+/// data matrix passed to [`lag_matrix_2d`] with the same layout, in input order.
+///
+/// ## Arguments
+/// * `series` - The series of the batch.
+/// * `layout` - The expected length of every series and the orientation of the result;
+///   see [`lag_matrix_2d`].
+/// * `lags` - The lags to create; negative lags create leads, see [`IntoLag`].
+/// * `fill` - The value to use to fill in lagged gaps.
+/// * `row_stride` - The number of elements along a row of the matrix, see [`lag_matrix_2d`].
+///
+/// ## Returns
+/// The matrix together with the outcome of every series, or an error affecting all series,
+/// e.g. [`LagError::EmptyData`] if no series is valid or [`LagError::LagExceedsValueCount`]
+/// if a lag spans more than the series length.
+///
+/// ## Example
+/// ```
+/// use timelag::{lag_matrix_2d, lag_matrix_2d_tolerant, LagError, MatrixLayout};
+///
+/// let batch: [&[f64]; 3] = [&[1.0, 2.0, 3.0], &[4.0, 5.0], &[f64::NAN; 3]];
+/// let layout = MatrixLayout::RowMajor(3);
+/// let partial = lag_matrix_2d_tolerant(&batch, layout, 0..=1, 0.0, 0).unwrap();
+///
+/// assert_eq!(
+///     partial.statuses(),
+///     &[Ok(0), Err(LagError::InvalidLength), Err(LagError::MissingValues)]
+/// );
+/// assert_eq!(
+///     partial.matrix(),
+///     &lag_matrix_2d(&[1.0, 2.0, 3.0], layout, 0..=1, 0.0, 0).unwrap()
+/// );
+/// ```
+pub fn lag_matrix_2d_tolerant<T, S, L, R>(
+    series: &[S],
+    layout: MatrixLayout,
+    lags: R,
+    fill: T,
+    row_stride: usize,
+) -> Result<PartialLagMatrix<T>, LagError>
+where
+    T: Copy + PartialEq,
+    S: AsRef<[T]>,
+    L: IntoLag,
+    R: IntoIterator<Item = L>,
+{
+    let series_length = layout.len();
+    let mut valid = Vec::new();
+    let statuses = series
+        .iter()
+        .map(|values| {
+            let values = values.as_ref();
+            if values.len() != series_length {
+                Err(LagError::InvalidLength)
+            } else if values.iter().all(is_missing) {
+                Err(LagError::MissingValues)
+            } else {
+                valid.extend_from_slice(values);
+                Ok(valid.len() / series_length - 1)
+            }
+        })
+        .collect();
+
+    let rows = MatrixLayout::RowMajor(series_length);
+    let data = match layout {
+        MatrixLayout::RowMajor(_) => valid,
+        MatrixLayout::ColumnMajor(_) if valid.is_empty() => valid,
+        MatrixLayout::ColumnMajor(_) => to_column_major(&valid, rows)?,
+    };
+    let matrix = lag_matrix_2d(&data, layout, lags, fill, row_stride)?;
+    Ok(PartialLagMatrix { matrix, statuses })
+}
+
+/// Determines whether the value is missing, i.e. does not equal itself like NaN.
+#[inline(always)]
+fn is_missing<T: PartialEq>(value: &T) -> bool {
+    value.ne(value)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_column_major_skips_invalid_series() {
+        let batch = vec![vec![1, 2, 3], vec![4, 5, 6, 7], vec![10, 20, 30]];
+        let layout = MatrixLayout::ColumnMajor(3);
+        let partial = lag_matrix_2d_tolerant(&batch, layout, [0, 1], -1, 0).unwrap();
+
+        assert!(!partial.is_complete());
+        assert_eq!(partial.status(2), Some(Ok(1)));
+        assert_eq!(
+            partial.errors().collect::<Vec<_>>(),
+            [(1, LagError::InvalidLength)]
+        );
+        let data = [1, 10, 2, 20, 3, 30];
+        assert_eq!(
+            partial.into_matrix(),
+            lag_matrix_2d(&data, layout, [0, 1], -1, 0).unwrap()
+        );
+    }
+
+    #[test]
+    fn test_errors_affecting_all_series() {
+        let batch = [[1.0, f64::NAN], [f64::NAN, f64::NAN]];
+        let layout = MatrixLayout::RowMajor(2);
+        let partial = lag_matrix_2d_tolerant(&batch, layout, [1], 0.0, 0).unwrap();
+        assert_eq!(partial.statuses(), &[Ok(0), Err(LagError::MissingValues)]);
+
+        assert_eq!(
+            lag_matrix_2d_tolerant(&batch[1..], layout, [1], 0.0, 0),
+            Err(LagError::EmptyData)
+        );
+        assert_eq!(
+            lag_matrix_2d_tolerant(&batch, layout, [3], 0.0, 0),
+            Err(LagError::LagExceedsValueCount)
+        );
+    }
+}
@@ -0,0 +1,581 @@
+use crate::{collect_lags, lag_matrix, IntoLag, LagError, LagMatrix};
+use core::ops::{Add, Sub};
+
+/// A feature engineering step, such as differencing, lagging or scaling, that can be
+/// composed into a [`Pipeline`].
+///
+/// A pipeline declares the steps once, such that the very same chain can be replayed on
+/// training data and at serving time.
+///
+/// ## Example
+/// ```
+/// use timelag::{Difference, Lag, Pipeline, SeriesTransform};
+///
+/// let pipeline = Pipeline::new(Difference::new(1), Lag::new(0..=1, 0).unwrap());
+/// let lagged = pipeline.apply(&[1, 2, 4, 7][..]).unwrap();
+///
+/// // The differences 1, 2, 3 and their first lag.
+/// assert_eq!(lagged, &[1, 2, 3, 0, 1, 2]);
+/// ```
+pub trait SeriesTransform<I: ?Sized> {
+    /// The result of the transformation.
+    type Output;
+
+    /// Transforms the input.
+    fn apply(&self, input: &I) -> Result<Self::Output, LagError>;
+}
+
+/// The inverse of a [`SeriesTransform`], e.g. to map model predictions in the transformed
+/// space back to the original scale.
+///
+/// ## Example
+/// ```
+/// use timelag::{Difference, InverseTransform, SeriesTransform};
+///
+/// let series = [3, 5, 4, 8];
+/// let difference = Difference::new(1).fit(&series).unwrap();
+///
+/// let differences = difference.apply(&series[..]).unwrap();
+/// assert_eq!(differences, vec![2, -1, 4]);
+/// assert_eq!(difference.inverse_apply(&differences[..]).unwrap(), series);
+/// ```
+pub trait InverseTransform<I: ?Sized> {
+    /// The result of the inverse transformation.
+    type Output;
+
+    /// Maps the transformed input back.
+    fn inverse_apply(&self, input: &I) -> Result<Self::Output, LagError>;
+}
+
+/// Two transformations applied one after the other.
+///
+/// Longer chains are built with [`then`](Self::then), nesting the pipelines.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Pipeline<A, B> {
+    first: A,
+    second: B,
+}
+
+impl<A, B> Pipeline<A, B> {
+    /// Creates a pipeline that feeds the output of `first` into `second`.
+    #[inline(always)]
+    pub const fn new(first: A, second: B) -> Self {
+        Self { first, second }
+    }
+
+    /// Creates a pipeline that feeds the output of this pipeline into `next`.
+    #[inline(always)]
+    pub const fn then<N>(self, next: N) -> Pipeline<Self, N> {
+        Pipeline::new(self, next)
+    }
+
+    /// The first step.
+    #[inline(always)]
+    pub const fn first(&self) -> &A {
+        &self.first
+    }
+
+    /// The second step.
+    #[inline(always)]
+    pub const fn second(&self) -> &B {
+        &self.second
+    }
+}
+
+impl<I, A, B> SeriesTransform<I> for Pipeline<A, B>
+where
+    I: ?Sized,
+    A: SeriesTransform<I>,
+    B: SeriesTransform<A::Output>,
+{
+    type Output = B::Output;
+
+    fn apply(&self, input: &I) -> Result<Self::Output, LagError> {
+        let intermediate = self.first.apply(input)?;
+        self.second.apply(&intermediate)
+    }
+}
+
+impl<I, A, B> InverseTransform<I> for Pipeline<A, B>
+where
+    I: ?Sized,
+    B: InverseTransform<I>,
+    A: InverseTransform<B::Output>,
+{
+    type Output = A::Output;
+
+    /// Inverts the second step, then the first.
+    fn inverse_apply(&self, input: &I) -> Result<Self::Output, LagError> {
+        let intermediate = self.second.inverse_apply(input)?;
+        self.first.inverse_apply(&intermediate)
+    }
+}
+
+/// Differences a series at a lag, i.e. `x[t + lag] - x[t]`, shortening it by `lag` values.
+///
+/// ## Example
+/// ```
+/// use timelag::{Difference, SeriesTransform};
+///
+/// let seasonal = Difference::new(2).apply(&[1, 5, 2, 7, 4][..]).unwrap();
+/// assert_eq!(seasonal, vec![1, 2, 2]);
+/// ```
+#[derive(Debug, Copy, Clone, Eq, PartialEq, Hash)]
+pub struct Difference {
+    lag: usize,
+}
+
+impl Difference {
+    /// Creates the differencing step of the specified lag, e.g. `1` for consecutive values.
+    #[inline(always)]
+    pub const fn new(lag: usize) -> Self {
+        Self { lag }
+    }
+
+    /// The lag of the differences.
+    #[inline(always)]
+    pub const fn lag(&self) -> usize {
+        self.lag
+    }
+
+    /// Stores the first `lag` values of the series, such that the differences can be
+    /// inverted; see [`FittedDifference`].
+    ///
+    /// ## Returns
+    /// The invertible differencing step, or [`LagError::InvalidLags`] if the lag is zero or
+    /// [`LagError::LagExceedsValueCount`] if the series is not longer than the lag.
+    pub fn fit<T: Copy>(&self, series: &[T]) -> Result<FittedDifference<T>, LagError> {
+        if self.lag == 0 {
+            return Err(LagError::InvalidLags);
+        }
+        if series.len() <= self.lag {
+            return Err(LagError::LagExceedsValueCount);
+        }
+        Ok(FittedDifference {
+            initial: series[..self.lag].to_vec(),
+        })
+    }
+}
+
+impl<T> SeriesTransform<[T]> for Difference
+where
+    T: Copy + Sub<Output = T>,
+{
+    type Output = Vec<T>;
+
+    /// ## Returns
+    /// The differences, or [`LagError::InvalidLags`] if the lag is zero or
+    /// [`LagError::LagExceedsValueCount`] if the series is not longer than the lag.
+    fn apply(&self, input: &[T]) -> Result<Self::Output, LagError> {
+        if self.lag == 0 {
+            return Err(LagError::InvalidLags);
+        }
+        if input.len() <= self.lag {
+            return Err(LagError::LagExceedsValueCount);
+        }
+        Ok(input
+            .iter()
+            .zip(&input[self.lag..])
+            .map(|(&previous, &next)| next - previous)
+            .collect())
+    }
+}
+
+impl<T> SeriesTransform<Vec<T>> for Difference
+where
+    T: Copy + Sub<Output = T>,
+{
+    type Output = Vec<T>;
+
+    #[inline(always)]
+    fn apply(&self, input: &Vec<T>) -> Result<Self::Output, LagError> {
+        self.apply(input.as_slice())
+    }
+}
+
+/// A [`Difference`] that stores the initial values of the series it was fitted on, such
+/// that the differences can be integrated back into the series.
+#[derive(Debug, Clone, PartialEq)]
+pub struct FittedDifference<T> {
+    initial: Vec<T>,
+}
+
+impl<T> FittedDifference<T> {
+    /// The lag of the differences.
+    #[inline(always)]
+    pub fn lag(&self) -> usize {
+        self.initial.len()
+    }
+
+    /// The first `lag` values of the series.
+    #[inline(always)]
+    pub fn initial(&self) -> &[T] {
+        &self.initial
+    }
+}
+
+impl<T> SeriesTransform<[T]> for FittedDifference<T>
+where
+    T: Copy + Sub<Output = T>,
+{
+    type Output = Vec<T>;
+
+    #[inline(always)]
+    fn apply(&self, input: &[T]) -> Result<Self::Output, LagError> {
+        Difference::new(self.lag()).apply(input)
+    }
+}
+
+impl<T> SeriesTransform<Vec<T>> for FittedDifference<T>
+where
+    T: Copy + Sub<Output = T>,
+{
+    type Output = Vec<T>;
+
+    #[inline(always)]
+    fn apply(&self, input: &Vec<T>) -> Result<Self::Output, LagError> {
+        self.apply(input.as_slice())
+    }
+}
+
+impl<T> InverseTransform<[T]> for FittedDifference<T>
+where
+    T: Copy + Add<Output = T>,
+{
+    type Output = Vec<T>;
+
+    /// Integrates the differences, starting from the stored initial values; the result is
+    /// `lag` values longer than the differences.
+    fn inverse_apply(&self, input: &[T]) -> Result<Self::Output, LagError> {
+        let lag = self.lag();
+        let mut series = Vec::with_capacity(lag + input.len());
+        series.extend_from_slice(&self.initial);
+        for (t, &difference) in input.iter().enumerate() {
+            series.push(series[t] + difference);
+        }
+        Ok(series)
+    }
+}
+
+impl<T> InverseTransform<Vec<T>> for FittedDifference<T>
+where
+    T: Copy + Add<Output = T>,
+{
+    type Output = Vec<T>;
+
+    #[inline(always)]
+    fn inverse_apply(&self, input: &Vec<T>) -> Result<Self::Output, LagError> {
+        self.inverse_apply(input.as_slice())
+    }
+}
+
+/// Creates the lag matrix of a series; see [`lag_matrix`].
+///
+/// The matrix has one row per lag and no row padding.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Lag<T> {
+    lags: Vec<isize>,
+    fill: T,
+}
+
+impl<T> Lag<T> {
+    /// Creates the lagging step.
+    ///
+    /// ## Arguments
+    /// * `lags` - The lags to create, see [`lag_matrix`].
+    /// * `fill` - The value to use to fill in lagged gaps.
+    ///
+    /// ## Returns
+    /// The step, or [`LagError::InvalidLags`] if a lag is out of range.
+    pub fn new<L: IntoLag, R: IntoIterator<Item = L>>(lags: R, fill: T) -> Result<Self, LagError> {
+        Ok(Self {
+            lags: collect_lags(lags)?,
+            fill,
+        })
+    }
+
+    /// The lags to create.
+    #[inline(always)]
+    pub fn lags(&self) -> &[isize] {
+        &self.lags
+    }
+}
+
+impl<T: Copy> SeriesTransform<[T]> for Lag<T> {
+    type Output = LagMatrix<T>;
+
+    #[inline(always)]
+    fn apply(&self, input: &[T]) -> Result<Self::Output, LagError> {
+        lag_matrix(input, self.lags.iter().copied(), self.fill, 0)
+    }
+}
+
+impl<T: Copy> SeriesTransform<Vec<T>> for Lag<T> {
+    type Output = LagMatrix<T>;
+
+    #[inline(always)]
+    fn apply(&self, input: &Vec<T>) -> Result<Self::Output, LagError> {
+        self.apply(input.as_slice())
+    }
+}
+
+/// The statistic computed by [`Rolling`].
+#[cfg(feature = "float")]
+#[cfg_attr(docsrs, doc(cfg(feature = "float")))]
+#[derive(Debug, Copy, Clone, Eq, PartialEq, Hash, Default)]
+pub enum RollingStat {
+    /// The arithmetic mean.
+    #[default]
+    Mean,
+    /// The sum.
+    Sum,
+    /// The sample standard deviation; `NaN` for windows of one value.
+    StdDev,
+    /// The smallest value.
+    Min,
+    /// The largest value.
+    Max,
+    /// The rank of the newest value among the values of the window, starting at one for the
+    /// smallest value and averaging ties, e.g. as a regime-robust replacement of raw values.
+    ///
+    /// Use [`Rolling::padded`] to keep the ranks aligned with the time steps of the series.
+    Rank,
+    /// The [`Rank`](Self::Rank) divided by the window size, i.e. the empirical quantile of the
+    /// newest value in `(0, 1]`.
+    PercentRank,
+}
+
+/// Computes a statistic over a rolling window of a series, shortening it by `window - 1`
+/// values; value `t` of the result summarizes the input values `t..t + window`.
+///
+/// With [`padded`](Self::padded), the leading `window - 1` values are filled in instead, such
+/// that value `t` summarizes the trailing window ending at input value `t`, aligned with the
+/// time steps of lag matrices of the series.
+///
+/// ## Example
+/// ```
+/// use timelag::{Rolling, RollingStat, SeriesTransform};
+///
+/// let means = Rolling::new(2, RollingStat::Mean).apply(&[1.0, 3.0, 2.0, 6.0][..]).unwrap();
+/// assert_eq!(means, vec![2.0, 2.5, 4.0]);
+/// ```
+#[cfg(feature = "float")]
+#[cfg_attr(docsrs, doc(cfg(feature = "float")))]
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub struct Rolling {
+    window: usize,
+    stat: RollingStat,
+    padding: Option<f64>,
+}
+
+#[cfg(feature = "float")]
+impl Rolling {
+    /// Creates the rolling statistic over windows of the specified size.
+    #[inline(always)]
+    pub const fn new(window: usize, stat: RollingStat) -> Self {
+        Self {
+            window,
+            stat,
+            padding: None,
+        }
+    }
+
+    /// Fills the leading `window - 1` values of the result with `fill`, such that the result
+    /// is as long as the series and aligned with the time steps of its lag matrices.
+    ///
+    /// ## Example
+    /// ```
+    /// use timelag::{Rolling, RollingStat, SeriesTransform};
+    ///
+    /// let ranks = Rolling::new(3, RollingStat::Rank).padded(f64::NAN);
+    /// let ranks: Vec<f64> = ranks.apply(&[1.0, 4.0, 2.0, 8.0][..]).unwrap();
+    ///
+    /// assert_eq!(ranks.len(), 4);
+    /// assert!(ranks[0].is_nan() && ranks[1].is_nan());
+    /// assert_eq!(ranks[2..], [2.0, 3.0]);
+    /// ```
+    #[inline(always)]
+    pub const fn padded(mut self, fill: f64) -> Self {
+        self.padding = Some(fill);
+        self
+    }
+
+    /// The number of values per window.
+    #[inline(always)]
+    pub const fn window(&self) -> usize {
+        self.window
+    }
+
+    /// The statistic computed per window.
+    #[inline(always)]
+    pub const fn stat(&self) -> RollingStat {
+        self.stat
+    }
+
+    /// The value filling in the leading values of the result, if padded.
+    #[inline(always)]
+    pub const fn padding(&self) -> Option<f64> {
+        self.padding
+    }
+}
+
+#[cfg(feature = "float")]
+impl<T: num_traits::Float> SeriesTransform<[T]> for Rolling {
+    type Output = Vec<T>;
+
+    /// ## Returns
+    /// The statistics, or [`LagError::InvalidLength`] if the window is empty or
+    /// [`LagError::LagExceedsValueCount`] if the series is shorter than the window.
+    fn apply(&self, input: &[T]) -> Result<Self::Output, LagError> {
+        if self.window == 0 {
+            return Err(LagError::InvalidLength);
+        }
+        if input.len() < self.window {
+            return Err(LagError::LagExceedsValueCount);
+        }
+
+        let count = T::from(self.window).ok_or(LagError::InvalidLength)?;
+        let padding = match self.padding {
+            Some(fill) => vec![T::from(fill).unwrap_or_else(T::nan); self.window - 1],
+            None => Vec::new(),
+        };
+        let stats = input.windows(self.window).map(|window| {
+            let sum = || window.iter().fold(T::zero(), |sum, &value| sum + value);
+            match self.stat {
+                RollingStat::Mean => sum() / count,
+                RollingStat::Sum => sum(),
+                RollingStat::StdDev => {
+                    let mean = sum() / count;
+                    let squares = window.iter().fold(T::zero(), |sum, &value| {
+                        sum + (value - mean) * (value - mean)
+                    });
+                    (squares / (count - T::one())).sqrt()
+                }
+                RollingStat::Min => window.iter().fold(T::infinity(), |min, &v| min.min(v)),
+                RollingStat::Max => window.iter().fold(T::neg_infinity(), |max, &v| max.max(v)),
+                RollingStat::Rank => rank(window),
+                RollingStat::PercentRank => rank(window) / count,
+            }
+        });
+        Ok(padding.into_iter().chain(stats).collect())
+    }
+}
+
+/// The rank of the last value among the values of the window, averaging ties.
+#[cfg(feature = "float")]
+fn rank<T: num_traits::Float>(window: &[T]) -> T {
+    let newest = window[window.len() - 1];
+    let (smaller, equal) = window
+        .iter()
+        .fold((0usize, 0usize), |(smaller, equal), &v| {
+            if v < newest {
+                (smaller + 1, equal)
+            } else if v == newest {
+                (smaller, equal + 1)
+            } else {
+                (smaller, equal)
+            }
+        });
+    let two = T::one() + T::one();
+    T::from(smaller).unwrap_or_else(T::nan)
+        + (T::from(equal).unwrap_or_else(T::nan) + T::one()) / two
+}
+
+#[cfg(feature = "float")]
+impl<T: num_traits::Float> SeriesTransform<Vec<T>> for Rolling {
+    type Output = Vec<T>;
+
+    #[inline(always)]
+    fn apply(&self, input: &Vec<T>) -> Result<Self::Output, LagError> {
+        self.apply(input.as_slice())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_pipeline_equals_manual_steps() {
+        let data = [3, 1, 4, 1, 5, 9, 2, 6];
+        let pipeline = Pipeline::new(Difference::new(1), Difference::new(2))
+            .then(Lag::new([0, 2], -1).unwrap());
+
+        let first = Difference::new(1).apply(&data[..]).unwrap();
+        let second = Difference::new(2).apply(&first).unwrap();
+        let expected = lag_matrix(&second, [0, 2], -1, 0).unwrap();
+        assert_eq!(pipeline.apply(&data[..]).unwrap(), expected);
+        assert_eq!(pipeline.first().second(), &Difference::new(2));
+    }
+
+    #[test]
+    fn test_errors_are_propagated() {
+        let pipeline = Pipeline::new(Difference::new(3), Lag::new([0], 0).unwrap());
+        assert_eq!(
+            pipeline.apply(&[1, 2, 3][..]).unwrap_err(),
+            LagError::LagExceedsValueCount
+        );
+        assert_eq!(
+            Difference::new(0).apply(&[1, 2][..]).unwrap_err(),
+            LagError::InvalidLags
+        );
+    }
+
+    #[test]
+    #[cfg(feature = "float")]
+    fn test_rolling_stats_and_scaling() {
+        use crate::ScaleScope;
+
+        let data = [1.0, 4.0, 2.0, 8.0, 5.0];
+        let apply = |stat| Rolling::new(3, stat).apply(&data[..]).unwrap();
+        assert_eq!(apply(RollingStat::Sum), vec![7.0, 14.0, 15.0]);
+        assert_eq!(apply(RollingStat::Min), vec![1.0, 2.0, 2.0]);
+        assert_eq!(apply(RollingStat::Max), vec![4.0, 8.0, 8.0]);
+        assert!((apply(RollingStat::StdDev)[0] - (7.0f64 / 3.0).sqrt()).abs() < 1e-12);
+        assert_eq!(apply(RollingStat::Rank), vec![2.0, 3.0, 2.0]);
+
+        // Ties share their average rank.
+        let ties = Rolling::new(4, RollingStat::PercentRank);
+        assert_eq!(ties.apply(&[3.0, 2.0, 3.0, 3.0][..]).unwrap(), vec![0.75]);
+
+        // Padded ranks line up with the rows of the lag matrix.
+        let ranks = Rolling::new(3, RollingStat::Rank).padded(-1.0);
+        let ranks = ranks.apply(&data[..]).unwrap();
+        assert_eq!(ranks, vec![-1.0, -1.0, 2.0, 3.0, 2.0]);
+        assert_eq!(
+            ranks.len(),
+            lag_matrix(&data, 0..=2, 0.0, 0).unwrap().num_cols()
+        );
+
+        // Scaling fitted on training data is replayed as the last step.
+        let features = Pipeline::new(
+            Rolling::new(2, RollingStat::Mean),
+            Lag::new(0..=1, 0.0).unwrap(),
+        );
+        let unscaled = features.apply(&data[..]).unwrap();
+        let mut train = unscaled.clone();
+        let scaling = train.scale_minmax(0.0..=1.0, ScaleScope::PerLag);
+
+        let pipeline = features.then(scaling);
+        assert_eq!(pipeline.apply(&data[..]).unwrap(), train);
+        assert_eq!(pipeline.second().inverse_apply(&train).unwrap(), unscaled);
+    }
+
+    #[test]
+    fn test_pipeline_inverse_reverses_the_steps() {
+        let series = [1, 4, 9, 16, 25, 36];
+        let first = Difference::new(1).fit(&series).unwrap();
+        let differences = first.apply(&series[..]).unwrap();
+        let second = Difference::new(2).fit(&differences).unwrap();
+
+        let pipeline = Pipeline::new(first, second);
+        let transformed = pipeline.apply(&series[..]).unwrap();
+        assert_eq!(transformed, vec![4, 4, 4]);
+        assert_eq!(pipeline.inverse_apply(&transformed).unwrap(), series);
+
+        assert_eq!(
+            Difference::new(6).fit(&series).unwrap_err(),
+            LagError::LagExceedsValueCount
+        );
+    }
+}
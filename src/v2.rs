@@ -0,0 +1,69 @@
+//! The builder-based interface of the crate in one place: a [`LagMatrixBuilder`] collects the
+//! options that are spread over positional arguments and separate functions at the crate
+//! root, a [`LagSchema`] pairs the options with the lags, and the results are typed as
+//! [`LagMatrix`] and its borrowed forms.
+//!
+//! This module only re-exports these types, such that migrating to it does not change any
+//! matrix: the functions at the crate root remain available and share the kernels of the
+//! builder, e.g. [`lag_matrix_2d`](crate::lag_matrix_2d) creates the same matrix as
+//! [`LagMatrixBuilder::build_2d`]. New options are added to the builder rather than as
+//! further positional arguments.
+//!
+//! ## Example
+//! ```
+//! use timelag::v2::{LagMatrixBuilder, LagSchema, MatrixLayout, OutputLayout};
+//!
+//! let data = [
+//!     1.0, 2.0, 3.0, 4.0,
+//!     10.0, 20.0, 30.0, 40.0,
+//! ];
+//!
+//! let builder = LagMatrixBuilder::new(f64::NAN)
+//!     .trim_incomplete(true)
+//!     .output_layout(OutputLayout::LagsAlongColumns);
+//! let schema = LagSchema::new(builder, 0..=1).unwrap();
+//! let lagged = schema.build(&data, MatrixLayout::RowMajor(4)).unwrap();
+//!
+//! assert_eq!(lagged.series_length(), 3);
+//! assert_eq!(
+//!     lagged,
+//!     &[
+//!         2.0, 20.0, 1.0, 10.0,
+//!         3.0, 30.0, 2.0, 20.0,
+//!         4.0, 40.0, 3.0, 30.0,
+//!     ]
+//! );
+//! ```
+
+pub use crate::{
+    build_all, IntoLag, LagDirection, LagError, LagMatrix, LagMatrixBuilder, LagMatrixCow,
+    LagMatrixShape, LagMatrixView, LagSchema, LagSet, MatrixLayout, OutputLayout, ParallelConfig,
+};
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{lag_matrix, lag_matrix_2d_oriented};
+
+    #[test]
+    fn test_matches_crate_root_functions() {
+        let data = [1, 2, 3, 4, 10, 20, 30, 40];
+        let layout = MatrixLayout::ColumnMajor(4);
+        let builder = LagMatrixBuilder::new(-1).output_layout(OutputLayout::LagsAlongRows);
+        let schema = LagSchema::new(builder, [0, 2, -1]).unwrap();
+        let expected = lag_matrix_2d_oriented(
+            &data,
+            layout,
+            [0, 2, -1],
+            -1,
+            0,
+            OutputLayout::LagsAlongRows,
+        );
+        assert_eq!(schema.build(&data, layout), expected);
+
+        let series = LagMatrixBuilder::new(0)
+            .row_stride(5)
+            .build(&data[..4], 0..=1);
+        assert_eq!(series, lag_matrix(&data[..4], 0..=1, 0, 5));
+    }
+}
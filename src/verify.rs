@@ -0,0 +1,97 @@
+use core::fmt::Arguments;
+
+/// The index written for a lagged gap when a kernel is run on the indices of the values.
+pub(crate) const GAP: usize = usize::MAX;
+
+/// The indices of `len` values, which are lagged in place of the values to check a kernel
+/// without requiring any bounds on the value type.
+pub(crate) fn indices(len: usize) -> Vec<usize> {
+    (0..len).collect()
+}
+
+/// The time step of the value that time step `t` of a series shifted by `shift` holds by the
+/// naive definition `lagged[t] == series[t - shift]`, or `None` for a lagged gap.
+///
+/// This intentionally does not share the offset math of the kernels, e.g.
+/// [`source_step`](crate::kernels::source_step) or
+/// [`shifted_ranges`](crate::kernels::shifted_ranges).
+pub(crate) fn naive_source(t: usize, shift: isize, series_length: usize) -> Option<usize> {
+    let source = t as isize - shift;
+    (0..series_length as isize)
+        .contains(&source)
+        .then_some(source as usize)
+}
+
+/// Asserts that a kernel run on the indices of the values wrote the index `expected` by the
+/// naive definition, or [`GAP`] for a lagged gap.
+///
+/// ## Panics
+/// Panics with the name of the kernel and the element if the indices differ.
+#[track_caller]
+pub(crate) fn check(kernel: &str, actual: usize, expected: Option<usize>, element: Arguments) {
+    assert_eq!(
+        actual,
+        expected.unwrap_or(GAP),
+        "{kernel}: {element} does not match the definition of the lag"
+    );
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{lag_matrix, lag_matrix_2d, lag_matrix_prefetched, LagMatrixBuilder, MatrixLayout};
+
+    #[test]
+    fn test_naive_source() {
+        assert_eq!(naive_source(3, 2, 5), Some(1));
+        assert_eq!(naive_source(1, 2, 5), None);
+        assert_eq!(naive_source(2, -2, 5), Some(4));
+        assert_eq!(naive_source(3, -2, 5), None);
+    }
+
+    #[test]
+    #[should_panic(expected = "lag_matrix: lag 1 at time step 2 does not match")]
+    fn test_check_rejects_mismatches() {
+        check(
+            "lag_matrix",
+            0,
+            Some(1),
+            format_args!("lag 1 at time step 2"),
+        );
+    }
+
+    #[test]
+    fn test_kernels_pass_verification() {
+        let lags = [0, 2, -1, 4];
+        let data: Vec<i32> = (1..=20).collect();
+        lag_matrix(&data, lags, -1, 23).unwrap();
+        lag_matrix_prefetched(&data, lags, -1, 0, 3).unwrap();
+
+        for layout in [MatrixLayout::RowMajor(5), MatrixLayout::ColumnMajor(5)] {
+            lag_matrix_2d(&data, layout, lags, -1, 0).unwrap();
+
+            let mut buffer = vec![0; 4 * 4 * 5];
+            LagMatrixBuilder::new(-1)
+                .build_2d_into(&mut buffer, &data, layout, lags)
+                .unwrap();
+        }
+    }
+
+    #[test]
+    #[cfg(feature = "ndarray")]
+    fn test_ndarray_kernels_pass_verification() {
+        use crate::{lag_along_axis, lag_batch, lag_matrix_into};
+        use ndarray::{Array1, Array2, Array3, Axis};
+
+        let lags = [0, 2, -1];
+        let batch = Array3::from_shape_fn((2, 3, 4), |(e, s, t)| e * 100 + s * 10 + t);
+        lag_batch(&batch, lags, 0).unwrap();
+        for axis in 0..3 {
+            lag_along_axis(&batch, Axis(axis), [0, -1], 0).unwrap();
+        }
+
+        let series = Array1::from_iter(0..4);
+        let mut out = Array2::zeros((4, 3));
+        lag_matrix_into(&series, lags, 0, out.view_mut().reversed_axes()).unwrap();
+    }
+}
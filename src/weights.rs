@@ -0,0 +1,104 @@
+use crate::{LagMatrix, TrimMode};
+use num_traits::Float;
+
+/// Creates exponentially decaying observation weights that down-weight older time steps.
+///
+/// The weight of time step `t` is `0.5^((len - 1 - t) / half_life)`, i.e. the most recent
+/// observation has a weight of one and the weight halves every `half_life` steps into the past.
+/// The weights can be attached to a matrix via [`LagMatrix::with_row_weights`](crate::LagMatrix::with_row_weights).
+///
+/// ## Arguments
+/// * `len` - The number of time steps, usually the series length.
+/// * `half_life` - The number of time steps after which the weight halves.
+///   A non-positive half-life yields a weight of one for the most recent step only.
+///
+/// ## Example
+/// ```
+/// use timelag::recency_weights;
+///
+/// let weights = recency_weights(4, 1.0);
+/// assert_eq!(weights, [0.125, 0.25, 0.5, 1.0]);
+/// ```
+pub fn recency_weights<T: Float>(len: usize, half_life: T) -> Vec<T> {
+    let half = T::from(0.5).expect("0.5 is representable");
+    (0..len)
+        .map(|t| {
+            let age = T::from(len - 1 - t).unwrap_or_else(T::infinity);
+            if age.is_zero() {
+                T::one()
+            } else if half_life <= T::zero() {
+                T::zero()
+            } else {
+                half.powf(age / half_life)
+            }
+        })
+        .collect()
+}
+
+impl<T: Float, S> LagMatrix<T, S> {
+    /// Creates exponentially decaying weights for the time steps of the matrix, see
+    /// [`recency_weights`], e.g. for weighted regression on the lagged features.
+    ///
+    /// The weights have one entry per time step, i.e. per row of a column-major matrix and
+    /// per column of a row-major matrix, and can be attached via
+    /// [`with_row_weights`](Self::with_row_weights). The time steps trimmed according to
+    /// `mode` receive a weight of zero; the most recent remaining time step weighs one.
+    ///
+    /// ## Arguments
+    /// * `half_life` - The number of time steps after which the weight halves.
+    /// * `mode` - The incomplete time steps to exclude by a weight of zero.
+    ///
+    /// ## Example
+    /// ```
+    /// use timelag::{lag_matrix, TrimMode};
+    ///
+    /// let lagged = lag_matrix(&[1.0, 2.0, 3.0, 4.0], 0..=1, 0.0, 0).unwrap();
+    ///
+    /// assert_eq!(lagged.recency_weights(1.0, TrimMode::None), [0.125, 0.25, 0.5, 1.0]);
+    /// assert_eq!(lagged.recency_weights(1.0, TrimMode::Forward), [0.0, 0.25, 0.5, 1.0]);
+    /// ```
+    pub fn recency_weights(&self, half_life: T, mode: TrimMode) -> Vec<T> {
+        let (mut leading, mut trailing) = self.incomplete_steps();
+        if matches!(mode, TrimMode::None | TrimMode::Backward) {
+            leading = 0;
+        }
+        if matches!(mode, TrimMode::None | TrimMode::Forward) {
+            trailing = 0;
+        }
+
+        let retained = self.series_length.saturating_sub(leading + trailing);
+        let mut weights = vec![T::zero(); self.series_length];
+        weights[leading..leading + retained].copy_from_slice(&recency_weights(retained, half_life));
+        weights
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_recency_weights_half_life() {
+        let weights = recency_weights(9, 4.0_f64);
+        assert_eq!(weights.len(), 9);
+        assert_eq!(weights[8], 1.0);
+        assert!((weights[4] - 0.5).abs() < 1e-12);
+        assert!((weights[0] - 0.25).abs() < 1e-12);
+        assert!(recency_weights::<f32>(0, 1.0).is_empty());
+    }
+
+    #[test]
+    fn test_recency_weights_zero_half_life() {
+        assert_eq!(recency_weights(3, 0.0_f32), [0.0, 0.0, 1.0]);
+    }
+
+    #[test]
+    fn test_matrix_weights_skip_incomplete_steps() {
+        let lagged = crate::lag_matrix(&[1.0, 2.0, 3.0, 4.0, 5.0], [-1, 2], 0.0, 0).unwrap();
+
+        let weights = lagged.recency_weights(1.0_f64, TrimMode::Both);
+        assert_eq!(weights, [0.0, 0.0, 0.5, 1.0, 0.0]);
+        assert_eq!(lagged.recency_weights(1.0, TrimMode::Backward)[3], 1.0);
+        assert!(lagged.with_row_weights(weights).is_ok());
+    }
+}
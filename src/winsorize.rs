@@ -0,0 +1,220 @@
+use crate::scaling::quantile;
+use crate::{collect_lags, lag_matrix_2d_impl, IntoLag, LagError, LagMatrix, MatrixLayout};
+use core::cmp::Ordering;
+use core::ops::RangeInclusive;
+use num_traits::Float;
+
+/// The clipping limits and the number of clipped values of every series; see [`winsorize`].
+#[derive(Debug, Clone, PartialEq)]
+pub struct Winsorization<T> {
+    limits: Vec<(T, T)>,
+    clipped: Vec<usize>,
+}
+
+impl<T: Copy> Winsorization<T> {
+    /// The lower and upper clipping limit of the specified series, or `None` if the
+    /// series does not exist.
+    #[inline(always)]
+    pub fn limits(&self, series: usize) -> Option<(T, T)> {
+        self.limits.get(series).copied()
+    }
+
+    /// The number of clipped values of each series.
+    #[inline(always)]
+    pub fn clipped(&self) -> &[usize] {
+        &self.clipped
+    }
+
+    /// The total number of clipped values.
+    pub fn total_clipped(&self) -> usize {
+        self.clipped.iter().sum()
+    }
+}
+
+/// Clips every series to the specified quantiles of its values, such that extreme spikes
+/// do not dominate every lag they appear in.
+///
+/// The quantiles are estimated per series in a first pass, interpolating linearly between
+/// the sorted values, and the values are clipped in place in a second pass. NaN values are
+/// ignored and kept.
+///
+/// ## Arguments
+/// * `data_matrix` - The series to clip, laid out according to `layout`.
+/// * `layout` - The layout of the data, specifying the length of each series.
+/// * `quantiles` - The lower and upper quantile in `[0, 1]`, e.g. `0.01..=0.99`.
+///
+/// ## Returns
+/// The clipping limits and counts, or an error:
+/// * [`LagError::InvalidLength`] if the data length is not a multiple of the series length,
+/// * [`LagError::InvalidQuantiles`] if a quantile lies outside `[0, 1]` or the lower quantile
+///   exceeds the upper one.
+///
+/// ## Example
+/// ```
+/// use timelag::{winsorize, MatrixLayout};
+///
+/// let mut data = [1.0, 2.0, 3.0, 4.0, 100.0];
+/// let report = winsorize(&mut data, MatrixLayout::RowMajor(5), 0.0..=0.75).unwrap();
+///
+/// assert_eq!(data, [1.0, 2.0, 3.0, 4.0, 4.0]);
+/// assert_eq!(report.total_clipped(), 1);
+/// ```
+pub fn winsorize<T: Float>(
+    data_matrix: &mut [T],
+    layout: MatrixLayout,
+    quantiles: RangeInclusive<f64>,
+) -> Result<Winsorization<T>, LagError> {
+    let (lower, upper) = quantiles.into_inner();
+    if !(0.0..=1.0).contains(&lower) || !(0.0..=1.0).contains(&upper) || lower > upper {
+        return Err(LagError::InvalidQuantiles);
+    }
+
+    let series_length = layout.len();
+    if series_length == 0 || !data_matrix.len().is_multiple_of(series_length) {
+        return Err(LagError::InvalidLength);
+    }
+
+    let num_series = data_matrix.len() / series_length;
+    let index = |series: usize, t: usize| match layout {
+        MatrixLayout::RowMajor(_) => series * series_length + t,
+        MatrixLayout::ColumnMajor(_) => t * num_series + series,
+    };
+
+    let mut limits = Vec::with_capacity(num_series);
+    let mut clipped = Vec::with_capacity(num_series);
+    let mut sorted = Vec::with_capacity(series_length);
+
+    for series in 0..num_series {
+        sorted.clear();
+        sorted.extend(
+            (0..series_length)
+                .map(|t| data_matrix[index(series, t)])
+                .filter(|value| !value.is_nan()),
+        );
+        sorted.sort_by(|a, b| a.partial_cmp(b).unwrap_or(Ordering::Equal));
+
+        let (Some(low), Some(high)) = (quantile(&sorted, lower), quantile(&sorted, upper)) else {
+            limits.push((T::nan(), T::nan()));
+            clipped.push(0);
+            continue;
+        };
+
+        let mut count = 0;
+        for t in 0..series_length {
+            let value = &mut data_matrix[index(series, t)];
+            if *value < low || *value > high {
+                *value = value.max(low).min(high);
+                count += 1;
+            }
+        }
+        limits.push((low, high));
+        clipped.push(count);
+    }
+
+    Ok(Winsorization { limits, clipped })
+}
+
+/// Create a time-lagged matrix of multiple time series values after clipping every series
+/// to the specified quantiles; see [`winsorize`] and [`lag_matrix_2d`](crate::lag_matrix_2d).
+///
+/// The input data is left unchanged.
+///
+/// ## Returns
+/// The matrix and the clipping limits and counts.
+///
+/// ## Example
+/// ```
+/// use timelag::{lag_matrix_2d_winsorized, MatrixLayout};
+///
+/// let data = [1.0, 2.0, 3.0, 4.0, 100.0];
+/// let (lagged, report) =
+///     lag_matrix_2d_winsorized(&data, MatrixLayout::RowMajor(5), 0..=1, f64::NAN, 0, 0.0..=0.75)
+///         .unwrap();
+///
+/// assert_eq!(lagged[4], 4.0);
+/// assert_eq!(report.clipped(), &[1]);
+/// ```
+pub fn lag_matrix_2d_winsorized<T: Float, L: IntoLag, R: IntoIterator<Item = L>>(
+    data_matrix: &[T],
+    layout: MatrixLayout,
+    lags: R,
+    fill: T,
+    row_stride: usize,
+    quantiles: RangeInclusive<f64>,
+) -> Result<(LagMatrix<T>, Winsorization<T>), LagError> {
+    let lags = collect_lags(lags)?;
+    let mut clipped = data_matrix.to_vec();
+    let report = winsorize(&mut clipped, layout, quantiles)?;
+    let matrix = lag_matrix_2d_impl(&clipped, layout, lags, None, fill, row_stride)?;
+    Ok((matrix, report))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    #[rustfmt::skip]
+    fn test_winsorize_per_series() {
+        let mut data = [
+            -50.0, 10.0,
+            1.0, 20.0,
+            2.0, f64::NAN,
+            3.0, 30.0,
+            4.0, 900.0,
+        ];
+        let report = winsorize(&mut data, MatrixLayout::ColumnMajor(5), 0.25..=0.75).unwrap();
+
+        assert_eq!(report.limits(0), Some((1.0, 3.0)));
+        assert_eq!(report.limits(1), Some((17.5, 247.5)));
+        assert_eq!(report.limits(2), None);
+        assert_eq!(report.clipped(), &[2, 2]);
+        assert_eq!(data[..4], [1.0, 17.5, 1.0, 20.0]);
+        assert!(data[5].is_nan());
+        assert_eq!(data[8..], [3.0, 247.5]);
+    }
+
+    #[test]
+    fn test_invalid_and_empty_series() {
+        let mut data = [1.0_f32, 2.0, 3.0];
+        assert_eq!(
+            winsorize(&mut data, MatrixLayout::RowMajor(2), 0.0..=1.0).unwrap_err(),
+            LagError::InvalidLength
+        );
+
+        let mut data = [f32::NAN; 2];
+        let report = winsorize(&mut data, MatrixLayout::RowMajor(2), 0.0..=1.0).unwrap();
+        assert_eq!(report.total_clipped(), 0);
+        assert!(report.limits(0).unwrap().0.is_nan());
+    }
+
+    #[test]
+    fn test_rejects_quantiles_outside_unit_interval() {
+        let mut data = [1.0, 2.0, 3.0];
+        assert_eq!(
+            winsorize(&mut data, MatrixLayout::RowMajor(3), 0.0..=1.5).unwrap_err(),
+            LagError::InvalidQuantiles
+        );
+        assert_eq!(
+            winsorize(&mut data, MatrixLayout::RowMajor(3), -0.1..=0.9).unwrap_err(),
+            LagError::InvalidQuantiles
+        );
+        assert_eq!(data, [1.0, 2.0, 3.0]);
+    }
+
+    #[test]
+    fn test_rejects_inverted_quantiles() {
+        let mut data = [1.0, 2.0, 3.0];
+        #[allow(clippy::reversed_empty_ranges)]
+        let inverted = 0.9..=0.1;
+        assert_eq!(
+            winsorize(&mut data, MatrixLayout::RowMajor(3), inverted).unwrap_err(),
+            LagError::InvalidQuantiles
+        );
+        assert_eq!(
+            lag_matrix_2d_winsorized(&data, MatrixLayout::RowMajor(3), [0], 0.0, 0, 0.75..=0.25)
+                .unwrap_err(),
+            LagError::InvalidQuantiles
+        );
+    }
+}
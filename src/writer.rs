@@ -0,0 +1,430 @@
+use crate::LagMatrix;
+use std::io::{self, Write};
+
+/// The text format of [`LagMatrix::write_rows`].
+///
+/// ## Example
+/// ```
+/// use timelag::RowFormat;
+///
+/// let format = RowFormat::delimited('\t').transposed().with_header().with_weights();
+/// assert_eq!(format.delimiter(), '\t');
+/// assert!(format.is_transposed());
+/// assert!(format.has_weights());
+/// ```
+#[derive(Debug, Copy, Clone, Eq, PartialEq, Hash)]
+pub struct RowFormat {
+    delimiter: char,
+    transposed: bool,
+    header: bool,
+    weights: bool,
+}
+
+impl RowFormat {
+    /// Comma-separated values.
+    #[inline(always)]
+    pub const fn csv() -> Self {
+        Self::delimited(',')
+    }
+
+    /// Values separated by the specified delimiter, e.g. `'\t'` or `' '`.
+    #[inline(always)]
+    pub const fn delimited(delimiter: char) -> Self {
+        Self {
+            delimiter,
+            transposed: false,
+            header: false,
+            weights: false,
+        }
+    }
+
+    /// Emits the columns of the matrix as lines, e.g. one line per time step of a
+    /// row-major matrix, without materializing a transposed copy.
+    #[inline(always)]
+    pub const fn transposed(mut self) -> Self {
+        self.transposed = true;
+        self
+    }
+
+    /// Emits a header line of [`column_names`](LagMatrix::column_names) or time steps,
+    /// whichever labels the emitted columns.
+    #[inline(always)]
+    pub const fn with_header(mut self) -> Self {
+        self.header = true;
+        self
+    }
+
+    /// Emits the [`row_weights`](LagMatrix::row_weights) of the time steps along with them:
+    /// as a trailing `weight` column if the time steps are emitted as lines, and as a
+    /// trailing line otherwise.
+    #[inline(always)]
+    pub const fn with_weights(mut self) -> Self {
+        self.weights = true;
+        self
+    }
+
+    /// The delimiter between values.
+    #[inline(always)]
+    pub const fn delimiter(&self) -> char {
+        self.delimiter
+    }
+
+    /// Determines whether the columns of the matrix are emitted as lines.
+    #[inline(always)]
+    pub const fn is_transposed(&self) -> bool {
+        self.transposed
+    }
+
+    /// Determines whether the row weights of the matrix are emitted.
+    #[inline(always)]
+    pub const fn has_weights(&self) -> bool {
+        self.weights
+    }
+}
+
+impl Default for RowFormat {
+    #[inline(always)]
+    fn default() -> Self {
+        Self::csv()
+    }
+}
+
+impl<T, S> LagMatrix<T, S>
+where
+    T: std::fmt::Display,
+    S: AsRef<[T]>,
+{
+    /// Writes the rows of the matrix as delimited text, e.g. to pipe large matrices into
+    /// external processes. Row padding is skipped.
+    ///
+    /// The values are written one by one; pass a [`BufWriter`](std::io::BufWriter) for
+    /// unbuffered writers such as files or pipes.
+    ///
+    /// ## Returns
+    /// An error of kind [`io::ErrorKind::InvalidInput`] if the format emits weights but the
+    /// matrix has none, or the error of the writer.
+    ///
+    /// ## Example
+    /// ```
+    /// use timelag::{lag_matrix, RowFormat};
+    ///
+    /// let lagged = lag_matrix(&[1, 2, 3], 0..=1, 0, 4).unwrap();
+    ///
+    /// let mut rows = Vec::new();
+    /// lagged.write_rows(&mut rows, RowFormat::csv()).unwrap();
+    /// assert_eq!(String::from_utf8(rows).unwrap(), "1,2,3\n0,1,2\n");
+    ///
+    /// let mut steps = Vec::new();
+    /// lagged.write_rows(&mut steps, RowFormat::csv().transposed().with_header()).unwrap();
+    /// assert_eq!(String::from_utf8(steps).unwrap(), "series0_lag0,series0_lag1\n1,0\n2,1\n3,2\n");
+    ///
+    /// let weighted = lagged.with_row_weights(vec![1, 2, 4]).unwrap();
+    /// let mut rows = Vec::new();
+    /// weighted.write_rows(&mut rows, RowFormat::csv().with_weights()).unwrap();
+    /// assert_eq!(String::from_utf8(rows).unwrap(), "1,2,3\n0,1,2\n1,2,4\n");
+    /// ```
+    pub fn write_rows<W: Write>(&self, writer: &mut W, format: RowFormat) -> io::Result<()> {
+        let data = self.data.as_ref();
+        let (lines, values) = if format.transposed {
+            (self.num_cols, self.num_rows)
+        } else {
+            (self.num_rows, self.num_cols)
+        };
+
+        let weights = match (format.weights, self.row_weights.as_deref()) {
+            (false, _) => None,
+            (true, Some(weights)) => Some(weights),
+            (true, None) => {
+                return Err(io::Error::new(
+                    io::ErrorKind::InvalidInput,
+                    "the matrix has no row weights",
+                ))
+            }
+        };
+
+        // The emitted columns are labeled by the features if they are the stored columns
+        // of a column-major matrix or the stored rows of a row-major matrix. The lines are
+        // the time steps then, each followed by its weight.
+        let steps_as_lines = self.row_major == format.transposed;
+        if format.header {
+            let labels = if steps_as_lines {
+                let mut labels = self.column_names();
+                if weights.is_some() {
+                    labels.push("weight".to_string());
+                }
+                labels
+            } else {
+                (0..self.series_length)
+                    .map(|t| format!("t{}", self.time_offset + t))
+                    .collect()
+            };
+            write_line(writer, format.delimiter, labels.iter())?;
+        }
+
+        for line in 0..lines {
+            let offsets = (0..values).map(|value| {
+                if format.transposed {
+                    value * self.row_stride + line
+                } else {
+                    line * self.row_stride + value
+                }
+            });
+            let weight = weights
+                .filter(|_| steps_as_lines)
+                .map(|weights| &weights[line]);
+            write_line(
+                writer,
+                format.delimiter,
+                offsets.map(|offset| &data[offset]).chain(weight),
+            )?;
+        }
+
+        if let Some(weights) = weights.filter(|_| !steps_as_lines) {
+            write_line(writer, format.delimiter, weights.iter())?;
+        }
+
+        Ok(())
+    }
+}
+
+impl<T, S> LagMatrix<T, S>
+where
+    T: std::fmt::Display,
+    S: AsRef<[T]>,
+{
+    /// Writes one libsvm line `target 1:value 2:value ...` per complete time step, e.g. for
+    /// libsvm, liblinear or XGBoost. Feature indices start at one and all values are written.
+    ///
+    /// Time steps with lagged gaps are skipped, see [`trim_incomplete`](Self::trim_incomplete).
+    ///
+    /// ## Arguments
+    /// * `writer` - The writer to emit the lines to.
+    /// * `targets` - The targets, one per time step of the matrix.
+    ///
+    /// ## Returns
+    /// An error of kind [`io::ErrorKind::InvalidInput`] if the number of targets does not
+    /// match the series length, or the error of the writer.
+    ///
+    /// ## Example
+    /// ```
+    /// use timelag::lag_matrix;
+    ///
+    /// let lagged = lag_matrix(&[1, 2, 3], 0..=1, 0, 0).unwrap();
+    ///
+    /// let mut lines = Vec::new();
+    /// lagged.write_libsvm(&mut lines, &[7, 8, 9]).unwrap();
+    /// assert_eq!(String::from_utf8(lines).unwrap(), "8 1:2 2:1\n9 1:3 2:2\n");
+    /// ```
+    pub fn write_libsvm<W: Write>(&self, writer: &mut W, targets: &[T]) -> io::Result<()> {
+        self.write_samples(writer, targets, "", |writer, feature, value| {
+            write!(writer, " {}:{value}", feature + 1)
+        })
+    }
+
+    /// Writes one Vowpal Wabbit line `target | name:value ...` per complete time step,
+    /// using the [`column_names`](Self::column_names) as feature names.
+    ///
+    /// Spaces, colons and vertical bars in the names are replaced with underscores.
+    /// Time steps with lagged gaps are skipped, see [`trim_incomplete`](Self::trim_incomplete).
+    ///
+    /// ## Arguments
+    /// * `writer` - The writer to emit the lines to.
+    /// * `targets` - The targets, one per time step of the matrix.
+    ///
+    /// ## Returns
+    /// An error of kind [`io::ErrorKind::InvalidInput`] if the number of targets does not
+    /// match the series length, or the error of the writer.
+    ///
+    /// ## Example
+    /// ```
+    /// use timelag::{lag_matrix_2d, MatrixLayout};
+    ///
+    /// let lagged = lag_matrix_2d(&[1, 2, 3], MatrixLayout::ColumnMajor(3), [1], 0, 0)
+    ///     .unwrap()
+    ///     .with_series_names(["demand"])
+    ///     .unwrap();
+    ///
+    /// let mut lines = Vec::new();
+    /// lagged.write_vw(&mut lines, &[2, 3, 4]).unwrap();
+    /// assert_eq!(String::from_utf8(lines).unwrap(), "3 | demand_lag1:1\n4 | demand_lag1:2\n");
+    /// ```
+    pub fn write_vw<W: Write>(&self, writer: &mut W, targets: &[T]) -> io::Result<()> {
+        let names: Vec<String> = self
+            .column_names()
+            .into_iter()
+            .map(|name| name.replace([' ', ':', '|'], "_"))
+            .collect();
+        self.write_samples(writer, targets, " |", |writer, feature, value| {
+            write!(writer, " {}:{value}", names[feature])
+        })
+    }
+
+    /// Writes the target, the separator and the features of every complete time step.
+    fn write_samples<W, F>(
+        &self,
+        writer: &mut W,
+        targets: &[T],
+        separator: &str,
+        mut write_feature: F,
+    ) -> io::Result<()>
+    where
+        W: Write,
+        F: FnMut(&mut W, usize, &T) -> io::Result<()>,
+    {
+        if targets.len() != self.series_length {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidInput,
+                "the number of targets must match the series length",
+            ));
+        }
+
+        let data = self.data.as_ref();
+        let features = if self.row_major {
+            self.num_rows
+        } else {
+            self.num_cols
+        };
+        let (leading, trailing) = self.incomplete_steps();
+        let complete = self.series_length.saturating_sub(trailing);
+        let gaps = self.gap_steps();
+        for (t, target) in targets.iter().enumerate().take(complete).skip(leading) {
+            if gaps.as_ref().is_some_and(|gaps| gaps[t]) {
+                continue;
+            }
+            write!(writer, "{target}{separator}")?;
+            for feature in 0..features {
+                let offset = if self.row_major {
+                    feature * self.row_stride + t
+                } else {
+                    t * self.row_stride + feature
+                };
+                write_feature(writer, feature, &data[offset])?;
+            }
+            writeln!(writer)?;
+        }
+
+        Ok(())
+    }
+}
+
+/// Writes the values separated by the delimiter, followed by a line break.
+fn write_line<W, V, I>(writer: &mut W, delimiter: char, values: I) -> io::Result<()>
+where
+    W: Write,
+    V: std::fmt::Display,
+    I: Iterator<Item = V>,
+{
+    for (index, value) in values.enumerate() {
+        if index > 0 {
+            write!(writer, "{delimiter}")?;
+        }
+        write!(writer, "{value}")?;
+    }
+    writeln!(writer)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{lag_matrix_2d, MatrixLayout};
+
+    #[test]
+    fn test_transposed_matches_transpose() {
+        let data = [1, 10, 2, 20, 3, 30];
+        let lagged = lag_matrix_2d(&data, MatrixLayout::ColumnMajor(3), [0, 1], -1, 7)
+            .unwrap()
+            .with_series_names(["a", "b"])
+            .unwrap();
+
+        for format in [RowFormat::delimited(' '), RowFormat::csv().with_header()] {
+            let mut direct = Vec::new();
+            lagged.write_rows(&mut direct, format.transposed()).unwrap();
+
+            let mut copied = Vec::new();
+            lagged.transpose().write_rows(&mut copied, format).unwrap();
+            assert_eq!(direct, copied);
+        }
+    }
+
+    #[test]
+    fn test_header_labels_the_emitted_columns() {
+        let lagged = lag_matrix_2d(&[1, 10, 2, 20], MatrixLayout::ColumnMajor(2), [1], 0, 0)
+            .unwrap()
+            .with_series_names(["a", "b"])
+            .unwrap();
+
+        let mut text = Vec::new();
+        lagged
+            .write_rows(&mut text, RowFormat::delimited('\t').with_header())
+            .unwrap();
+        assert_eq!(
+            String::from_utf8(text).unwrap(),
+            "a_lag1\tb_lag1\n0\t0\n1\t10\n"
+        );
+
+        let mut text = Vec::new();
+        lagged
+            .write_rows(&mut text, RowFormat::csv().transposed().with_header())
+            .unwrap();
+        assert_eq!(String::from_utf8(text).unwrap(), "t0,t1\n0,1\n0,10\n");
+    }
+
+    #[test]
+    fn test_weights_follow_the_time_steps() {
+        let lagged = lag_matrix_2d(&[1, 10, 2, 20], MatrixLayout::ColumnMajor(2), [1], 0, 0)
+            .unwrap()
+            .with_series_names(["a", "b"])
+            .unwrap()
+            .with_row_weights(vec![5, 7])
+            .unwrap();
+        let format = RowFormat::csv().with_header().with_weights();
+
+        let mut text = Vec::new();
+        lagged.write_rows(&mut text, format).unwrap();
+        assert_eq!(
+            String::from_utf8(text).unwrap(),
+            "a_lag1,b_lag1,weight\n0,0,5\n1,10,7\n"
+        );
+
+        let mut text = Vec::new();
+        lagged.write_rows(&mut text, format.transposed()).unwrap();
+        assert_eq!(String::from_utf8(text).unwrap(), "t0,t1\n0,1\n0,10\n5,7\n");
+
+        let mut copied = Vec::new();
+        lagged.transpose().write_rows(&mut copied, format).unwrap();
+        let mut direct = Vec::new();
+        lagged.write_rows(&mut direct, format.transposed()).unwrap();
+        assert_eq!(copied, direct);
+
+        let unweighted = lag_matrix_2d(&[1, 2], MatrixLayout::ColumnMajor(2), [0], 0, 0).unwrap();
+        let error = unweighted.write_rows(&mut Vec::new(), format).unwrap_err();
+        assert_eq!(error.kind(), io::ErrorKind::InvalidInput);
+    }
+
+    #[test]
+    fn test_learner_formats_agree_across_layouts() {
+        let data = [1, 2, 3, 4];
+        let targets = [5, 6, 7, 8];
+        let row_major = crate::lag_matrix(&data, [0, 2], 0, 6)
+            .unwrap()
+            .with_series_names(["a b"])
+            .unwrap();
+        let column_major = row_major.transpose();
+
+        for lagged in [&row_major, &column_major] {
+            let mut lines = Vec::new();
+            lagged.write_libsvm(&mut lines, &targets).unwrap();
+            assert_eq!(String::from_utf8(lines).unwrap(), "7 1:3 2:1\n8 1:4 2:2\n");
+
+            let mut lines = Vec::new();
+            lagged.write_vw(&mut lines, &targets).unwrap();
+            assert_eq!(
+                String::from_utf8(lines).unwrap(),
+                "7 | a_b_lag0:3 a_b_lag2:1\n8 | a_b_lag0:4 a_b_lag2:2\n"
+            );
+        }
+
+        let error = row_major.write_libsvm(&mut Vec::new(), &[1]).unwrap_err();
+        assert_eq!(error.kind(), io::ErrorKind::InvalidInput);
+    }
+}
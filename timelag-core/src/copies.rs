@@ -0,0 +1,153 @@
+use crate::{shifted_ranges, source_step};
+use core::ops::Range;
+
+/// The copies filling the time steps `steps` of one lag of a row-major lag matrix, i.e. a
+/// block with one row per series holding series `s` shifted by `shifts[s]`.
+///
+/// Every copy is a pair of a source range into the row-major data matrix of the
+/// `shifts.len()` series of `series_length` time steps each, and a target range into the
+/// block, whose rows start every `row_stride` elements. Lagged gaps and the time steps
+/// outside `steps` are not copied.
+///
+/// ## Panics
+/// The iterator panics if a shift spans more than the series length; see
+/// [`exceeds_length`](crate::exceeds_length).
+///
+/// ## Example
+/// ```
+/// use timelag_core::{copy_ranges, row_major_copies};
+///
+/// // Two series of three time steps, the first lagged and the second led by one step.
+/// let data = [1, 2, 3, 10, 20, 30];
+/// let mut block = [0; 8];
+/// copy_ranges(&data, &mut block, row_major_copies(&[1, -1], 3, 4, 0..3));
+///
+/// assert_eq!(block, [0, 1, 2, 0, 20, 30, 0, 0]);
+/// ```
+pub fn row_major_copies(
+    shifts: &[isize],
+    series_length: usize,
+    row_stride: usize,
+    steps: Range<usize>,
+) -> impl Iterator<Item = (Range<usize>, Range<usize>)> + '_ {
+    shifts
+        .iter()
+        .enumerate()
+        .filter_map(move |(series, &shift)| {
+            let (sources, targets) = shifted_ranges(shift, series_length);
+            let (from, to) = (targets.start.max(steps.start), targets.end.min(steps.end));
+            if from >= to {
+                return None;
+            }
+
+            let source = series * series_length + sources.start + (from - targets.start);
+            let target = series * row_stride + from;
+            Some((source..source + (to - from), target..target + (to - from)))
+        })
+}
+
+/// The copies filling time step `t` of one lag of a column-major lag matrix, i.e. the
+/// values of all series side by side, where series `s` is shifted by `shifts[s]`.
+///
+/// Every copy is a pair of a source range into the column-major data matrix of the
+/// `shifts.len()` interleaved series of `series_length` time steps each, and a target range
+/// relative to the first value of the lag. Adjacent series shifted by the same amount are
+/// copied at once, e.g. all of them if they share one shift. Lagged gaps are not copied.
+///
+/// ## Example
+/// ```
+/// use timelag_core::{column_major_copies, copy_ranges};
+///
+/// // Two interleaved series of three time steps, both lagged by one step.
+/// let data = [1, 10, 2, 20, 3, 30];
+/// let copies: Vec<_> = column_major_copies(&[1, 1], 3, 2).collect();
+/// assert_eq!(copies, [(2..4, 0..2)]);
+///
+/// let mut row = [0; 2];
+/// copy_ranges(&data, &mut row, copies);
+/// assert_eq!(row, [2, 20]);
+/// ```
+pub fn column_major_copies(
+    shifts: &[isize],
+    series_length: usize,
+    t: usize,
+) -> impl Iterator<Item = (Range<usize>, Range<usize>)> + '_ {
+    let num_series = shifts.len();
+    let mut series = 0;
+    core::iter::from_fn(move || {
+        while series < num_series {
+            let (start, shift) = (series, shifts[series]);
+            series += shifts[start..].iter().take_while(|&&s| s == shift).count();
+            if let Some(source) = source_step(t, shift, series_length) {
+                let offset = source * num_series;
+                return Some((offset + start..offset + series, start..series));
+            }
+        }
+        None
+    })
+}
+
+/// Copies the values of every source range into its target range, e.g. the copies of
+/// [`row_major_copies`] or [`column_major_copies`].
+///
+/// ## Panics
+/// Panics if a range is out of bounds, or if the lengths of a source and its target differ.
+pub fn copy_ranges<T: Copy>(
+    source: &[T],
+    target: &mut [T],
+    copies: impl IntoIterator<Item = (Range<usize>, Range<usize>)>,
+) {
+    for (sources, targets) in copies {
+        target[targets].copy_from_slice(&source[sources]);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const SHIFTS: [[isize; 3]; 4] = [[0, 0, 0], [2, 2, -1], [-3, 1, 1], [4, -4, 0]];
+
+    #[test]
+    fn test_row_major_copies_match_source_steps() {
+        let data: [usize; 12] = core::array::from_fn(|i| i + 1);
+        for shifts in SHIFTS {
+            for steps in [0..4, 1..3, 3..4, 2..2] {
+                let mut block = [0; 15];
+                copy_ranges(
+                    &data,
+                    &mut block,
+                    row_major_copies(&shifts, 4, 5, steps.clone()),
+                );
+                for (series, &shift) in shifts.iter().enumerate() {
+                    for t in 0..4 {
+                        let expected = source_step(t, shift, 4)
+                            .filter(|_| steps.contains(&t))
+                            .map_or(0, |source| data[series * 4 + source]);
+                        assert_eq!(block[series * 5 + t], expected, "{shifts:?} at {t}");
+                    }
+                    assert_eq!(block[series * 5 + 4], 0, "the row padding is not written");
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn test_column_major_copies_match_source_steps() {
+        let data: [usize; 12] = core::array::from_fn(|i| i + 1);
+        for shifts in SHIFTS {
+            for t in 0..4 {
+                let mut row = [0; 3];
+                copy_ranges(&data, &mut row, column_major_copies(&shifts, 4, t));
+                for (series, &shift) in shifts.iter().enumerate() {
+                    let expected =
+                        source_step(t, shift, 4).map_or(0, |source| data[source * 3 + series]);
+                    assert_eq!(row[series], expected, "{shifts:?} at {t}");
+                }
+            }
+        }
+
+        // Adjacent series of the same shift are copied at once.
+        assert_eq!(column_major_copies(&[1, 1, -1], 4, 2).count(), 2);
+    }
+}
@@ -0,0 +1,152 @@
+//! # timelag-core — kernels for time-lagged time series data
+//!
+//! The allocation-free core of the [timelag](https://crates.io/crates/timelag) crate: the
+//! offset math of lagged and led series and the kernels copying them into caller-provided
+//! buffers. The crate is `no_std` and has no dependencies, e.g. for embedded targets and
+//! FFI bindings; most users should depend on `timelag` instead, which re-exports this
+//! crate as `timelag::kernels`.
+//!
+//! Shifts are signed: positive shifts lag a series, i.e. time step `t` holds the value of
+//! time step `t - shift`, and negative shifts lead it. The kernels of matrices of multiple
+//! series describe their copies as pairs of source and target ranges, such that callers may
+//! copy the values in their own way, and [`LagOffsets`] locates the values of the result.
+//!
+//! ## Example
+//!
+//! ```
+//! use timelag_core::{shift_series_into, source_step};
+//!
+//! let series = [1, 2, 3, 4];
+//! let mut led = [0; 4];
+//! shift_series_into(&series, -1, &mut led);
+//!
+//! assert_eq!(led, [2, 3, 4, 0]);
+//! assert_eq!(source_step(3, -1, series.len()), None);
+//! ```
+
+// SPDX-FileCopyrightText: 2023 Markus Mayer
+// SPDX-License-Identifier: EUPL-1.2
+
+#![no_std]
+#![forbid(unsafe_code)]
+
+mod copies;
+mod offsets;
+
+pub use copies::{column_major_copies, copy_ranges, row_major_copies};
+pub use offsets::{LagOffsets, LayoutError, MatrixLayout};
+
+use core::ops::Range;
+
+/// Determines whether any lag or lead spans more than the series length.
+///
+/// ## Example
+/// ```
+/// use timelag_core::exceeds_length;
+///
+/// assert!(!exceeds_length(&[0, 4, -4], 4));
+/// assert!(exceeds_length(&[1, -5], 4));
+/// ```
+pub fn exceeds_length(lags: &[isize], series_length: usize) -> bool {
+    lags.iter().any(|lag| lag.unsigned_abs() > series_length)
+}
+
+/// The source time step of time step `t` of a series shifted by `shift`, if any.
+///
+/// ## Returns
+/// The time step of the value to copy, or `None` if time step `t` is a lagged gap, i.e.
+/// the source would lie before the start or after the end of the series.
+///
+/// ## Example
+/// ```
+/// use timelag_core::source_step;
+///
+/// assert_eq!(source_step(3, 2, 5), Some(1));
+/// assert_eq!(source_step(1, 2, 5), None);
+/// assert_eq!(source_step(2, -2, 5), Some(4));
+/// ```
+#[inline(always)]
+pub fn source_step(t: usize, shift: isize, series_length: usize) -> Option<usize> {
+    let amount = shift.unsigned_abs();
+    if shift >= 0 {
+        t.checked_sub(amount)
+    } else {
+        Some(t + amount).filter(|&source| source < series_length)
+    }
+}
+
+/// The source and target time steps of a series of the specified length shifted by `shift`.
+///
+/// Copying the source range of a series into the target range of its lagged version creates
+/// the shifted series; all time steps outside the target range are lagged gaps.
+///
+/// ## Panics
+/// Panics if the shift spans more than the series length; see [`exceeds_length`].
+///
+/// ## Example
+/// ```
+/// use timelag_core::shifted_ranges;
+///
+/// assert_eq!(shifted_ranges(1, 4), (0..3, 1..4));
+/// assert_eq!(shifted_ranges(-1, 4), (1..4, 0..3));
+/// ```
+#[inline(always)]
+pub fn shifted_ranges(shift: isize, series_length: usize) -> (Range<usize>, Range<usize>) {
+    let amount = shift.unsigned_abs();
+    let count = series_length - amount;
+    if shift >= 0 {
+        (0..count, amount..series_length)
+    } else {
+        (amount..series_length, 0..count)
+    }
+}
+
+/// Copies the series shifted by `shift` into `lagged`, leaving the lagged gaps untouched,
+/// e.g. to fill one row of a lag matrix that was initialized with the fill value.
+///
+/// ## Panics
+/// Panics if the lengths of the series and `lagged` differ, or if the shift spans
+/// more than the series length.
+///
+/// ## Example
+/// ```
+/// use timelag_core::shift_series_into;
+///
+/// let mut lagged = [f64::INFINITY; 3];
+/// shift_series_into(&[1.0, 2.0, 3.0], 1, &mut lagged);
+///
+/// assert_eq!(lagged, [f64::INFINITY, 1.0, 2.0]);
+/// ```
+pub fn shift_series_into<T: Copy>(series: &[T], shift: isize, lagged: &mut [T]) {
+    assert_eq!(
+        series.len(),
+        lagged.len(),
+        "the lagged series must be as long as the source series"
+    );
+    let (sources, targets) = shifted_ranges(shift, series.len());
+    lagged[targets].copy_from_slice(&series[sources]);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_kernel_matches_source_steps() {
+        let series = [1, 2, 3, 4, 5];
+        for shift in -5..=5 {
+            let mut lagged = [0; 5];
+            shift_series_into(&series, shift, &mut lagged);
+            for (t, &value) in lagged.iter().enumerate() {
+                let expected = source_step(t, shift, series.len()).map_or(0, |s| series[s]);
+                assert_eq!(value, expected, "shift {shift}, time step {t}");
+            }
+        }
+    }
+
+    #[test]
+    #[should_panic]
+    fn test_kernel_rejects_mismatched_lengths() {
+        shift_series_into(&[1, 2, 3], 1, &mut [0; 2]);
+    }
+}
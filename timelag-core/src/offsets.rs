@@ -0,0 +1,257 @@
+use core::fmt::{Display, Formatter};
+
+/// Describes the layout of the data matrix.
+#[derive(Debug, Copy, Clone, Eq, PartialEq, Ord, PartialOrd)]
+pub enum MatrixLayout {
+    /// Data is laid out row-wise, i.e. reach row of the matrix contains a time series
+    /// and the columns represent points in time.
+    ///
+    /// The values represent the number of elements per row, i.e. the length of each time series.
+    RowMajor(usize),
+    /// Data is laid out column-wise, i.e. reach column of the matrix contains a time series
+    /// and the rows represent points in time.
+    ///
+    /// The values represent the number of elements per column, i.e. the length of each time series.
+    ColumnMajor(usize),
+}
+
+impl MatrixLayout {
+    /// The length of each time series.
+    pub const fn len(&self) -> usize {
+        match self {
+            MatrixLayout::RowMajor(len) => *len,
+            MatrixLayout::ColumnMajor(len) => *len,
+        }
+    }
+
+    /// Determines whether the time series are empty.
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+}
+
+/// An error describing the buffer of a lag matrix; see [`LagOffsets::new`].
+#[derive(Debug, Copy, Clone, Eq, PartialEq)]
+#[non_exhaustive]
+pub enum LayoutError {
+    /// No lags were specified.
+    InvalidLags,
+    /// There are no series or time steps.
+    EmptyData,
+    /// The row stride is less than the number of elements per row.
+    InvalidStride,
+}
+
+impl core::error::Error for LayoutError {}
+
+impl Display for LayoutError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> core::fmt::Result {
+        match self {
+            LayoutError::InvalidLags => write!(f, "No lags were specified"),
+            LayoutError::EmptyData => write!(f, "The matrix has no series or time steps"),
+            LayoutError::InvalidStride => write!(
+                f,
+                "The row stride must be greater than or equal to the number of elements per row"
+            ),
+        }
+    }
+}
+
+/// The offsets of the values in the buffer of a lag matrix, e.g. for FFI consumers or
+/// custom kernels that index the buffer directly.
+///
+/// This is the offset arithmetic the `timelag` crate itself uses. The value of series `series` at lag
+/// index `lag_index` and time step `t` is located at
+/// * `(lag_index * series_count + series) * row_stride + t` in row-major matrices, and at
+/// * `t * row_stride + lag_index * series_count + series` in column-major matrices.
+///
+/// ## Example
+/// ```
+/// use timelag_core::{LagOffsets, MatrixLayout};
+///
+/// // Two series of three time steps with two lags, one row per time step.
+/// let offsets = LagOffsets::new(MatrixLayout::ColumnMajor(3), 2, 2, 6).unwrap();
+///
+/// // The second series at lag index one and time step two.
+/// assert_eq!(offsets.offset_of(1, 1, 2), 15);
+/// assert_eq!(offsets.get(1, 1, 3), None);
+/// assert_eq!(offsets.len(), 18);
+/// ```
+#[derive(Debug, Copy, Clone, Eq, PartialEq, Hash)]
+pub struct LagOffsets {
+    row_major: bool,
+    series_length: usize,
+    series_count: usize,
+    num_lags: usize,
+    static_count: usize,
+    row_stride: usize,
+}
+
+impl LagOffsets {
+    /// Describes the buffer of a matrix without static columns.
+    ///
+    /// ## Arguments
+    /// * `layout` - The layout of the matrix, specifying the length of each series.
+    /// * `series_count` - The number of lagged series.
+    /// * `num_lags` - The number of lags.
+    /// * `row_stride` - The number of elements between subsequent rows; `0` for no padding.
+    ///
+    /// ## Returns
+    /// The offsets, or
+    /// * [`LayoutError::InvalidLags`] if there are no lags,
+    /// * [`LayoutError::EmptyData`] if there are no series or time steps,
+    /// * [`LayoutError::InvalidStride`] if the stride is shorter than a row.
+    pub fn new(
+        layout: MatrixLayout,
+        series_count: usize,
+        num_lags: usize,
+        row_stride: usize,
+    ) -> Result<Self, LayoutError> {
+        if num_lags == 0 {
+            return Err(LayoutError::InvalidLags);
+        }
+
+        let series_length = layout.len();
+        if series_length == 0 || series_count == 0 {
+            return Err(LayoutError::EmptyData);
+        }
+
+        let (row_major, min_stride) = match layout {
+            MatrixLayout::RowMajor(_) => (true, series_length),
+            MatrixLayout::ColumnMajor(_) => (false, series_count * num_lags),
+        };
+
+        let row_stride = if row_stride == 0 {
+            min_stride
+        } else {
+            row_stride
+        };
+        if row_stride < min_stride {
+            return Err(LayoutError::InvalidStride);
+        }
+
+        Ok(Self {
+            row_major,
+            series_length,
+            series_count,
+            num_lags,
+            static_count: 0,
+            row_stride,
+        })
+    }
+
+    /// Describes the buffer of a matrix of the specified shape with `static_count` static
+    /// columns, without validating it; see [`new`](Self::new).
+    pub const fn from_shape(
+        layout: MatrixLayout,
+        series_count: usize,
+        num_lags: usize,
+        static_count: usize,
+        row_stride: usize,
+    ) -> Self {
+        Self {
+            row_major: matches!(layout, MatrixLayout::RowMajor(_)),
+            series_length: layout.len(),
+            series_count,
+            num_lags,
+            static_count,
+            row_stride,
+        }
+    }
+
+    /// The offset of the value of series `series` at lag index `lag_index` and time step `t`.
+    ///
+    /// The indices are not validated; see [`get`](Self::get) for a checked variant.
+    #[inline(always)]
+    pub const fn offset_of(&self, series: usize, lag_index: usize, t: usize) -> usize {
+        if self.row_major {
+            (lag_index * self.series_count + series) * self.row_stride + t
+        } else {
+            t * self.row_stride + lag_index * self.series_count + series
+        }
+    }
+
+    /// The offset of the value of series `series` at lag index `lag_index` and time step `t`,
+    /// or `None` if any of the indices is out of range.
+    #[inline(always)]
+    pub const fn get(&self, series: usize, lag_index: usize, t: usize) -> Option<usize> {
+        if series < self.series_count && lag_index < self.num_lags && t < self.series_length {
+            Some(self.offset_of(series, lag_index, t))
+        } else {
+            None
+        }
+    }
+
+    /// The offset of static covariate `index` at time step `t`, or `None` if any of the
+    /// indices is out of range, e.g. of the covariates that `timelag` appends via
+    /// `LagMatrix::with_static_columns`.
+    pub const fn static_offset(&self, index: usize, t: usize) -> Option<usize> {
+        if index >= self.static_count || t >= self.series_length {
+            return None;
+        }
+
+        let features = self.num_lags * self.series_count;
+        Some(if self.row_major {
+            (features + index) * self.row_stride + t
+        } else {
+            t * self.row_stride + features + index
+        })
+    }
+
+    /// The number of elements in the buffer, including the row padding.
+    pub const fn len(&self) -> usize {
+        let features = self.num_lags * self.series_count;
+        if self.row_major {
+            (features + self.static_count) * self.row_stride
+        } else {
+            self.series_length * self.row_stride
+        }
+    }
+
+    /// Determines whether the buffer is empty.
+    #[inline(always)]
+    pub const fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// The number of elements between subsequent rows.
+    #[inline(always)]
+    pub const fn row_stride(&self) -> usize {
+        self.row_stride
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_new_validates_shape() {
+        let layout = MatrixLayout::ColumnMajor(3);
+        assert_eq!(
+            LagOffsets::new(layout, 2, 0, 0),
+            Err(LayoutError::InvalidLags)
+        );
+        assert_eq!(
+            LagOffsets::new(layout, 0, 2, 0),
+            Err(LayoutError::EmptyData)
+        );
+        assert_eq!(
+            LagOffsets::new(layout, 2, 2, 3),
+            Err(LayoutError::InvalidStride)
+        );
+        assert_eq!(LagOffsets::new(layout, 2, 2, 0).unwrap().row_stride(), 4);
+    }
+
+    #[test]
+    fn test_static_offsets_follow_the_features() {
+        let rows = LagOffsets::from_shape(MatrixLayout::RowMajor(4), 2, 3, 1, 5);
+        assert_eq!(rows.static_offset(0, 2), Some(6 * 5 + 2));
+        assert_eq!(rows.len(), 7 * 5);
+
+        let columns = LagOffsets::from_shape(MatrixLayout::ColumnMajor(4), 2, 3, 1, 8);
+        assert_eq!(columns.static_offset(0, 2), Some(2 * 8 + 6));
+        assert_eq!(columns.static_offset(1, 2), None);
+        assert_eq!(columns.len(), 4 * 8);
+    }
+}